@@ -0,0 +1,616 @@
+//! API key authentication and rate limiting
+//!
+//! Every `/api` request passes through [`authenticate`]. A key may be sent as
+//! `Authorization: Bearer <key>` or `X-API-Key: <key>`. Requests without a key
+//! are served with the public scope and limited per client IP; keyed requests
+//! draw from the token bucket of their key and are counted per UTC day in
+//! `api_key_usage`. Looking up a key that is not cached, and presenting one
+//! that is unknown, are charged to the anonymous bucket of the client IP. The
+//! configured `AUTH` value acts as a bootstrap admin key.
+//!
+//! Full `export=true` dumps need the partner scope and `/api/admin` the
+//! admin scope. The client IP is the peer address, or the address
+//! `X-Forwarded-For` gives when the peer is a trusted proxy.
+
+use std::{
+    collections::HashMap,
+    net::{IpAddr, SocketAddr},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use actix_web::{
+    body::{EitherBody, MessageBody},
+    dev::{ServiceRequest, ServiceResponse},
+    http::header::{HeaderMap, AUTHORIZATION},
+    middleware::Next,
+    web, HttpMessage as _,
+};
+use anyhow::Context as _;
+use chrono::{NaiveDate, Utc};
+use moka::future::Cache;
+use tokio::{sync::Mutex, time::interval};
+use tracing::{debug, error};
+
+use etl_core::{
+    configuration::{AppState, State},
+    error::Error,
+    helpers::ApiKeyScope,
+};
+
+use crate::error::ApiError;
+
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// How long a resolved key is kept before it is looked up again
+const KEY_CACHE_TTL: Duration = Duration::from_secs(60);
+
+/// Resolved keys kept at most, unknown ones included
+const MAX_CACHED_KEYS: u64 = 10_000;
+
+/// How often usage counters are written to the database
+const USAGE_FLUSH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Buckets kept at most. Refilled buckets are dropped first; beyond that,
+/// new clients share one overflow bucket.
+const MAX_BUCKETS: usize = 100_000;
+
+/// Bucket of the clients arriving while the bucket map is full
+const OVERFLOW_BUCKET: &str = "ip:overflow";
+
+/// Token cost of a full `export=true` dump, every other request costs 1
+const EXPORT_COST: f64 = 10.0;
+
+/// Caller resolved from the request headers, available to handlers through
+/// request extensions.
+#[derive(Debug, Clone)]
+pub struct ApiIdentity {
+    pub key_hash: Option<String>,
+    pub name: String,
+    pub scope: ApiKeyScope,
+    pub rate_limit_per_minute: u32,
+    pub burst: u32,
+}
+
+#[derive(Debug)]
+struct TokenBucket {
+    tokens: f64,
+    updated: Instant,
+    /// From then on the bucket is full again, no different from a new one
+    full_at: Instant,
+}
+
+impl TokenBucket {
+    fn new(capacity: f64) -> Self {
+        let now = Instant::now();
+        Self {
+            tokens: capacity,
+            updated: now,
+            full_at: now,
+        }
+    }
+
+    fn is_full(&self, now: Instant) -> bool {
+        self.full_at <= now
+    }
+
+    /// Take `cost` tokens, or return the seconds until they are available
+    fn take(
+        &mut self,
+        cost: f64,
+        capacity: f64,
+        per_second: f64,
+    ) -> Result<(), u64> {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * per_second).min(capacity);
+        self.updated = now;
+
+        // A request costing more than the bucket holds would never pass
+        let cost = cost.min(capacity);
+        if self.tokens >= cost {
+            self.tokens -= cost;
+            if per_second > 0.0 {
+                self.full_at = now
+                    + Duration::from_secs_f64(
+                        (capacity - self.tokens) / per_second,
+                    );
+            } else {
+                self.full_at = now + Duration::from_secs(86_400 * 365);
+            }
+            return Ok(());
+        }
+
+        // A key limited to zero requests per minute never refills
+        if per_second <= 0.0 {
+            return Err(60);
+        }
+
+        Err(((cost - self.tokens) / per_second).ceil() as u64)
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct UsageCounter {
+    requests: i64,
+    throttled: i64,
+}
+
+/// Shared authentication and rate limiting state of the API server
+pub struct ApiGuard {
+    keys: Cache<String, Option<ApiIdentity>>,
+    buckets: Mutex<HashMap<String, TokenBucket>>,
+    usage: Mutex<HashMap<(String, NaiveDate), UsageCounter>>,
+}
+
+impl ApiGuard {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self {
+            keys: Cache::builder()
+                .max_capacity(MAX_CACHED_KEYS)
+                .time_to_live(KEY_CACHE_TTL)
+                .build(),
+            buckets: Mutex::new(HashMap::new()),
+            usage: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Drop resolved keys so revocations apply immediately
+    pub fn invalidate_keys(&self) {
+        self.keys.invalidate_all();
+    }
+
+    async fn identify(
+        &self,
+        state: &AppState<State>,
+        headers: &HeaderMap,
+        ip: &str,
+    ) -> Result<ApiIdentity, Error> {
+        let anonymous = ApiIdentity {
+            key_hash: None,
+            name: String::from("anonymous"),
+            scope: ApiKeyScope::Public,
+            rate_limit_per_minute: state.config.api_rate_limit_per_minute,
+            burst: state.config.api_rate_limit_burst,
+        };
+
+        let Some(key) = extract_key(headers)? else {
+            return Ok(anonymous);
+        };
+
+        if !state.config.auth.is_empty()
+            && constant_time_eq(key.as_bytes(), state.config.auth.as_bytes())
+        {
+            return Ok(ApiIdentity {
+                name: String::from("bootstrap"),
+                scope: ApiKeyScope::Admin,
+                ..anonymous
+            });
+        }
+
+        let key_hash = sha256::digest(key);
+        match self.keys.get(&key_hash).await {
+            Some(Some(identity)) => return Ok(identity),
+            Some(None) => return Err(self.reject(&anonymous, ip).await),
+            // The lookup is charged whatever it finds
+            None => self.acquire(&anonymous, ip, 1.0).await?,
+        }
+
+        let identity = self
+            .keys
+            .try_get_with_by_ref(&key_hash, async {
+                let api_key = state
+                    .database
+                    .api_key
+                    .get_active_by_hash(&key_hash)
+                    .await?;
+
+                let Some(api_key) = api_key else {
+                    return Ok::<_, Error>(None);
+                };
+
+                Ok(Some(ApiIdentity {
+                    key_hash: Some(api_key.key_hash),
+                    name: api_key.name,
+                    scope: api_key.scope.parse()?,
+                    rate_limit_per_minute: api_key
+                        .rate_limit_per_minute
+                        .try_into()?,
+                    burst: api_key.burst.try_into()?,
+                }))
+            })
+            .await
+            .map_err(|e| Error::TaskError(e.to_string()))?;
+
+        identity
+            .ok_or_else(|| Error::Unauthorized(String::from("invalid API key")))
+    }
+
+    /// Charge an unknown key to the anonymous bucket of the client
+    async fn reject(&self, anonymous: &ApiIdentity, ip: &str) -> Error {
+        match self.acquire(anonymous, ip, 1.0).await {
+            Ok(()) => Error::Unauthorized(String::from("invalid API key")),
+            Err(e) => e,
+        }
+    }
+
+    async fn acquire(
+        &self,
+        identity: &ApiIdentity,
+        ip: &str,
+        cost: f64,
+    ) -> Result<(), Error> {
+        let bucket_key = match &identity.key_hash {
+            Some(key_hash) => format!("key:{}", key_hash),
+            None => format!("ip:{}", ip),
+        };
+
+        let capacity = f64::from(identity.burst);
+        let per_second = f64::from(identity.rate_limit_per_minute) / 60.0;
+
+        let result = {
+            let mut buckets = self.buckets.lock().await;
+            let now = Instant::now();
+            if !buckets.contains_key(&bucket_key)
+                && buckets.len() >= MAX_BUCKETS
+            {
+                buckets.retain(|_, bucket| !bucket.is_full(now));
+            }
+            let bucket_key = if buckets.contains_key(&bucket_key)
+                || buckets.len() < MAX_BUCKETS
+                || identity.key_hash.is_some()
+            {
+                bucket_key
+            } else {
+                String::from(OVERFLOW_BUCKET)
+            };
+
+            buckets
+                .entry(bucket_key)
+                .or_insert_with(|| TokenBucket::new(capacity))
+                .take(cost, capacity, per_second)
+        };
+
+        if let Some(key_hash) = &identity.key_hash {
+            let mut usage = self.usage.lock().await;
+            let counter = usage
+                .entry((key_hash.to_owned(), Utc::now().date_naive()))
+                .or_default();
+
+            match result {
+                Ok(()) => counter.requests += 1,
+                Err(_) => counter.throttled += 1,
+            }
+        }
+
+        result.map_err(|retry_after| {
+            debug!("Rate limited {} from {}", identity.name, ip);
+            Error::RateLimited { retry_after }
+        })
+    }
+
+    async fn flush(&self, state: &AppState<State>) {
+        let pending = std::mem::take(&mut *self.usage.lock().await);
+        let mut failed = HashMap::new();
+
+        for ((key_hash, day), counter) in pending {
+            if let Err(e) = state
+                .database
+                .api_key
+                .add_usage(&key_hash, day, counter.requests, counter.throttled)
+                .await
+            {
+                error!("Failed to store API key usage: {}", e);
+                failed.insert((key_hash, day), counter);
+            }
+        }
+
+        // Keep counters that could not be written for the next flush
+        if !failed.is_empty() {
+            let mut usage = self.usage.lock().await;
+            for (key, counter) in failed {
+                let entry = usage.entry(key).or_default();
+                entry.requests += counter.requests;
+                entry.throttled += counter.throttled;
+            }
+        }
+
+        let now = Instant::now();
+        self.buckets
+            .lock()
+            .await
+            .retain(|_, bucket| !bucket.is_full(now));
+    }
+}
+
+/// Middleware resolving the caller, checking the route scope and charging
+/// the rate limiter before the request reaches its handler.
+pub async fn authenticate(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<EitherBody<impl MessageBody>>, actix_web::Error> {
+    match authorize(&req).await {
+        Ok(identity) => {
+            req.extensions_mut().insert(identity);
+            next.call(req)
+                .await
+                .map(ServiceResponse::map_into_left_body)
+        },
+        Err(e) => Ok(req.error_response(ApiError(e)).map_into_right_body()),
+    }
+}
+
+async fn authorize(req: &ServiceRequest) -> Result<ApiIdentity, Error> {
+    let guard = req
+        .app_data::<web::Data<ApiGuard>>()
+        .context("ApiGuard is not registered")?;
+    let state = req
+        .app_data::<web::Data<AppState<State>>>()
+        .context("AppState is not registered")?;
+
+    let ip = client_ip(
+        req.peer_addr(),
+        req.headers(),
+        &state.config.api_trusted_proxies,
+    );
+
+    let identity = guard.identify(state, req.headers(), &ip).await?;
+    let required = required_scope(req.path(), req.query_string());
+
+    if identity.scope < required {
+        return Err(Error::Forbidden(format!("{} scope required", required)));
+    }
+
+    guard
+        .acquire(&identity, &ip, request_cost(req.query_string()))
+        .await?;
+
+    Ok(identity)
+}
+
+/// Periodically persist usage counters and drop idle buckets
+pub async fn usage_flush_task(
+    app_state: AppState<State>,
+    guard: Arc<ApiGuard>,
+) -> Result<(), Error> {
+    let mut flush_interval = interval(USAGE_FLUSH_INTERVAL);
+
+    loop {
+        flush_interval.tick().await;
+        guard.flush(&app_state).await;
+    }
+}
+
+fn required_scope(path: &str, query_string: &str) -> ApiKeyScope {
+    if path.starts_with("/api/admin") || path == "/api/test-push" {
+        return ApiKeyScope::Admin;
+    }
+
+    if is_export(query_string) {
+        return ApiKeyScope::Partner;
+    }
+
+    ApiKeyScope::Public
+}
+
+fn is_export(query_string: &str) -> bool {
+    web::Query::<HashMap<String, String>>::from_query(query_string)
+        .map(|query| query.get("export").is_some_and(|v| v == "true"))
+        .unwrap_or(false)
+}
+
+fn request_cost(query_string: &str) -> f64 {
+    if is_export(query_string) {
+        return EXPORT_COST;
+    }
+
+    1.0
+}
+
+/// IP the request is limited by: the peer address, unless the peer is a
+/// trusted proxy. `X-Forwarded-For` is then read from the right, skipping
+/// trusted proxies, as entries left of them are set by the client.
+fn client_ip(
+    peer: Option<SocketAddr>,
+    headers: &HeaderMap,
+    trusted: &[IpAddr],
+) -> String {
+    let Some(peer) = peer.map(|peer| peer.ip()) else {
+        return String::from("unknown");
+    };
+    if !trusted.contains(&peer) {
+        return peer.to_string();
+    }
+
+    let forwarded = headers
+        .get_all("x-forwarded-for")
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+        .collect::<Vec<_>>();
+
+    for entry in forwarded.into_iter().rev() {
+        match entry.parse::<IpAddr>() {
+            Ok(ip) if trusted.contains(&ip) => continue,
+            Ok(ip) => return ip.to_string(),
+            Err(_) => break,
+        }
+    }
+
+    peer.to_string()
+}
+
+fn extract_key(headers: &HeaderMap) -> Result<Option<String>, Error> {
+    if let Some(value) = headers.get(AUTHORIZATION) {
+        let value = value
+            .to_str()
+            .map_err(|e| Error::HeaderToStrError(e.to_string()))?;
+
+        return match value.strip_prefix("Bearer ") {
+            Some(key) => Ok(Some(key.trim().to_owned())),
+            None => Err(Error::Unauthorized(String::from(
+                "expected Bearer authorization",
+            ))),
+        };
+    }
+
+    if let Some(value) = headers.get(API_KEY_HEADER) {
+        let value = value
+            .to_str()
+            .map_err(|e| Error::HeaderToStrError(e.to_string()))?;
+        return Ok(Some(value.trim().to_owned()));
+    }
+
+    Ok(None)
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+
+    a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::http::header::{HeaderName, HeaderValue};
+
+    #[test]
+    fn test_bucket_allows_burst_then_throttles() {
+        let mut bucket = TokenBucket::new(3.0);
+
+        for _ in 0..3 {
+            assert!(bucket.take(1.0, 3.0, 1.0).is_ok());
+        }
+
+        // Empty bucket refilling one token per second
+        assert_eq!(bucket.take(1.0, 3.0, 1.0), Err(1));
+    }
+
+    #[test]
+    fn test_bucket_caps_cost_at_capacity() {
+        let mut bucket = TokenBucket::new(5.0);
+
+        assert!(bucket.take(EXPORT_COST, 5.0, 1.0).is_ok());
+        assert!(bucket.take(1.0, 5.0, 1.0).is_err());
+    }
+
+    #[test]
+    fn test_required_scope() {
+        assert_eq!(
+            required_scope("/api/admin/api-keys", ""),
+            ApiKeyScope::Admin
+        );
+        assert_eq!(required_scope("/api/test-push", ""), ApiKeyScope::Admin);
+        assert_eq!(required_scope("/api/leases", ""), ApiKeyScope::Public);
+        assert_eq!(
+            required_scope("/api/leases", "export=true"),
+            ApiKeyScope::Partner
+        );
+    }
+
+    #[test]
+    fn test_client_ip_trusts_forwarded_for_from_proxies_only() {
+        let proxy: IpAddr = "10.0.0.2".parse().unwrap();
+        let peer = |ip: &str| Some(SocketAddr::new(ip.parse().unwrap(), 443));
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            HeaderName::from_static("x-forwarded-for"),
+            HeaderValue::from_static("1.1.1.1, 203.0.113.7, 10.0.0.2"),
+        );
+
+        // A client sending the header itself is limited by its address
+        assert_eq!(
+            client_ip(peer("198.51.100.4"), &headers, &[]),
+            "198.51.100.4"
+        );
+        assert_eq!(
+            client_ip(peer("198.51.100.4"), &headers, &[proxy]),
+            "198.51.100.4"
+        );
+        // Behind the proxy, the spoofed leftmost entry is ignored
+        assert_eq!(
+            client_ip(peer("10.0.0.2"), &headers, &[proxy]),
+            "203.0.113.7"
+        );
+        assert_eq!(
+            client_ip(peer("10.0.0.2"), &HeaderMap::new(), &[proxy]),
+            "10.0.0.2"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_bucket_map_is_capped() {
+        let guard = ApiGuard::new();
+        let identity = ApiIdentity {
+            key_hash: None,
+            name: String::from("anonymous"),
+            scope: ApiKeyScope::Public,
+            rate_limit_per_minute: 60,
+            burst: 10,
+        };
+
+        for i in 0..MAX_BUCKETS + 10 {
+            let ip = format!("ip-{}", i);
+            guard.acquire(&identity, &ip, 1.0).await.unwrap();
+        }
+
+        let buckets = guard.buckets.lock().await;
+        assert_eq!(buckets.len(), MAX_BUCKETS + 1);
+        assert!(buckets.contains_key(OVERFLOW_BUCKET));
+    }
+
+    #[tokio::test]
+    async fn test_unknown_keys_are_rate_limited_per_ip() {
+        let guard = ApiGuard::new();
+        let anonymous = ApiIdentity {
+            key_hash: None,
+            name: String::from("anonymous"),
+            scope: ApiKeyScope::Public,
+            rate_limit_per_minute: 60,
+            burst: 2,
+        };
+
+        for _ in 0..2 {
+            assert!(matches!(
+                guard.reject(&anonymous, "198.51.100.4").await,
+                Error::Unauthorized(_)
+            ));
+        }
+        assert!(matches!(
+            guard.reject(&anonymous, "198.51.100.4").await,
+            Error::RateLimited { .. }
+        ));
+        assert!(matches!(
+            guard.reject(&anonymous, "203.0.113.7").await,
+            Error::Unauthorized(_)
+        ));
+    }
+
+    #[test]
+    fn test_request_cost() {
+        assert_eq!(request_cost("export=true&format=csv"), EXPORT_COST);
+        assert_eq!(request_cost("period=3m"), 1.0);
+        assert_eq!(request_cost(""), 1.0);
+    }
+
+    #[test]
+    fn test_extract_key() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(extract_key(&headers).unwrap(), None);
+
+        headers.insert(
+            HeaderName::from_static(API_KEY_HEADER),
+            HeaderValue::from_static("nls_abc"),
+        );
+        assert_eq!(extract_key(&headers).unwrap(), Some("nls_abc".into()));
+
+        headers.insert(AUTHORIZATION, HeaderValue::from_static("Bearer nls_x"));
+        assert_eq!(extract_key(&headers).unwrap(), Some("nls_x".into()));
+
+        headers.insert(AUTHORIZATION, HeaderValue::from_static("Basic abc"));
+        assert!(extract_key(&headers).is_err());
+    }
+}
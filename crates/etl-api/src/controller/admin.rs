@@ -0,0 +1,940 @@
+//! Admin API endpoints
+//!
+//! Operational endpoints under `/api/admin`. Access requires an API key with
+//! the admin scope, enforced by the authentication middleware.
+
+use std::{collections::HashMap, str::FromStr};
+
+use actix_web::{get, post, web, HttpResponse};
+use chrono::{DateTime, Days, NaiveDate, Utc};
+use rand::{distributions::Alphanumeric, Rng as _};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use etl_core::{
+    configuration::{AppState, State},
+    error::Error,
+    helpers::{AdminCommandType, ApiKeyScope, RegistryChange, RegistryEntity},
+    model::{Actions, ApiKey, PushDelivery, PushNotification},
+};
+
+use crate::{
+    auth::{ApiGuard, ApiIdentity},
+    handler::cache_refresher,
+};
+
+/// Length of the random part of a generated key
+const API_KEY_LENGTH: usize = 40;
+
+/// Characters of the key kept in clear to identify it
+const API_KEY_PREFIX_LENGTH: usize = 12;
+
+/// Default token bucket of a new key
+const DEFAULT_RATE_LIMIT_PER_MINUTE: i32 = 600;
+const DEFAULT_BURST: i32 = 120;
+
+/// Default window of the usage report, in days
+const DEFAULT_USAGE_DAYS: u64 = 30;
+
+/// Default and maximum number of rows of the log endpoints
+const DEFAULT_LOG_LIMIT: i64 = 50;
+const MAX_LOG_LIMIT: i64 = 500;
+
+// =============================================================================
+// API Keys
+// =============================================================================
+
+#[utoipa::path(
+    tag = "Admin",
+    responses((status = 200, description = "API keys, without their hash", body = Vec<Object>))
+)]
+#[get("/admin/api-keys")]
+pub async fn api_keys(
+    state: web::Data<AppState<State>>,
+) -> Result<HttpResponse, crate::error::ApiError> {
+    let data = state.database.api_key.get_all().await?;
+    Ok(HttpResponse::Ok().json(data))
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateApiKeyRequest {
+    name: String,
+    scope: String,
+    rate_limit_per_minute: Option<i32>,
+    burst: Option<i32>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CreateApiKeyResponse {
+    /// Plaintext key, only returned once
+    pub key: String,
+    #[schema(value_type = Object)]
+    pub api_key: ApiKey,
+}
+
+#[utoipa::path(
+    tag = "Admin",
+    request_body = CreateApiKeyRequest,
+    responses(
+        (status = 200, body = CreateApiKeyResponse),
+        (status = 400, description = "Invalid parameters"),
+    )
+)]
+#[post("/admin/api-keys")]
+pub async fn create_api_key(
+    state: web::Data<AppState<State>>,
+    data: web::Json<CreateApiKeyRequest>,
+) -> Result<HttpResponse, crate::error::ApiError> {
+    let scope: ApiKeyScope = data.scope.parse()?;
+    let secret: String = rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(API_KEY_LENGTH)
+        .map(char::from)
+        .collect();
+    let key = format!("nls_{}", secret);
+
+    let api_key = ApiKey {
+        key_hash: sha256::digest(&key),
+        key_prefix: key[..API_KEY_PREFIX_LENGTH].to_owned(),
+        name: data.name.to_owned(),
+        scope: scope.to_string(),
+        rate_limit_per_minute: data
+            .rate_limit_per_minute
+            .unwrap_or(DEFAULT_RATE_LIMIT_PER_MINUTE),
+        burst: data.burst.unwrap_or(DEFAULT_BURST),
+        active: true,
+        created_at: Utc::now(),
+    };
+
+    state.database.api_key.insert(&api_key).await?;
+
+    Ok(HttpResponse::Ok().json(CreateApiKeyResponse { key, api_key }))
+}
+
+#[utoipa::path(
+    tag = "Admin",
+    responses((status = 200, description = "Whether a key was revoked", body = Object))
+)]
+#[post("/admin/api-keys/{prefix}/revoke")]
+pub async fn revoke_api_key(
+    state: web::Data<AppState<State>>,
+    guard: web::Data<ApiGuard>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, crate::error::ApiError> {
+    let result = state.database.api_key.deactivate(&path).await?;
+    guard.invalidate_keys();
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "revoked": result.rows_affected() > 0,
+    })))
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct ApiKeyUsageQuery {
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+    key: Option<String>,
+}
+
+#[utoipa::path(
+    tag = "Admin",
+    params(ApiKeyUsageQuery),
+    responses((status = 200, description = "Daily request and throttle counters per key", body = Vec<Object>))
+)]
+#[get("/admin/api-keys/usage")]
+pub async fn api_key_usage(
+    state: web::Data<AppState<State>>,
+    query: web::Query<ApiKeyUsageQuery>,
+) -> Result<HttpResponse, crate::error::ApiError> {
+    let to = query.to.unwrap_or_else(|| Utc::now().date_naive());
+    let from = query.from.unwrap_or_else(|| {
+        to.checked_sub_days(Days::new(DEFAULT_USAGE_DAYS))
+            .unwrap_or(to)
+    });
+
+    let data = state
+        .database
+        .api_key
+        .get_usage(from, to, query.key.to_owned())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(data))
+}
+
+// =============================================================================
+// Configuration
+// =============================================================================
+
+/// Effective configuration of the API process, secrets redacted
+#[utoipa::path(
+    tag = "Admin",
+    responses((status = 200, description = "Settings after the file and environment layers, with defaults applied", body = Object))
+)]
+#[get("/admin/config")]
+pub async fn config(
+    state: web::Data<AppState<State>>,
+) -> Result<HttpResponse, crate::error::ApiError> {
+    Ok(HttpResponse::Ok().json(&state.config))
+}
+
+// =============================================================================
+// Caches
+// =============================================================================
+
+#[utoipa::path(
+    tag = "Admin",
+    responses((status = 200, body = Vec<String>))
+)]
+#[get("/admin/caches")]
+pub async fn caches() -> Result<HttpResponse, crate::error::ApiError> {
+    Ok(HttpResponse::Ok().json(cache_refresher::ALL_CACHE_NAMES))
+}
+
+#[utoipa::path(
+    tag = "Admin",
+    responses(
+        (status = 200, description = "Cache purged", body = Object),
+        (status = 400, description = "Invalid parameters"),
+    )
+)]
+#[post("/admin/caches/{name}/purge")]
+pub async fn purge_cache(
+    state: web::Data<AppState<State>>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, crate::error::ApiError> {
+    if !cache_refresher::purge_cache(&state, &path) {
+        return Err(unknown_cache(&path));
+    }
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({ "purged": *path })))
+}
+
+#[utoipa::path(
+    tag = "Admin",
+    responses(
+        (status = 200, description = "Cache refreshed", body = Object),
+        (status = 400, description = "Invalid parameters"),
+    )
+)]
+#[post("/admin/caches/{name}/refresh")]
+pub async fn refresh_cache(
+    state: web::Data<AppState<State>>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, crate::error::ApiError> {
+    if !cache_refresher::refresh_cache(&state, &path).await? {
+        return Err(unknown_cache(&path));
+    }
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({ "refreshed": *path })))
+}
+
+fn unknown_cache(name: &str) -> crate::error::ApiError {
+    Error::InvalidOption {
+        option: name.to_owned(),
+    }
+    .into()
+}
+
+// =============================================================================
+// Registry
+// =============================================================================
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RegistrySyncResponse {
+    pub active_protocols: Vec<String>,
+    /// Protocols added since the last sync
+    pub added: Vec<String>,
+    /// Protocols deprecated since the last sync
+    pub removed: Vec<String>,
+    /// Every protocol, currency and pool added or deprecated
+    pub changes: Vec<RegistryAuditEntry>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RegistryAuditEntry {
+    /// `protocol`, `currency` or `pool`
+    pub entity: String,
+    pub name: String,
+    /// `added` or `deprecated`
+    pub change: String,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+/// Rerun the protocol and currency discovery of `State::new`, sync the
+/// registry tables and reload the registry of this process. The ingest
+/// process picks the changes up at its next registry reload.
+#[utoipa::path(
+    tag = "Admin",
+    responses((status = 200, body = RegistrySyncResponse))
+)]
+#[post("/admin/registry/sync")]
+pub async fn sync_registry(
+    state: web::Data<AppState<State>>,
+) -> Result<HttpResponse, crate::error::ApiError> {
+    let changes = state.reconcile_registry().await?;
+
+    let mut active_protocols: Vec<String> =
+        state.registry().protocols.keys().cloned().collect();
+    active_protocols.sort();
+
+    let protocols = |kind: RegistryChange| -> Vec<String> {
+        changes
+            .iter()
+            .filter(|(entity, _, change)| {
+                *entity == RegistryEntity::Protocol && *change == kind
+            })
+            .map(|(_, name, _)| name.to_owned())
+            .collect()
+    };
+    let added = protocols(RegistryChange::Added);
+    let removed = protocols(RegistryChange::Deprecated);
+
+    let changes = changes
+        .into_iter()
+        .map(|(entity, name, change)| RegistryAuditEntry {
+            entity: entity.into(),
+            name,
+            change: change.into(),
+            created_at: None,
+        })
+        .collect();
+
+    Ok(HttpResponse::Ok().json(RegistrySyncResponse {
+        active_protocols,
+        added,
+        removed,
+        changes,
+    }))
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct RegistryAuditQuery {
+    limit: Option<i64>,
+    /// `protocol`, `currency` or `pool`
+    entity: Option<String>,
+}
+
+#[utoipa::path(
+    tag = "Admin",
+    params(RegistryAuditQuery),
+    responses(
+        (status = 200, body = Vec<RegistryAuditEntry>),
+        (status = 400, description = "Invalid parameters"),
+    )
+)]
+#[get("/admin/registry/audit")]
+pub async fn registry_audit(
+    state: web::Data<AppState<State>>,
+    query: web::Query<RegistryAuditQuery>,
+) -> Result<HttpResponse, crate::error::ApiError> {
+    let entity = query
+        .entity
+        .as_deref()
+        .map(RegistryEntity::from_str)
+        .transpose()?;
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_LOG_LIMIT)
+        .clamp(1, MAX_LOG_LIMIT);
+
+    let data = state
+        .database
+        .registry_audit
+        .get_recent(entity, limit)
+        .await?
+        .into_iter()
+        .map(|item| RegistryAuditEntry {
+            entity: item.entity,
+            name: item.name,
+            change: item.change,
+            created_at: Some(item.created_at),
+        })
+        .collect::<Vec<_>>();
+
+    Ok(HttpResponse::Ok().json(data))
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ContractHistoryEntry {
+    pub protocol: String,
+    /// `leaser`, `lpp`, `oracle`, `profit` or `reserve`
+    pub role: String,
+    pub contract: String,
+    pub valid_from: i64,
+    /// Last height of the contract, `None` while current
+    pub valid_to: Option<i64>,
+    pub recorded_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct ContractHistoryQuery {
+    protocol: Option<String>,
+}
+
+/// Contracts each protocol role has pointed to, with the heights they were
+/// valid over
+#[utoipa::path(
+    tag = "Admin",
+    params(ContractHistoryQuery),
+    responses((status = 200, body = Vec<ContractHistoryEntry>))
+)]
+#[get("/admin/registry/contracts")]
+pub async fn contract_history(
+    state: web::Data<AppState<State>>,
+    query: web::Query<ContractHistoryQuery>,
+) -> Result<HttpResponse, crate::error::ApiError> {
+    let history = &state.database.protocol_contract_history;
+    let items = match query.protocol.as_deref() {
+        Some(protocol) => history.get_by_protocol(protocol).await?,
+        None => history.get_all().await?,
+    };
+
+    let data = items
+        .into_iter()
+        .map(|item| ContractHistoryEntry {
+            protocol: item.protocol,
+            role: item.role,
+            contract: item.contract,
+            valid_from: item.valid_from,
+            valid_to: item.valid_to,
+            recorded_at: item.recorded_at,
+        })
+        .collect::<Vec<_>>();
+
+    Ok(HttpResponse::Ok().json(data))
+}
+
+// =============================================================================
+// Ingest Commands
+// =============================================================================
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct LogQuery {
+    limit: Option<i64>,
+    action: Option<String>,
+}
+
+impl LogQuery {
+    fn limit(&self) -> i64 {
+        self.limit
+            .unwrap_or(DEFAULT_LOG_LIMIT)
+            .clamp(1, MAX_LOG_LIMIT)
+    }
+}
+
+#[utoipa::path(
+    tag = "Admin",
+    params(LogQuery),
+    responses((status = 200, description = "Queued commands, most recent first", body = Vec<Object>))
+)]
+#[get("/admin/commands")]
+pub async fn commands(
+    state: web::Data<AppState<State>>,
+    query: web::Query<LogQuery>,
+) -> Result<HttpResponse, crate::error::ApiError> {
+    let data = state
+        .database
+        .admin_command
+        .get_recent(query.limit())
+        .await?;
+    Ok(HttpResponse::Ok().json(data))
+}
+
+/// Queue a run of the aggregation task in the ingest process
+#[utoipa::path(
+    tag = "Admin",
+    responses((status = 202, description = "Command queued"))
+)]
+#[post("/admin/commands/aggregation")]
+pub async fn run_aggregation(
+    state: web::Data<AppState<State>>,
+    identity: web::ReqData<ApiIdentity>,
+) -> Result<HttpResponse, crate::error::ApiError> {
+    let command = state
+        .database
+        .admin_command
+        .insert(
+            AdminCommandType::Aggregation,
+            None,
+            None,
+            Some(identity.name.to_owned()),
+        )
+        .await?;
+    Ok(HttpResponse::Accepted().json(command))
+}
+
+/// Queue the decoding of messages ingested before their bodies were decoded
+#[utoipa::path(
+    tag = "Admin",
+    responses((status = 202, description = "Command queued"))
+)]
+#[post("/admin/commands/decode-messages")]
+pub async fn decode_messages(
+    state: web::Data<AppState<State>>,
+    identity: web::ReqData<ApiIdentity>,
+) -> Result<HttpResponse, crate::error::ApiError> {
+    let command = state
+        .database
+        .admin_command
+        .insert(
+            AdminCommandType::DecodeMessages,
+            None,
+            None,
+            Some(identity.name.to_owned()),
+        )
+        .await?;
+    Ok(HttpResponse::Accepted().json(command))
+}
+
+/// Queue the derivation of the staking and governance tables from the
+/// messages indexed before them
+#[utoipa::path(
+    tag = "Admin",
+    responses((status = 202, description = "Command queued"))
+)]
+#[post("/admin/commands/staking-backfill")]
+pub async fn staking_backfill(
+    state: web::Data<AppState<State>>,
+    identity: web::ReqData<ApiIdentity>,
+) -> Result<HttpResponse, crate::error::ApiError> {
+    let command = state
+        .database
+        .admin_command
+        .insert(
+            AdminCommandType::StakingBackfill,
+            None,
+            None,
+            Some(identity.name.to_owned()),
+        )
+        .await?;
+    Ok(HttpResponse::Accepted().json(command))
+}
+
+/// Queue the derivation of the address activity index from the messages
+/// indexed before it
+#[utoipa::path(
+    tag = "Admin",
+    responses((status = 202, description = "Command queued"))
+)]
+#[post("/admin/commands/activity-backfill")]
+pub async fn activity_backfill(
+    state: web::Data<AppState<State>>,
+    identity: web::ReqData<ApiIdentity>,
+) -> Result<HttpResponse, crate::error::ApiError> {
+    let command = state
+        .database
+        .admin_command
+        .insert(
+            AdminCommandType::ActivityBackfill,
+            None,
+            None,
+            Some(identity.name.to_owned()),
+        )
+        .await?;
+    Ok(HttpResponse::Accepted().json(command))
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ResyncRequest {
+    from_height: i64,
+    to_height: i64,
+}
+
+/// Queue a resync of a block range in the ingest process. Blocks already
+/// present in the block table are skipped.
+#[utoipa::path(
+    tag = "Admin",
+    request_body = ResyncRequest,
+    responses(
+        (status = 202, description = "Command queued"),
+        (status = 400, description = "Invalid parameters"),
+    )
+)]
+#[post("/admin/commands/resync")]
+pub async fn resync(
+    state: web::Data<AppState<State>>,
+    identity: web::ReqData<ApiIdentity>,
+    data: web::Json<ResyncRequest>,
+) -> Result<HttpResponse, crate::error::ApiError> {
+    if data.from_height < 1 || data.to_height < data.from_height {
+        return Err(Error::InvalidOption {
+            option: format!("{}-{}", data.from_height, data.to_height),
+        }
+        .into());
+    }
+
+    let command = state
+        .database
+        .admin_command
+        .insert(
+            AdminCommandType::Resync,
+            Some(data.from_height),
+            Some(data.to_height),
+            Some(identity.name.to_owned()),
+        )
+        .await?;
+    Ok(HttpResponse::Accepted().json(command))
+}
+
+// =============================================================================
+// Subscriptions
+// =============================================================================
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct DeactivateSubscriptionsRequest {
+    address: Option<String>,
+    endpoint: Option<String>,
+}
+
+#[utoipa::path(
+    tag = "Admin",
+    request_body = DeactivateSubscriptionsRequest,
+    responses(
+        (status = 200, description = "Number of deactivated subscriptions", body = Object),
+        (status = 400, description = "Invalid parameters"),
+    )
+)]
+#[post("/admin/subscriptions/deactivate")]
+pub async fn deactivate_subscriptions(
+    state: web::Data<AppState<State>>,
+    data: web::Json<DeactivateSubscriptionsRequest>,
+) -> Result<HttpResponse, crate::error::ApiError> {
+    let subscription = &state.database.subscription;
+    let result = match (&data.address, &data.endpoint) {
+        (_, Some(endpoint)) => {
+            subscription.deactivate(endpoint.to_owned()).await?
+        },
+        (Some(address), None) => {
+            subscription
+                .deactivate_by_address(address.to_lowercase())
+                .await?
+        },
+        (None, None) => {
+            return Err(Error::MissingParams(String::from(
+                "address or endpoint",
+            ))
+            .into())
+        },
+    };
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "deactivated": result.rows_affected(),
+    })))
+}
+
+// =============================================================================
+// Action History
+// =============================================================================
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ActionHistoryEntry {
+    pub action: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[utoipa::path(
+    tag = "Admin",
+    params(LogQuery),
+    responses(
+        (status = 200, body = Vec<ActionHistoryEntry>),
+        (status = 400, description = "Invalid parameters"),
+    )
+)]
+#[get("/admin/action-history")]
+pub async fn action_history(
+    state: web::Data<AppState<State>>,
+    query: web::Query<LogQuery>,
+) -> Result<HttpResponse, crate::error::ApiError> {
+    let action_type = match query.action.as_deref() {
+        Some("mp_asset") => Some(Actions::MpAssetAction.to_string()),
+        Some("aggregation") => Some(Actions::AggregationAction.to_string()),
+        Some(action) => {
+            return Err(Error::InvalidOption {
+                option: action.to_owned(),
+            }
+            .into())
+        },
+        None => None,
+    };
+
+    let data = state
+        .database
+        .action_history
+        .get_recent(action_type, query.limit())
+        .await?
+        .into_iter()
+        .map(|item| ActionHistoryEntry {
+            action: match Actions::from_str(&item.action_type) {
+                Ok(Actions::MpAssetAction) => String::from("mp_asset"),
+                Ok(Actions::AggregationAction) => String::from("aggregation"),
+                Err(_) => item.action_type,
+            },
+            created_at: item.created_at,
+        })
+        .collect::<Vec<_>>();
+
+    Ok(HttpResponse::Ok().json(data))
+}
+
+// =============================================================================
+// Push Notifications
+// =============================================================================
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct PushNotificationsQuery {
+    limit: Option<i64>,
+    lease: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PushNotificationEntry {
+    #[serde(flatten)]
+    #[schema(value_type = Object)]
+    pub notification: PushNotification,
+    #[schema(value_type = Vec<Object>)]
+    pub delivered_to: Vec<PushDelivery>,
+}
+
+#[utoipa::path(
+    tag = "Admin",
+    params(PushNotificationsQuery),
+    responses((status = 200, body = Vec<PushNotificationEntry>))
+)]
+#[get("/admin/push-notifications")]
+pub async fn push_notifications(
+    state: web::Data<AppState<State>>,
+    query: web::Query<PushNotificationsQuery>,
+) -> Result<HttpResponse, crate::error::ApiError> {
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_LOG_LIMIT)
+        .clamp(1, MAX_LOG_LIMIT);
+
+    let notifications = state
+        .database
+        .push_notification
+        .get_recent(query.lease.to_owned(), limit)
+        .await?;
+    let ids: Vec<i64> = notifications.iter().map(|n| n.id).collect();
+    let mut deliveries: HashMap<i64, Vec<PushDelivery>> = HashMap::new();
+
+    for delivery in state
+        .database
+        .push_delivery
+        .get_by_notifications(&ids)
+        .await?
+    {
+        deliveries
+            .entry(delivery.notification_id)
+            .or_default()
+            .push(delivery);
+    }
+
+    let data: Vec<PushNotificationEntry> = notifications
+        .into_iter()
+        .map(|notification| PushNotificationEntry {
+            delivered_to: deliveries
+                .remove(&notification.id)
+                .unwrap_or_default(),
+            notification,
+        })
+        .collect();
+
+    Ok(HttpResponse::Ok().json(data))
+}
+
+// =============================================================================
+// Push Delivery Stats
+// =============================================================================
+
+/// Default window of the push statistics, in hours
+const DEFAULT_PUSH_STATS_HOURS: i64 = 24;
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct PushStatsQuery {
+    /// Window in hours, defaults to 24
+    hours: Option<i64>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PushStatsResponse {
+    pub from: DateTime<Utc>,
+    /// Notifications queued in the window per outbox status
+    pub outbox: HashMap<String, i64>,
+    pub deliveries: PushDeliveryStats,
+    pub channels: Vec<PushChannelStats>,
+    pub status_codes: Vec<PushStatusCodeCount>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PushDeliveryStats {
+    pub total: i64,
+    pub delivered: i64,
+    pub failed: i64,
+    pub avg_attempts: Option<f64>,
+    pub avg_latency_ms: Option<f64>,
+    pub p95_latency_ms: Option<f64>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PushChannelStats {
+    /// `web_push`, `webhook`, `email` or `bot`
+    pub channel: String,
+    pub total: i64,
+    pub delivered: i64,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PushStatusCodeCount {
+    /// Last status of the remote service, null when it was not reached
+    pub status_code: Option<i32>,
+    pub count: i64,
+}
+
+#[utoipa::path(
+    tag = "Admin",
+    params(PushStatsQuery),
+    responses(
+        (status = 200, body = PushStatsResponse),
+        (status = 400, description = "Invalid parameters"),
+    )
+)]
+#[get("/admin/push-stats")]
+pub async fn push_stats(
+    state: web::Data<AppState<State>>,
+    query: web::Query<PushStatsQuery>,
+) -> Result<HttpResponse, crate::error::ApiError> {
+    let hours = query.hours.unwrap_or(DEFAULT_PUSH_STATS_HOURS);
+
+    if hours <= 0 {
+        return Err(Error::InvalidOption {
+            option: String::from("hours"),
+        }
+        .into());
+    }
+
+    let from = Utc::now() - chrono::Duration::hours(hours);
+    let (outbox, deliveries, channels, status_codes) = tokio::try_join!(
+        state.database.push_outbox.count_by_status(from),
+        state.database.push_delivery.get_stats(from),
+        state.database.push_delivery.count_by_channel(from),
+        state.database.push_delivery.count_by_status_code(from),
+    )?;
+
+    Ok(HttpResponse::Ok().json(PushStatsResponse {
+        from,
+        outbox: outbox.into_iter().collect(),
+        deliveries: PushDeliveryStats {
+            total: deliveries.total,
+            delivered: deliveries.delivered,
+            failed: deliveries.failed,
+            avg_attempts: deliveries.avg_attempts,
+            avg_latency_ms: deliveries.avg_latency_ms,
+            p95_latency_ms: deliveries.p95_latency_ms,
+        },
+        channels: channels
+            .into_iter()
+            .map(|stats| PushChannelStats {
+                channel: stats.channel,
+                total: stats.total,
+                delivered: stats.delivered,
+            })
+            .collect(),
+        status_codes: status_codes
+            .into_iter()
+            .map(|(status_code, count)| PushStatusCodeCount {
+                status_code,
+                count,
+            })
+            .collect(),
+    }))
+}
+
+// =============================================================================
+// Subscription Stats
+// =============================================================================
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SubscriptionStatsResponse {
+    /// Start of the window of the delivery counts
+    pub from: DateTime<Utc>,
+    pub browsers: Vec<SubscriptionBrowserStats>,
+    /// Active subscriptions per age bucket, youngest first
+    pub ages: Vec<SubscriptionCount>,
+    /// Active subscriptions per VAPID key id
+    pub vapid_keys: Vec<SubscriptionCount>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SubscriptionBrowserStats {
+    /// Browser family from the user agent
+    pub browser: String,
+    pub active: i64,
+    pub inactive: i64,
+    /// Web Push deliveries in the window
+    pub deliveries: i64,
+    pub failed: i64,
+    /// Share of failed deliveries, null without deliveries
+    pub failure_rate: Option<f64>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SubscriptionCount {
+    pub key: String,
+    pub count: i64,
+}
+
+#[utoipa::path(
+    tag = "Admin",
+    params(PushStatsQuery),
+    responses(
+        (status = 200, body = SubscriptionStatsResponse),
+        (status = 400, description = "Invalid parameters"),
+    )
+)]
+#[get("/admin/subscription-stats")]
+pub async fn subscription_stats(
+    state: web::Data<AppState<State>>,
+    query: web::Query<PushStatsQuery>,
+) -> Result<HttpResponse, crate::error::ApiError> {
+    let hours = query.hours.unwrap_or(DEFAULT_PUSH_STATS_HOURS);
+
+    if hours <= 0 {
+        return Err(Error::InvalidOption {
+            option: String::from("hours"),
+        }
+        .into());
+    }
+
+    let from = Utc::now() - chrono::Duration::hours(hours);
+    let subscription = &state.database.subscription;
+    let (browsers, ages, vapid_keys) = tokio::try_join!(
+        subscription.count_by_browser(from),
+        subscription.count_by_age(),
+        subscription.count_by_vapid_key(),
+    )?;
+
+    let counts = |rows: Vec<(String, i64)>| {
+        rows.into_iter()
+            .map(|(key, count)| SubscriptionCount { key, count })
+            .collect()
+    };
+
+    Ok(HttpResponse::Ok().json(SubscriptionStatsResponse {
+        from,
+        browsers: browsers
+            .into_iter()
+            .map(|stats| SubscriptionBrowserStats {
+                failure_rate: (stats.deliveries > 0)
+                    .then(|| stats.failed as f64 / stats.deliveries as f64),
+                browser: stats.browser,
+                active: stats.active,
+                inactive: stats.inactive,
+                deliveries: stats.deliveries,
+                failed: stats.failed,
+            })
+            .collect(),
+        ages: counts(ages),
+        vapid_keys: counts(vapid_keys),
+    }))
+}
@@ -0,0 +1,204 @@
+//! Alert rule endpoints
+//!
+//! Rules belong to a browser push subscription, identified by the wallet
+//! address and the `auth` key of the subscription. They are evaluated by the
+//! ingest process, see `etl_core::alerts`.
+
+use actix_web::{get, post, web, HttpResponse};
+use bigdecimal::BigDecimal;
+use serde::Deserialize;
+use utoipa::{IntoParams, ToSchema};
+
+use etl_core::{
+    alerts,
+    configuration::{AppState, State},
+    dao::postgre::alert_rule::AlertRuleParams,
+    error::Error,
+};
+
+/// Rules a single subscription may hold
+const MAX_RULES_PER_SUBSCRIPTION: i64 = 20;
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct AlertRulesQuery {
+    address: String,
+    auth: String,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct AlertRuleRequest {
+    address: String,
+    auth: String,
+    /// `lease_ltv`, `price`, `lp_earnings` or `position_closed`
+    kind: String,
+    /// Lease of `lease_ltv`, optional lease of `position_closed`
+    lease: Option<String>,
+    /// Currency of `price`
+    symbol: Option<String>,
+    /// Protocol of `price`
+    protocol: Option<String>,
+    /// `above` or `below`, for threshold rules
+    comparison: Option<String>,
+    /// LTV in percent, price or earnings in stable
+    #[schema(value_type = Option<String>)]
+    threshold: Option<BigDecimal>,
+    /// `take-profit` or `stop-loss` for `position_closed`, any close when
+    /// omitted
+    strategy: Option<String>,
+    /// Defaults to true
+    active: Option<bool>,
+}
+
+impl AlertRuleRequest {
+    fn params(&self) -> AlertRuleParams {
+        AlertRuleParams {
+            kind: self.kind.to_owned(),
+            lease: self.lease.to_owned(),
+            symbol: self.symbol.to_owned(),
+            protocol: self.protocol.to_owned(),
+            comparison: self.comparison.to_owned(),
+            threshold: self.threshold.to_owned(),
+            strategy: self.strategy.to_owned(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct DeleteAlertRuleRequest {
+    address: String,
+    auth: String,
+}
+
+#[utoipa::path(
+    tag = "Alerts",
+    params(AlertRulesQuery),
+    responses((status = 200, description = "Alert rules of the subscription", body = Vec<Object>))
+)]
+#[get("/alerts")]
+pub async fn alert_rules(
+    state: web::Data<AppState<State>>,
+    query: web::Query<AlertRulesQuery>,
+) -> Result<HttpResponse, crate::error::ApiError> {
+    let data = state
+        .database
+        .alert_rule
+        .get_by_subscription(query.address.to_owned(), query.auth.to_owned())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(data))
+}
+
+#[utoipa::path(
+    tag = "Alerts",
+    request_body = AlertRuleRequest,
+    responses(
+        (status = 200, description = "Created alert rule", body = Object),
+        (status = 400, description = "Invalid parameters"),
+        (status = 404, description = "Subscription not found"),
+    )
+)]
+#[post("/alerts")]
+pub async fn create_alert_rule(
+    state: web::Data<AppState<State>>,
+    data: web::Json<AlertRuleRequest>,
+) -> Result<HttpResponse, crate::error::ApiError> {
+    let params = alerts::validate(data.params())?;
+    let (exists, count) = tokio::try_join!(
+        state
+            .database
+            .subscription
+            .isExists(data.address.to_owned(), data.auth.to_owned()),
+        async {
+            state
+                .database
+                .alert_rule
+                .count_by_subscription(
+                    data.address.to_owned(),
+                    data.auth.to_owned(),
+                )
+                .await
+                .map_err(Error::from)
+        },
+    )?;
+
+    if !exists {
+        return Err(Error::NotFound(String::from("subscription")).into());
+    }
+
+    if count >= MAX_RULES_PER_SUBSCRIPTION {
+        return Err(Error::InvalidOption {
+            option: format!(
+                "at most {} rules per subscription",
+                MAX_RULES_PER_SUBSCRIPTION
+            ),
+        }
+        .into());
+    }
+
+    let rule = state
+        .database
+        .alert_rule
+        .insert(data.address.to_owned(), data.auth.to_owned(), params)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(rule))
+}
+
+#[utoipa::path(
+    tag = "Alerts",
+    request_body = AlertRuleRequest,
+    responses(
+        (status = 200, description = "Updated alert rule", body = Object),
+        (status = 400, description = "Invalid parameters"),
+        (status = 404, description = "Rule not found"),
+    )
+)]
+#[post("/alerts/{id}")]
+pub async fn update_alert_rule(
+    state: web::Data<AppState<State>>,
+    path: web::Path<i64>,
+    data: web::Json<AlertRuleRequest>,
+) -> Result<HttpResponse, crate::error::ApiError> {
+    let params = alerts::validate(data.params())?;
+    let rule = state
+        .database
+        .alert_rule
+        .update(
+            *path,
+            data.address.to_owned(),
+            data.auth.to_owned(),
+            params,
+            data.active.unwrap_or(true),
+        )
+        .await?
+        .ok_or_else(|| Error::NotFound(format!("alert rule {}", path)))?;
+
+    Ok(HttpResponse::Ok().json(rule))
+}
+
+#[utoipa::path(
+    tag = "Alerts",
+    request_body = DeleteAlertRuleRequest,
+    responses(
+        (status = 200, description = "Alert rule deleted", body = Object),
+        (status = 404, description = "Rule not found"),
+    )
+)]
+#[post("/alerts/{id}/delete")]
+pub async fn delete_alert_rule(
+    state: web::Data<AppState<State>>,
+    path: web::Path<i64>,
+    data: web::Json<DeleteAlertRuleRequest>,
+) -> Result<HttpResponse, crate::error::ApiError> {
+    let result = state
+        .database
+        .alert_rule
+        .delete(*path, data.address.to_owned(), data.auth.to_owned())
+        .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(Error::NotFound(format!("alert rule {}", path)).into());
+    }
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({ "deleted": *path })))
+}
@@ -0,0 +1,346 @@
+//! Notification channel endpoints
+//!
+//! Besides Web Push, a browser subscription can have notifications delivered
+//! to signed webhooks, email addresses and bot chats. Channels are
+//! identified by the wallet address and the `auth` key of the subscription,
+//! like alert rules. Web Push itself can be turned off with the `web_push`
+//! channel.
+//!
+//! Email addresses are delivered to once confirmed with the code mailed to
+//! them, so a subscription can not have mail sent to someone else.
+
+use std::str::FromStr as _;
+
+use actix_web::{get, post, web, HttpResponse};
+use chrono::{TimeDelta, Utc};
+use rand::{distributions::Alphanumeric, Rng as _};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use etl_core::{
+    channel,
+    configuration::{AppState, State},
+    error::Error,
+    helpers::ChannelKind,
+    model::SubscriptionChannel,
+};
+
+/// Channels a single subscription may hold
+const MAX_CHANNELS_PER_SUBSCRIPTION: usize = 10;
+
+/// Length of a generated webhook secret
+const WEBHOOK_SECRET_LENGTH: usize = 48;
+
+/// Length of the code confirming an email address
+const CONFIRMATION_CODE_LENGTH: usize = 12;
+
+/// Minimum time between two codes mailed to an unconfirmed address
+const CONFIRMATION_RESEND_MINUTES: i64 = 15;
+
+/// Time a confirmation code is valid for
+const CONFIRMATION_TTL_HOURS: i64 = 24;
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct ChannelsQuery {
+    address: String,
+    auth: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ChannelsResponse {
+    /// Whether notifications are delivered to the browser itself
+    web_push: bool,
+    #[schema(value_type = Vec<Object>)]
+    channels: Vec<SubscriptionChannel>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ChannelRequest {
+    address: String,
+    auth: String,
+    /// `web_push`, `webhook`, `email` or `bot`
+    channel: String,
+    /// HTTPS URL of a webhook, email address or bot chat id. Ignored for
+    /// `web_push`.
+    target: Option<String>,
+    /// Defaults to true
+    active: Option<bool>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ChannelResponse {
+    #[serde(flatten)]
+    #[schema(value_type = Object)]
+    channel: SubscriptionChannel,
+    /// Signing secret of a webhook
+    secret: Option<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ConfirmChannelRequest {
+    address: String,
+    auth: String,
+    channel: String,
+    target: String,
+    /// Code mailed to the target
+    code: String,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct DeleteChannelRequest {
+    address: String,
+    auth: String,
+    channel: String,
+    target: String,
+}
+
+fn random_string(length: usize) -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(length)
+        .map(char::from)
+        .collect()
+}
+
+fn parse_channel(channel: &str) -> Result<ChannelKind, Error> {
+    ChannelKind::from_str(channel).map_err(|_| Error::InvalidOption {
+        option: channel.to_owned(),
+    })
+}
+
+#[utoipa::path(
+    tag = "Channels",
+    params(ChannelsQuery),
+    responses(
+        (status = 200, body = ChannelsResponse),
+        (status = 404, description = "Subscription not found"),
+    )
+)]
+#[get("/subscribe/channels")]
+pub async fn subscription_channels(
+    state: web::Data<AppState<State>>,
+    query: web::Query<ChannelsQuery>,
+) -> Result<HttpResponse, crate::error::ApiError> {
+    let (subscription, channels) = tokio::try_join!(
+        state
+            .database
+            .subscription
+            .get_one(query.address.to_owned(), query.auth.to_owned()),
+        state.database.subscription_channel.get_by_subscription(
+            query.address.to_owned(),
+            query.auth.to_owned()
+        ),
+    )?;
+
+    let subscription = subscription
+        .ok_or_else(|| Error::NotFound(String::from("subscription")))?;
+
+    Ok(HttpResponse::Ok().json(ChannelsResponse {
+        web_push: subscription.web_push,
+        channels,
+    }))
+}
+
+#[utoipa::path(
+    tag = "Channels",
+    request_body = ChannelRequest,
+    responses(
+        (status = 200, description = "Channel of the subscription, or the Web Push state. A new email address gets a confirmation code and is delivered to once confirmed.", body = ChannelResponse),
+        (status = 400, description = "Invalid or unavailable channel"),
+        (status = 404, description = "Subscription not found"),
+    )
+)]
+#[post("/subscribe/channels")]
+pub async fn set_channel(
+    state: web::Data<AppState<State>>,
+    data: web::Json<ChannelRequest>,
+) -> Result<HttpResponse, crate::error::ApiError> {
+    let kind = parse_channel(&data.channel)?;
+    let active = data.active.unwrap_or(true);
+
+    let (exists, channels) = tokio::try_join!(
+        state
+            .database
+            .subscription
+            .isExists(data.address.to_owned(), data.auth.to_owned()),
+        async {
+            state
+                .database
+                .subscription_channel
+                .get_by_subscription(
+                    data.address.to_owned(),
+                    data.auth.to_owned(),
+                )
+                .await
+                .map_err(Error::from)
+        },
+    )?;
+
+    if !exists {
+        return Err(Error::NotFound(String::from("subscription")).into());
+    }
+
+    if kind == ChannelKind::WebPush {
+        state
+            .database
+            .subscription
+            .set_web_push(data.address.to_owned(), data.auth.to_owned(), active)
+            .await?;
+        return Ok(
+            HttpResponse::Ok().json(serde_json::json!({ "web_push": active }))
+        );
+    }
+
+    if state.channels.get(kind).is_none() {
+        return Err(Error::InvalidOption {
+            option: format!("{} channel not available", kind),
+        }
+        .into());
+    }
+
+    let target = data
+        .target
+        .to_owned()
+        .filter(|t| !t.is_empty())
+        .ok_or_else(|| Error::MissingParams(String::from("target")))?;
+    channel::validate_target(kind, &target).await?;
+
+    let known = channels
+        .iter()
+        .any(|c| c.channel == kind.to_string() && c.target == target);
+    if !known && channels.len() >= MAX_CHANNELS_PER_SUBSCRIPTION {
+        return Err(Error::InvalidOption {
+            option: format!(
+                "at most {} channels per subscription",
+                MAX_CHANNELS_PER_SUBSCRIPTION
+            ),
+        }
+        .into());
+    }
+
+    // Existing webhooks keep their secret
+    let secret = (kind == ChannelKind::Webhook)
+        .then(|| random_string(WEBHOOK_SECRET_LENGTH));
+
+    let channel = state
+        .database
+        .subscription_channel
+        .upsert(
+            data.address.to_owned(),
+            data.auth.to_owned(),
+            kind.to_string(),
+            target,
+            secret,
+            active,
+        )
+        .await?;
+
+    if channel.confirmed_at.is_none() {
+        send_confirmation(&state, &channel.auth, &channel.target).await?;
+    }
+
+    Ok(HttpResponse::Ok().json(ChannelResponse {
+        secret: channel.secret.to_owned(),
+        channel,
+    }))
+}
+
+/// Mail a confirmation code to an unconfirmed email channel, at most once
+/// per resend interval
+async fn send_confirmation(
+    state: &AppState<State>,
+    auth: &str,
+    target: &str,
+) -> Result<(), Error> {
+    let Some(email) = &state.channels.email else {
+        return Ok(());
+    };
+
+    let code = random_string(CONFIRMATION_CODE_LENGTH);
+    let send = state
+        .database
+        .subscription_channel
+        .start_confirmation(
+            auth.to_owned(),
+            ChannelKind::Email.to_string(),
+            target.to_owned(),
+            code.to_owned(),
+            Utc::now() - TimeDelta::minutes(CONFIRMATION_RESEND_MINUTES),
+        )
+        .await?;
+
+    if send {
+        email.send_confirmation(target, &code).await?;
+    }
+
+    Ok(())
+}
+
+#[utoipa::path(
+    tag = "Channels",
+    request_body = ConfirmChannelRequest,
+    responses(
+        (status = 200, description = "Channel confirmed", body = Object),
+        (status = 404, description = "No unconfirmed channel with this code"),
+    )
+)]
+#[post("/subscribe/channels/confirm")]
+pub async fn confirm_channel(
+    state: web::Data<AppState<State>>,
+    data: web::Json<ConfirmChannelRequest>,
+) -> Result<HttpResponse, crate::error::ApiError> {
+    let kind = parse_channel(&data.channel)?;
+    let result = state
+        .database
+        .subscription_channel
+        .confirm(
+            data.address.to_owned(),
+            data.auth.to_owned(),
+            kind.to_string(),
+            data.target.to_owned(),
+            data.code.to_owned(),
+            Utc::now() - TimeDelta::hours(CONFIRMATION_TTL_HOURS),
+        )
+        .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(Error::NotFound(String::from("confirmation code")).into());
+    }
+
+    Ok(
+        HttpResponse::Ok()
+            .json(serde_json::json!({ "confirmed": data.target })),
+    )
+}
+
+#[utoipa::path(
+    tag = "Channels",
+    request_body = DeleteChannelRequest,
+    responses(
+        (status = 200, description = "Channel deleted", body = Object),
+        (status = 404, description = "Channel not found"),
+    )
+)]
+#[post("/subscribe/channels/delete")]
+pub async fn delete_channel(
+    state: web::Data<AppState<State>>,
+    data: web::Json<DeleteChannelRequest>,
+) -> Result<HttpResponse, crate::error::ApiError> {
+    let kind = parse_channel(&data.channel)?;
+    let result = state
+        .database
+        .subscription_channel
+        .delete(
+            data.address.to_owned(),
+            data.auth.to_owned(),
+            kind.to_string(),
+            data.target.to_owned(),
+        )
+        .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(Error::NotFound(format!("{} channel", kind)).into());
+    }
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({ "deleted": data.target })))
+}
@@ -6,8 +6,10 @@ use actix_web::{get, web, HttpResponse, Responder};
 use anyhow::Context as _;
 use bigdecimal::BigDecimal;
 use chrono::{DateTime, Utc};
+use futures::TryStreamExt as _;
 use futures::{future::join_all, TryFutureExt as _};
 use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
 
 use etl_core::{
     cache_keys,
@@ -20,13 +22,13 @@ use etl_core::{
     model::{LS_History, LS_Opening, TokenLoan},
 };
 
-use crate::csv_response::{to_csv_response, to_streaming_csv_response};
+use crate::response::{respond, stream, Format, FormatQuery};
 
 // =============================================================================
 // Leases Search
 // =============================================================================
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
 pub struct LeasesSearchQuery {
     skip: Option<i64>,
     limit: Option<i64>,
@@ -34,6 +36,11 @@ pub struct LeasesSearchQuery {
     search: Option<String>,
 }
 
+#[utoipa::path(
+    tag = "Leases",
+    params(LeasesSearchQuery),
+    responses((status = 200, description = "Lease ids matching the search", body = Vec<String>))
+)]
 #[get("/leases-search")]
 pub async fn leases_search(
     state: web::Data<AppState<State>>,
@@ -63,10 +70,16 @@ pub async fn leases_search(
 // Leases Monthly
 // =============================================================================
 
+#[utoipa::path(
+    tag = "Leases",
+    params(FormatQuery),
+    responses((status = 200, description = "Leases opened per month", body = Vec<Object>))
+)]
 #[get("/leases-monthly")]
 pub async fn leases_monthly(
     state: web::Data<AppState<State>>,
-) -> Result<impl Responder, crate::error::ApiError> {
+    format: Format,
+) -> Result<HttpResponse, crate::error::ApiError> {
     let data = cached_fetch(
         &state.api_cache.leases_monthly,
         cache_keys::LEASES_MONTHLY,
@@ -74,23 +87,29 @@ pub async fn leases_monthly(
     )
     .await?;
 
-    Ok(web::Json(data))
+    respond(format.get(), &data, "leases-monthly")
 }
 
 // =============================================================================
 // Leased Assets
 // =============================================================================
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
 pub struct LeasedAssetsQuery {
     protocol: Option<String>,
 }
 
+#[utoipa::path(
+    tag = "Leases",
+    params(LeasedAssetsQuery, FormatQuery),
+    responses((status = 200, description = "Leased amount per asset", body = Vec<Object>))
+)]
 #[get("/leased-assets")]
 pub async fn leased_assets(
     state: web::Data<AppState<State>>,
     query: web::Query<LeasedAssetsQuery>,
-) -> Result<impl Responder, crate::error::ApiError> {
+    format: Format,
+) -> Result<HttpResponse, crate::error::ApiError> {
     let cache_key =
         build_protocol_cache_key("leased_assets", query.protocol.as_deref());
     let protocol = query.protocol.clone();
@@ -99,7 +118,9 @@ pub async fn leased_assets(
         cached_fetch(&state.api_cache.leased_assets, &cache_key, || async {
             let data = if let Some(protocol_key) = &protocol {
                 let protocol_key = protocol_key.to_uppercase();
-                if let Some(protocol) = state.protocols.get(&protocol_key) {
+                if let Some(protocol) =
+                    state.registry().protocols.get(&protocol_key)
+                {
                     state
                         .database
                         .ls_opening
@@ -115,29 +136,31 @@ pub async fn leased_assets(
         })
         .await?;
 
-    Ok(web::Json(data))
+    respond(format.get(), &data, "leased-assets")
 }
 
 // =============================================================================
 // Lease Value Stats
 // =============================================================================
 
-#[derive(Debug, Deserialize)]
-pub struct LeaseValueStatsQuery {
-    format: Option<String>,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct LeaseValueStat {
     pub asset: String,
+    #[schema(value_type = String)]
     pub avg_value: BigDecimal,
+    #[schema(value_type = String)]
     pub max_value: BigDecimal,
 }
 
+#[utoipa::path(
+    tag = "Leases",
+    params(FormatQuery),
+    responses((status = 200, description = "JSON, CSV, NDJSON or Parquet, see `format`", body = Vec<LeaseValueStat>))
+)]
 #[get("/lease-value-stats")]
 pub async fn lease_value_stats(
     state: web::Data<AppState<State>>,
-    query: web::Query<LeaseValueStatsQuery>,
+    format: Format,
 ) -> Result<HttpResponse, crate::error::ApiError> {
     let data = cached_fetch(
         &state.api_cache.lease_value_stats,
@@ -155,20 +178,23 @@ pub async fn lease_value_stats(
         })
         .collect();
 
-    match query.format.as_deref() {
-        Some("csv") => to_csv_response(&stats, "lease-value-stats.csv"),
-        _ => Ok(HttpResponse::Ok().json(stats)),
-    }
+    respond(format.get(), &stats, "lease-value-stats")
 }
 
 // =============================================================================
 // Loans by Token
 // =============================================================================
 
+#[utoipa::path(
+    tag = "Leases",
+    params(FormatQuery),
+    responses((status = 200, description = "Open loans per token", body = Vec<Object>))
+)]
 #[get("/loans-by-token")]
 pub async fn loans_by_token(
     state: web::Data<AppState<State>>,
-) -> Result<impl Responder, crate::error::ApiError> {
+    format: Format,
+) -> Result<HttpResponse, crate::error::ApiError> {
     let data = cached_fetch(
         &state.api_cache.loans_by_token,
         cache_keys::LOANS_BY_TOKEN,
@@ -186,28 +212,29 @@ pub async fn loans_by_token(
     )
     .await?;
 
-    Ok(web::Json(data))
+    respond(format.get(), &data, "loans-by-token")
 }
 
 // =============================================================================
 // Loans Granted
 // =============================================================================
 
-#[derive(Debug, Deserialize)]
-pub struct LoansGrantedQuery {
-    format: Option<String>,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct LoanGranted {
     pub asset: String,
+    #[schema(value_type = String)]
     pub loan: BigDecimal,
 }
 
+#[utoipa::path(
+    tag = "Leases",
+    params(FormatQuery),
+    responses((status = 200, description = "JSON, CSV, NDJSON or Parquet, see `format`", body = Vec<LoanGranted>))
+)]
 #[get("/loans-granted")]
 pub async fn loans_granted(
     state: web::Data<AppState<State>>,
-    query: web::Query<LoansGrantedQuery>,
+    format: Format,
 ) -> Result<HttpResponse, crate::error::ApiError> {
     let data = cached_fetch(
         &state.api_cache.loans_granted,
@@ -224,17 +251,14 @@ pub async fn loans_granted(
         })
         .collect();
 
-    match query.format.as_deref() {
-        Some("csv") => to_csv_response(&loans, "loans-granted.csv"),
-        _ => Ok(HttpResponse::Ok().json(loans)),
-    }
+    respond(format.get(), &loans, "loans-granted")
 }
 
 // =============================================================================
 // Lease Opening(s) - Single or Batch
 // =============================================================================
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
 pub struct LsOpeningQuery {
     /// Single lease contract ID (for detailed response with fees, history, etc.)
     lease: Option<String>,
@@ -243,20 +267,28 @@ pub struct LsOpeningQuery {
 }
 
 /// Detailed response for single lease lookup
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct LsOpeningResponse {
+    #[schema(value_type = Object)]
     pub lease: LS_Opening,
+    #[schema(value_type = String)]
     pub downpayment_price: BigDecimal,
+    #[schema(value_type = String)]
     pub lpn_price: BigDecimal,
+    #[schema(value_type = String)]
     pub fee: BigDecimal,
+    #[schema(value_type = String)]
     pub repayment_value: BigDecimal,
+    #[schema(value_type = Vec<Object>)]
     pub history: Vec<LS_History>,
 }
 
 /// Simplified response for batch lease lookup
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct LsOpeningBatchItem {
+    #[schema(value_type = Object)]
     pub lease: LS_Opening,
+    #[schema(value_type = String)]
     pub downpayment_price: BigDecimal,
 }
 
@@ -296,6 +328,11 @@ async fn get_ls_opening_batch_item(
     Ok(None)
 }
 
+#[utoipa::path(
+    tag = "Leases",
+    params(LsOpeningQuery),
+    responses((status = 200, description = "Lease details for `lease`, or basic info for each of `leases`", body = Object))
+)]
 #[get("/ls-opening")]
 pub async fn ls_opening(
     state: web::Data<AppState<State>>,
@@ -333,9 +370,10 @@ pub async fn ls_opening(
                 ))?;
 
             let base_currency = state
-                .config
+                .registry()
                 .hash_map_pool_currency
                 .get(&lease.LS_loan_pool_id)
+                .cloned()
                 .context(format!(
                     "currency not found in hash map pool in protocol {}",
                     &protocol
@@ -386,9 +424,10 @@ pub async fn ls_opening(
 
             for repayment in repayments {
                 let currency = state
-                    .config
+                    .registry()
                     .hash_map_currencies
                     .get(&repayment.LS_payment_symbol)
+                    .cloned()
                     .context(format!(
                         "currency not found  {}",
                         &repayment.LS_payment_symbol
@@ -420,18 +459,24 @@ pub async fn ls_opening(
 // Loan Closings
 // =============================================================================
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
 pub struct LsLoanClosingQuery {
     skip: Option<i64>,
     limit: Option<i64>,
     address: String,
 }
 
+#[utoipa::path(
+    tag = "Leases",
+    params(LsLoanClosingQuery, FormatQuery),
+    responses((status = 200, description = "Closed leases of an address", body = Vec<Object>))
+)]
 #[get("/ls-loan-closing")]
 pub async fn ls_loan_closing(
     state: web::Data<AppState<State>>,
     query: web::Query<LsLoanClosingQuery>,
-) -> Result<impl Responder, crate::error::ApiError> {
+    format: Format,
+) -> Result<HttpResponse, crate::error::ApiError> {
     let skip = query.skip.unwrap_or(0);
     let mut limit = query.limit.unwrap_or(10);
 
@@ -445,32 +490,35 @@ pub async fn ls_loan_closing(
         .get_leases(query.address.to_owned(), skip, limit)
         .await?;
 
-    Ok(web::Json(items))
+    respond(format.get(), &items, "ls-loan-closing")
 }
 
 // =============================================================================
 // Liquidations
 // =============================================================================
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
 pub struct LiquidationsQuery {
-    format: Option<String>,
     period: Option<String>,
     from: Option<DateTime<Utc>>,
     export: Option<bool>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct Liquidation {
     pub timestamp: DateTime<Utc>,
     pub ticker: String,
     pub contract_id: String,
     pub user: Option<String>,
     pub transaction_type: Option<String>,
+    #[schema(value_type = String)]
     pub liquidation_amount: BigDecimal,
     pub closed_loan: bool,
+    #[schema(value_type = String)]
     pub down_payment: BigDecimal,
+    #[schema(value_type = String)]
     pub loan: BigDecimal,
+    #[schema(value_type = Option<String>)]
     pub liquidation_price: Option<BigDecimal>,
 }
 
@@ -495,25 +543,25 @@ impl From<etl_core::dao::postgre::ls_liquidation::LiquidationData>
     }
 }
 
+#[utoipa::path(
+    tag = "Leases",
+    params(LiquidationsQuery, FormatQuery),
+    responses((status = 200, description = "JSON, CSV, NDJSON or Parquet, see `format`; `export=true` streams every row, as CSV unless another format is requested", body = Vec<Liquidation>))
+)]
 #[get("/liquidations")]
 pub async fn liquidations(
     state: web::Data<AppState<State>>,
     query: web::Query<LiquidationsQuery>,
+    format: Format,
 ) -> Result<HttpResponse, crate::error::ApiError> {
-    // Handle export=true: return all data as streaming CSV
+    // Handle export=true: stream every row from a database cursor
     if query.export.unwrap_or(false) {
-        let data = cached_fetch(
-            &state.api_cache.liquidations,
-            cache_keys::LIQUIDATIONS,
-            || async {
-                state.database.ls_liquidation.get_all_liquidations().await
-            },
-        )
-        .await?;
-
-        let response: Vec<Liquidation> =
-            data.into_iter().map(Into::into).collect();
-        return to_streaming_csv_response(response, "liquidations.csv");
+        let rows = state
+            .database
+            .ls_liquidation
+            .stream_liquidations(None, None)
+            .map_ok(Liquidation::from);
+        return Ok(stream(format.export(), rows, "liquidations"));
     }
 
     let months = parse_period_months(&query.period)?;
@@ -536,32 +584,30 @@ pub async fn liquidations(
 
     let response: Vec<Liquidation> = data.into_iter().map(Into::into).collect();
 
-    match query.format.as_deref() {
-        Some("csv") => to_csv_response(&response, "liquidations.csv"),
-        _ => Ok(HttpResponse::Ok().json(response)),
-    }
+    respond(format.get(), &response, "liquidations")
 }
 
 // =============================================================================
 // Interest Repayments
 // =============================================================================
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
 pub struct InterestRepaymentsQuery {
-    format: Option<String>,
     period: Option<String>,
     from: Option<DateTime<Utc>>,
     export: Option<bool>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct InterestRepayment {
     pub timestamp: DateTime<Utc>,
     pub contract_id: String,
     pub position_owner: String,
     pub position_type: String,
     pub event_type: String,
+    #[schema(value_type = String)]
     pub loan_interest_repaid: BigDecimal,
+    #[schema(value_type = String)]
     pub margin_interest_repaid: BigDecimal,
 }
 
@@ -583,29 +629,25 @@ impl From<etl_core::dao::postgre::ls_repayment::InterestRepaymentData>
     }
 }
 
+#[utoipa::path(
+    tag = "Leases",
+    params(InterestRepaymentsQuery, FormatQuery),
+    responses((status = 200, description = "JSON, CSV, NDJSON or Parquet, see `format`; `export=true` streams every row, as CSV unless another format is requested", body = Vec<InterestRepayment>))
+)]
 #[get("/interest-repayments")]
 pub async fn interest_repayments(
     state: web::Data<AppState<State>>,
     query: web::Query<InterestRepaymentsQuery>,
+    format: Format,
 ) -> Result<HttpResponse, crate::error::ApiError> {
-    // Handle export=true: return all data as streaming CSV
+    // Handle export=true: stream every row from a database cursor
     if query.export.unwrap_or(false) {
-        let data = cached_fetch(
-            &state.api_cache.interest_repayments,
-            cache_keys::INTEREST_REPAYMENTS,
-            || async {
-                state
-                    .database
-                    .ls_repayment
-                    .get_interest_repayments_with_window(None, None)
-                    .await
-            },
-        )
-        .await?;
-
-        let response: Vec<InterestRepayment> =
-            data.into_iter().map(Into::into).collect();
-        return to_streaming_csv_response(response, "interest-repayments.csv");
+        let rows = state
+            .database
+            .ls_repayment
+            .stream_interest_repayments(None, None)
+            .map_ok(InterestRepayment::from);
+        return Ok(stream(format.export(), rows, "interest-repayments"));
     }
 
     let months = parse_period_months(&query.period)?;
@@ -629,37 +671,38 @@ pub async fn interest_repayments(
     let response: Vec<InterestRepayment> =
         data.into_iter().map(Into::into).collect();
 
-    match query.format.as_deref() {
-        Some("csv") => to_csv_response(&response, "interest-repayments.csv"),
-        _ => Ok(HttpResponse::Ok().json(response)),
-    }
+    respond(format.get(), &response, "interest-repayments")
 }
 
 // =============================================================================
 // Historically Opened
 // =============================================================================
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
 pub struct HistoricallyOpenedQuery {
-    format: Option<String>,
     period: Option<String>,
     from: Option<DateTime<Utc>>,
     export: Option<bool>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct HistoricallyOpened {
     pub contract_id: String,
     pub user: String,
     pub leased_asset: String,
     pub opening_date: DateTime<Utc>,
     pub position_type: String,
+    #[schema(value_type = String)]
     pub down_payment_amount: BigDecimal,
     pub down_payment_asset: String,
+    #[schema(value_type = String)]
     pub loan: BigDecimal,
+    #[schema(value_type = String)]
     pub total_position_amount_lpn: BigDecimal,
+    #[schema(value_type = Option<String>)]
     pub price: Option<BigDecimal>,
     pub open: bool,
+    #[schema(value_type = Option<String>)]
     pub liquidation_price: Option<BigDecimal>,
 }
 
@@ -684,29 +727,25 @@ impl From<etl_core::dao::postgre::ls_opening::HistoricallyOpened>
     }
 }
 
+#[utoipa::path(
+    tag = "Leases",
+    params(HistoricallyOpenedQuery, FormatQuery),
+    responses((status = 200, description = "JSON, CSV, NDJSON or Parquet, see `format`; `export=true` streams every row, as CSV unless another format is requested", body = Vec<HistoricallyOpened>))
+)]
 #[get("/historically-opened")]
 pub async fn historically_opened(
     state: web::Data<AppState<State>>,
     query: web::Query<HistoricallyOpenedQuery>,
+    format: Format,
 ) -> Result<HttpResponse, crate::error::ApiError> {
-    // Handle export=true: return all data as streaming CSV
+    // Handle export=true: stream every row from a database cursor
     if query.export.unwrap_or(false) {
-        let data = cached_fetch(
-            &state.api_cache.historically_opened,
-            cache_keys::HISTORICALLY_OPENED,
-            || async {
-                state
-                    .database
-                    .ls_opening
-                    .get_all_historically_opened()
-                    .await
-            },
-        )
-        .await?;
-
-        let response: Vec<HistoricallyOpened> =
-            data.into_iter().map(Into::into).collect();
-        return to_streaming_csv_response(response, "historically-opened.csv");
+        let rows = state
+            .database
+            .ls_opening
+            .stream_historically_opened(None, None)
+            .map_ok(HistoricallyOpened::from);
+        return Ok(stream(format.export(), rows, "historically-opened"));
     }
 
     let months = parse_period_months(&query.period)?;
@@ -730,29 +769,27 @@ pub async fn historically_opened(
     let response: Vec<HistoricallyOpened> =
         data.into_iter().map(Into::into).collect();
 
-    match query.format.as_deref() {
-        Some("csv") => to_csv_response(&response, "historically-opened.csv"),
-        _ => Ok(HttpResponse::Ok().json(response)),
-    }
+    respond(format.get(), &response, "historically-opened")
 }
 
 // =============================================================================
 // Historically Repaid
 // =============================================================================
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
 pub struct HistoricallyRepaidQuery {
-    format: Option<String>,
     period: Option<String>,
     from: Option<DateTime<Utc>>,
     export: Option<bool>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct HistoricallyRepaid {
     pub contract_id: String,
     pub symbol: String,
+    #[schema(value_type = String)]
     pub loan: BigDecimal,
+    #[schema(value_type = String)]
     pub total_repaid: BigDecimal,
     pub close_timestamp: Option<DateTime<Utc>>,
     pub loan_closed: String,
@@ -775,25 +812,25 @@ impl From<etl_core::dao::postgre::ls_repayment::HistoricallyRepaid>
     }
 }
 
+#[utoipa::path(
+    tag = "Leases",
+    params(HistoricallyRepaidQuery, FormatQuery),
+    responses((status = 200, description = "JSON, CSV, NDJSON or Parquet, see `format`; `export=true` streams every row, as CSV unless another format is requested", body = Vec<HistoricallyRepaid>))
+)]
 #[get("/historically-repaid")]
 pub async fn historically_repaid(
     state: web::Data<AppState<State>>,
     query: web::Query<HistoricallyRepaidQuery>,
+    format: Format,
 ) -> Result<HttpResponse, crate::error::ApiError> {
-    // Handle export=true: return all data as streaming CSV
+    // Handle export=true: stream every row from a database cursor
     if query.export.unwrap_or(false) {
-        let data = cached_fetch(
-            &state.api_cache.historically_repaid,
-            cache_keys::HISTORICALLY_REPAID,
-            || async {
-                state.database.ls_repayment.get_historically_repaid().await
-            },
-        )
-        .await?;
-
-        let response: Vec<HistoricallyRepaid> =
-            data.into_iter().map(Into::into).collect();
-        return to_streaming_csv_response(response, "historically-repaid.csv");
+        let rows = state
+            .database
+            .ls_repayment
+            .stream_historically_repaid(None, None)
+            .map_ok(HistoricallyRepaid::from);
+        return Ok(stream(format.export(), rows, "historically-repaid"));
     }
 
     let months = parse_period_months(&query.period)?;
@@ -817,29 +854,27 @@ pub async fn historically_repaid(
     let response: Vec<HistoricallyRepaid> =
         data.into_iter().map(Into::into).collect();
 
-    match query.format.as_deref() {
-        Some("csv") => to_csv_response(&response, "historically-repaid.csv"),
-        _ => Ok(HttpResponse::Ok().json(response)),
-    }
+    respond(format.get(), &response, "historically-repaid")
 }
 
 // =============================================================================
 // Historically Liquidated
 // =============================================================================
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
 pub struct HistoricallyLiquidatedQuery {
-    format: Option<String>,
     period: Option<String>,
     from: Option<DateTime<Utc>>,
     export: Option<bool>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct HistoricallyLiquidated {
     pub contract_id: String,
     pub asset: String,
+    #[schema(value_type = String)]
     pub loan: BigDecimal,
+    #[schema(value_type = Option<String>)]
     pub total_liquidated: Option<BigDecimal>,
 }
 
@@ -858,32 +893,25 @@ impl From<etl_core::dao::postgre::ls_liquidation::HistoricallyLiquidated>
     }
 }
 
+#[utoipa::path(
+    tag = "Leases",
+    params(HistoricallyLiquidatedQuery, FormatQuery),
+    responses((status = 200, description = "JSON, CSV, NDJSON or Parquet, see `format`; `export=true` streams every row, as CSV unless another format is requested", body = Vec<HistoricallyLiquidated>))
+)]
 #[get("/historically-liquidated")]
 pub async fn historically_liquidated(
     state: web::Data<AppState<State>>,
     query: web::Query<HistoricallyLiquidatedQuery>,
+    format: Format,
 ) -> Result<HttpResponse, crate::error::ApiError> {
-    // Handle export=true: return all data as streaming CSV
+    // Handle export=true: stream every row from a database cursor
     if query.export.unwrap_or(false) {
-        let data = cached_fetch(
-            &state.api_cache.historically_liquidated,
-            cache_keys::HISTORICALLY_LIQUIDATED,
-            || async {
-                state
-                    .database
-                    .ls_liquidation
-                    .get_historically_liquidated()
-                    .await
-            },
-        )
-        .await?;
-
-        let response: Vec<HistoricallyLiquidated> =
-            data.into_iter().map(Into::into).collect();
-        return to_streaming_csv_response(
-            response,
-            "historically-liquidated.csv",
-        );
+        let rows = state
+            .database
+            .ls_liquidation
+            .stream_historically_liquidated(None, None)
+            .map_ok(HistoricallyLiquidated::from);
+        return Ok(stream(format.export(), rows, "historically-liquidated"));
     }
 
     let months = parse_period_months(&query.period)?;
@@ -907,10 +935,5 @@ pub async fn historically_liquidated(
     let response: Vec<HistoricallyLiquidated> =
         data.into_iter().map(Into::into).collect();
 
-    match query.format.as_deref() {
-        Some("csv") => {
-            to_csv_response(&response, "historically-liquidated.csv")
-        },
-        _ => Ok(HttpResponse::Ok().json(response)),
-    }
+    respond(format.get(), &response, "historically-liquidated")
 }
@@ -5,7 +5,9 @@
 use actix_web::{get, web, HttpResponse};
 use bigdecimal::BigDecimal;
 use chrono::{DateTime, Utc};
+use futures::TryStreamExt as _;
 use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
 
 use etl_core::{
     cache_keys,
@@ -14,14 +16,15 @@ use etl_core::{
     helpers::{build_cache_key, cached_fetch, parse_period_months},
 };
 
-use crate::csv_response::{to_csv_response, to_streaming_csv_response};
+use crate::response::{respond, stream, Format, FormatQuery, ResponseFormat};
 
 // =============================================================================
 // Pools (batch endpoint)
 // =============================================================================
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct PoolsResponse {
+    #[schema(value_type = Vec<Object>)]
     pub protocols: Vec<PoolUtilizationLevel>,
     /// Optimal utilization rate threshold (percentage)
     pub optimal: String,
@@ -29,9 +32,16 @@ pub struct PoolsResponse {
 
 /// Batch endpoint to get pool data for all pools in a single request.
 /// Returns utilization levels, supplied/borrowed amounts, and borrow APR.
+/// CSV, NDJSON and Parquet list the pools without the optimal rate.
+#[utoipa::path(
+    tag = "Liquidity",
+    params(FormatQuery),
+    responses((status = 200, body = PoolsResponse))
+)]
 #[get("/pools")]
 pub async fn pools(
     state: web::Data<AppState<State>>,
+    format: Format,
 ) -> Result<HttpResponse, crate::error::ApiError> {
     let data =
         cached_fetch(&state.api_cache.pools, cache_keys::POOLS, || async {
@@ -43,6 +53,11 @@ pub async fn pools(
         })
         .await?;
 
+    let format = format.get();
+    if format != ResponseFormat::Json {
+        return respond(format, &data, "pools");
+    }
+
     Ok(HttpResponse::Ok().json(PoolsResponse {
         protocols: data,
         optimal: String::from("70.00"),
@@ -53,11 +68,19 @@ pub async fn pools(
 // LP Withdraw
 // =============================================================================
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
 pub struct LpWithdrawQuery {
     tx: String,
 }
 
+#[utoipa::path(
+    tag = "Liquidity",
+    params(LpWithdrawQuery),
+    responses(
+        (status = 200, description = "Withdrawal of the transaction", body = Object),
+        (status = 404, description = "Not found"),
+    )
+)]
 #[get("/lp-withdraw")]
 pub async fn lp_withdraw(
     state: web::Data<AppState<State>>,
@@ -76,23 +99,24 @@ pub async fn lp_withdraw(
 // Current Lenders
 // =============================================================================
 
-#[derive(Debug, Deserialize)]
-pub struct CurrentLendersQuery {
-    format: Option<String>,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct Lender {
     pub joined: Option<DateTime<Utc>>,
     pub pool: Option<String>,
     pub lender: String,
+    #[schema(value_type = String)]
     pub lent_stables: BigDecimal,
 }
 
+#[utoipa::path(
+    tag = "Liquidity",
+    params(FormatQuery),
+    responses((status = 200, description = "JSON, CSV, NDJSON or Parquet, see `format`", body = Vec<Lender>))
+)]
 #[get("/current-lenders")]
 pub async fn current_lenders(
     state: web::Data<AppState<State>>,
-    query: web::Query<CurrentLendersQuery>,
+    format: Format,
 ) -> Result<HttpResponse, crate::error::ApiError> {
     let data = cached_fetch(
         &state.api_cache.current_lenders,
@@ -111,29 +135,26 @@ pub async fn current_lenders(
         })
         .collect();
 
-    match query.format.as_deref() {
-        Some("csv") => to_csv_response(&lenders, "current-lenders.csv"),
-        _ => Ok(HttpResponse::Ok().json(lenders)),
-    }
+    respond(format.get(), &lenders, "current-lenders")
 }
 
 // =============================================================================
 // Historical Lenders
 // =============================================================================
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
 pub struct HistoricalLendersQuery {
-    format: Option<String>,
     period: Option<String>,
     from: Option<DateTime<Utc>>,
     export: Option<bool>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct HistoricalLender {
     pub transaction_type: String,
     pub timestamp: DateTime<Utc>,
     pub user: String,
+    #[schema(value_type = String)]
     pub amount: BigDecimal,
     pub pool: String,
 }
@@ -152,25 +173,25 @@ impl From<etl_core::dao::postgre::lp_deposit::HistoricalLender>
     }
 }
 
+#[utoipa::path(
+    tag = "Liquidity",
+    params(HistoricalLendersQuery, FormatQuery),
+    responses((status = 200, description = "JSON, CSV, NDJSON or Parquet, see `format`; `export=true` streams every row, as CSV unless another format is requested", body = Vec<HistoricalLender>))
+)]
 #[get("/historical-lenders")]
 pub async fn historical_lenders(
     state: web::Data<AppState<State>>,
     query: web::Query<HistoricalLendersQuery>,
+    format: Format,
 ) -> Result<HttpResponse, crate::error::ApiError> {
-    // Handle export=true: return all data as streaming CSV
+    // Handle export=true: stream every row from a database cursor
     if query.export.unwrap_or(false) {
-        let data = cached_fetch(
-            &state.api_cache.historical_lenders,
-            cache_keys::HISTORICAL_LENDERS,
-            || async {
-                state.database.lp_deposit.get_all_historical_lenders().await
-            },
-        )
-        .await?;
-
-        let response: Vec<HistoricalLender> =
-            data.into_iter().map(Into::into).collect();
-        return to_streaming_csv_response(response, "historical-lenders.csv");
+        let rows = state
+            .database
+            .lp_deposit
+            .stream_historical_lenders(None, None)
+            .map_ok(HistoricalLender::from);
+        return Ok(stream(format.export(), rows, "historical-lenders"));
     }
 
     let months = parse_period_months(&query.period)?;
@@ -194,8 +215,5 @@ pub async fn historical_lenders(
     let response: Vec<HistoricalLender> =
         data.into_iter().map(Into::into).collect();
 
-    match query.format.as_deref() {
-        Some("csv") => to_csv_response(&response, "historical-lenders.csv"),
-        _ => Ok(HttpResponse::Ok().json(response)),
-    }
+    respond(format.get(), &response, "historical-lenders")
 }
@@ -2,27 +2,35 @@
 //!
 //! Endpoints for TVL, borrowed amounts, supplied funds, open interest, and time series data.
 
+use std::str::FromStr as _;
+
 use actix_web::{get, web, HttpResponse, Responder};
 use bigdecimal::BigDecimal;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, TimeDelta, Utc};
 use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
 
 use etl_core::{
     cache_keys,
     configuration::{AppState, State},
+    error::Error,
     helpers::{
         build_cache_key, build_protocol_cache_key, cached_fetch,
-        parse_period_months,
+        parse_period_months, FeeGroup,
     },
     model::MonthlyActiveWallet,
 };
 
-use crate::csv_response::to_csv_response;
+use crate::response::{respond, Format, FormatQuery};
 
 // =============================================================================
 // Total Value Locked
 // =============================================================================
 
+#[utoipa::path(
+    tag = "Metrics",
+    responses((status = 200, body = TvlResponse))
+)]
 #[get("/total-value-locked")]
 pub async fn total_value_locked(
     state: web::Data<AppState<State>>,
@@ -46,8 +54,9 @@ pub async fn total_value_locked(
     }))
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct TvlResponse {
+    #[schema(value_type = String)]
     pub total_value_locked: BigDecimal,
 }
 
@@ -55,6 +64,10 @@ pub struct TvlResponse {
 // Total Transaction Value
 // =============================================================================
 
+#[utoipa::path(
+    tag = "Metrics",
+    responses((status = 200, body = TotalTxValueResponse))
+)]
 #[get("/total-tx-value")]
 pub async fn total_tx_value(
     state: web::Data<AppState<State>>,
@@ -71,8 +84,9 @@ pub async fn total_tx_value(
     }))
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct TotalTxValueResponse {
+    #[schema(value_type = String)]
     pub total_tx_value: BigDecimal,
 }
 
@@ -80,6 +94,10 @@ pub struct TotalTxValueResponse {
 // Supplied Funds
 // =============================================================================
 
+#[utoipa::path(
+    tag = "Metrics",
+    responses((status = 200, body = SuppliedFundsResponse))
+)]
 #[get("/supplied-funds")]
 pub async fn supplied_funds(
     state: web::Data<AppState<State>>,
@@ -98,8 +116,9 @@ pub async fn supplied_funds(
     Ok(web::Json(SuppliedFundsResponse { amount: data }))
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct SuppliedFundsResponse {
+    #[schema(value_type = String)]
     pub amount: BigDecimal,
 }
 
@@ -107,6 +126,10 @@ pub struct SuppliedFundsResponse {
 // Open Interest
 // =============================================================================
 
+#[utoipa::path(
+    tag = "Metrics",
+    responses((status = 200, body = OpenInterestResponse))
+)]
 #[get("/open-interest")]
 pub async fn open_interest(
     state: web::Data<AppState<State>>,
@@ -123,8 +146,9 @@ pub async fn open_interest(
     }))
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct OpenInterestResponse {
+    #[schema(value_type = String)]
     pub open_interest: BigDecimal,
 }
 
@@ -132,6 +156,10 @@ pub struct OpenInterestResponse {
 // Open Position Value
 // =============================================================================
 
+#[utoipa::path(
+    tag = "Metrics",
+    responses((status = 200, body = OpenPositionValueResponse))
+)]
 #[get("/open-position-value")]
 pub async fn open_position_value(
     state: web::Data<AppState<State>>,
@@ -148,8 +176,9 @@ pub async fn open_position_value(
     }))
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct OpenPositionValueResponse {
+    #[schema(value_type = String)]
     pub open_position_value: BigDecimal,
 }
 
@@ -157,11 +186,16 @@ pub struct OpenPositionValueResponse {
 // Borrowed
 // =============================================================================
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
 pub struct BorrowedQuery {
     protocol: Option<String>,
 }
 
+#[utoipa::path(
+    tag = "Metrics",
+    params(BorrowedQuery),
+    responses((status = 200, body = BorrowedResponse))
+)]
 #[get("/borrowed")]
 pub async fn borrowed(
     state: web::Data<AppState<State>>,
@@ -175,7 +209,9 @@ pub async fn borrowed(
         cached_fetch(&state.api_cache.borrowed, &cache_key, || async {
             let result = if let Some(protocol_key) = &protocol {
                 let protocol_key = protocol_key.to_uppercase();
-                if let Some(protocol) = state.protocols.get(&protocol_key) {
+                if let Some(protocol) =
+                    state.registry().protocols.get(&protocol_key)
+                {
                     state
                         .database
                         .ls_opening
@@ -194,8 +230,9 @@ pub async fn borrowed(
     Ok(web::Json(BorrowedResponse { borrowed }))
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct BorrowedResponse {
+    #[schema(value_type = String)]
     pub borrowed: BigDecimal,
 }
 
@@ -203,18 +240,23 @@ pub struct BorrowedResponse {
 // Supplied/Borrowed History
 // =============================================================================
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
 pub struct SuppliedBorrowedQuery {
     protocol: Option<String>,
-    format: Option<String>,
     period: Option<String>,
     from: Option<DateTime<Utc>>,
 }
 
+#[utoipa::path(
+    tag = "Metrics",
+    params(SuppliedBorrowedQuery, FormatQuery),
+    responses((status = 200, description = "JSON, CSV, NDJSON or Parquet, see `format`", body = Vec<Object>))
+)]
 #[get("/supplied-borrowed-history")]
 pub async fn supplied_borrowed_history(
     state: web::Data<AppState<State>>,
     query: web::Query<SuppliedBorrowedQuery>,
+    format: Format,
 ) -> Result<HttpResponse, crate::error::ApiError> {
     let months = parse_period_months(&query.period)?;
     let period_str = query.period.as_deref().unwrap_or("3m");
@@ -230,7 +272,9 @@ pub async fn supplied_borrowed_history(
     let fetch = || async {
         let data = if let Some(protocol_key) = &protocol {
             let protocol_key = protocol_key.to_uppercase();
-            if let Some(protocol) = state.protocols.get(&protocol_key) {
+            if let Some(protocol) =
+                state.registry().protocols.get(&protocol_key)
+            {
                 state
                     .database
                     .lp_pool_state
@@ -245,6 +289,7 @@ pub async fn supplied_borrowed_history(
             }
         } else {
             let protocols: Vec<String> = state
+                .registry()
                 .protocols
                 .values()
                 .map(|p| p.contracts.lpp.to_owned())
@@ -272,27 +317,29 @@ pub async fn supplied_borrowed_history(
         fetch().await?
     };
 
-    match query.format.as_deref() {
-        Some("csv") => to_csv_response(&data, "supplied-borrowed-history.csv"),
-        _ => Ok(HttpResponse::Ok().json(data)),
-    }
+    respond(format.get(), &data, "supplied-borrowed-history")
 }
 
 // =============================================================================
 // Monthly Active Wallets
 // =============================================================================
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
 pub struct MonthlyActiveWalletsQuery {
-    format: Option<String>,
     period: Option<String>,
     from: Option<DateTime<Utc>>,
 }
 
+#[utoipa::path(
+    tag = "Metrics",
+    params(MonthlyActiveWalletsQuery, FormatQuery),
+    responses((status = 200, description = "JSON, CSV, NDJSON or Parquet, see `format`", body = Vec<Object>))
+)]
 #[get("/monthly-active-wallets")]
 pub async fn monthly_active_wallets(
     state: web::Data<AppState<State>>,
     query: web::Query<MonthlyActiveWalletsQuery>,
+    format: Format,
 ) -> Result<HttpResponse, crate::error::ApiError> {
     let months = parse_period_months(&query.period)?;
     let period_str = query.period.as_deref().unwrap_or("3m");
@@ -322,8 +369,103 @@ pub async fn monthly_active_wallets(
         fetch().await?
     };
 
-    match query.format.as_deref() {
-        Some("csv") => to_csv_response(&data, "monthly-active-wallets.csv"),
-        _ => Ok(HttpResponse::Ok().json(data)),
+    respond(format.get(), &data, "monthly-active-wallets")
+}
+
+// =============================================================================
+// Transaction Failure Rate
+// =============================================================================
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct TxFailureRateQuery {
+    /// Defaults to 30 days before `to`
+    from: Option<DateTime<Utc>>,
+    /// Defaults to now
+    to: Option<DateTime<Utc>>,
+}
+
+#[utoipa::path(
+    tag = "Metrics",
+    params(TxFailureRateQuery, FormatQuery),
+    responses(
+        (status = 200, description = "Share of failed contract executions per protocol, with the failures per reason", body = Vec<Object>),
+        (status = 400, description = "Invalid range"),
+    )
+)]
+#[get("/tx-failure-rate")]
+pub async fn tx_failure_rate(
+    state: web::Data<AppState<State>>,
+    query: web::Query<TxFailureRateQuery>,
+    format: Format,
+) -> Result<HttpResponse, crate::error::ApiError> {
+    let to = query.to.unwrap_or_else(Utc::now);
+    let from = query.from.unwrap_or(to - TimeDelta::days(30));
+    if from > to {
+        return Err(Error::InvalidOption {
+            option: String::from("from before to"),
+        }
+        .into());
+    }
+
+    let data = state
+        .database
+        .raw_message
+        .get_failure_rates(from, to)
+        .await?;
+
+    respond(format.get(), &data, "tx-failure-rate")
+}
+
+// =============================================================================
+// Fees
+// =============================================================================
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct FeesQuery {
+    /// `action` (default), `type` or `address`
+    group: Option<String>,
+    /// Only the fees paid by this address
+    address: Option<String>,
+    /// Defaults to 30 days before `to`
+    from: Option<DateTime<Utc>>,
+    /// Defaults to now
+    to: Option<DateTime<Utc>>,
+}
+
+#[utoipa::path(
+    tag = "Metrics",
+    params(FeesQuery, FormatQuery),
+    responses(
+        (status = 200, description = "Daily fees and gas by contract action, message type or fee payer", body = Vec<Object>),
+        (status = 400, description = "Invalid group or range"),
+    )
+)]
+#[get("/fees")]
+pub async fn fees(
+    state: web::Data<AppState<State>>,
+    query: web::Query<FeesQuery>,
+    format: Format,
+) -> Result<HttpResponse, crate::error::ApiError> {
+    let group = FeeGroup::from_str(query.group.as_deref().unwrap_or("action"))?;
+    let to = query.to.unwrap_or_else(Utc::now);
+    let from = query.from.unwrap_or(to - TimeDelta::days(30));
+    if from > to {
+        return Err(Error::InvalidOption {
+            option: String::from("from before to"),
+        }
+        .into());
     }
+
+    let data = state
+        .database
+        .fee_daily
+        .get_series(
+            group,
+            query.address.as_deref().map(str::to_lowercase),
+            from,
+            to,
+        )
+        .await?;
+
+    respond(format.get(), &data, "fees")
 }
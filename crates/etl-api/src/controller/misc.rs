@@ -8,31 +8,54 @@ use actix_web::{get, post, web, HttpRequest, HttpResponse, Responder};
 use anyhow::Context;
 use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
 
 use etl_core::{
     configuration::{AppState, State},
+    dao::postgre::raw_message::{TxsCursor, TxsParams},
     error::Error,
-    helpers::{Filter_Types, Status},
-    model, push, types,
-    types::{Bucket_Type, PushData, PUSH_TYPES},
+    helpers::{Filter_Types, IbcTransferStatus, Status, TxStatus},
+    model,
+    push::{self, Recipient},
+    telemetry,
+    template::DEFAULT_LOCALE,
+    types,
+    types::{
+        Bucket_Type, DigestItem, DigestPush, LeasePush, PushData, PUSH_TYPES,
+    },
 };
 
+use crate::response::{respond, Format, FormatQuery, ResponseFormat};
+
 // =============================================================================
 // Prices
 // =============================================================================
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
 pub struct PricesQuery {
     interval: i64,
     protocol: String,
     key: String,
 }
 
+/// Price of a protocol asset at a time, in milliseconds since the epoch
+#[derive(Debug, Serialize)]
+struct Price {
+    timestamp: i64,
+    price: f64,
+}
+
+#[utoipa::path(
+    tag = "Misc",
+    params(PricesQuery, FormatQuery),
+    responses((status = 200, description = "Price series of a protocol asset: `[milliseconds, price]` pairs in JSON, `timestamp` and `price` columns in CSV, NDJSON and Parquet", body = Vec<Vec<f64>>))
+)]
 #[get("/prices")]
 pub async fn prices(
     state: web::Data<AppState<State>>,
     query: web::Query<PricesQuery>,
-) -> Result<impl Responder, crate::error::ApiError> {
+    format: Format,
+) -> Result<HttpResponse, crate::error::ApiError> {
     let mut interval = query.interval;
 
     if interval > 100 {
@@ -62,7 +85,17 @@ pub async fn prices(
         prices.push((ms, p));
     }
 
-    Ok(web::Json(prices))
+    let format = format.get();
+    if format == ResponseFormat::Json {
+        return respond(format, &prices, "prices");
+    }
+
+    let prices: Vec<Price> = prices
+        .into_iter()
+        .map(|(timestamp, price)| Price { timestamp, price })
+        .collect();
+
+    respond(format, &prices, "prices")
 }
 
 fn get_interval_group(interval: i64) -> i32 {
@@ -79,6 +112,10 @@ fn get_interval_group(interval: i64) -> i32 {
 // Blocks
 // =============================================================================
 
+#[utoipa::path(
+    tag = "Misc",
+    responses((status = 200, description = "Indexed block statistics", body = Object))
+)]
 #[get("/blocks")]
 pub async fn blocks(
     state: web::Data<AppState<State>>,
@@ -91,19 +128,34 @@ pub async fn blocks(
 // Transactions
 // =============================================================================
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
 pub struct TxsQuery {
     skip: Option<i64>,
     limit: Option<i64>,
     filter: Option<String>,
     to: Option<String>,
     address: String,
+    /// `success` or `failed`; failed messages carry the `codespace`,
+    /// `raw_log` and `failure` reason of their transaction
+    status: Option<String>,
+    /// `block:tx_hash:index:inner_index` of the last message of the
+    /// previous page, used instead of `skip`
+    cursor: Option<String>,
 }
 
+#[utoipa::path(
+    tag = "Misc",
+    params(TxsQuery, FormatQuery),
+    responses(
+        (status = 200, description = "Transactions of an address, with the decoded message in `data`", body = Vec<Object>),
+        (status = 400, description = "Invalid parameters"),
+    )
+)]
 #[get("/txs")]
 pub async fn txs(
     state: web::Data<AppState<State>>,
     query: web::Query<TxsQuery>,
+    format: Format,
 ) -> Result<HttpResponse, crate::error::ApiError> {
     let skip = query.skip.unwrap_or(0);
     let mut limit = query.limit.unwrap_or(10);
@@ -132,6 +184,16 @@ pub async fn txs(
     }
 
     let address = query.address.to_lowercase().to_owned();
+    let status = query
+        .status
+        .as_deref()
+        .map(TxStatus::from_str)
+        .transpose()?;
+    let cursor = query
+        .cursor
+        .as_deref()
+        .map(TxsCursor::from_str)
+        .transpose()?;
 
     for filter in &filters {
         let item = Filter_Types::from_str(filter)?;
@@ -173,32 +235,112 @@ pub async fn txs(
         combine = true;
     }
 
-    let data = state
+    let mut data = state
         .database
         .raw_message
-        .get(address.to_owned(), skip, limit, filters, to, combine)
+        .get(TxsParams {
+            address: address.to_owned(),
+            skip,
+            limit,
+            filter: filters,
+            to,
+            combine,
+            status,
+            cursor,
+        })
         .await?;
 
-    Ok(HttpResponse::Ok().json(data))
+    // Tabular formats carry the decoded message as JSON text
+    let format = format.get();
+    if matches!(format, ResponseFormat::Csv | ResponseFormat::Parquet) {
+        for message in &mut data {
+            message.data = message
+                .data
+                .take()
+                .map(|data| serde_json::Value::String(data.to_string()));
+        }
+    }
+
+    respond(format, &data, "txs")
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct IbcTransfersQuery {
+    address: String,
+    /// Comma separated states, defaults to `pending,failed,timed_out`
+    status: Option<String>,
+    skip: Option<i64>,
+    limit: Option<i64>,
+}
+
+#[utoipa::path(
+    tag = "Misc",
+    params(IbcTransfersQuery, FormatQuery),
+    responses(
+        (status = 200, description = "IBC transfers sent or received by an address", body = Vec<Object>),
+        (status = 400, description = "Invalid parameters"),
+    )
+)]
+#[get("/ibc-transfers")]
+pub async fn ibc_transfers(
+    state: web::Data<AppState<State>>,
+    query: web::Query<IbcTransfersQuery>,
+    format: Format,
+) -> Result<HttpResponse, crate::error::ApiError> {
+    let skip = query.skip.unwrap_or(0).max(0);
+    let limit = query.limit.unwrap_or(10).clamp(1, 100);
+
+    let statuses = match query.status.as_deref() {
+        Some(status) => status
+            .split(',')
+            .filter(|s| !s.is_empty())
+            .map(|s| {
+                IbcTransferStatus::from_str(s)
+                    .map(String::from)
+                    .map_err(|_| Error::InvalidOption {
+                        option: s.to_owned(),
+                    })
+            })
+            .collect::<Result<Vec<_>, _>>()?,
+        None => vec![
+            IbcTransferStatus::Pending.to_string(),
+            IbcTransferStatus::Failed.to_string(),
+            IbcTransferStatus::TimedOut.to_string(),
+        ],
+    };
+
+    let data = state
+        .database
+        .ibc_transfer
+        .get_by_address(query.address.to_lowercase(), statuses, skip, limit)
+        .await?;
+
+    respond(format.get(), &data, "ibc_transfers")
 }
 
 // =============================================================================
 // History Stats
 // =============================================================================
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
 pub struct HistoryStatsQuery {
     address: String,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
 pub struct HistoryStatsResponse {
     pub pnl: f64,
     pub tx_volume: f64,
     pub win_rate: f64,
+    #[schema(value_type = Vec<Object>)]
     pub bucket: Vec<Bucket_Type>,
 }
 
+#[utoipa::path(
+    tag = "Misc",
+    params(HistoryStatsQuery),
+    responses((status = 200, body = HistoryStatsResponse))
+)]
 #[get("/history-stats")]
 pub async fn history_stats(
     state: web::Data<AppState<State>>,
@@ -255,11 +397,15 @@ pub async fn history_stats(
 // Version
 // =============================================================================
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct VersionResponse<'a> {
     pub version: Option<&'a str>,
 }
 
+#[utoipa::path(
+    tag = "Misc",
+    responses((status = 200, body = VersionResponse))
+)]
 #[get("/version")]
 pub async fn version() -> Result<impl Responder, crate::error::ApiError> {
     const VERSION: Option<&str> = option_env!("CARGO_PKG_VERSION");
@@ -267,22 +413,50 @@ pub async fn version() -> Result<impl Responder, crate::error::ApiError> {
     Ok(web::Json(VersionResponse { version: VERSION }))
 }
 
+// =============================================================================
+// Prometheus
+// =============================================================================
+
+/// Prometheus metrics of the process, served outside `/api` and its API
+/// key, so left out of the OpenAPI specification
+#[get("/metrics")]
+pub async fn prometheus(
+    state: web::Data<AppState<State>>,
+) -> Result<impl Responder, crate::error::ApiError> {
+    let body = telemetry::metrics().render(&state.database.pool)?;
+
+    Ok(HttpResponse::Ok()
+        .content_type(telemetry::CONTENT_TYPE)
+        .body(body))
+}
+
 // =============================================================================
 // Subscribe
 // =============================================================================
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, IntoParams)]
 pub struct SubscribeQuery {
     address: String,
     auth: String,
     active: bool,
+    /// Check the subscription to a single lease
+    lease: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct SubscribeResponse {
     pub result: bool,
 }
 
+#[utoipa::path(
+    tag = "Misc",
+    request_body(content = Object, description = "Web push subscription"),
+    responses(
+        (status = 200, description = "Subscription status", body = String, content_type = "text/plain"),
+        (status = 403, description = "`lease` is not a lease of `address`"),
+        (status = 404, description = "`lease` is not indexed")
+    )
+)]
 #[post("/subscribe")]
 pub async fn subscribe_post(
     state: web::Data<AppState<State>>,
@@ -312,6 +486,24 @@ pub async fn subscribe_post(
         None
     };
 
+    let locale = match subscription.locale.as_deref() {
+        Some(locale) => Some(parse_locale(locale)?),
+        None => None,
+    };
+
+    let vapid_key_id = match &subscription.vapid_key_id {
+        Some(id) if state.config.vapid_keys.iter().any(|key| &key.id == id) => {
+            id.to_owned()
+        },
+        Some(_) => {
+            return Err(Error::InvalidOption {
+                option: String::from("vapid_key_id"),
+            }
+            .into())
+        },
+        None => state.config.vapid_key_id.to_owned(),
+    };
+
     let data = model::Subscription {
         active: None,
         address: subscription.address.to_owned(),
@@ -321,8 +513,18 @@ pub async fn subscribe_post(
         ip,
         user_agent,
         expiration,
+        web_push: true,
+        locale: locale
+            .to_owned()
+            .unwrap_or_else(|| String::from(DEFAULT_LOCALE)),
+        vapid_key_id,
+        created_at: Utc::now(),
     };
 
+    if let Some(lease) = subscription.lease.to_owned() {
+        return subscribe_lease(&state, data, lease, locale).await;
+    }
+
     let (_, item) = tokio::try_join!(
         state
             .database
@@ -349,6 +551,18 @@ pub async fn subscribe_post(
             )
             .await?;
 
+        if let Some(locale) = locale {
+            state
+                .database
+                .subscription
+                .set_locale(
+                    subscription.address.to_owned(),
+                    subscription.data.keys.auth.to_owned(),
+                    locale,
+                )
+                .await?;
+        }
+
         let b = if active {
             String::from(Status::Subscribed)
         } else {
@@ -363,16 +577,160 @@ pub async fn subscribe_post(
     Ok(HttpResponse::Ok().body(String::from(Status::Subscribed)))
 }
 
+/// Locale of a subscription, e.g. `en` or `es-AR`. Locales without
+/// templates are stored as given and rendered in English.
+fn parse_locale(locale: &str) -> Result<String, Error> {
+    let valid = (2..=16).contains(&locale.len())
+        && locale
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+
+    if !valid {
+        return Err(Error::InvalidOption {
+            option: String::from("locale"),
+        });
+    }
+
+    Ok(locale.replace('_', "-"))
+}
+
+/// Toggles the subscription of a browser to a single lease of the wallet.
+/// The browser subscription is registered first when the wallet has none
+/// yet.
+async fn subscribe_lease(
+    state: &AppState<State>,
+    data: model::Subscription,
+    lease: String,
+    locale: Option<String>,
+) -> Result<HttpResponse, crate::error::ApiError> {
+    let owner = state
+        .database
+        .ls_opening
+        .get_owner(lease.to_owned())
+        .await?;
+    match owner {
+        Some(owner) if owner == data.address => {},
+        Some(_) => {
+            return Err(Error::Forbidden(String::from(
+                "lease of another wallet",
+            ))
+            .into())
+        },
+        None => return Err(Error::NotFound(format!("lease {}", lease)).into()),
+    }
+
+    let exists = state
+        .database
+        .subscription
+        .get_one(data.address.to_owned(), data.auth.to_owned())
+        .await?;
+    let address = data.address.to_owned();
+    let auth = data.auth.to_owned();
+
+    match (exists, locale) {
+        (None, _) => {
+            state.database.subscription.insert(data).await?;
+        },
+        (Some(_), Some(locale)) => {
+            state
+                .database
+                .subscription
+                .set_locale(address.to_owned(), auth.to_owned(), locale)
+                .await?;
+        },
+        (Some(_), None) => {},
+    }
+
+    let item = state
+        .database
+        .subscription_lease
+        .get_one(auth.to_owned(), lease.to_owned())
+        .await?;
+
+    let active = match item {
+        Some(item) => {
+            let active = !item.active;
+            state
+                .database
+                .subscription_lease
+                .update(active, auth, lease)
+                .await?;
+            active
+        },
+        None => {
+            state
+                .database
+                .subscription_lease
+                .insert(address, auth, lease)
+                .await?;
+            true
+        },
+    };
+
+    let b = if active {
+        String::from(Status::Subscribed)
+    } else {
+        String::from(Status::Unsubscribed)
+    };
+
+    Ok(HttpResponse::Ok().body(b))
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct VapidKeyResponse {
+    /// Sent back as `vapid_key_id` when subscribing
+    pub key_id: String,
+    /// Application server key of the push subscription, base64url
+    pub public_key: String,
+}
+
+/// VAPID key new browser subscriptions should be created with
+#[utoipa::path(
+    tag = "Misc",
+    responses((status = 200, body = VapidKeyResponse))
+)]
+#[get("/subscribe/vapid-key")]
+pub async fn vapid_key(
+    state: web::Data<AppState<State>>,
+) -> Result<HttpResponse, crate::error::ApiError> {
+    let key = state
+        .config
+        .vapid_keys
+        .iter()
+        .find(|key| key.id == state.config.vapid_key_id)
+        .ok_or_else(|| Error::NotFound(String::from("VAPID key")))?;
+
+    Ok(HttpResponse::Ok().json(VapidKeyResponse {
+        key_id: key.id.to_owned(),
+        public_key: String::from_utf8_lossy(&key.public_key).trim().to_owned(),
+    }))
+}
+
+#[utoipa::path(
+    tag = "Misc",
+    params(SubscribeQuery),
+    responses((status = 200, body = SubscribeResponse))
+)]
 #[get("/subscribe")]
 pub async fn subscribe_get(
     state: web::Data<AppState<State>>,
     query: web::Query<SubscribeQuery>,
 ) -> Result<HttpResponse, crate::error::ApiError> {
-    let result = state
-        .database
-        .subscription
-        .isExists(query.address.to_owned(), query.auth.to_owned())
-        .await?;
+    let result = match &query.lease {
+        Some(lease) => state
+            .database
+            .subscription_lease
+            .get_one(query.auth.to_owned(), lease.to_owned())
+            .await?
+            .is_some_and(|item| item.active),
+        None => {
+            state
+                .database
+                .subscription
+                .isExists(query.address.to_owned(), query.auth.to_owned())
+                .await?
+        },
+    };
 
     Ok(HttpResponse::Ok().json(SubscribeResponse { result }))
 }
@@ -381,92 +739,133 @@ pub async fn subscribe_get(
 // Test Push
 // =============================================================================
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct TestPushResponse {
     pub data: bool,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
 pub struct TestPushQuery {
-    auth: Option<String>,
     r#type: String,
     address: String,
 }
 
+/// Requires an admin API key, see `auth::required_scope`
+#[utoipa::path(
+    tag = "Misc",
+    params(TestPushQuery),
+    responses((status = 200, body = TestPushResponse))
+)]
 #[get("/test-push")]
 pub async fn test_push(
     state: web::Data<AppState<State>>,
     query: web::Query<TestPushQuery>,
 ) -> Result<HttpResponse, crate::error::ApiError> {
-    let auth = query.auth.to_owned().context("Auth is required")?;
-
-    if auth != state.config.auth {
-        return Ok(HttpResponse::Ok().json(TestPushResponse { data: false }));
-    };
-
     let push_type = PUSH_TYPES::from_str(&query.r#type)?;
 
     let push_data = match push_type {
-        PUSH_TYPES::Funding => push_funding(),
-        PUSH_TYPES::FundingRecommended => push_funding_recommended(),
-        PUSH_TYPES::FundNow => push_fund_now(),
-        PUSH_TYPES::PartiallyLiquidated => push_partially_liquidated(),
-        PUSH_TYPES::FullyLiquidated => push_fully_liquidated(),
+        PUSH_TYPES::Funding => push_warning(PUSH_TYPES::Funding, 1, 850),
+        PUSH_TYPES::FundingRecommended => {
+            push_warning(PUSH_TYPES::FundingRecommended, 2, 865)
+        },
+        PUSH_TYPES::FundNow => push_warning(PUSH_TYPES::FundNow, 3, 865),
+        PUSH_TYPES::PartiallyLiquidated => {
+            push_liquidated(PUSH_TYPES::PartiallyLiquidated)
+        },
+        PUSH_TYPES::FullyLiquidated => {
+            push_liquidated(PUSH_TYPES::FullyLiquidated)
+        },
+        PUSH_TYPES::Alert => push_alert(),
+        PUSH_TYPES::Digest => push_digest(),
         PUSH_TYPES::Unsupported => push_unsupported(),
-    };
+    }
+    .map_err(Error::from)?;
+
+    // Delivered by the push outbox worker of the ingest process
+    let mut transaction = state.database.pool.begin().await?;
+    push::enqueue(
+        &state,
+        Recipient::Wallet(query.address.to_owned()),
+        push_data,
+        &mut transaction,
+    )
+    .await?;
+    transaction.commit().await?;
 
-    push::send(state.as_ref().clone(), query.address.to_owned(), push_data)
-        .await?;
     Ok(HttpResponse::Ok().json(TestPushResponse { data: true }))
 }
 
-fn push_funding() -> PushData {
-    PushData {
-        r#type: PUSH_TYPES::Funding.to_string(),
-        body: format!(
-            r#"{{"level": {}, "ltv": {}, "position": "nolus1tgjl63gyrpwx6323vgeehcenwj8myvdurzfd0pskrgyrl2pqp98sx527j4"}}"#,
-            1, 850
-        ),
-    }
-}
-
-fn push_funding_recommended() -> PushData {
-    PushData {
-        r#type: PUSH_TYPES::FundingRecommended.to_string(),
-        body: format!(
-            r#"{{"level": {}, "ltv": {}, "position": "nolus1tgjl63gyrpwx6323vgeehcenwj8myvdurzfd0pskrgyrl2pqp98sx527j4"}}"#,
-            2, 865
-        ),
-    }
+const TEST_POSITION: &str =
+    "nolus1tgjl63gyrpwx6323vgeehcenwj8myvdurzfd0pskrgyrl2pqp98sx527j4";
+const TEST_PROTOCOL: &str = "OSMOSIS-OSMOSIS-USDC_NOBLE";
+
+fn push_warning(
+    push_type: PUSH_TYPES,
+    level: i16,
+    ltv: i64,
+) -> Result<PushData, serde_json::Error> {
+    PushData::new(
+        push_type,
+        &LeasePush {
+            position: String::from(TEST_POSITION),
+            level: Some(level),
+            ltv: Some(ltv),
+            ltv_percent: Some(format!("{:.1}", ltv as f64 / 10.0)),
+            symbol: Some(String::from("ATOM")),
+            amount_stable: Some(String::from("1250.00")),
+            protocol: Some(String::from(TEST_PROTOCOL)),
+            ..LeasePush::default()
+        },
+    )
 }
 
-fn push_fund_now() -> PushData {
-    PushData {
-        r#type: PUSH_TYPES::FundNow.to_string(),
-        body: format!(
-            r#"{{"level": {}, "ltv": {}, "position": "nolus1tgjl63gyrpwx6323vgeehcenwj8myvdurzfd0pskrgyrl2pqp98sx527j4"}}"#,
-            3, 865
-        ),
-    }
+fn push_liquidated(
+    push_type: PUSH_TYPES,
+) -> Result<PushData, serde_json::Error> {
+    PushData::new(
+        push_type,
+        &LeasePush {
+            position: String::from(TEST_POSITION),
+            symbol: Some(String::from("ATOM")),
+            amount: Some(String::from("12.5")),
+            amount_stable: Some(String::from("61.25")),
+            protocol: Some(String::from(TEST_PROTOCOL)),
+            ..LeasePush::default()
+        },
+    )
 }
 
-fn push_partially_liquidated() -> PushData {
-    PushData {
-        r#type: PUSH_TYPES::PartiallyLiquidated.to_string(),
-        body: r#"{"position": "nolus1tgjl63gyrpwx6323vgeehcenwj8myvdurzfd0pskrgyrl2pqp98sx527j4"}"#.to_string(),
-    }
+fn push_alert() -> Result<PushData, serde_json::Error> {
+    PushData::new(
+        PUSH_TYPES::Alert,
+        &serde_json::json!({
+            "rule": 1,
+            "variant": "price",
+            "kind": "price",
+            "value": "7.95",
+            "comparison": "below",
+            "threshold": "8",
+            "symbol": "ATOM",
+            "protocol": TEST_PROTOCOL,
+        }),
+    )
 }
 
-fn push_fully_liquidated() -> PushData {
-    PushData {
-        r#type: PUSH_TYPES::FullyLiquidated.to_string(),
-        body: r#"{"position": "nolus1tgjl63gyrpwx6323vgeehcenwj8myvdurzfd0pskrgyrl2pqp98sx527j4"}"#.to_string(),
-    }
+fn push_digest() -> Result<PushData, serde_json::Error> {
+    PushData::new(
+        PUSH_TYPES::Digest,
+        &DigestPush {
+            count: 3,
+            positions: 1,
+            items: vec![DigestItem {
+                r#type: PUSH_TYPES::Funding.to_string(),
+                position: Some(String::from(TEST_POSITION)),
+                count: 3,
+            }],
+        },
+    )
 }
 
-fn push_unsupported() -> PushData {
-    PushData {
-        r#type: PUSH_TYPES::Unsupported.to_string(),
-        body: r#"{}"#.to_string(),
-    }
+fn push_unsupported() -> Result<PushData, serde_json::Error> {
+    PushData::new(PUSH_TYPES::Unsupported, &serde_json::json!({}))
 }
@@ -2,11 +2,18 @@
 //!
 //! Consolidated controllers organized by domain.
 
+pub mod admin;
+pub mod alerts;
+pub mod channels;
 pub mod leases;
 pub mod liquidity;
 pub mod metrics;
 pub mod misc;
 pub mod pnl;
 pub mod positions;
+pub mod preferences;
 pub mod protocols;
+pub mod search;
+pub mod staking;
 pub mod treasury;
+pub mod wallets;
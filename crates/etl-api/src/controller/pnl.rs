@@ -8,6 +8,7 @@ use actix_web::{get, web, HttpResponse, Responder};
 use bigdecimal::BigDecimal;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
 
 use etl_core::{
     cache_keys,
@@ -15,17 +16,22 @@ use etl_core::{
     helpers::{build_cache_key, cached_fetch, parse_period_months},
 };
 
-use crate::csv_response::{to_csv_response, to_streaming_csv_response};
+use crate::response::{respond, stream, Format, FormatQuery};
 
 // =============================================================================
 // Realized PnL (by address)
 // =============================================================================
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
 pub struct RealizedPnlQuery {
     address: String,
 }
 
+#[utoipa::path(
+    tag = "PnL",
+    params(RealizedPnlQuery),
+    responses((status = 200, body = RealizedPnlResponse))
+)]
 #[get("/realized-pnl")]
 pub async fn realized_pnl(
     state: web::Data<AppState<State>>,
@@ -40,7 +46,7 @@ pub async fn realized_pnl(
     Ok(web::Json(RealizedPnlResponse { realized_pnl: data }))
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct RealizedPnlResponse {
     pub realized_pnl: f64,
 }
@@ -49,16 +55,22 @@ pub struct RealizedPnlResponse {
 // Realized PnL Data (by address)
 // =============================================================================
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
 pub struct RealizedPnlDataQuery {
     address: String,
 }
 
+#[utoipa::path(
+    tag = "PnL",
+    params(RealizedPnlDataQuery, FormatQuery),
+    responses((status = 200, description = "Realized PnL per lease of an address", body = Vec<Object>))
+)]
 #[get("/realized-pnl-data")]
 pub async fn realized_pnl_data(
     state: web::Data<AppState<State>>,
     query: web::Query<RealizedPnlDataQuery>,
-) -> Result<impl Responder, crate::error::ApiError> {
+    format: Format,
+) -> Result<HttpResponse, crate::error::ApiError> {
     let address = query.address.to_lowercase().to_owned();
     let data = state
         .database
@@ -66,13 +78,17 @@ pub async fn realized_pnl_data(
         .get_realized_pnl_data(address)
         .await?;
 
-    Ok(web::Json(data))
+    respond(format.get(), &data, "realized-pnl-data")
 }
 
 // =============================================================================
 // Realized PnL Stats (platform-wide)
 // =============================================================================
 
+#[utoipa::path(
+    tag = "PnL",
+    responses((status = 200, body = RealizedPnlStatsResponse))
+)]
 #[get("/realized-pnl-stats")]
 pub async fn realized_pnl_stats(
     state: web::Data<AppState<State>>,
@@ -95,8 +111,9 @@ pub async fn realized_pnl_stats(
     Ok(web::Json(RealizedPnlStatsResponse { amount: data }))
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct RealizedPnlStatsResponse {
+    #[schema(value_type = String)]
     pub amount: BigDecimal,
 }
 
@@ -104,35 +121,31 @@ pub struct RealizedPnlStatsResponse {
 // Realized PnL by Wallet
 // =============================================================================
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
 pub struct RealizedPnlWalletQuery {
-    format: Option<String>,
     period: Option<String>,
     from: Option<DateTime<Utc>>,
     export: Option<bool>,
 }
 
+#[utoipa::path(
+    tag = "PnL",
+    params(RealizedPnlWalletQuery, FormatQuery),
+    responses((status = 200, description = "JSON, CSV, NDJSON or Parquet, see `format`; `export=true` streams every row, as CSV unless another format is requested", body = Vec<Object>))
+)]
 #[get("/realized-pnl-wallet")]
 pub async fn realized_pnl_wallet(
     state: web::Data<AppState<State>>,
     query: web::Query<RealizedPnlWalletQuery>,
+    format: Format,
 ) -> Result<HttpResponse, crate::error::ApiError> {
-    // Handle export=true: return all data as streaming CSV
+    // Handle export=true: stream every row from a database cursor
     if query.export.unwrap_or(false) {
-        let data = cached_fetch(
-            &state.api_cache.realized_pnl_wallet,
-            cache_keys::REALIZED_PNL_WALLET,
-            || async {
-                state
-                    .database
-                    .ls_opening
-                    .get_realized_pnl_by_wallet_with_window(None, None)
-                    .await
-            },
-        )
-        .await?;
-
-        return to_streaming_csv_response(data, "realized-pnl-wallet.csv");
+        let rows = state
+            .database
+            .ls_opening
+            .stream_realized_pnl_by_wallet(None, None);
+        return Ok(stream(format.export(), rows, "realized-pnl-wallet"));
     }
 
     let months = parse_period_months(&query.period)?;
@@ -153,16 +166,17 @@ pub async fn realized_pnl_wallet(
     )
     .await?;
 
-    match query.format.as_deref() {
-        Some("csv") => to_csv_response(&data, "realized-pnl-wallet.csv"),
-        _ => Ok(HttpResponse::Ok().json(data)),
-    }
+    respond(format.get(), &data, "realized-pnl-wallet")
 }
 
 // =============================================================================
 // Unrealized PnL (platform-wide)
 // =============================================================================
 
+#[utoipa::path(
+    tag = "PnL",
+    responses((status = 200, body = UnrealizedPnlResponse))
+)]
 #[get("/unrealized-pnl")]
 pub async fn unrealized_pnl(
     state: web::Data<AppState<State>>,
@@ -179,8 +193,9 @@ pub async fn unrealized_pnl(
     }))
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct UnrealizedPnlResponse {
+    #[schema(value_type = String)]
     pub unrealized_pnl: BigDecimal,
 }
 
@@ -188,17 +203,23 @@ pub struct UnrealizedPnlResponse {
 // Unrealized PnL by Address
 // =============================================================================
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
 pub struct UnrealizedPnlByAddressQuery {
     address: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct UnrealizedPnlByAddressResponse {
+    #[schema(value_type = String)]
     pub unrealized_pnl: BigDecimal,
 }
 
 /// Returns the current unrealized PnL for an address by summing PnL from all active positions.
+#[utoipa::path(
+    tag = "PnL",
+    params(UnrealizedPnlByAddressQuery),
+    responses((status = 200, body = UnrealizedPnlByAddressResponse))
+)]
 #[get("/unrealized-pnl-by-address")]
 pub async fn unrealized_pnl_by_address(
     state: web::Data<AppState<State>>,
@@ -219,17 +240,23 @@ pub async fn unrealized_pnl_by_address(
 // PnL Over Time
 // =============================================================================
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
 pub struct PnlOverTimeQuery {
     interval: i64,
     address: String,
 }
 
+#[utoipa::path(
+    tag = "PnL",
+    params(PnlOverTimeQuery, FormatQuery),
+    responses((status = 200, description = "PnL series of an address", body = Vec<Object>))
+)]
 #[get("/pnl-over-time")]
 pub async fn pnl_over_time(
     state: web::Data<AppState<State>>,
     query: web::Query<PnlOverTimeQuery>,
-) -> Result<impl Responder, crate::error::ApiError> {
+    format: Format,
+) -> Result<HttpResponse, crate::error::ApiError> {
     let mut interval = query.interval;
 
     if interval > 30 {
@@ -242,5 +269,5 @@ pub async fn pnl_over_time(
         .get_pnl_over_time(query.address.to_owned(), interval)
         .await?;
 
-    Ok(web::Json(data))
+    respond(format.get(), &data, "pnl-over-time")
 }
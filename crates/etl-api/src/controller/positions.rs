@@ -5,6 +5,7 @@
 use actix_web::{get, web, HttpResponse, Responder};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
 
 use etl_core::{
     cache_keys,
@@ -13,23 +14,34 @@ use etl_core::{
     model::{DailyPositionsPoint, LS_Amount, PositionBucket, TokenPosition},
 };
 
-use crate::csv_response::{to_csv_response, to_streaming_csv_response};
+use crate::response::{respond, stream, Format, FormatQuery};
 
 // =============================================================================
 // All Positions
 // =============================================================================
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
 pub struct PositionsQuery {
-    format: Option<String>,
     export: Option<bool>,
 }
 
+#[utoipa::path(
+    tag = "Positions",
+    params(PositionsQuery, FormatQuery),
+    responses((status = 200, description = "JSON, CSV, NDJSON or Parquet, see `format`; `export=true` streams every row, as CSV unless another format is requested", body = Vec<Object>))
+)]
 #[get("/positions")]
 pub async fn positions(
     state: web::Data<AppState<State>>,
     query: web::Query<PositionsQuery>,
+    format: Format,
 ) -> Result<HttpResponse, crate::error::ApiError> {
+    // Handle export=true: stream every row from a database cursor
+    if query.export.unwrap_or(false) {
+        let rows = state.database.ls_state.stream_all_positions();
+        return Ok(stream(format.export(), rows, "positions"));
+    }
+
     let data = cached_fetch(
         &state.api_cache.positions,
         cache_keys::POSITIONS,
@@ -37,25 +49,23 @@ pub async fn positions(
     )
     .await?;
 
-    // Handle export=true: return all data as streaming CSV
-    if query.export.unwrap_or(false) {
-        return to_streaming_csv_response(data, "positions.csv");
-    }
-
-    match query.format.as_deref() {
-        Some("csv") => to_csv_response(&data, "positions.csv"),
-        _ => Ok(HttpResponse::Ok().json(data)),
-    }
+    respond(format.get(), &data, "positions")
 }
 
 // =============================================================================
 // Position Buckets
 // =============================================================================
 
+#[utoipa::path(
+    tag = "Positions",
+    params(FormatQuery),
+    responses((status = 200, description = "Open positions grouped by size", body = Vec<Object>))
+)]
 #[get("/position-buckets")]
 pub async fn position_buckets(
     state: web::Data<AppState<State>>,
-) -> Result<impl Responder, crate::error::ApiError> {
+    format: Format,
+) -> Result<HttpResponse, crate::error::ApiError> {
     let data = cached_fetch(
         &state.api_cache.position_buckets,
         cache_keys::POSITION_BUCKETS,
@@ -74,24 +84,29 @@ pub async fn position_buckets(
     )
     .await?;
 
-    Ok(web::Json(data))
+    respond(format.get(), &data, "position-buckets")
 }
 
 // =============================================================================
 // Daily Positions
 // =============================================================================
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
 pub struct DailyPositionsQuery {
-    format: Option<String>,
     period: Option<String>,
     from: Option<DateTime<Utc>>,
 }
 
+#[utoipa::path(
+    tag = "Positions",
+    params(DailyPositionsQuery, FormatQuery),
+    responses((status = 200, description = "JSON, CSV, NDJSON or Parquet, see `format`", body = Vec<Object>))
+)]
 #[get("/daily-positions")]
 pub async fn daily_positions(
     state: web::Data<AppState<State>>,
     query: web::Query<DailyPositionsQuery>,
+    format: Format,
 ) -> Result<HttpResponse, crate::error::ApiError> {
     let months = parse_period_months(&query.period)?;
     let period_str = query.period.as_deref().unwrap_or("3m");
@@ -116,20 +131,23 @@ pub async fn daily_positions(
         })
         .await?;
 
-    match query.format.as_deref() {
-        Some("csv") => to_csv_response(&data, "daily-positions.csv"),
-        _ => Ok(HttpResponse::Ok().json(data)),
-    }
+    respond(format.get(), &data, "daily-positions")
 }
 
 // =============================================================================
 // Open Positions by Token
 // =============================================================================
 
+#[utoipa::path(
+    tag = "Positions",
+    params(FormatQuery),
+    responses((status = 200, description = "Open positions per token", body = Vec<Object>))
+)]
 #[get("/open-positions-by-token")]
 pub async fn open_positions_by_token(
     state: web::Data<AppState<State>>,
-) -> Result<impl Responder, crate::error::ApiError> {
+    format: Format,
+) -> Result<HttpResponse, crate::error::ApiError> {
     let data = cached_fetch(
         &state.api_cache.open_positions_by_token,
         cache_keys::OPEN_POSITIONS_BY_TOKEN,
@@ -151,24 +169,31 @@ pub async fn open_positions_by_token(
     )
     .await?;
 
-    Ok(web::Json(data))
+    respond(format.get(), &data, "open-positions-by-token")
 }
 
 // =============================================================================
 // Position Debt Value
 // =============================================================================
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
 pub struct PositionDebtValueQuery {
     address: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct PositionDebtValueResponse {
+    #[schema(value_type = Vec<Object>)]
     pub position: Vec<LS_Amount>,
+    #[schema(value_type = Vec<Object>)]
     pub debt: Vec<LS_Amount>,
 }
 
+#[utoipa::path(
+    tag = "Positions",
+    params(PositionDebtValueQuery),
+    responses((status = 200, body = PositionDebtValueResponse))
+)]
 #[get("/position-debt-value")]
 pub async fn position_debt_value(
     state: web::Data<AppState<State>>,
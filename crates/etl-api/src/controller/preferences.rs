@@ -0,0 +1,238 @@
+//! Notification preference endpoints
+//!
+//! A subscription can mute push types, set the minimum interval between two
+//! notifications of a type for the same lease, and quiet hours in its time
+//! zone. Notifications held back by the interval or the quiet hours are sent
+//! later as one digest, unless `digest` is off. See `etl_core::push`.
+
+use std::str::FromStr as _;
+
+use actix_web::{get, post, web, HttpResponse};
+use chrono::{NaiveTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use etl_core::{
+    configuration::{AppState, State},
+    error::Error,
+    model::SubscriptionPreference,
+    types::PUSH_TYPES,
+};
+
+/// Longest minimum interval, one week
+const MAX_INTERVAL_MINUTES: i32 = 7 * 24 * 60;
+
+const TIME_FORMAT: &str = "%H:%M";
+
+const DEFAULT_TIMEZONE: &str = "UTC";
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct PreferencesQuery {
+    address: String,
+    auth: String,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct PreferencesRequest {
+    address: String,
+    auth: String,
+    /// Push types not to deliver, e.g. `Funding`
+    #[serde(default)]
+    muted_types: Vec<String>,
+    /// Minutes between two notifications of a type for the same lease.
+    /// Defaults to the server setting.
+    min_interval_minutes: Option<i32>,
+    /// Start of the quiet hours as `HH:MM`, together with `quiet_end`
+    quiet_start: Option<String>,
+    quiet_end: Option<String>,
+    /// IANA time zone of the quiet hours, `UTC` by default
+    timezone: Option<String>,
+    /// Send held notifications as a digest instead of dropping them.
+    /// Defaults to true.
+    digest: Option<bool>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PreferencesResponse {
+    muted_types: Vec<String>,
+    min_interval_minutes: i32,
+    quiet_start: Option<String>,
+    quiet_end: Option<String>,
+    timezone: String,
+    digest: bool,
+}
+
+impl PreferencesResponse {
+    fn new(preference: SubscriptionPreference, default_interval: i32) -> Self {
+        Self {
+            muted_types: preference.muted_types,
+            min_interval_minutes: preference
+                .min_interval_minutes
+                .unwrap_or(default_interval),
+            quiet_start: preference
+                .quiet_start
+                .map(|t| t.format(TIME_FORMAT).to_string()),
+            quiet_end: preference
+                .quiet_end
+                .map(|t| t.format(TIME_FORMAT).to_string()),
+            timezone: preference.timezone,
+            digest: preference.digest,
+        }
+    }
+}
+
+fn parse_time(value: &str) -> Result<NaiveTime, Error> {
+    NaiveTime::parse_from_str(value, TIME_FORMAT).map_err(|_| {
+        Error::InvalidOption {
+            option: value.to_owned(),
+        }
+    })
+}
+
+#[utoipa::path(
+    tag = "Preferences",
+    params(PreferencesQuery),
+    responses(
+        (status = 200, body = PreferencesResponse),
+        (status = 404, description = "Subscription not found"),
+    )
+)]
+#[get("/subscribe/preferences")]
+pub async fn subscription_preferences(
+    state: web::Data<AppState<State>>,
+    query: web::Query<PreferencesQuery>,
+) -> Result<HttpResponse, crate::error::ApiError> {
+    let (exists, preference) = tokio::try_join!(
+        state
+            .database
+            .subscription
+            .isExists(query.address.to_owned(), query.auth.to_owned()),
+        async {
+            state
+                .database
+                .subscription_preference
+                .get(query.address.to_owned(), query.auth.to_owned())
+                .await
+                .map_err(Error::from)
+        },
+    )?;
+
+    if !exists {
+        return Err(Error::NotFound(String::from("subscription")).into());
+    }
+
+    let preference = preference.unwrap_or_else(|| SubscriptionPreference {
+        address: query.address.to_owned(),
+        auth: query.auth.to_owned(),
+        muted_types: vec![],
+        min_interval_minutes: None,
+        quiet_start: None,
+        quiet_end: None,
+        timezone: String::from(DEFAULT_TIMEZONE),
+        digest: true,
+        updated_at: Utc::now(),
+    });
+
+    Ok(HttpResponse::Ok().json(PreferencesResponse::new(
+        preference,
+        state.config.push_min_interval_minutes,
+    )))
+}
+
+/// Replaces the preferences of the subscription; omitted fields take their
+/// defaults
+#[utoipa::path(
+    tag = "Preferences",
+    request_body = PreferencesRequest,
+    responses(
+        (status = 200, body = PreferencesResponse),
+        (status = 400, description = "Invalid preference"),
+        (status = 404, description = "Subscription not found"),
+    )
+)]
+#[post("/subscribe/preferences")]
+pub async fn set_preferences(
+    state: web::Data<AppState<State>>,
+    data: web::Json<PreferencesRequest>,
+) -> Result<HttpResponse, crate::error::ApiError> {
+    let data = data.into_inner();
+
+    for push_type in &data.muted_types {
+        PUSH_TYPES::from_str(push_type).map_err(|_| Error::InvalidOption {
+            option: push_type.to_owned(),
+        })?;
+    }
+
+    if let Some(minutes) = data.min_interval_minutes {
+        if !(0..=MAX_INTERVAL_MINUTES).contains(&minutes) {
+            return Err(Error::InvalidOption {
+                option: format!(
+                    "min_interval_minutes must be between 0 and {}",
+                    MAX_INTERVAL_MINUTES
+                ),
+            }
+            .into());
+        }
+    }
+
+    let (quiet_start, quiet_end) = match (&data.quiet_start, &data.quiet_end) {
+        (Some(start), Some(end)) => {
+            (Some(parse_time(start)?), Some(parse_time(end)?))
+        },
+        (None, None) => (None, None),
+        _ => {
+            return Err(Error::MissingParams(String::from(
+                "quiet_start and quiet_end",
+            ))
+            .into())
+        },
+    };
+
+    let timezone = data
+        .timezone
+        .unwrap_or_else(|| String::from(DEFAULT_TIMEZONE));
+
+    let (exists, known_timezone) = tokio::try_join!(
+        state
+            .database
+            .subscription
+            .isExists(data.address.to_owned(), data.auth.to_owned()),
+        async {
+            state
+                .database
+                .subscription_preference
+                .is_timezone(timezone.to_owned())
+                .await
+                .map_err(Error::from)
+        },
+    )?;
+
+    if !exists {
+        return Err(Error::NotFound(String::from("subscription")).into());
+    }
+
+    if !known_timezone {
+        return Err(Error::InvalidOption { option: timezone }.into());
+    }
+
+    let preference = state
+        .database
+        .subscription_preference
+        .upsert(SubscriptionPreference {
+            address: data.address,
+            auth: data.auth,
+            muted_types: data.muted_types,
+            min_interval_minutes: data.min_interval_minutes,
+            quiet_start,
+            quiet_end,
+            timezone,
+            digest: data.digest.unwrap_or(true),
+            updated_at: Utc::now(),
+        })
+        .await?;
+
+    Ok(HttpResponse::Ok().json(PreferencesResponse::new(
+        preference,
+        state.config.push_min_interval_minutes,
+    )))
+}
@@ -7,6 +7,7 @@ use std::collections::HashMap;
 
 use actix_web::{get, web, HttpResponse, Responder};
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
 use etl_core::{
     configuration::{AppState, State},
@@ -17,7 +18,7 @@ use etl_core::{
 // Response Types
 // =============================================================================
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct ProtocolContracts {
     pub leaser: Option<String>,
     pub lpp: Option<String>,
@@ -26,7 +27,7 @@ pub struct ProtocolContracts {
     pub reserve: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct ProtocolInfo {
     pub name: String,
     pub network: Option<String>,
@@ -39,7 +40,7 @@ pub struct ProtocolInfo {
     pub deprecated_at: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct ProtocolsResponse {
     pub protocols: Vec<ProtocolInfo>,
     pub count: usize,
@@ -47,7 +48,7 @@ pub struct ProtocolsResponse {
     pub deprecated_count: i64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct CurrencyProtocolInfo {
     pub protocol: String,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -58,7 +59,7 @@ pub struct CurrencyProtocolInfo {
     pub dex_symbol: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct CurrencyInfo {
     pub ticker: String,
     pub decimal_digits: i16,
@@ -68,7 +69,7 @@ pub struct CurrencyInfo {
     pub protocols: Vec<CurrencyProtocolInfo>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct CurrenciesResponse {
     pub currencies: Vec<CurrencyInfo>,
     pub count: usize,
@@ -81,6 +82,10 @@ pub struct CurrenciesResponse {
 // =============================================================================
 
 /// Get all protocols (active and deprecated)
+#[utoipa::path(
+    tag = "Protocols",
+    responses((status = 200, body = ProtocolsResponse))
+)]
 #[get("/protocols")]
 pub async fn get_protocols(
     state: web::Data<AppState<State>>,
@@ -120,6 +125,10 @@ pub async fn get_protocols(
 }
 
 /// Get only active protocols
+#[utoipa::path(
+    tag = "Protocols",
+    responses((status = 200, body = ProtocolsResponse))
+)]
 #[get("/protocols/active")]
 pub async fn get_active_protocols(
     state: web::Data<AppState<State>>,
@@ -159,6 +168,13 @@ pub async fn get_active_protocols(
 }
 
 /// Get a single protocol by name
+#[utoipa::path(
+    tag = "Protocols",
+    responses(
+        (status = 200, body = ProtocolInfo),
+        (status = 404, description = "Not found"),
+    )
+)]
 #[get("/protocols/{name}")]
 pub async fn get_protocol_by_name(
     state: web::Data<AppState<State>>,
@@ -218,6 +234,10 @@ async fn build_protocol_map(
 }
 
 /// Get all currencies (active and deprecated)
+#[utoipa::path(
+    tag = "Protocols",
+    responses((status = 200, body = CurrenciesResponse))
+)]
 #[get("/currencies")]
 pub async fn get_currencies(
     state: web::Data<AppState<State>>,
@@ -254,6 +274,10 @@ pub async fn get_currencies(
 }
 
 /// Get only active currencies
+#[utoipa::path(
+    tag = "Protocols",
+    responses((status = 200, body = CurrenciesResponse))
+)]
 #[get("/currencies/active")]
 pub async fn get_active_currencies(
     state: web::Data<AppState<State>>,
@@ -290,6 +314,13 @@ pub async fn get_active_currencies(
 }
 
 /// Get a single currency by ticker
+#[utoipa::path(
+    tag = "Protocols",
+    responses(
+        (status = 200, body = CurrencyInfo),
+        (status = 404, description = "Not found"),
+    )
+)]
 #[get("/currencies/{ticker}")]
 pub async fn get_currency_by_ticker(
     state: web::Data<AppState<State>>,
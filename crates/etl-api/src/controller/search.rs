@@ -0,0 +1,212 @@
+//! Search endpoint
+//!
+//! Classifies the input and looks it up where it can refer to: tx hashes in
+//! `raw_message`, heights in `block`, contracts among leases and the
+//! protocol registry, accounts by their activity index, and any other
+//! text among protocol names and transaction memos.
+
+use actix_web::{get, web, HttpResponse};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use etl_core::{
+    configuration::{AppState, State},
+    error::Error,
+    helpers::{SearchHitKind, SearchTerm, MIN_MEMO_QUERY},
+    model::ProtocolRegistry,
+};
+
+use crate::response::{respond, Format, FormatQuery};
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct SearchQuery {
+    /// Address, lease or pool contract, protocol name, tx hash, block
+    /// height or memo fragment
+    q: String,
+    /// Most memo matches returned, defaults to 20, at most 100
+    limit: Option<i64>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SearchHit {
+    kind: String,
+    /// Address, contract, protocol name, tx hash or height
+    id: String,
+    protocol: Option<String>,
+    /// Lease owner, role of a protocol contract, pool currency or memo
+    detail: Option<String>,
+    /// Messages of a wallet or transaction
+    messages: Option<i64>,
+    block: Option<i64>,
+    /// Last activity of a wallet, opening of a lease or time of a tx
+    timestamp: Option<DateTime<Utc>>,
+}
+
+impl SearchHit {
+    fn new(kind: SearchHitKind, id: String) -> SearchHit {
+        SearchHit {
+            kind: kind.into(),
+            id,
+            protocol: None,
+            detail: None,
+            messages: None,
+            block: None,
+            timestamp: None,
+        }
+    }
+}
+
+#[utoipa::path(
+    tag = "Misc",
+    params(SearchQuery, FormatQuery),
+    responses(
+        (status = 200, description = "Wallets, leases, pools, protocols, transactions and blocks matching the input", body = Vec<SearchHit>),
+        (status = 400, description = "Empty query"),
+    )
+)]
+#[get("/search")]
+pub async fn search(
+    state: web::Data<AppState<State>>,
+    query: web::Query<SearchQuery>,
+    format: Format,
+) -> Result<HttpResponse, crate::error::ApiError> {
+    let limit = query.limit.unwrap_or(20).clamp(1, 100);
+    if query.q.trim().is_empty() {
+        return Err(Error::MissingParams(String::from("q")).into());
+    }
+
+    let mut hits = vec![];
+
+    match SearchTerm::classify(&query.q) {
+        SearchTerm::TxHash(tx_hash) => {
+            if let Some(tx) =
+                state.database.raw_message.get_tx_summary(tx_hash).await?
+            {
+                let mut hit = SearchHit::new(SearchHitKind::Tx, tx.tx_hash);
+                hit.detail = tx.memo.filter(|memo| !memo.is_empty());
+                hit.messages = Some(tx.messages);
+                hit.block = Some(tx.block);
+                hit.timestamp = Some(tx.timestamp);
+                hits.push(hit);
+            }
+        },
+        SearchTerm::Height(height) => {
+            if let Some(block) = state.database.block.get_one(height).await? {
+                let mut hit =
+                    SearchHit::new(SearchHitKind::Block, block.id.to_string());
+                hit.block = Some(block.id);
+                hits.push(hit);
+            }
+        },
+        SearchTerm::Account(address) => {
+            let activity = state
+                .database
+                .address_activity
+                .get_summary(address.to_owned())
+                .await?;
+            if activity.messages > 0 {
+                let mut hit = SearchHit::new(SearchHitKind::Wallet, address);
+                hit.messages = Some(activity.messages);
+                hit.timestamp = activity.last_seen;
+                hits.push(hit);
+            }
+        },
+        SearchTerm::Contract(contract) => {
+            hits.extend(search_contract(&state, contract).await?);
+        },
+        SearchTerm::Text(text) => {
+            let protocols = state
+                .database
+                .protocol_registry
+                .search_by_name(&text)
+                .await?;
+            hits.extend(protocols.into_iter().map(|protocol| {
+                let mut hit = SearchHit::new(
+                    SearchHitKind::Protocol,
+                    protocol.protocol_name.to_owned(),
+                );
+                hit.protocol = Some(protocol.protocol_name);
+                hit.detail = protocol.dex;
+                hit
+            }));
+
+            if text.chars().count() >= MIN_MEMO_QUERY {
+                let txs = state
+                    .database
+                    .raw_message
+                    .search_memo(&text, limit)
+                    .await?;
+                hits.extend(txs.into_iter().map(|tx| {
+                    let mut hit = SearchHit::new(SearchHitKind::Tx, tx.tx_hash);
+                    hit.detail = tx.memo;
+                    hit.messages = Some(tx.messages);
+                    hit.block = Some(tx.block);
+                    hit.timestamp = Some(tx.timestamp);
+                    hit
+                }));
+            }
+        },
+    }
+
+    respond(format.get(), &hits, "search")
+}
+
+/// A contract is a lease, the pool of a protocol or another contract of it
+async fn search_contract(
+    state: &AppState<State>,
+    contract: String,
+) -> Result<Vec<SearchHit>, Error> {
+    if let Some(lease) =
+        state.database.ls_opening.get(contract.to_owned()).await?
+    {
+        let mut hit = SearchHit::new(SearchHitKind::Lease, contract);
+        hit.protocol = state.get_protocol_by_pool_id(&lease.LS_loan_pool_id);
+        hit.detail = Some(lease.LS_address_id);
+        hit.timestamp = Some(lease.LS_timestamp);
+        return Ok(vec![hit]);
+    }
+
+    if let Some(protocol) = state
+        .database
+        .protocol_registry
+        .get_by_contract(&contract)
+        .await?
+    {
+        let hit = if protocol.lpp_contract.as_deref() == Some(&contract) {
+            let mut hit = SearchHit::new(SearchHitKind::Pool, contract);
+            hit.detail = Some(protocol.lpn_symbol);
+            hit.protocol = Some(protocol.protocol_name);
+            hit
+        } else {
+            let mut hit = SearchHit::new(
+                SearchHitKind::Protocol,
+                protocol.protocol_name.to_owned(),
+            );
+            hit.detail = contract_role(&protocol, &contract).map(String::from);
+            hit.protocol = Some(protocol.protocol_name);
+            hit
+        };
+        return Ok(vec![hit]);
+    }
+
+    Ok(vec![])
+}
+
+fn contract_role(
+    protocol: &ProtocolRegistry,
+    contract: &str,
+) -> Option<&'static str> {
+    let contract = Some(contract);
+    if protocol.leaser_contract.as_deref() == contract {
+        Some("leaser")
+    } else if protocol.oracle_contract.as_deref() == contract {
+        Some("oracle")
+    } else if protocol.profit_contract.as_deref() == contract {
+        Some("profit")
+    } else if protocol.reserve_contract.as_deref() == contract {
+        Some("reserve")
+    } else {
+        None
+    }
+}
@@ -0,0 +1,171 @@
+//! Staking and governance endpoints
+//!
+//! Derived from the staking, distribution and governance messages of
+//! successful transactions, see `staking_event` and `governance_vote`.
+//! Amounts are in the smallest denomination of the native currency.
+
+use actix_web::{get, web, HttpResponse};
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::Deserialize;
+use utoipa::IntoParams;
+
+use etl_core::{
+    configuration::{AppState, State},
+    error::Error,
+};
+
+use crate::response::{respond, Format, FormatQuery};
+
+/// Longest range of the validator share series
+const MAX_SHARE_DAYS: i64 = 366;
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct DelegationsQuery {
+    /// Required unless `validator` is set
+    delegator: Option<String>,
+    /// Without `delegator`, the total stake of the validator
+    validator: Option<String>,
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+}
+
+#[utoipa::path(
+    tag = "Staking",
+    params(DelegationsQuery, FormatQuery),
+    responses(
+        (status = 200, description = "Delegation changes with the staked balance per validator after each", body = Vec<Object>),
+        (status = 400, description = "Neither delegator nor validator set"),
+    )
+)]
+#[get("/staking/delegations")]
+pub async fn delegations(
+    state: web::Data<AppState<State>>,
+    query: web::Query<DelegationsQuery>,
+    format: Format,
+) -> Result<HttpResponse, crate::error::ApiError> {
+    let delegator = query.delegator.as_deref().map(str::to_lowercase);
+    let validator = query.validator.as_deref().map(str::to_lowercase);
+    if delegator.is_none() && validator.is_none() {
+        return Err(
+            Error::MissingParams(String::from("delegator, validator")).into()
+        );
+    }
+
+    let data = state
+        .database
+        .staking_event
+        .get_balance_history(delegator, validator, query.from, query.to)
+        .await?;
+
+    respond(format.get(), &data, "staking_delegations")
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct DelegatorQuery {
+    delegator: String,
+}
+
+#[utoipa::path(
+    tag = "Staking",
+    params(DelegatorQuery, FormatQuery),
+    responses((status = 200, description = "Reward claims with the cumulative rewards in the native currency and stable", body = Vec<Object>))
+)]
+#[get("/staking/rewards")]
+pub async fn rewards(
+    state: web::Data<AppState<State>>,
+    query: web::Query<DelegatorQuery>,
+    format: Format,
+) -> Result<HttpResponse, crate::error::ApiError> {
+    let data = state
+        .database
+        .staking_event
+        .get_claimed_rewards(query.delegator.to_lowercase())
+        .await?;
+
+    respond(format.get(), &data, "staking_rewards")
+}
+
+#[utoipa::path(
+    tag = "Staking",
+    params(DelegatorQuery, FormatQuery),
+    responses((status = 200, description = "Undelegations still unbonding, by completion time", body = Vec<Object>))
+)]
+#[get("/staking/unbonding")]
+pub async fn unbonding(
+    state: web::Data<AppState<State>>,
+    query: web::Query<DelegatorQuery>,
+    format: Format,
+) -> Result<HttpResponse, crate::error::ApiError> {
+    let data = state
+        .database
+        .staking_event
+        .get_unbonding(query.delegator.to_lowercase())
+        .await?;
+
+    respond(format.get(), &data, "staking_unbonding")
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct ValidatorSharesQuery {
+    /// Defaults to 30 days before `to`
+    from: Option<DateTime<Utc>>,
+    /// Defaults to now
+    to: Option<DateTime<Utc>>,
+}
+
+#[utoipa::path(
+    tag = "Staking",
+    params(ValidatorSharesQuery, FormatQuery),
+    responses(
+        (status = 200, description = "Daily stake of each validator and its share of the indexed stake", body = Vec<Object>),
+        (status = 400, description = "Invalid range"),
+    )
+)]
+#[get("/staking/validators")]
+pub async fn validator_shares(
+    state: web::Data<AppState<State>>,
+    query: web::Query<ValidatorSharesQuery>,
+    format: Format,
+) -> Result<HttpResponse, crate::error::ApiError> {
+    let to = query.to.unwrap_or_else(Utc::now);
+    let from = query.from.unwrap_or(to - TimeDelta::days(30));
+    if from > to || to - from > TimeDelta::days(MAX_SHARE_DAYS) {
+        return Err(Error::InvalidOption {
+            option: format!("range of at most {} days", MAX_SHARE_DAYS),
+        }
+        .into());
+    }
+
+    let data = state
+        .database
+        .staking_event
+        .get_validator_shares(from, to)
+        .await?;
+
+    respond(format.get(), &data, "staking_validators")
+}
+
+#[utoipa::path(
+    tag = "Governance",
+    params(("id" = i64, Path, description = "Proposal id"), FormatQuery),
+    responses((status = 200, description = "Votes and the stake of the voters at their vote per option", body = Vec<Object>))
+)]
+#[get("/governance/proposals/{id}/tally")]
+pub async fn proposal_tally(
+    state: web::Data<AppState<State>>,
+    path: web::Path<i64>,
+    format: Format,
+) -> Result<HttpResponse, crate::error::ApiError> {
+    let proposal_id = path.into_inner();
+    let data = state
+        .database
+        .governance_vote
+        .get_tally(proposal_id)
+        .await?;
+
+    respond(
+        format.get(),
+        &data,
+        &format!("proposal-{}-tally", proposal_id),
+    )
+}
@@ -6,6 +6,7 @@ use actix_web::{get, web, HttpResponse, Responder};
 use bigdecimal::BigDecimal;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
 
 use etl_core::{
     cache_keys,
@@ -14,12 +15,16 @@ use etl_core::{
     model::RevenueSeriesPoint,
 };
 
-use crate::csv_response::to_csv_response;
+use crate::response::{respond, Format, FormatQuery};
 
 // =============================================================================
 // Revenue
 // =============================================================================
 
+#[utoipa::path(
+    tag = "Treasury",
+    responses((status = 200, body = RevenueResponse))
+)]
 #[get("/revenue")]
 pub async fn revenue(
     state: web::Data<AppState<State>>,
@@ -33,8 +38,9 @@ pub async fn revenue(
     Ok(web::Json(RevenueResponse { revenue: data }))
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct RevenueResponse {
+    #[schema(value_type = String)]
     pub revenue: BigDecimal,
 }
 
@@ -42,17 +48,22 @@ pub struct RevenueResponse {
 // Revenue Series
 // =============================================================================
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
 pub struct RevenueSeriesQuery {
-    format: Option<String>,
     period: Option<String>,
     from: Option<DateTime<Utc>>,
 }
 
+#[utoipa::path(
+    tag = "Treasury",
+    params(RevenueSeriesQuery, FormatQuery),
+    responses((status = 200, description = "JSON, CSV, NDJSON or Parquet, see `format`", body = Vec<Object>))
+)]
 #[get("/revenue-series")]
 pub async fn revenue_series(
     state: web::Data<AppState<State>>,
     query: web::Query<RevenueSeriesQuery>,
+    format: Format,
 ) -> Result<HttpResponse, crate::error::ApiError> {
     let months = parse_period_months(&query.period)?;
     let period_str = query.period.as_deref().unwrap_or("3m");
@@ -81,16 +92,17 @@ pub async fn revenue_series(
         fetch().await?
     };
 
-    match query.format.as_deref() {
-        Some("csv") => to_csv_response(&data, "revenue-series.csv"),
-        _ => Ok(HttpResponse::Ok().json(data)),
-    }
+    respond(format.get(), &data, "revenue-series")
 }
 
 // =============================================================================
 // Distributed
 // =============================================================================
 
+#[utoipa::path(
+    tag = "Treasury",
+    responses((status = 200, body = DistributedResponse))
+)]
 #[get("/distributed")]
 pub async fn distributed(
     state: web::Data<AppState<State>>,
@@ -111,8 +123,9 @@ pub async fn distributed(
     Ok(web::Json(DistributedResponse { distributed: data }))
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct DistributedResponse {
+    #[schema(value_type = String)]
     pub distributed: BigDecimal,
 }
 
@@ -120,17 +133,22 @@ pub struct DistributedResponse {
 // Buyback
 // =============================================================================
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
 pub struct BuybackQuery {
-    format: Option<String>,
     period: Option<String>,
     from: Option<DateTime<Utc>>,
 }
 
+#[utoipa::path(
+    tag = "Treasury",
+    params(BuybackQuery, FormatQuery),
+    responses((status = 200, description = "JSON, CSV, NDJSON or Parquet, see `format`", body = Vec<Object>))
+)]
 #[get("/buyback")]
 pub async fn buyback(
     state: web::Data<AppState<State>>,
     query: web::Query<BuybackQuery>,
+    format: Format,
 ) -> Result<HttpResponse, crate::error::ApiError> {
     let months = parse_period_months(&query.period)?;
     let period_str = query.period.as_deref().unwrap_or("3m");
@@ -145,16 +163,17 @@ pub async fn buyback(
     })
     .await?;
 
-    match query.format.as_deref() {
-        Some("csv") => to_csv_response(&data, "buyback.csv"),
-        _ => Ok(HttpResponse::Ok().json(data)),
-    }
+    respond(format.get(), &data, "buyback")
 }
 
 // =============================================================================
 // Buyback Total
 // =============================================================================
 
+#[utoipa::path(
+    tag = "Treasury",
+    responses((status = 200, body = BuybackTotalResponse))
+)]
 #[get("/buyback-total")]
 pub async fn buyback_total(
     state: web::Data<AppState<State>>,
@@ -171,8 +190,9 @@ pub async fn buyback_total(
     }))
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct BuybackTotalResponse {
+    #[schema(value_type = String)]
     pub buyback_total: BigDecimal,
 }
 
@@ -180,6 +200,10 @@ pub struct BuybackTotalResponse {
 // Incentives Pool
 // =============================================================================
 
+#[utoipa::path(
+    tag = "Treasury",
+    responses((status = 200, body = IncentivesPoolResponse))
+)]
 #[get("/incentives-pool")]
 pub async fn incentives_pool(
     state: web::Data<AppState<State>>,
@@ -196,8 +220,9 @@ pub async fn incentives_pool(
     }))
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct IncentivesPoolResponse {
+    #[schema(value_type = String)]
     pub incentives_pool: BigDecimal,
 }
 
@@ -205,11 +230,16 @@ pub struct IncentivesPoolResponse {
 // Earnings
 // =============================================================================
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
 pub struct EarningsQuery {
     address: String,
 }
 
+#[utoipa::path(
+    tag = "Treasury",
+    params(EarningsQuery),
+    responses((status = 200, body = EarningsResponse))
+)]
 #[get("/earnings")]
 pub async fn earnings(
     state: web::Data<AppState<State>>,
@@ -220,7 +250,8 @@ pub async fn earnings(
     Ok(web::Json(EarningsResponse { earnings }))
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct EarningsResponse {
+    #[schema(value_type = String)]
     pub earnings: BigDecimal,
 }
@@ -0,0 +1,440 @@
+//! Wallet API endpoints
+//!
+//! Per-wallet accounting statement for tax and portfolio reporting.
+
+use std::{
+    collections::{HashMap, HashSet},
+    str::FromStr,
+};
+
+use actix_web::{get, web, HttpResponse};
+use anyhow::Context as _;
+use bigdecimal::{BigDecimal, Zero as _};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use etl_core::{
+    configuration::{AppState, State},
+    dao::postgre::raw_message::WalletStatementRow,
+    error::Error,
+};
+
+use crate::response::{respond, Format, FormatQuery};
+
+// =============================================================================
+// Wallet Statement
+// =============================================================================
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct StatementQuery {
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct StatementEntry {
+    pub timestamp: DateTime<Utc>,
+    pub event: String,
+    pub reference: String,
+    pub tx_hash: Option<String>,
+    pub asset: Option<String>,
+    #[schema(value_type = Option<String>)]
+    pub amount: Option<BigDecimal>,
+    #[schema(value_type = Option<String>)]
+    pub cost_basis_stable: Option<BigDecimal>,
+    #[schema(value_type = Option<String>)]
+    pub proceeds_stable: Option<BigDecimal>,
+    #[schema(value_type = Option<String>)]
+    pub fee_amount: Option<BigDecimal>,
+    pub fee_denom: Option<String>,
+    #[schema(value_type = Option<String>)]
+    pub fee_stable: Option<BigDecimal>,
+    #[schema(value_type = Option<String>)]
+    pub opening_fee_stable: Option<BigDecimal>,
+    #[schema(value_type = Option<String>)]
+    pub realized_gain_stable: Option<BigDecimal>,
+}
+
+#[utoipa::path(
+    tag = "Wallets",
+    params(StatementQuery, FormatQuery),
+    responses((status = 200, description = "JSON, CSV, NDJSON or Parquet, see `format`", body = Vec<StatementEntry>))
+)]
+#[get("/wallets/{address}/statement")]
+pub async fn statement(
+    state: web::Data<AppState<State>>,
+    path: web::Path<String>,
+    query: web::Query<StatementQuery>,
+    format: Format,
+) -> Result<HttpResponse, crate::error::ApiError> {
+    let address = path.into_inner().to_lowercase();
+    let rows = state
+        .database
+        .raw_message
+        .get_wallet_statement(address.to_owned(), query.from, query.to)
+        .await?;
+
+    let mut entries = Vec::with_capacity(rows.len());
+    for (row, mut entry) in ledger(rows, query.from, query.to) {
+        price_entry(&state, &row, &mut entry).await?;
+        entries.push(entry);
+    }
+
+    respond(format.get(), &entries, &format!("statement-{}", address))
+}
+
+/// Running state of the ledger: the cost basis and receipts of each LP
+/// position, and the proceeds of each lease before its closing
+#[derive(Default)]
+struct Positions {
+    lp: HashMap<String, (BigDecimal, BigDecimal)>,
+    lease_proceeds: HashMap<String, BigDecimal>,
+}
+
+/// Lines of the window, oldest first. The LP cost basis is tracked per pool
+/// from the first deposit and the proceeds of a lease from its first
+/// partial close or liquidation, so rows before `from` are processed but
+/// left out. A transaction may produce several lines; its network fee is
+/// reported once, on the first of them.
+fn ledger(
+    mut rows: Vec<WalletStatementRow>,
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+) -> Vec<(WalletStatementRow, StatementEntry)> {
+    rows.sort_by_key(|row| row.timestamp);
+
+    let mut positions = Positions::default();
+    let mut charged_txs: HashSet<String> = HashSet::new();
+    let mut lines = Vec::with_capacity(rows.len());
+
+    for row in rows {
+        let mut entry = build_entry(&row, &mut positions);
+
+        let in_window = from.map_or(true, |from| row.timestamp >= from)
+            && to.map_or(true, |to| row.timestamp <= to);
+        if !in_window {
+            continue;
+        }
+
+        if let Some(tx_hash) = &entry.tx_hash {
+            if !charged_txs.insert(tx_hash.to_owned()) {
+                entry.fee_amount = None;
+                entry.fee_denom = None;
+            }
+        }
+
+        lines.push((row, entry));
+    }
+
+    lines
+}
+
+fn build_entry(
+    row: &WalletStatementRow,
+    positions: &mut Positions,
+) -> StatementEntry {
+    let mut entry = StatementEntry {
+        timestamp: row.timestamp,
+        event: row.event.to_owned(),
+        reference: row.reference.to_owned(),
+        tx_hash: row.tx_hash.to_owned(),
+        asset: row.asset.to_owned(),
+        amount: row.amount.to_owned(),
+        cost_basis_stable: row.cost_basis_stable.to_owned(),
+        proceeds_stable: row.proceeds_stable.to_owned(),
+        fee_amount: row.fee_amount.to_owned(),
+        fee_denom: row.fee_denom.to_owned(),
+        fee_stable: None,
+        opening_fee_stable: None,
+        realized_gain_stable: row.realized_gain_stable.to_owned(),
+    };
+
+    match row.event.as_str() {
+        "lp_deposit" => {
+            let (cost, receipts) = positions
+                .lp
+                .entry(row.reference.to_owned())
+                .or_insert_with(|| (BigDecimal::zero(), BigDecimal::zero()));
+            *cost += row.cost_basis_stable.to_owned().unwrap_or_default();
+            *receipts += row.receipts.to_owned().unwrap_or_default();
+        },
+        "lp_withdraw" => {
+            // Average cost per receipt of the pool position
+            if let (Some((cost, receipts)), Some(withdrawn)) =
+                (positions.lp.get_mut(&row.reference), &row.receipts)
+            {
+                if !receipts.is_zero() {
+                    let withdrawn = withdrawn.min(receipts).to_owned();
+                    let basis = &*cost * &withdrawn / &*receipts;
+                    *cost -= &basis;
+                    *receipts -= &withdrawn;
+                    entry.realized_gain_stable = row
+                        .proceeds_stable
+                        .as_ref()
+                        .map(|proceeds| proceeds - &basis);
+                    entry.cost_basis_stable = Some(basis);
+                }
+            }
+        },
+        "lease_partial_close" | "lease_liquidation" => {
+            *positions
+                .lease_proceeds
+                .entry(row.reference.to_owned())
+                .or_default() +=
+                row.proceeds_stable.to_owned().unwrap_or_default();
+        },
+        "lease_close" => {
+            // The whole lease is realized at its closing, against the
+            // proceeds of its partial closes and liquidations too
+            let earlier = positions
+                .lease_proceeds
+                .remove(&row.reference)
+                .unwrap_or_default();
+            if let (Some(proceeds), Some(cost)) =
+                (&row.proceeds_stable, &row.cost_basis_stable)
+            {
+                entry.realized_gain_stable = Some(proceeds + earlier - cost);
+            }
+        },
+        _ => {},
+    }
+
+    entry
+}
+
+/// Stable values of a line at its date: the opening fee of a lease, the
+/// claimed rewards and the network fee
+async fn price_entry(
+    state: &AppState<State>,
+    row: &WalletStatementRow,
+    entry: &mut StatementEntry,
+) -> Result<(), Error> {
+    match row.event.as_str() {
+        "lease_open" => {
+            entry.opening_fee_stable =
+                opening_fee(state, &row.reference).await?;
+        },
+        "staking_reward" | "reward_claim" => {
+            // Rewards are income: valued at the claim date with no basis
+            let mut total = BigDecimal::zero();
+            let mut assets = Vec::new();
+
+            for (denom, amount) in parse_coins(row.rewards.as_deref())? {
+                if let Some((ticker, normalized, stable)) =
+                    coin_in_stable(state, &denom, &amount, &row.timestamp)
+                        .await?
+                {
+                    entry.amount = Some(normalized);
+                    assets.push(ticker);
+                    total += stable;
+                }
+            }
+
+            if assets.len() > 1 {
+                entry.amount = None;
+            }
+
+            entry.asset = Some(assets.join(","));
+            entry.proceeds_stable = Some(total.to_owned());
+            entry.realized_gain_stable = Some(total);
+        },
+        _ => {},
+    }
+
+    if let (Some(fee_amount), Some(fee_denom)) =
+        (&entry.fee_amount, &entry.fee_denom)
+    {
+        entry.fee_stable =
+            coin_in_stable(state, fee_denom, fee_amount, &entry.timestamp)
+                .await?
+                .map(|(_, _, stable)| stable);
+    }
+
+    Ok(())
+}
+
+/// Opening fee of a lease in stable, see [`State::get_fees`]
+async fn opening_fee(
+    state: &AppState<State>,
+    contract_id: &str,
+) -> Result<Option<BigDecimal>, Error> {
+    let Some(lease) = state
+        .database
+        .ls_opening
+        .get(contract_id.to_owned())
+        .await?
+    else {
+        return Ok(None);
+    };
+
+    let Some(protocol) = state.get_protocol_by_pool_id(&lease.LS_loan_pool_id)
+    else {
+        return Ok(None);
+    };
+
+    let currency = state.get_currency(&lease.LS_asset_symbol)?;
+    let fee = state.get_fees(&lease, protocol).await?;
+    let fee = fee / BigDecimal::from(u64::pow(10, currency.1.try_into()?));
+
+    Ok(Some(fee))
+}
+
+/// Convert a raw on-chain coin amount to its ticker, the amount normalized
+/// by the currency decimals and its stable value at the given date.
+/// Returns `None` for denoms missing from the currency registry.
+async fn coin_in_stable(
+    state: &AppState<State>,
+    denom: &str,
+    amount: &BigDecimal,
+    date_time: &DateTime<Utc>,
+) -> Result<Option<(String, BigDecimal, BigDecimal)>, Error> {
+    let Some(ticker) = state
+        .registry()
+        .hash_map_denom_ticker
+        .get(&denom.to_uppercase())
+        .cloned()
+    else {
+        return Ok(None);
+    };
+
+    let currency = state.get_currency(&ticker)?;
+    let normalized =
+        amount / BigDecimal::from(u64::pow(10, currency.1.try_into()?));
+    let stable = state
+        .in_stable_by_date(&ticker, &normalized.to_string(), None, date_time)
+        .await?;
+
+    Ok(Some((currency.0.to_owned(), normalized, stable)))
+}
+
+/// Parse a Cosmos coin list such as `"1500unls,20ibc/ABC..."`
+fn parse_coins(
+    value: Option<&str>,
+) -> Result<Vec<(String, BigDecimal)>, Error> {
+    let mut coins = Vec::new();
+
+    for coin in value.unwrap_or_default().split(',') {
+        let coin = coin.trim();
+        if coin.is_empty() {
+            continue;
+        }
+
+        let split = coin
+            .find(|c: char| !c.is_ascii_digit())
+            .context(format!("invalid coin {}", coin))?;
+        let (amount, denom) = coin.split_at(split);
+        coins.push((denom.to_owned(), BigDecimal::from_str(amount)?));
+    }
+
+    Ok(coins)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(secs: i64, event: &str, tx_hash: &str) -> WalletStatementRow {
+        WalletStatementRow {
+            timestamp: DateTime::from_timestamp(secs, 0).unwrap(),
+            event: event.to_owned(),
+            reference: String::from("pool"),
+            tx_hash: Some(tx_hash.to_owned()),
+            asset: None,
+            amount: None,
+            receipts: None,
+            cost_basis_stable: None,
+            proceeds_stable: None,
+            realized_gain_stable: None,
+            rewards: None,
+            fee_amount: Some(BigDecimal::from(500)),
+            fee_denom: Some(String::from("unls")),
+        }
+    }
+
+    fn at(secs: i64) -> Option<DateTime<Utc>> {
+        DateTime::from_timestamp(secs, 0)
+    }
+
+    #[test]
+    fn test_ledger_is_in_chronological_order() {
+        let rows = vec![
+            row(30, "lease_repay", "c"),
+            row(10, "lease_open", "a"),
+            row(20, "lease_close", "b"),
+        ];
+        let events: Vec<String> = ledger(rows, None, None)
+            .into_iter()
+            .map(|(_, entry)| entry.event)
+            .collect();
+
+        assert_eq!(events, ["lease_open", "lease_close", "lease_repay"]);
+    }
+
+    #[test]
+    fn test_ledger_carries_lp_cost_basis_into_the_window() {
+        let mut deposit = row(10, "lp_deposit", "a");
+        deposit.cost_basis_stable = Some(BigDecimal::from(100));
+        deposit.receipts = Some(BigDecimal::from(10));
+        let mut withdraw = row(20, "lp_withdraw", "b");
+        withdraw.receipts = Some(BigDecimal::from(5));
+        withdraw.proceeds_stable = Some(BigDecimal::from(80));
+        let later = row(30, "lp_deposit", "c");
+
+        let lines = ledger(vec![deposit, withdraw, later], at(15), at(25));
+
+        assert_eq!(lines.len(), 1);
+        let (_, entry) = &lines[0];
+        assert_eq!(entry.event, "lp_withdraw");
+        assert_eq!(entry.cost_basis_stable, Some(BigDecimal::from(50)));
+        assert_eq!(entry.realized_gain_stable, Some(BigDecimal::from(30)));
+    }
+
+    #[test]
+    fn test_ledger_counts_partial_closes_in_the_gain_of_a_lease() {
+        let mut partial_close = row(10, "lease_partial_close", "a");
+        partial_close.proceeds_stable = Some(BigDecimal::from(40));
+        let mut liquidation = row(20, "lease_liquidation", "b");
+        liquidation.proceeds_stable = Some(BigDecimal::from(15));
+        let mut close = row(30, "lease_close", "c");
+        close.cost_basis_stable = Some(BigDecimal::from(100));
+        close.proceeds_stable = Some(BigDecimal::from(70));
+
+        let lines =
+            ledger(vec![partial_close, liquidation, close], at(15), None);
+
+        let gains: Vec<(String, Option<BigDecimal>)> = lines
+            .into_iter()
+            .map(|(_, entry)| (entry.event, entry.realized_gain_stable))
+            .collect();
+        assert_eq!(
+            gains,
+            [
+                (String::from("lease_liquidation"), None),
+                (String::from("lease_close"), Some(BigDecimal::from(25))),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_ledger_reports_the_fee_of_a_transaction_once() {
+        let rows = vec![
+            row(10, "lease_open", "a"),
+            row(10, "lp_deposit", "a"),
+            row(20, "lease_repay", "b"),
+        ];
+        let fees: Vec<Option<BigDecimal>> = ledger(rows, None, None)
+            .into_iter()
+            .map(|(_, entry)| entry.fee_amount)
+            .collect();
+
+        assert_eq!(
+            fees,
+            [
+                Some(BigDecimal::from(500)),
+                None,
+                Some(BigDecimal::from(500))
+            ]
+        );
+    }
+}
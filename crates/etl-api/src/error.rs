@@ -1,4 +1,7 @@
-use actix_web::{http::StatusCode, HttpResponse, ResponseError};
+use actix_web::{
+    http::{header::RETRY_AFTER, StatusCode},
+    HttpResponse, ResponseError,
+};
 use etl_core::error::Error;
 
 /// Wrapper around core Error that implements actix_web::ResponseError
@@ -55,17 +58,28 @@ impl ResponseError for ApiError {
             | Error::INT(_)
             | Error::BigDecimalError(_)
             | Error::DecodeDateTimeError(_)
-            | Error::Base64DecodeError(_) => StatusCode::BAD_REQUEST,
+            | Error::Base64DecodeError(_)
+            | Error::EmailAddressError(_) => StatusCode::BAD_REQUEST,
+
+            // 401 Unauthorized - missing or unknown API key
+            Error::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+
+            // 403 Forbidden - API key scope does not cover the route
+            Error::Forbidden(_) => StatusCode::FORBIDDEN,
+
+            // 429 Too Many Requests - rate limit bucket is empty
+            Error::RateLimited { .. } => StatusCode::TOO_MANY_REQUESTS,
 
             // 404 Not Found - requested resource does not exist
-            Error::NotSupportedCurrency(_) | Error::ProtocolError(_) => {
-                StatusCode::NOT_FOUND
-            },
+            Error::NotSupportedCurrency(_)
+            | Error::ProtocolError(_)
+            | Error::NotFound(_) => StatusCode::NOT_FOUND,
 
             // 502 Bad Gateway - upstream service error
             Error::ReqwestError(_)
             | Error::TonicStatus(_)
-            | Error::GrpsError(_) => StatusCode::BAD_GATEWAY,
+            | Error::GrpsError(_)
+            | Error::SmtpError(_) => StatusCode::BAD_GATEWAY,
 
             // 504 Gateway Timeout - upstream timed out
             Error::TokioElapsedError(_) => StatusCode::GATEWAY_TIMEOUT,
@@ -98,6 +112,7 @@ impl ResponseError for ApiError {
             | Error::InvalidHeader(_)
             | Error::EceError(_)
             | Error::JWT(_)
+            | Error::EmailError(_)
             | Error::AcquireError(_) => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
@@ -109,6 +124,12 @@ impl ResponseError for ApiError {
             "message": self.0.to_string(),
             "status": status.as_u16(),
         });
-        HttpResponse::build(status).json(body)
+        let mut response = HttpResponse::build(status);
+
+        if let Error::RateLimited { retry_after } = &self.0 {
+            response.insert_header((RETRY_AFTER, retry_after.to_string()));
+        }
+
+        response.json(body)
     }
 }
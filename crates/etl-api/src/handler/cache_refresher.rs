@@ -75,6 +75,7 @@ impl RefreshTracker {
 /// The macro generates:
 /// - ALL_CACHE_NAMES: Array of all cache key constants
 /// - ttl_for_cache(): Returns the TTL Duration for a given cache name
+/// - purge_cache(): Invalidates every entry of a cache by name
 macro_rules! define_caches {
     (
         $(
@@ -82,7 +83,7 @@ macro_rules! define_caches {
         ),* $(,)?
     ) => {
         /// All cache names for iteration (auto-generated from define_caches!)
-        const ALL_CACHE_NAMES: &[&str] = &[
+        pub const ALL_CACHE_NAMES: &[&str] = &[
             $(cache_keys::$key),*
         ];
 
@@ -95,6 +96,18 @@ macro_rules! define_caches {
             )*
             CACHE_TTL_STANDARD // unreachable for known caches
         }
+
+        /// Invalidate every entry of a cache by name (auto-generated).
+        /// Returns false for an unknown cache name.
+        pub fn purge_cache(app_state: &AppState<State>, cache_name: &str) -> bool {
+            $(
+                if cache_name == cache_keys::$key {
+                    app_state.api_cache.$field.invalidate_all();
+                    return true;
+                }
+            )*
+            false
+        }
     };
 }
 
@@ -318,6 +331,20 @@ async fn refresh_all_caches_parallel(
 // Refresh Dispatch
 // =============================================================================
 
+/// Refresh a known cache on demand, outside of the background schedule.
+/// Returns false for an unknown cache name.
+pub async fn refresh_cache(
+    app_state: &AppState<State>,
+    cache_name: &str,
+) -> Result<bool, Error> {
+    if !ALL_CACHE_NAMES.contains(&cache_name) {
+        return Ok(false);
+    }
+
+    refresh_single_cache(app_state, cache_name).await?;
+    Ok(true)
+}
+
 /// Refresh a single cache by name
 async fn refresh_single_cache(
     app_state: &AppState<State>,
@@ -882,7 +909,7 @@ async fn refresh_borrowed(app_state: &AppState<State>) -> Result<(), Error> {
     app_state.api_cache.borrowed.insert(total_key, total).await;
 
     // Set per-protocol borrowed from the batch result
-    for (protocol_key, protocol) in app_state.protocols.iter() {
+    for (protocol_key, protocol) in app_state.registry().protocols.iter() {
         let cache_key =
             build_protocol_cache_key("borrowed", Some(protocol_key));
         let data = borrowed_by_protocol
@@ -2,18 +2,23 @@ use tracing::{error, Level};
 
 use etl_core::{
     configuration::{
-        get_configuration, set_configuration, AppState, Config, State,
+        get_configuration, registry_reload_task, set_configuration, AppState,
+        Config, State,
     },
     error::Error,
     provider::{DatabasePool, Grpc, HTTP},
+    settings::config_path,
 };
 
+mod auth;
 mod controller;
-mod csv_response;
 mod error;
 mod handler;
+mod openapi;
+mod response;
 mod server;
 
+use auth::ApiGuard;
 use handler::cache_refresher;
 
 #[tokio::main]
@@ -62,10 +67,13 @@ async fn run_server() -> Result<(), Error> {
 
     let state = State::new(config.clone(), database, grpc, http).await?;
     let app_state = AppState::new(state);
+    let guard = ApiGuard::new();
 
-    let (_, _) = tokio::try_join!(
-        server::server_task(&app_state),
+    let (_, _, _, _) = tokio::try_join!(
+        server::server_task(&app_state, guard.clone()),
         cache_refresher::cache_refresh_task(app_state.clone()),
+        registry_reload_task(app_state.clone()),
+        auth::usage_flush_task(app_state.clone(), guard),
     )?;
 
     Ok(())
@@ -73,7 +81,7 @@ async fn run_server() -> Result<(), Error> {
 
 async fn init() -> Result<(Config, DatabasePool), Error> {
     set_configuration()?;
-    let config = get_configuration()?;
+    let config = get_configuration(config_path().as_deref())?;
 
     etl_core::migration::run_migrations(&config.database_url).await?;
 
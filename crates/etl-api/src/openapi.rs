@@ -0,0 +1,193 @@
+//! OpenAPI specification
+//!
+//! The document is generated from the `#[utoipa::path]` annotations of the
+//! controllers and served at `/api/openapi.json`, with a Redoc UI at
+//! `/api/docs`. Every route of [`crate::server::api_routes`] must be listed
+//! here; `/metrics` is served outside `/api` for Prometheus and left out.
+
+use actix_web::{get, web, HttpResponse};
+use utoipa::{
+    openapi::{
+        security::{
+            ApiKey, ApiKeyValue, HttpAuthScheme, HttpBuilder, SecurityScheme,
+        },
+        OpenApi as OpenApiDoc,
+    },
+    Modify, OpenApi,
+};
+
+use crate::{
+    auth::API_KEY_HEADER,
+    controller::{
+        admin, alerts, channels, leases, liquidity, metrics, misc, pnl,
+        positions, preferences, protocols, search, staking, treasury, wallets,
+    },
+};
+
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "Nolus ETL API"),
+    servers((url = "/api")),
+    paths(
+        treasury::revenue, treasury::revenue_series, treasury::distributed, treasury::buyback, treasury::buyback_total, treasury::incentives_pool, treasury::earnings,
+        metrics::total_value_locked, metrics::total_tx_value, metrics::supplied_funds, metrics::open_interest, metrics::open_position_value, metrics::borrowed, metrics::supplied_borrowed_history, metrics::monthly_active_wallets, metrics::tx_failure_rate, metrics::fees,
+        pnl::realized_pnl, pnl::realized_pnl_data, pnl::realized_pnl_stats, pnl::realized_pnl_wallet, pnl::unrealized_pnl, pnl::unrealized_pnl_by_address, pnl::pnl_over_time,
+        leases::leases_search, leases::leases_monthly, leases::leased_assets, leases::lease_value_stats, leases::loans_by_token, leases::loans_granted, leases::ls_opening, leases::ls_loan_closing, leases::liquidations, leases::interest_repayments, leases::historically_opened, leases::historically_repaid, leases::historically_liquidated,
+        positions::positions, positions::position_buckets, positions::daily_positions, positions::open_positions_by_token, positions::position_debt_value,
+        liquidity::pools, liquidity::lp_withdraw, liquidity::current_lenders, liquidity::historical_lenders,
+        misc::prices, misc::blocks, misc::txs, misc::ibc_transfers, search::search, misc::history_stats, misc::version, misc::vapid_key, misc::subscribe_get, misc::subscribe_post, misc::test_push,
+        protocols::get_protocols, protocols::get_active_protocols, protocols::get_protocol_by_name, protocols::get_currencies, protocols::get_active_currencies, protocols::get_currency_by_ticker,
+        staking::delegations, staking::rewards, staking::unbonding, staking::validator_shares, staking::proposal_tally,
+        wallets::statement,
+        alerts::alert_rules, alerts::create_alert_rule, alerts::update_alert_rule, alerts::delete_alert_rule,
+        channels::subscription_channels, channels::set_channel, channels::confirm_channel, channels::delete_channel,
+        preferences::subscription_preferences, preferences::set_preferences,
+        admin::api_keys, admin::create_api_key, admin::revoke_api_key, admin::api_key_usage, admin::config, admin::caches, admin::purge_cache, admin::refresh_cache, admin::sync_registry, admin::registry_audit, admin::contract_history, admin::commands, admin::run_aggregation, admin::resync, admin::decode_messages, admin::staking_backfill, admin::activity_backfill, admin::deactivate_subscriptions, admin::action_history, admin::push_notifications, admin::push_stats, admin::subscription_stats,
+        openapi_json,
+    ),
+    modifiers(&SecurityAddon),
+    security((), ("bearer" = []), ("api_key" = [])),
+    tags(
+        (name = "Treasury", description = "Protocol revenue, buybacks and distributions"),
+        (name = "Metrics", description = "Platform-wide totals and series"),
+        (name = "PnL", description = "Realized and unrealized profit and loss"),
+        (name = "Leases", description = "Lease lifecycle data"),
+        (name = "Positions", description = "Open positions"),
+        (name = "Liquidity", description = "Lending pools and lenders"),
+        (name = "Misc", description = "Prices, transactions and push subscriptions"),
+        (name = "Protocols", description = "Protocol and currency registry"),
+        (name = "Staking", description = "Delegations, rewards and unbonding"),
+        (name = "Governance", description = "Proposal vote tallies"),
+        (name = "Wallets", description = "Per-wallet reports"),
+        (name = "Alerts", description = "Alert rules of push subscriptions"),
+        (name = "Channels", description = "Notification channels of push subscriptions"),
+        (name = "Preferences", description = "Notification preferences of push subscriptions"),
+        (name = "Admin", description = "Operational endpoints, admin scope only"),
+    )
+)]
+pub struct ApiDoc;
+
+/// API keys may be sent as a bearer token or in the `X-API-Key` header,
+/// see [`crate::auth`]
+struct SecurityAddon;
+
+impl Modify for SecurityAddon {
+    fn modify(&self, openapi: &mut OpenApiDoc) {
+        let components =
+            openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "bearer",
+            SecurityScheme::Http(
+                HttpBuilder::new().scheme(HttpAuthScheme::Bearer).build(),
+            ),
+        );
+        components.add_security_scheme(
+            "api_key",
+            SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::new(
+                API_KEY_HEADER,
+            ))),
+        );
+    }
+}
+
+#[utoipa::path(
+    tag = "Misc",
+    responses((status = 200, description = "This OpenAPI document", body = Object))
+)]
+#[get("/openapi.json")]
+pub async fn openapi_json(
+    doc: web::Data<OpenApiDoc>,
+) -> Result<HttpResponse, crate::error::ApiError> {
+    Ok(HttpResponse::Ok().json(doc.as_ref()))
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{
+        http::{Method, StatusCode},
+        test::{call_service, init_service, TestRequest},
+        App, HttpResponse,
+    };
+
+    use super::*;
+    use crate::server::api_routes;
+
+    /// Every documented operation is routed to a handler with its method.
+    /// Unrouted requests reach the default service instead; the handlers
+    /// fail on the missing app data without running.
+    #[actix_web::test]
+    async fn every_documented_operation_is_routed() {
+        let app = init_service(
+            App::new()
+                .service(web::scope("/api").configure(api_routes))
+                .default_service(web::to(HttpResponse::ImATeapot)),
+        )
+        .await;
+
+        let spec = ApiDoc::openapi();
+        let mut operations = 0;
+
+        for (path, item) in &spec.paths.paths {
+            let uri = path
+                .split('/')
+                .map(|segment| match segment.starts_with('{') {
+                    true => "x",
+                    false => segment,
+                })
+                .collect::<Vec<_>>()
+                .join("/");
+
+            for (method, operation) in [
+                (Method::GET, &item.get),
+                (Method::POST, &item.post),
+                (Method::PUT, &item.put),
+                (Method::DELETE, &item.delete),
+                (Method::PATCH, &item.patch),
+            ] {
+                if operation.is_none() {
+                    continue;
+                }
+                operations += 1;
+
+                let req = TestRequest::default()
+                    .method(method.to_owned())
+                    .uri(&format!("/api{}", uri))
+                    .to_request();
+                let res = call_service(&app, req).await;
+
+                assert_ne!(
+                    res.status(),
+                    StatusCode::IM_A_TEAPOT,
+                    "{} {} is documented but not routed",
+                    method,
+                    path
+                );
+            }
+        }
+
+        assert!(operations > 0, "no operations documented");
+    }
+
+    /// Successful responses describe their body
+    #[test]
+    fn every_documented_response_has_a_body() {
+        let spec = ApiDoc::openapi();
+
+        for (path, item) in &spec.paths.paths {
+            for operation in
+                [&item.get, &item.post, &item.put, &item.delete, &item.patch]
+                    .into_iter()
+                    .flatten()
+            {
+                let ok = operation.responses.responses.get("200");
+                let has_body = match ok {
+                    Some(utoipa::openapi::RefOr::T(response)) => {
+                        !response.content.is_empty()
+                    },
+                    _ => true,
+                };
+                assert!(has_body, "200 response of {} has no body", path);
+            }
+        }
+    }
+}
@@ -0,0 +1,649 @@
+//! Response formats for list endpoints
+//!
+//! Every list endpoint can answer as JSON, CSV, NDJSON or Parquet. The format
+//! is taken from the `format` query parameter, falling back to the `Accept`
+//! header. Buffered results go through [`respond`], database cursors through
+//! [`stream`] so large exports are never held in memory.
+
+use std::{
+    future::{ready, Ready},
+    io::{self, Write},
+    str::FromStr,
+    sync::{Arc, Mutex},
+};
+
+use actix_web::{
+    dev::Payload,
+    http::header,
+    web::{self, Bytes},
+    FromRequest, HttpRequest, HttpResponse, HttpResponseBuilder,
+};
+use arrow_schema::{DataType, Field, FieldRef, Schema};
+use futures::{Stream, TryStreamExt as _};
+use parquet::arrow::ArrowWriter;
+use serde::{Deserialize, Serialize};
+use serde_arrow::schema::{SchemaLike as _, TracingOptions};
+use serde_json::Value;
+use tracing::error;
+use utoipa::IntoParams;
+
+use etl_core::error::Error;
+
+use crate::error::ApiError;
+
+/// Streamed bodies are flushed to the client once a chunk reaches this size
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Rows buffered per Parquet row group when streaming
+const ROW_GROUP_SIZE: usize = 8192;
+
+const CSV_MIME: &str = "text/csv";
+const NDJSON_MIME: &str = "application/x-ndjson";
+const PARQUET_MIME: &str = "application/vnd.apache.parquet";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResponseFormat {
+    Json,
+    Csv,
+    Ndjson,
+    Parquet,
+}
+
+impl ResponseFormat {
+    fn content_type(self) -> &'static str {
+        match self {
+            ResponseFormat::Json => "application/json",
+            ResponseFormat::Csv => CSV_MIME,
+            ResponseFormat::Ndjson => NDJSON_MIME,
+            ResponseFormat::Parquet => PARQUET_MIME,
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            ResponseFormat::Json => "json",
+            ResponseFormat::Csv => "csv",
+            ResponseFormat::Ndjson => "ndjson",
+            ResponseFormat::Parquet => "parquet",
+        }
+    }
+
+    fn from_mime(mime: &str) -> Option<Self> {
+        match mime {
+            "application/json" => Some(ResponseFormat::Json),
+            CSV_MIME => Some(ResponseFormat::Csv),
+            NDJSON_MIME | "application/jsonl" => Some(ResponseFormat::Ndjson),
+            PARQUET_MIME | "application/x-parquet" => {
+                Some(ResponseFormat::Parquet)
+            },
+            _ => None,
+        }
+    }
+
+    /// Headers shared by buffered and streamed responses
+    fn builder(self, name: &str) -> HttpResponseBuilder {
+        let mut builder = HttpResponse::Ok();
+        builder.content_type(self.content_type());
+
+        if self != ResponseFormat::Json {
+            builder.insert_header((
+                header::CONTENT_DISPOSITION,
+                format!(
+                    "attachment; filename=\"{}.{}\"",
+                    name,
+                    self.extension()
+                ),
+            ));
+        }
+
+        builder
+    }
+}
+
+impl FromStr for ResponseFormat {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "json" => Ok(ResponseFormat::Json),
+            "csv" => Ok(ResponseFormat::Csv),
+            "ndjson" | "jsonl" => Ok(ResponseFormat::Ndjson),
+            "parquet" => Ok(ResponseFormat::Parquet),
+            _ => Err(Error::InvalidOption {
+                option: String::from("format"),
+            }),
+        }
+    }
+}
+
+/// Query parameter documented on every list endpoint
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct FormatQuery {
+    /// `json`, `csv`, `ndjson` or `parquet`; takes precedence over `Accept`
+    pub format: Option<String>,
+}
+
+/// Format requested by the client, `None` when neither the `format` parameter
+/// nor the `Accept` header names one
+#[derive(Debug, Clone, Copy)]
+pub struct Format(Option<ResponseFormat>);
+
+impl Format {
+    /// Format for regular responses, JSON unless requested otherwise
+    pub fn get(self) -> ResponseFormat {
+        self.0.unwrap_or(ResponseFormat::Json)
+    }
+
+    /// Format for `export=true` responses, which have always defaulted to CSV
+    pub fn export(self) -> ResponseFormat {
+        self.0.unwrap_or(ResponseFormat::Csv)
+    }
+
+    fn negotiate(req: &HttpRequest) -> Result<Self, Error> {
+        let query = web::Query::<FormatQuery>::from_query(req.query_string())
+            .map(web::Query::into_inner)
+            .unwrap_or(FormatQuery { format: None });
+
+        if let Some(format) = query.format {
+            return format.parse().map(|f| Format(Some(f)));
+        }
+
+        let accept = req
+            .headers()
+            .get(header::ACCEPT)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default();
+
+        let mut ranges: Vec<(f32, &str)> = accept
+            .split(',')
+            .filter_map(|range| {
+                let mut parts = range.split(';').map(str::trim);
+                let mime = parts.next().filter(|m| !m.is_empty())?;
+                let quality = parts
+                    .find_map(|p| p.strip_prefix("q="))
+                    .and_then(|q| q.parse().ok())
+                    .unwrap_or(1.0);
+                Some((quality, mime))
+            })
+            .collect();
+        ranges.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+        let format = ranges
+            .into_iter()
+            .filter(|(quality, _)| *quality > 0.0)
+            .find_map(|(_, mime)| ResponseFormat::from_mime(mime));
+
+        Ok(Format(format))
+    }
+}
+
+impl FromRequest for Format {
+    type Error = ApiError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        ready(Format::negotiate(req).map_err(ApiError::from))
+    }
+}
+
+/// Builds a response from rows already in memory
+pub fn respond<T: Serialize>(
+    format: ResponseFormat,
+    data: &[T],
+    name: &str,
+) -> Result<HttpResponse, ApiError> {
+    let body = match format {
+        ResponseFormat::Json => {
+            serde_json::to_vec(data).map_err(Error::from)?
+        },
+        ResponseFormat::Csv => {
+            let mut encoder = CsvEncoder::new();
+            for row in data {
+                encoder.push(row)?;
+            }
+            encoder.take()?
+        },
+        ResponseFormat::Ndjson => {
+            let mut buffer = Vec::new();
+            for row in data {
+                push_ndjson(&mut buffer, row)?;
+            }
+            buffer
+        },
+        ResponseFormat::Parquet => {
+            let mut encoder = ParquetEncoder::default();
+            encoder.push(data)?;
+            encoder.finish()?
+        },
+    };
+
+    Ok(format.builder(name).body(body))
+}
+
+/// Builds a chunked response that encodes rows as they arrive from `rows`
+pub fn stream<T, E, S>(
+    format: ResponseFormat,
+    rows: S,
+    name: &str,
+) -> HttpResponse
+where
+    T: Serialize + 'static,
+    E: Into<Error> + 'static,
+    S: Stream<Item = Result<T, E>> + Unpin + 'static,
+{
+    let rows = rows.map_err(Into::into);
+    let body = match format {
+        ResponseFormat::Json => encode_json(rows),
+        ResponseFormat::Csv => encode_csv(rows),
+        ResponseFormat::Ndjson => encode_ndjson(rows),
+        ResponseFormat::Parquet => encode_parquet(rows),
+    };
+    let export = name.to_owned();
+    let body = body.inspect_err(move |e| {
+        error!("Export of {} aborted: {}", export, e);
+    });
+
+    format.builder(name).streaming(body)
+}
+
+type ByteStream =
+    std::pin::Pin<Box<dyn Stream<Item = Result<Bytes, ApiError>>>>;
+
+fn encode_json<T: Serialize + 'static>(
+    mut rows: impl Stream<Item = Result<T, Error>> + Unpin + 'static,
+) -> ByteStream {
+    Box::pin(async_stream::try_stream! {
+        let mut buffer = vec![b'['];
+        let mut first = true;
+
+        while let Some(row) = rows.try_next().await? {
+            if !first {
+                buffer.push(b',');
+            }
+            first = false;
+            serde_json::to_writer(&mut buffer, &row).map_err(Error::from)?;
+
+            if buffer.len() >= CHUNK_SIZE {
+                yield Bytes::from(std::mem::take(&mut buffer));
+            }
+        }
+
+        buffer.push(b']');
+        yield Bytes::from(buffer);
+    })
+}
+
+fn encode_ndjson<T: Serialize + 'static>(
+    mut rows: impl Stream<Item = Result<T, Error>> + Unpin + 'static,
+) -> ByteStream {
+    Box::pin(async_stream::try_stream! {
+        let mut buffer = Vec::new();
+
+        while let Some(row) = rows.try_next().await? {
+            push_ndjson(&mut buffer, &row)?;
+
+            if buffer.len() >= CHUNK_SIZE {
+                yield Bytes::from(std::mem::take(&mut buffer));
+            }
+        }
+
+        if !buffer.is_empty() {
+            yield Bytes::from(buffer);
+        }
+    })
+}
+
+fn encode_csv<T: Serialize + 'static>(
+    mut rows: impl Stream<Item = Result<T, Error>> + Unpin + 'static,
+) -> ByteStream {
+    Box::pin(async_stream::try_stream! {
+        let mut encoder = CsvEncoder::new();
+
+        while let Some(row) = rows.try_next().await? {
+            encoder.push(&row)?;
+
+            if encoder.len() >= CHUNK_SIZE {
+                yield Bytes::from(encoder.take()?);
+            }
+        }
+
+        let rest = encoder.take()?;
+        if !rest.is_empty() {
+            yield Bytes::from(rest);
+        }
+    })
+}
+
+fn encode_parquet<T: Serialize + 'static>(
+    mut rows: impl Stream<Item = Result<T, Error>> + Unpin + 'static,
+) -> ByteStream {
+    Box::pin(async_stream::try_stream! {
+        let mut encoder = ParquetEncoder::default();
+        let mut batch = Vec::with_capacity(ROW_GROUP_SIZE);
+
+        while let Some(row) = rows.try_next().await? {
+            batch.push(row);
+
+            if batch.len() >= ROW_GROUP_SIZE {
+                encoder.push(&batch)?;
+                batch.clear();
+                yield Bytes::from(encoder.take());
+            }
+        }
+
+        if !batch.is_empty() {
+            encoder.push(&batch)?;
+        }
+        yield Bytes::from(encoder.finish()?);
+    })
+}
+
+fn push_ndjson<T: Serialize>(
+    buffer: &mut Vec<u8>,
+    row: &T,
+) -> Result<(), ApiError> {
+    serde_json::to_writer(&mut *buffer, row).map_err(Error::from)?;
+    buffer.push(b'\n');
+    Ok(())
+}
+
+/// CSV writer whose output can be drained between rows; the header is
+/// written with the first row only
+struct CsvEncoder {
+    writer: csv::Writer<Vec<u8>>,
+}
+
+impl CsvEncoder {
+    fn new() -> Self {
+        Self {
+            writer: csv::Writer::from_writer(Vec::new()),
+        }
+    }
+
+    fn push<T: Serialize>(&mut self, row: &T) -> Result<(), ApiError> {
+        self.writer.serialize(row).map_err(|e| {
+            Error::ServerError(format!("CSV serialization error: {}", e))
+        })?;
+        Ok(())
+    }
+
+    fn len(&self) -> usize {
+        self.writer.get_ref().len()
+    }
+
+    /// Drains the output written so far, later rows continue without a header
+    fn take(&mut self) -> Result<Vec<u8>, ApiError> {
+        let next = csv::WriterBuilder::new()
+            .has_headers(false)
+            .from_writer(Vec::new());
+        let writer = std::mem::replace(&mut self.writer, next);
+        let data = writer.into_inner().map_err(|e| {
+            Error::ServerError(format!("CSV writer error: {}", e))
+        })?;
+        Ok(data)
+    }
+}
+
+/// In-memory sink shared with the Parquet writer so finished row groups can be
+/// drained while the writer keeps the file open
+#[derive(Clone, Default)]
+struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl SharedBuffer {
+    fn take(&self) -> Vec<u8> {
+        self.0
+            .lock()
+            .map(|mut buffer| std::mem::take(&mut *buffer))
+            .unwrap_or_default()
+    }
+}
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0
+            .lock()
+            .map_err(|e| io::Error::other(e.to_string()))?
+            .extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Parquet writer that takes its schema from the first batch and writes each
+/// batch as a row group
+#[derive(Default)]
+struct ParquetEncoder {
+    sink: SharedBuffer,
+    writer: Option<(Vec<FieldRef>, ArrowWriter<SharedBuffer>)>,
+}
+
+impl ParquetEncoder {
+    fn push<T: Serialize>(&mut self, rows: &[T]) -> Result<(), ApiError> {
+        if rows.is_empty() {
+            return Ok(());
+        }
+
+        if self.writer.is_none() {
+            let fields = schema_fields(rows)?;
+            let schema = Arc::new(Schema::new(fields.clone()));
+            let writer = ArrowWriter::try_new(self.sink.clone(), schema, None)
+                .map_err(parquet_error)?;
+            self.writer = Some((fields, writer));
+        }
+
+        if let Some((fields, writer)) = self.writer.as_mut() {
+            let rows = rows
+                .iter()
+                .map(|row| conform(fields, row))
+                .collect::<Result<Vec<_>, _>>()?;
+            let batch = serde_arrow::to_record_batch(fields, &rows)
+                .map_err(parquet_error)?;
+            writer.write(&batch).map_err(parquet_error)?;
+            writer.flush().map_err(parquet_error)?;
+        }
+
+        Ok(())
+    }
+
+    fn take(&self) -> Vec<u8> {
+        self.sink.take()
+    }
+
+    /// Writes the footer; an export without rows yields a file with an empty
+    /// schema
+    fn finish(self) -> Result<Vec<u8>, ApiError> {
+        let writer = match self.writer {
+            Some((_, writer)) => writer,
+            None => ArrowWriter::try_new(
+                self.sink.clone(),
+                Arc::new(Schema::empty()),
+                None,
+            )
+            .map_err(parquet_error)?,
+        };
+        writer.close().map_err(parquet_error)?;
+        Ok(self.sink.take())
+    }
+}
+
+/// Fields traced from the first batch. Columns without a value in it have
+/// no type yet and are written as text.
+fn schema_fields<T: Serialize>(rows: &[T]) -> Result<Vec<FieldRef>, ApiError> {
+    let options = TracingOptions::default().allow_null_fields(true);
+    let fields =
+        Vec::<FieldRef>::from_samples(rows, options).map_err(parquet_error)?;
+
+    Ok(fields
+        .into_iter()
+        .map(|field| match field.data_type() {
+            DataType::Null => {
+                Arc::new(Field::new(field.name(), DataType::LargeUtf8, true))
+            },
+            _ => field,
+        })
+        .collect())
+}
+
+/// A row as JSON, with the values of text columns that are not strings
+/// written as their JSON text
+fn conform<T: Serialize>(
+    fields: &[FieldRef],
+    row: &T,
+) -> Result<Value, ApiError> {
+    let mut row = serde_json::to_value(row).map_err(Error::from)?;
+
+    if let Value::Object(columns) = &mut row {
+        for field in fields {
+            if *field.data_type() != DataType::LargeUtf8 {
+                continue;
+            }
+            if let Some(value) = columns.get_mut(field.name()) {
+                if !matches!(value, Value::Null | Value::String(_)) {
+                    *value = Value::String(value.to_string());
+                }
+            }
+        }
+    }
+
+    Ok(row)
+}
+
+fn parquet_error(e: impl std::fmt::Display) -> ApiError {
+    ApiError(Error::ServerError(format!("Parquet encoding error: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::test::TestRequest;
+    use futures::stream;
+
+    use super::*;
+
+    #[derive(Serialize, Deserialize)]
+    struct Row {
+        id: i64,
+        name: Option<String>,
+    }
+
+    fn rows(count: i64) -> Vec<Row> {
+        (0..count)
+            .map(|id| Row {
+                id,
+                name: (id % 2 == 0).then(|| format!("row-{}", id)),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_format_parameter_takes_precedence_over_accept() {
+        let req = TestRequest::with_uri("/x?format=ndjson")
+            .insert_header((header::ACCEPT, CSV_MIME))
+            .to_http_request();
+        let format = Format::negotiate(&req).unwrap();
+        assert_eq!(format.get(), ResponseFormat::Ndjson);
+
+        let req = TestRequest::with_uri("/x?format=xml").to_http_request();
+        assert!(Format::negotiate(&req).is_err());
+    }
+
+    #[test]
+    fn test_accept_header_is_ranked_by_quality() {
+        let req = TestRequest::default()
+            .insert_header((
+                header::ACCEPT,
+                "text/csv;q=0.5, application/vnd.apache.parquet",
+            ))
+            .to_http_request();
+        let format = Format::negotiate(&req).unwrap();
+        assert_eq!(format.get(), ResponseFormat::Parquet);
+
+        let req = TestRequest::default()
+            .insert_header((header::ACCEPT, "text/html, */*;q=0.8"))
+            .to_http_request();
+        let format = Format::negotiate(&req).unwrap();
+        assert_eq!(format.get(), ResponseFormat::Json);
+        assert_eq!(format.export(), ResponseFormat::Csv);
+    }
+
+    #[actix_web::test]
+    async fn test_streamed_csv_writes_a_single_header() {
+        let data = rows(20_000);
+        let rows = stream::iter(data.into_iter().map(Ok::<_, Error>));
+        let response = super::stream(ResponseFormat::Csv, rows, "rows");
+        let body = actix_web::body::to_bytes(response.into_body())
+            .await
+            .unwrap();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+
+        assert_eq!(body.matches("id,name").count(), 1);
+        assert_eq!(body.lines().count(), 20_001);
+    }
+
+    #[actix_web::test]
+    async fn test_streamed_json_matches_buffered_json() {
+        let rows = stream::iter(rows(3).into_iter().map(Ok::<_, Error>));
+        let response = super::stream(ResponseFormat::Json, rows, "rows");
+        let body = actix_web::body::to_bytes(response.into_body())
+            .await
+            .unwrap();
+
+        assert_eq!(body.to_vec(), serde_json::to_vec(&self::rows(3)).unwrap());
+    }
+
+    #[actix_web::test]
+    async fn test_parquet_files_are_complete() {
+        for count in [0, 3] {
+            let response =
+                respond(ResponseFormat::Parquet, &rows(count), "rows").unwrap();
+            let body = actix_web::body::to_bytes(response.into_body())
+                .await
+                .unwrap();
+
+            assert!(body.starts_with(b"PAR1"));
+            assert!(body.ends_with(b"PAR1"));
+        }
+    }
+
+    #[derive(Serialize)]
+    struct Amount {
+        timestamp: chrono::DateTime<chrono::Utc>,
+        amount: Option<bigdecimal::BigDecimal>,
+        count: Option<i64>,
+    }
+
+    #[actix_web::test]
+    async fn test_parquet_columns_empty_in_the_first_batch_are_text() {
+        let data = (0..ROW_GROUP_SIZE as i64 + 10).map(|id| Amount {
+            timestamp: chrono::Utc::now(),
+            amount: (id >= ROW_GROUP_SIZE as i64).then(|| id.into()),
+            count: (id >= ROW_GROUP_SIZE as i64).then_some(id),
+        });
+        let rows = stream::iter(data.map(Ok::<_, Error>));
+        let response = super::stream(ResponseFormat::Parquet, rows, "rows");
+        let body = actix_web::body::to_bytes(response.into_body())
+            .await
+            .unwrap();
+
+        let reader =
+            parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder::try_new(body)
+                .unwrap();
+        let types: Vec<_> = reader
+            .schema()
+            .fields()
+            .iter()
+            .map(|field| field.data_type().to_string())
+            .collect();
+        assert_eq!(types, ["LargeUtf8", "LargeUtf8", "LargeUtf8"]);
+
+        let count: usize = reader
+            .build()
+            .unwrap()
+            .map(|batch| batch.unwrap().num_rows())
+            .sum();
+        assert_eq!(count, ROW_GROUP_SIZE + 10);
+    }
+}
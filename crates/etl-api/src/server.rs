@@ -2,28 +2,45 @@ use actix_cors::Cors;
 use actix_files::Files;
 use actix_web::{dev::Server, http::header, middleware, web, App, HttpServer};
 
+use std::sync::Arc;
+
+use utoipa::OpenApi as _;
+use utoipa_redoc::{Redoc, Servable as _};
+
 use etl_core::{
     configuration::{AppState, State},
     error::Error,
 };
 
-use crate::controller::{
-    leases, liquidity, metrics, misc, pnl, positions, protocols, treasury,
+use crate::{
+    auth::{self, ApiGuard},
+    controller::{
+        admin, alerts, channels, leases, liquidity, metrics, misc, pnl,
+        positions, preferences, protocols, search, staking, treasury, wallets,
+    },
+    openapi::{self, ApiDoc},
 };
 
-pub async fn server_task(app_state: &AppState<State>) -> Result<(), Error> {
+pub async fn server_task(
+    app_state: &AppState<State>,
+    guard: Arc<ApiGuard>,
+) -> Result<(), Error> {
     let app = app_state.clone();
     tokio::spawn(async move {
-        let server = init_server(app)?;
+        let server = init_server(app, guard)?;
         server.await?;
         Ok(())
     })
     .await?
 }
 
-fn init_server(app_state: AppState<State>) -> Result<Server, Error> {
+fn init_server(
+    app_state: AppState<State>,
+    guard: Arc<ApiGuard>,
+) -> Result<Server, Error> {
     let host = app_state.config.server_host.to_owned();
     let port = app_state.config.port;
+    let spec = ApiDoc::openapi();
 
     let server = HttpServer::new(move || {
         let app = app_state.clone();
@@ -44,82 +61,23 @@ fn init_server(app_state: AppState<State>) -> Result<Server, Error> {
             })
             .allowed_methods(vec!["GET", "POST"])
             .allowed_headers(vec![header::AUTHORIZATION, header::ACCEPT])
-            .allowed_header(header::CONTENT_TYPE);
+            .allowed_header(header::CONTENT_TYPE)
+            .allowed_header(auth::API_KEY_HEADER);
 
         App::new()
             .wrap(cors)
             .wrap(middleware::Compress::default())
             .app_data(web::Data::new(app_state.clone()))
+            .app_data(web::Data::from(guard.clone()))
             .app_data(web::JsonConfig::default().limit(4096))
+            .app_data(web::Data::new(spec.clone()))
+            .service(Redoc::with_url("/api/docs", spec.clone()))
             .service(
                 web::scope("/api")
-                    // Treasury endpoints
-                    .service(treasury::revenue)
-                    .service(treasury::revenue_series)
-                    .service(treasury::distributed)
-                    .service(treasury::buyback)
-                    .service(treasury::buyback_total)
-                    .service(treasury::incentives_pool)
-                    .service(treasury::earnings)
-                    // Metrics endpoints
-                    .service(metrics::total_value_locked)
-                    .service(metrics::total_tx_value)
-                    .service(metrics::supplied_funds)
-                    .service(metrics::open_interest)
-                    .service(metrics::open_position_value)
-                    .service(metrics::borrowed)
-                    .service(metrics::supplied_borrowed_history)
-                    .service(metrics::monthly_active_wallets)
-                    // PnL endpoints
-                    .service(pnl::realized_pnl)
-                    .service(pnl::realized_pnl_data)
-                    .service(pnl::realized_pnl_stats)
-                    .service(pnl::realized_pnl_wallet)
-                    .service(pnl::unrealized_pnl)
-                    .service(pnl::unrealized_pnl_by_address)
-                    .service(pnl::pnl_over_time)
-                    // Lease endpoints
-                    .service(leases::leases_search)
-                    .service(leases::leases_monthly)
-                    .service(leases::leased_assets)
-                    .service(leases::lease_value_stats)
-                    .service(leases::loans_by_token)
-                    .service(leases::loans_granted)
-                    .service(leases::ls_opening)
-                    .service(leases::ls_loan_closing)
-                    .service(leases::liquidations)
-                    .service(leases::interest_repayments)
-                    .service(leases::historically_opened)
-                    .service(leases::historically_repaid)
-                    .service(leases::historically_liquidated)
-                    // Position endpoints
-                    .service(positions::positions)
-                    .service(positions::position_buckets)
-                    .service(positions::daily_positions)
-                    .service(positions::open_positions_by_token)
-                    .service(positions::position_debt_value)
-                    // Liquidity endpoints
-                    .service(liquidity::pools)
-                    .service(liquidity::lp_withdraw)
-                    .service(liquidity::current_lenders)
-                    .service(liquidity::historical_lenders)
-                    // Misc endpoints
-                    .service(misc::prices)
-                    .service(misc::blocks)
-                    .service(misc::txs)
-                    .service(misc::history_stats)
-                    .service(misc::version)
-                    .service(misc::subscribe_get)
-                    .service(misc::subscribe_post)
-                    .service(misc::test_push)
-                    // Protocol & Currency endpoints
-                    .service(protocols::get_protocols)
-                    .service(protocols::get_active_protocols)
-                    .service(protocols::get_protocol_by_name)
-                    .service(protocols::get_currencies)
-                    .service(protocols::get_active_currencies)
-                    .service(protocols::get_currency_by_ticker),
+                    .wrap(middleware::from_fn(auth::authenticate))
+                    .configure(api_routes),
             )
+            .service(misc::prometheus)
             .service(Files::new("/", static_dir).index_file("index.html"))
     })
     .bind((host, port))?
@@ -127,3 +85,125 @@ fn init_server(app_state: AppState<State>) -> Result<Server, Error> {
     .run();
     Ok(server)
 }
+
+/// Routes served under `/api`
+pub fn api_routes(cfg: &mut web::ServiceConfig) {
+    cfg
+        // Treasury endpoints
+        .service(treasury::revenue)
+        .service(treasury::revenue_series)
+        .service(treasury::distributed)
+        .service(treasury::buyback)
+        .service(treasury::buyback_total)
+        .service(treasury::incentives_pool)
+        .service(treasury::earnings)
+        // Metrics endpoints
+        .service(metrics::total_value_locked)
+        .service(metrics::total_tx_value)
+        .service(metrics::supplied_funds)
+        .service(metrics::open_interest)
+        .service(metrics::open_position_value)
+        .service(metrics::borrowed)
+        .service(metrics::supplied_borrowed_history)
+        .service(metrics::monthly_active_wallets)
+        .service(metrics::tx_failure_rate)
+        .service(metrics::fees)
+        // PnL endpoints
+        .service(pnl::realized_pnl)
+        .service(pnl::realized_pnl_data)
+        .service(pnl::realized_pnl_stats)
+        .service(pnl::realized_pnl_wallet)
+        .service(pnl::unrealized_pnl)
+        .service(pnl::unrealized_pnl_by_address)
+        .service(pnl::pnl_over_time)
+        // Lease endpoints
+        .service(leases::leases_search)
+        .service(leases::leases_monthly)
+        .service(leases::leased_assets)
+        .service(leases::lease_value_stats)
+        .service(leases::loans_by_token)
+        .service(leases::loans_granted)
+        .service(leases::ls_opening)
+        .service(leases::ls_loan_closing)
+        .service(leases::liquidations)
+        .service(leases::interest_repayments)
+        .service(leases::historically_opened)
+        .service(leases::historically_repaid)
+        .service(leases::historically_liquidated)
+        // Position endpoints
+        .service(positions::positions)
+        .service(positions::position_buckets)
+        .service(positions::daily_positions)
+        .service(positions::open_positions_by_token)
+        .service(positions::position_debt_value)
+        // Liquidity endpoints
+        .service(liquidity::pools)
+        .service(liquidity::lp_withdraw)
+        .service(liquidity::current_lenders)
+        .service(liquidity::historical_lenders)
+        // Misc endpoints
+        .service(misc::prices)
+        .service(misc::blocks)
+        .service(misc::txs)
+        .service(misc::ibc_transfers)
+        .service(search::search)
+        .service(misc::history_stats)
+        .service(misc::version)
+        .service(misc::vapid_key)
+        .service(misc::subscribe_get)
+        .service(misc::subscribe_post)
+        .service(misc::test_push)
+        // Protocol & Currency endpoints
+        .service(protocols::get_protocols)
+        .service(protocols::get_active_protocols)
+        .service(protocols::get_protocol_by_name)
+        .service(protocols::get_currencies)
+        .service(protocols::get_active_currencies)
+        .service(protocols::get_currency_by_ticker)
+        // Staking & governance endpoints
+        .service(staking::delegations)
+        .service(staking::rewards)
+        .service(staking::unbonding)
+        .service(staking::validator_shares)
+        .service(staking::proposal_tally)
+        // Wallet endpoints
+        .service(wallets::statement)
+        // Alert rule endpoints
+        .service(alerts::alert_rules)
+        .service(alerts::create_alert_rule)
+        .service(alerts::delete_alert_rule)
+        .service(alerts::update_alert_rule)
+        // Notification channel endpoints
+        .service(channels::subscription_channels)
+        .service(channels::set_channel)
+        .service(channels::confirm_channel)
+        .service(channels::delete_channel)
+        // Notification preference endpoints
+        .service(preferences::subscription_preferences)
+        .service(preferences::set_preferences)
+        // Admin endpoints
+        .service(admin::api_keys)
+        .service(admin::create_api_key)
+        .service(admin::revoke_api_key)
+        .service(admin::api_key_usage)
+        .service(admin::config)
+        .service(admin::caches)
+        .service(admin::purge_cache)
+        .service(admin::refresh_cache)
+        .service(admin::sync_registry)
+        .service(admin::registry_audit)
+        .service(admin::contract_history)
+        .service(admin::commands)
+        .service(admin::run_aggregation)
+        .service(admin::resync)
+        .service(admin::decode_messages)
+        .service(admin::staking_backfill)
+        .service(admin::activity_backfill)
+        .service(admin::deactivate_subscriptions)
+        .service(admin::action_history)
+        .service(admin::push_notifications)
+        .service(admin::push_stats)
+        .service(admin::subscription_stats)
+        // API documentation
+        .service(openapi::openapi_json);
+}
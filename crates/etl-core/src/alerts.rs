@@ -0,0 +1,399 @@
+//! User-configurable alert rules
+//!
+//! Rules of a subscription are evaluated by the ingest process. Threshold
+//! rules (LTV, price, LP earnings) are checked on every price tick and fire
+//! once when their condition starts to hold; they re-arm when it stops
+//! holding. Close rules fire on every matching lease close event.
+//! Notifications are queued in the push outbox like any other push.
+
+use std::{collections::HashMap, str::FromStr as _};
+
+use bigdecimal::{num_bigint::BigInt, BigDecimal, Zero as _};
+use sqlx::Transaction;
+
+use crate::{
+    configuration::{AppState, PriceCacheKey, State},
+    dao::{
+        postgre::alert_rule::{AlertRuleParams, LeaseAlertState},
+        DataBase,
+    },
+    error::Error,
+    helpers::{AlertComparison, AlertRuleKind, Auto_Close_Strategies},
+    model::AlertRule,
+    push::{self, Recipient},
+    types::{PushData, PUSH_TYPES},
+};
+
+/// Check that the fields required by the rule kind are set and drop the
+/// ones it does not use
+pub fn validate(params: AlertRuleParams) -> Result<AlertRuleParams, Error> {
+    let kind = parse_kind(&params.kind)?;
+
+    let require = |value: Option<String>, name: &str| {
+        value
+            .filter(|v| !v.is_empty())
+            .ok_or_else(|| Error::MissingParams(name.to_owned()))
+    };
+
+    let mut rule = AlertRuleParams {
+        kind: kind.to_string(),
+        ..AlertRuleParams::default()
+    };
+
+    match kind {
+        AlertRuleKind::LeaseLtv => {
+            rule.lease = Some(require(params.lease, "lease")?);
+        },
+        AlertRuleKind::Price => {
+            rule.symbol = Some(require(params.symbol, "symbol")?);
+            rule.protocol = Some(require(params.protocol, "protocol")?);
+        },
+        AlertRuleKind::LpEarnings => {},
+        AlertRuleKind::PositionClosed => {
+            rule.lease = params.lease.filter(|v| !v.is_empty());
+            rule.strategy = match params.strategy {
+                Some(strategy) => Some(
+                    Auto_Close_Strategies::from_str(&strategy)
+                        .map_err(|_| Error::InvalidOption { option: strategy })?
+                        .to_string(),
+                ),
+                None => None,
+            };
+            return Ok(rule);
+        },
+    }
+
+    let comparison = require(params.comparison, "comparison")?;
+    rule.comparison = Some(
+        AlertComparison::from_str(&comparison)
+            .map_err(|_| Error::InvalidOption { option: comparison })?
+            .to_string(),
+    );
+
+    let threshold = params
+        .threshold
+        .ok_or_else(|| Error::MissingParams(String::from("threshold")))?;
+    if threshold <= BigDecimal::zero() {
+        return Err(Error::InvalidOption {
+            option: String::from("threshold"),
+        });
+    }
+    rule.threshold = Some(threshold);
+
+    Ok(rule)
+}
+
+fn parse_kind(kind: &str) -> Result<AlertRuleKind, Error> {
+    AlertRuleKind::from_str(kind).map_err(|_| Error::InvalidOption {
+        option: kind.to_owned(),
+    })
+}
+
+/// Whether the threshold condition of a rule holds for `value`
+pub fn holds(rule: &AlertRule, value: &BigDecimal) -> bool {
+    let (Some(comparison), Some(threshold)) =
+        (&rule.comparison, &rule.threshold)
+    else {
+        return false;
+    };
+
+    match AlertComparison::from_str(comparison) {
+        Ok(AlertComparison::Above) => value > threshold,
+        Ok(AlertComparison::Below) => value < threshold,
+        Err(_) => false,
+    }
+}
+
+/// Evaluate the threshold rules against the latest prices. Called after every
+/// price tick.
+pub async fn evaluate_prices(app_state: &AppState<State>) -> Result<(), Error> {
+    let prices = app_state.latest_prices.read().await.clone();
+    let database = &app_state.database;
+
+    let (price_rules, lease_states, earnings_rules) = tokio::try_join!(
+        database.alert_rule.get_active_by_kind(AlertRuleKind::Price),
+        database.alert_rule.get_lease_states(),
+        database
+            .alert_rule
+            .get_active_by_kind(AlertRuleKind::LpEarnings),
+    )?;
+
+    let mut values = vec![];
+
+    for rule in price_rules {
+        let (Some(symbol), Some(protocol)) = (&rule.symbol, &rule.protocol)
+        else {
+            continue;
+        };
+        if let Some(price) =
+            prices.get(&(symbol.to_owned(), protocol.to_owned()))
+        {
+            values.push((rule, price.to_owned()));
+        }
+    }
+
+    for state in lease_states {
+        if let Some(ltv) = lease_ltv(app_state, &prices, &state) {
+            values.push((state.rule, ltv));
+        }
+    }
+
+    // Earnings are computed once per wallet
+    let mut earnings: HashMap<String, BigDecimal> = HashMap::new();
+    for rule in earnings_rules {
+        let value = match earnings.get(&rule.address) {
+            Some(value) => value.to_owned(),
+            None => {
+                let value = database
+                    .lp_pool_state
+                    .get_earnings(rule.address.to_owned())
+                    .await?;
+                earnings.insert(rule.address.to_owned(), value.to_owned());
+                value
+            },
+        };
+        values.push((rule, value));
+    }
+
+    let mut transaction = database.pool.begin().await?;
+    for (rule, value) in values {
+        let triggered = holds(&rule, &value);
+        if triggered == rule.triggered {
+            continue;
+        }
+
+        database
+            .alert_rule
+            .set_state(
+                rule.id,
+                triggered,
+                Some(value.to_owned()),
+                triggered,
+                &mut transaction,
+            )
+            .await?;
+
+        if triggered {
+            notify(app_state, &rule, Some(value), &mut transaction).await?;
+        }
+    }
+    transaction.commit().await?;
+
+    Ok(())
+}
+
+/// LTV of a lease in percent, with its position valued in the pool currency
+/// at the latest prices
+fn lease_ltv(
+    app_state: &AppState<State>,
+    prices: &HashMap<PriceCacheKey, BigDecimal>,
+    state: &LeaseAlertState,
+) -> Option<BigDecimal> {
+    let protocol = app_state.get_protocol_by_pool_id(&state.pool_id)?;
+    let pool_currency =
+        app_state.get_currency_by_pool_id(&state.pool_id).ok()?;
+    let asset_decimals = app_state
+        .registry()
+        .hash_map_currencies
+        .get(&state.asset_symbol)?
+        .1;
+
+    let price =
+        prices.get(&(state.asset_symbol.to_owned(), protocol.to_owned()))?;
+    let pool_currency_price =
+        prices.get(&(pool_currency.0.to_owned(), protocol))?;
+
+    ltv(
+        &state.debt,
+        pool_currency.1,
+        &state.amount,
+        asset_decimals,
+        price,
+        pool_currency_price,
+    )
+}
+
+/// LTV in percent of `debt` raw units of the pool currency against `amount`
+/// raw units of the lease asset. Both are scaled by the decimals of their
+/// currency before the asset is valued in the pool currency.
+fn ltv(
+    debt: &BigDecimal,
+    pool_currency_decimals: i16,
+    amount: &BigDecimal,
+    asset_decimals: i16,
+    price: &BigDecimal,
+    pool_currency_price: &BigDecimal,
+) -> Option<BigDecimal> {
+    if pool_currency_price.is_zero() {
+        return None;
+    }
+
+    let value = units(amount, asset_decimals) * price / pool_currency_price;
+    if value.is_zero() {
+        return None;
+    }
+
+    let debt = units(debt, pool_currency_decimals);
+    Some((debt / value * BigDecimal::from(100)).round(2))
+}
+
+/// Whole units of `raw` minimal units of a currency with `decimals`
+fn units(raw: &BigDecimal, decimals: i16) -> BigDecimal {
+    raw / BigDecimal::new(BigInt::from(1), -i64::from(decimals))
+}
+
+/// Fire the close rules matching a lease close event. `strategy` is set when
+/// the lease was closed by an auto-close strategy.
+pub async fn on_position_closed(
+    app_state: &AppState<State>,
+    lease: String,
+    strategy: Option<Auto_Close_Strategies>,
+    transaction: &mut Transaction<'_, DataBase>,
+) -> Result<(), Error> {
+    let owner = app_state
+        .database
+        .ls_opening
+        .get_owner(lease.to_owned())
+        .await?;
+
+    let rules = app_state
+        .database
+        .alert_rule
+        .get_position_closed(
+            lease.to_owned(),
+            owner,
+            strategy.map(String::from),
+            transaction,
+        )
+        .await?;
+
+    for rule in rules {
+        app_state
+            .database
+            .alert_rule
+            .set_state(rule.id, false, None, true, transaction)
+            .await?;
+
+        let rule = AlertRule {
+            lease: Some(lease.to_owned()),
+            ..rule
+        };
+        notify(app_state, &rule, None, transaction).await?;
+    }
+
+    Ok(())
+}
+
+async fn notify(
+    app_state: &AppState<State>,
+    rule: &AlertRule,
+    value: Option<BigDecimal>,
+    transaction: &mut Transaction<'_, DataBase>,
+) -> Result<(), Error> {
+    let body = serde_json::json!({
+        "rule": rule.id,
+        "variant": rule.kind,
+        "kind": rule.kind,
+        "value": value.map(|v| v.to_string()),
+        "comparison": rule.comparison,
+        "threshold": rule.threshold.as_ref().map(|v| v.to_string()),
+        "lease": rule.lease,
+        "position": rule.lease,
+        "symbol": rule.symbol,
+        "protocol": rule.protocol,
+        "strategy": rule.strategy,
+    });
+
+    let push_data = PushData::new(PUSH_TYPES::Alert, &body)?;
+
+    push::enqueue(
+        app_state,
+        Recipient::AlertRule(rule.id),
+        push_data,
+        transaction,
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params(kind: &str) -> AlertRuleParams {
+        AlertRuleParams {
+            kind: kind.to_owned(),
+            ..AlertRuleParams::default()
+        }
+    }
+
+    #[test]
+    fn validate_requires_fields_of_the_kind() {
+        assert!(matches!(
+            validate(params("lease_ltv")),
+            Err(Error::MissingParams(field)) if field == "lease"
+        ));
+        assert!(matches!(
+            validate(params("unknown")),
+            Err(Error::InvalidOption { .. })
+        ));
+
+        let rule = validate(AlertRuleParams {
+            symbol: Some(String::from("ATOM")),
+            protocol: Some(String::from("OSMOSIS-OSMOSIS-USDC_NOBLE")),
+            comparison: Some(String::from("below")),
+            threshold: Some(BigDecimal::from(8)),
+            strategy: Some(String::from("take-profit")),
+            ..params("price")
+        })
+        .unwrap();
+        assert_eq!(rule.symbol.as_deref(), Some("ATOM"));
+        assert_eq!(rule.strategy, None);
+    }
+
+    #[test]
+    fn validate_close_rule_checks_strategy() {
+        let rule = validate(AlertRuleParams {
+            strategy: Some(String::from("take-profit")),
+            threshold: Some(BigDecimal::from(1)),
+            ..params("position_closed")
+        })
+        .unwrap();
+        assert_eq!(rule.strategy.as_deref(), Some("take-profit"));
+        assert_eq!(rule.threshold, None);
+
+        assert!(matches!(
+            validate(AlertRuleParams {
+                strategy: Some(String::from("trailing")),
+                ..params("position_closed")
+            }),
+            Err(Error::InvalidOption { .. })
+        ));
+    }
+
+    #[test]
+    fn ltv_scales_by_the_decimals_of_each_currency() {
+        // 1.5 of an 18-decimal asset at 2 against 1 of a 6-decimal LPN at 1
+        let amount = BigDecimal::from_str("1500000000000000000").unwrap();
+        let debt = BigDecimal::from(1_000_000);
+        let value = ltv(
+            &debt,
+            6,
+            &amount,
+            18,
+            &BigDecimal::from(2),
+            &BigDecimal::from(1),
+        );
+        assert_eq!(value, Some(BigDecimal::from_str("33.33").unwrap()));
+
+        // Same decimals on both sides
+        let value = ltv(
+            &BigDecimal::from(500),
+            6,
+            &BigDecimal::from(1000),
+            6,
+            &BigDecimal::from(1),
+            &BigDecimal::from(1),
+        );
+        assert_eq!(value, Some(BigDecimal::from(50)));
+    }
+}
@@ -0,0 +1,81 @@
+use async_trait::async_trait;
+use reqwest::Client;
+
+use crate::{configuration::BotConfig, error::Error, helpers::ChannelKind};
+
+use super::{mismatch, Destination, Notification, NotificationChannel};
+
+/// Message to a chat through a bot HTTP API following the Telegram
+/// `sendMessage` call
+pub struct BotChannel {
+    http: Client,
+    api_url: String,
+    token: String,
+}
+
+impl BotChannel {
+    pub fn new(http: Client, config: &BotConfig) -> Self {
+        Self {
+            http,
+            api_url: config.api_url.trim_end_matches('/').to_owned(),
+            token: config.token.to_owned(),
+        }
+    }
+}
+
+#[async_trait]
+impl NotificationChannel for BotChannel {
+    async fn send(
+        &self,
+        destination: &Destination,
+        notification: &Notification,
+    ) -> Result<u16, Error> {
+        let Destination::Bot { chat_id } = destination else {
+            return Err(mismatch(ChannelKind::Bot, destination));
+        };
+
+        let response = self
+            .http
+            .post(format!("{}/bot{}/sendMessage", self.api_url, self.token))
+            .json(&serde_json::json!({
+                "chat_id": chat_id,
+                "text": notification.text(),
+            }))
+            .send()
+            .await?;
+
+        Ok(response.status().as_u16())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::channel::stand_in;
+
+    #[tokio::test]
+    async fn posts_message_to_chat() {
+        let (url, server) = stand_in::http(200).await;
+        let channel = BotChannel::new(
+            Client::new(),
+            &BotConfig {
+                api_url: format!("{}/", url),
+                token: String::from("123:abc"),
+            },
+        );
+        let destination = Destination::Bot {
+            chat_id: String::from("-1001"),
+        };
+        let notification = stand_in::notification(3, "Alert", r#"{"rule": 1}"#);
+
+        let status = channel.send(&destination, &notification).await.unwrap();
+        assert_eq!(status, 200);
+
+        let request = server.await.unwrap();
+        let body: serde_json::Value =
+            serde_json::from_slice(&request.body).unwrap();
+        assert_eq!(request.path, "/bot123:abc/sendMessage");
+        assert_eq!(body["chat_id"], "-1001");
+        assert_eq!(body["text"], notification.text());
+    }
+}
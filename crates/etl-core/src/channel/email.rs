@@ -0,0 +1,163 @@
+use async_trait::async_trait;
+use lettre::{
+    message::{header::ContentType, Mailbox},
+    transport::smtp::authentication::Credentials,
+    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+};
+
+use crate::{configuration::SmtpConfig, error::Error, helpers::ChannelKind};
+
+use super::{mismatch, Destination, Notification, NotificationChannel};
+
+/// Plain text email through the configured SMTP relay.
+///
+/// Statuses follow HTTP: an accepted message is 200 and a permanent SMTP
+/// rejection 400, so it is not retried. Transient rejections are errors.
+pub struct EmailChannel {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+impl EmailChannel {
+    pub fn new(config: &SmtpConfig) -> Result<Self, Error> {
+        let builder = if config.starttls {
+            AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&config.host)?
+        } else {
+            AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(
+                &config.host,
+            )
+        };
+        let mut builder = builder.port(config.port);
+
+        if let (Some(username), Some(password)) =
+            (&config.username, &config.password)
+        {
+            builder = builder.credentials(Credentials::new(
+                username.to_owned(),
+                password.to_owned(),
+            ));
+        }
+
+        Ok(Self {
+            transport: builder.build(),
+            from: config.from.parse()?,
+        })
+    }
+}
+
+#[async_trait]
+impl NotificationChannel for EmailChannel {
+    async fn send(
+        &self,
+        destination: &Destination,
+        notification: &Notification,
+    ) -> Result<u16, Error> {
+        let Destination::Email { to } = destination else {
+            return Err(mismatch(ChannelKind::Email, destination));
+        };
+
+        self.deliver(
+            to,
+            notification.content.title.to_owned(),
+            notification.text(),
+        )
+        .await
+    }
+}
+
+impl EmailChannel {
+    /// Mail the code confirming `to` as a channel of a subscription
+    pub async fn send_confirmation(
+        &self,
+        to: &str,
+        code: &str,
+    ) -> Result<u16, Error> {
+        self.deliver(
+            to,
+            String::from("Confirm your Nolus notifications"),
+            format!(
+                "Enter this code to receive Nolus notifications at {}:\n\n{}\n\nIgnore this email if you did not ask for them.",
+                to, code
+            ),
+        )
+        .await
+    }
+
+    async fn deliver(
+        &self,
+        to: &str,
+        subject: String,
+        body: String,
+    ) -> Result<u16, Error> {
+        let message = Message::builder()
+            .from(self.from.to_owned())
+            .to(to.parse()?)
+            .subject(subject)
+            .header(ContentType::TEXT_PLAIN)
+            .body(body)?;
+
+        match self.transport.send(message).await {
+            Ok(_) => Ok(200),
+            Err(error) if error.is_permanent() => {
+                tracing::warn!("Email to {} rejected: {}", to, error);
+                Ok(400)
+            },
+            Err(error) => Err(error.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::channel::stand_in;
+
+    fn channel(port: u16) -> EmailChannel {
+        EmailChannel::new(&SmtpConfig {
+            host: String::from("127.0.0.1"),
+            port,
+            username: None,
+            password: None,
+            from: String::from("Nolus <alerts@example.com>"),
+            starttls: false,
+        })
+        .unwrap()
+    }
+
+    fn notification() -> Notification {
+        stand_in::notification(7, "Alert", r#"{"rule": 1}"#)
+    }
+
+    #[tokio::test]
+    async fn sends_through_relay() {
+        let (port, server) = stand_in::smtp("250 OK").await;
+        let destination = Destination::Email {
+            to: String::from("user@example.com"),
+        };
+
+        let status = channel(port)
+            .send(&destination, &notification())
+            .await
+            .unwrap();
+        assert_eq!(status, 200);
+
+        let data = server.await.unwrap();
+        assert!(data.contains("To: user@example.com"));
+        assert!(data.contains("Subject: Alert title"));
+        assert!(data.contains("Alert body"));
+    }
+
+    #[tokio::test]
+    async fn permanent_rejection_is_not_retried() {
+        let (port, _server) = stand_in::smtp("550 no such user").await;
+        let destination = Destination::Email {
+            to: String::from("gone@example.com"),
+        };
+
+        let status = channel(port)
+            .send(&destination, &notification())
+            .await
+            .unwrap();
+        assert_eq!(status, 400);
+    }
+}
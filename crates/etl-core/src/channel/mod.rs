@@ -0,0 +1,263 @@
+//! Notification channels
+//!
+//! A queued notification is delivered to every destination of its
+//! recipients: the browser through Web Push and the channels a subscription
+//! added in `subscription_channel` (signed webhooks, email, chat bots).
+//! Every channel reports an HTTP-like status, which is recorded in
+//! `push_delivery` and decides whether the delivery is retried.
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use reqwest::Client;
+use serde_json::Value;
+
+use crate::{
+    configuration::Config,
+    error::Error,
+    helpers::ChannelKind,
+    model::{Subscription, SubscriptionChannel},
+    template::Content,
+    types::{PushData, PushHeader},
+};
+
+mod bot;
+mod email;
+#[cfg(test)]
+mod stand_in;
+mod web_push;
+mod webhook;
+
+pub use bot::BotChannel;
+pub use email::EmailChannel;
+pub use web_push::WebPushChannel;
+pub use webhook::{check_url, is_public, sign, WebhookChannel};
+
+/// Notification as queued in the outbox, with the id of its
+/// `push_notification` row and its content in the locale of the destination
+#[derive(Debug, Clone)]
+pub struct Notification {
+    pub id: i64,
+    pub header: PushHeader,
+    pub data: PushData,
+    pub content: Content,
+}
+
+impl Notification {
+    /// JSON for browsers and webhooks: the push type, the fields of the
+    /// notification and its rendered content
+    pub fn payload(&self) -> String {
+        let data = serde_json::from_str(&self.data.body)
+            .unwrap_or_else(|_| Value::String(self.data.body.to_owned()));
+
+        serde_json::json!({
+            "type": self.data.r#type,
+            "data": data,
+            "title": self.content.title,
+            "body": self.content.body,
+            "link": self.content.link,
+            "icon": self.content.icon,
+        })
+        .to_string()
+    }
+
+    /// Plain text for channels read by people
+    pub fn text(&self) -> String {
+        format!(
+            "{}\n\n{}\n{}",
+            self.content.title, self.content.body, self.content.link
+        )
+    }
+}
+
+/// Where a channel delivers a notification
+#[derive(Debug, Clone)]
+pub enum Destination {
+    WebPush(Subscription),
+    /// Webhooks of subscribers are limited to public addresses, the one of
+    /// the operator is `trusted`
+    Webhook {
+        url: String,
+        secret: String,
+        trusted: bool,
+    },
+    Email {
+        to: String,
+    },
+    Bot {
+        chat_id: String,
+    },
+}
+
+impl Destination {
+    /// Destination of a channel row, `None` for a webhook without a secret
+    pub fn from_channel(channel: &SubscriptionChannel) -> Option<Self> {
+        let kind: ChannelKind = channel.channel.parse().ok()?;
+        let target = channel.target.to_owned();
+
+        match kind {
+            ChannelKind::WebPush => None,
+            ChannelKind::Webhook => Some(Destination::Webhook {
+                url: target,
+                secret: channel.secret.to_owned()?,
+                trusted: false,
+            }),
+            ChannelKind::Email => Some(Destination::Email { to: target }),
+            ChannelKind::Bot => Some(Destination::Bot { chat_id: target }),
+        }
+    }
+
+    pub fn kind(&self) -> ChannelKind {
+        match self {
+            Destination::WebPush(_) => ChannelKind::WebPush,
+            Destination::Webhook { .. } => ChannelKind::Webhook,
+            Destination::Email { .. } => ChannelKind::Email,
+            Destination::Bot { .. } => ChannelKind::Bot,
+        }
+    }
+
+    /// Value of `push_delivery.endpoint`
+    pub fn endpoint(&self) -> &str {
+        match self {
+            Destination::WebPush(subscription) => &subscription.endpoint,
+            Destination::Webhook { url, .. } => url,
+            Destination::Email { to } => to,
+            Destination::Bot { chat_id } => chat_id,
+        }
+    }
+}
+
+/// Destination of a notification with the wallet it is delivered for and
+/// the locale it is rendered in
+#[derive(Debug, Clone)]
+pub struct Target {
+    pub address: String,
+    /// Auth key of the subscription, none for the ops webhook
+    pub auth: Option<String>,
+    pub locale: String,
+    pub destination: Destination,
+}
+
+#[async_trait]
+pub trait NotificationChannel: Send + Sync {
+    /// Deliver a notification, returning the status of the remote service.
+    /// Errors are transport failures and are retried.
+    async fn send(
+        &self,
+        destination: &Destination,
+        notification: &Notification,
+    ) -> Result<u16, Error>;
+}
+
+/// Channels available to this process. Email and bots are only available
+/// when configured.
+pub struct Channels {
+    pub web_push: WebPushChannel,
+    pub webhook: WebhookChannel,
+    pub email: Option<EmailChannel>,
+    pub bot: Option<BotChannel>,
+}
+
+impl Channels {
+    pub fn new(config: &Config, http: Client) -> Result<Self, Error> {
+        let email = match &config.smtp {
+            Some(smtp) => Some(EmailChannel::new(smtp)?),
+            None => None,
+        };
+        let bot = config
+            .bot
+            .as_ref()
+            .map(|bot| BotChannel::new(http.clone(), bot));
+
+        Ok(Self {
+            web_push: WebPushChannel::new(http.clone(), config),
+            webhook: WebhookChannel::new(
+                http,
+                Duration::from_secs(config.timeout),
+            )?,
+            email,
+            bot,
+        })
+    }
+
+    pub fn get(&self, kind: ChannelKind) -> Option<&dyn NotificationChannel> {
+        match kind {
+            ChannelKind::WebPush => Some(&self.web_push),
+            ChannelKind::Webhook => Some(&self.webhook),
+            ChannelKind::Email => {
+                self.email.as_ref().map(|c| c as &dyn NotificationChannel)
+            },
+            ChannelKind::Bot => {
+                self.bot.as_ref().map(|c| c as &dyn NotificationChannel)
+            },
+        }
+    }
+}
+
+/// Check the target a subscription gives for a channel: an HTTPS URL of a
+/// public host for webhooks, an email address or a bot chat id
+pub async fn validate_target(
+    kind: ChannelKind,
+    target: &str,
+) -> Result<(), Error> {
+    let invalid = || Error::InvalidOption {
+        option: format!("{} target", kind),
+    };
+
+    match kind {
+        ChannelKind::WebPush => Ok(()),
+        ChannelKind::Webhook => webhook::check_url(target).await,
+        ChannelKind::Email => {
+            target.parse::<lettre::Address>()?;
+            Ok(())
+        },
+        ChannelKind::Bot => {
+            if target.is_empty() || target.len() > 64 {
+                return Err(invalid());
+            }
+            Ok(())
+        },
+    }
+}
+
+/// Error for a destination handed to the wrong channel
+fn mismatch(kind: ChannelKind, destination: &Destination) -> Error {
+    Error::InvalidOption {
+        option: format!(
+            "{} destination for {} channel",
+            destination.kind(),
+            kind
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn validate_target_per_channel() {
+        assert!(validate_target(
+            ChannelKind::Webhook,
+            "https://93.184.215.14/hook"
+        )
+        .await
+        .is_ok());
+        assert!(validate_target(
+            ChannelKind::Webhook,
+            "http://93.184.215.14/hook"
+        )
+        .await
+        .is_err());
+        assert!(
+            validate_target(ChannelKind::Webhook, "https://10.1.2.3/hook")
+                .await
+                .is_err()
+        );
+        assert!(validate_target(ChannelKind::Email, "user@example.com")
+            .await
+            .is_ok());
+        assert!(validate_target(ChannelKind::Email, "user").await.is_err());
+        assert!(validate_target(ChannelKind::Bot, "").await.is_err());
+    }
+}
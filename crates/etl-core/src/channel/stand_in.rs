@@ -0,0 +1,144 @@
+//! Local stand-ins for the remote services of the channels, answering a
+//! single exchange and handing back what they received
+
+use std::collections::HashMap;
+
+use crate::{
+    template::Content,
+    types::{PushData, PushHeader, Urgency},
+};
+
+use super::Notification;
+
+use tokio::{
+    io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+    net::TcpListener,
+    task::JoinHandle,
+};
+
+pub struct Request {
+    pub path: String,
+    /// Header names are lowercase
+    pub headers: HashMap<String, String>,
+    pub body: Vec<u8>,
+}
+
+/// HTTP server answering one request with `status`. Returns its base URL.
+pub async fn http(status: u16) -> (String, JoinHandle<Request>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+
+    let handle = tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut reader = BufReader::new(stream);
+
+        let mut line = String::new();
+        reader.read_line(&mut line).await.unwrap();
+        let path = line.split_whitespace().nth(1).unwrap().to_owned();
+
+        let mut headers = HashMap::new();
+        loop {
+            line.clear();
+            reader.read_line(&mut line).await.unwrap();
+            let Some((name, value)) = line.trim_end().split_once(':') else {
+                break;
+            };
+            headers.insert(name.to_lowercase(), value.trim().to_owned());
+        }
+
+        let length = headers
+            .get("content-length")
+            .map_or(0, |v| v.parse().unwrap());
+        let mut body = vec![0; length];
+        reader.read_exact(&mut body).await.unwrap();
+
+        let response = format!(
+            "HTTP/1.1 {} Stand-in\r\ncontent-length: 0\r\nconnection: close\r\n\r\n",
+            status
+        );
+        reader
+            .get_mut()
+            .write_all(response.as_bytes())
+            .await
+            .unwrap();
+
+        Request {
+            path,
+            headers,
+            body,
+        }
+    });
+
+    (url, handle)
+}
+
+/// SMTP server accepting one session, replying `rcpt_reply` to `RCPT TO`.
+/// Returns its port and the message data it received.
+pub async fn smtp(rcpt_reply: &'static str) -> (u16, JoinHandle<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+
+    let handle = tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut reader = BufReader::new(stream);
+        let mut data = String::new();
+
+        reply(&mut reader, "220 stand-in ESMTP").await;
+
+        let mut line = String::new();
+        while reader.read_line(&mut line).await.unwrap_or(0) > 0 {
+            let command = line.to_uppercase();
+            if command.starts_with("EHLO") {
+                reply(&mut reader, "250 stand-in").await;
+            } else if command.starts_with("RCPT") {
+                reply(&mut reader, rcpt_reply).await;
+            } else if command.starts_with("DATA") {
+                reply(&mut reader, "354 go ahead").await;
+                loop {
+                    line.clear();
+                    reader.read_line(&mut line).await.unwrap();
+                    if line == ".\r\n" {
+                        break;
+                    }
+                    data.push_str(&line);
+                }
+                reply(&mut reader, "250 queued").await;
+            } else if command.starts_with("QUIT") {
+                reply(&mut reader, "221 bye").await;
+                break;
+            } else {
+                reply(&mut reader, "250 OK").await;
+            }
+            line.clear();
+        }
+
+        data
+    });
+
+    (port, handle)
+}
+
+async fn reply(reader: &mut BufReader<tokio::net::TcpStream>, line: &str) {
+    let line = format!("{}\r\n", line);
+    reader.get_mut().write_all(line.as_bytes()).await.unwrap();
+}
+
+pub fn notification(id: i64, push_type: &str, body: &str) -> Notification {
+    Notification {
+        id,
+        header: PushHeader {
+            ttl: 60,
+            urgency: Urgency::High,
+        },
+        data: PushData {
+            r#type: push_type.to_owned(),
+            body: body.to_owned(),
+        },
+        content: Content {
+            title: format!("{} title", push_type),
+            body: format!("{} body", push_type),
+            link: String::from("https://app.nolus.io"),
+            icon: String::from("https://app.nolus.io/icons/nolus.png"),
+        },
+    }
+}
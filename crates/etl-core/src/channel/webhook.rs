@@ -0,0 +1,290 @@
+use std::{
+    net::{IpAddr, SocketAddr},
+    sync::Arc,
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use reqwest::{
+    dns::{Addrs, Name, Resolve, Resolving},
+    redirect::Policy,
+    Client, Url,
+};
+use sha2::Sha256;
+use tokio::net::lookup_host;
+use url::Host;
+
+use crate::{error::Error, helpers::ChannelKind};
+
+use super::{mismatch, Destination, Notification, NotificationChannel};
+
+/// Status of a delivery refused because the webhook does not resolve to
+/// public addresses. It is not retried.
+const FORBIDDEN: u16 = 403;
+
+/// JSON POST to a URL of the subscriber, signed with the secret of the
+/// webhook.
+///
+/// Receivers verify `X-Nolus-Signature` against the HMAC-SHA256 of
+/// `{X-Nolus-Timestamp}.{body}` and may use `X-Nolus-Delivery` to drop
+/// retried duplicates.
+///
+/// Webhooks of subscribers only reach public addresses: the URL is checked
+/// before every delivery and the client resolves names through
+/// [`PublicResolver`], so a name rebound to a private address after the
+/// check is refused too. Redirects are not followed.
+pub struct WebhookChannel {
+    /// Client of subscriber webhooks
+    public: Client,
+    /// Client of the webhook of the operator, which may be internal
+    http: Client,
+}
+
+impl WebhookChannel {
+    pub fn new(http: Client, timeout: Duration) -> Result<Self, Error> {
+        let public = Client::builder()
+            .timeout(timeout)
+            .redirect(Policy::none())
+            .dns_resolver(Arc::new(PublicResolver))
+            .build()?;
+
+        Ok(Self { public, http })
+    }
+}
+
+/// Whether `ip` is reachable from the internet: not loopback, private,
+/// shared, link-local, unique-local, multicast or reserved
+pub fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            !(ip.is_unspecified()
+                || ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_documentation()
+                || a == 0
+                || (a == 100 && (64..128).contains(&b))
+                || a >= 224)
+        },
+        IpAddr::V6(ip) => {
+            if let Some(ip) = ip.to_ipv4_mapped() {
+                return is_public(IpAddr::V4(ip));
+            }
+            let first = ip.segments()[0];
+            !(ip.is_unspecified()
+                || ip.is_loopback()
+                || ip.is_multicast()
+                || first & 0xfe00 == 0xfc00
+                || first & 0xffc0 == 0xfe80)
+        },
+    }
+}
+
+/// Check a webhook URL of a subscriber: HTTPS to a host whose addresses are
+/// all public. Resolution failures are errors of their own, a non-public
+/// address is `InvalidOption`.
+pub async fn check_url(target: &str) -> Result<(), Error> {
+    let invalid = |reason: &str| Error::InvalidOption {
+        option: format!("webhook target: {}", reason),
+    };
+
+    let url = Url::parse(target).map_err(|_| invalid("not a URL"))?;
+    if url.scheme() != "https" {
+        return Err(invalid("not HTTPS"));
+    }
+
+    let port = url.port_or_known_default().unwrap_or(443);
+    let addrs: Vec<IpAddr> = match url.host() {
+        Some(Host::Ipv4(ip)) => vec![ip.into()],
+        Some(Host::Ipv6(ip)) => vec![ip.into()],
+        Some(Host::Domain(name)) => {
+            lookup_host((name, port)).await?.map(|a| a.ip()).collect()
+        },
+        None => return Err(invalid("no host")),
+    };
+
+    if addrs.is_empty() || !addrs.into_iter().all(is_public) {
+        return Err(invalid("not a public address"));
+    }
+
+    Ok(())
+}
+
+/// Resolver of the subscriber webhook client, failing for names with a
+/// non-public address
+struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let addrs: Vec<SocketAddr> =
+                lookup_host((name.as_str(), 0)).await?.collect();
+            if !addrs.iter().all(|addr| is_public(addr.ip())) {
+                return Err(format!(
+                    "{} resolves to a non-public address",
+                    name.as_str()
+                )
+                .into());
+            }
+
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// Value of the `X-Nolus-Signature` header for a body sent at `timestamp`
+pub fn sign(secret: &str, timestamp: i64, body: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+        .expect("HMAC accepts keys of any length");
+    mac.update(format!("{}.{}", timestamp, body).as_bytes());
+
+    let digest: String = mac
+        .finalize()
+        .into_bytes()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+
+    format!("sha256={}", digest)
+}
+
+#[async_trait]
+impl NotificationChannel for WebhookChannel {
+    async fn send(
+        &self,
+        destination: &Destination,
+        notification: &Notification,
+    ) -> Result<u16, Error> {
+        let Destination::Webhook {
+            url,
+            secret,
+            trusted,
+        } = destination
+        else {
+            return Err(mismatch(ChannelKind::Webhook, destination));
+        };
+
+        let client = if *trusted {
+            &self.http
+        } else {
+            match check_url(url).await {
+                Ok(()) => &self.public,
+                Err(Error::InvalidOption { option }) => {
+                    tracing::warn!("Webhook {} refused: {}", url, option);
+                    return Ok(FORBIDDEN);
+                },
+                Err(error) => return Err(error),
+            }
+        };
+
+        let body = notification.payload();
+        let timestamp = Utc::now().timestamp();
+
+        let response = client
+            .post(url)
+            .header("user-agent", "nolus-etl")
+            .header("content-type", "application/json")
+            .header("x-nolus-event", &notification.data.r#type)
+            .header("x-nolus-delivery", notification.id.to_string())
+            .header("x-nolus-timestamp", timestamp.to_string())
+            .header("x-nolus-signature", sign(secret, timestamp, &body))
+            .body(body)
+            .send()
+            .await?;
+
+        Ok(response.status().as_u16())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::channel::stand_in;
+
+    fn channel() -> WebhookChannel {
+        WebhookChannel::new(Client::new(), Duration::from_secs(5)).unwrap()
+    }
+
+    #[tokio::test]
+    async fn posts_signed_payload() {
+        let (url, server) = stand_in::http(204).await;
+        let destination = Destination::Webhook {
+            url: format!("{}/hooks/nolus", url),
+            secret: String::from("s3cret"),
+            trusted: true,
+        };
+        let notification = stand_in::notification(
+            42,
+            "Repayed",
+            r#"{"lease": "nolus1lease"}"#,
+        );
+
+        let status = channel().send(&destination, &notification).await.unwrap();
+        assert_eq!(status, 204);
+
+        let request = server.await.unwrap();
+        let body = String::from_utf8(request.body).unwrap();
+        let timestamp: i64 =
+            request.headers["x-nolus-timestamp"].parse().unwrap();
+
+        assert_eq!(request.path, "/hooks/nolus");
+        assert_eq!(body, notification.payload());
+        assert_eq!(request.headers["x-nolus-event"], "Repayed");
+        assert_eq!(request.headers["x-nolus-delivery"], "42");
+        assert_eq!(
+            request.headers["x-nolus-signature"],
+            sign("s3cret", timestamp, &body)
+        );
+    }
+
+    #[tokio::test]
+    async fn subscriber_webhooks_reach_public_addresses_only() {
+        for url in [
+            "https://127.0.0.1/hook",
+            "https://localhost/hook",
+            "https://10.0.0.8/hook",
+            "https://172.20.1.1/hook",
+            "https://192.168.1.1/hook",
+            "https://169.254.169.254/latest/meta-data",
+            "https://100.64.0.1/hook",
+            "https://[::1]/hook",
+            "https://[fd00::1]/hook",
+            "https://[fe80::1]/hook",
+            "https://[::ffff:10.0.0.1]/hook",
+            "http://93.184.215.14/hook",
+        ] {
+            assert!(
+                matches!(
+                    check_url(url).await,
+                    Err(Error::InvalidOption { .. })
+                ),
+                "{} accepted",
+                url
+            );
+        }
+        assert!(check_url("https://93.184.215.14/hook").await.is_ok());
+        assert!(check_url("https://[2606:2800:21f:cb07::1]/hook")
+            .await
+            .is_ok());
+
+        let destination = Destination::Webhook {
+            url: String::from("https://127.0.0.1:9/hook"),
+            secret: String::from("s3cret"),
+            trusted: false,
+        };
+        let notification = stand_in::notification(1, "Repayed", "{}");
+        let status = channel().send(&destination, &notification).await.unwrap();
+        assert_eq!(status, FORBIDDEN);
+    }
+
+    #[test]
+    fn signature_covers_timestamp_and_body() {
+        assert_eq!(
+            sign("key", 1700000000, r#"{"type": "Repayed", "data": {}}"#),
+            "sha256=83d9716601b0ce93cf169e8a52f95d4dc23fa9de48f7699363f7da0eb988356d"
+        );
+    }
+}
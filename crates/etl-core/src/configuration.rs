@@ -1,12 +1,25 @@
 use std::{
-    collections::HashMap, env, fs, ops::Deref, str::FromStr, sync::Arc,
+    collections::{HashMap, HashSet},
+    fs,
+    net::IpAddr,
+    ops::Deref,
+    path::Path,
+    str::FromStr,
+    sync::Arc,
     time::Duration,
 };
 
+use arc_swap::ArcSwap;
 use moka::future::Cache;
-use tokio::sync::{RwLock, Semaphore};
+use serde::{Serialize, Serializer};
+use tokio::{
+    sync::{RwLock, Semaphore},
+    time::{self, Instant},
+};
+use url::Url;
 
 use crate::{
+    channel::Channels,
     dao::postgre::{
         lp_deposit::HistoricalLender,
         lp_lender_state::CurrentLender,
@@ -17,13 +30,16 @@ use crate::{
         ls_state::LeaseValueStats,
     },
     error::Error,
+    helpers::{ContractRole, IngestRole, RegistryChange, RegistryEntity},
     model::{
         Buyback, DailyPositionsPoint, LP_Pool, Leased_Asset, Leases_Monthly,
         MonthlyActiveWallet, PoolConfigUpsert, Position, PositionBucket,
-        ProtocolRegistry, RevenueSeriesPoint, Supplied_Borrowed_Series,
-        TokenLoan, TokenPosition,
+        ProtocolContractHistory, ProtocolRegistry, RevenueSeriesPoint,
+        Supplied_Borrowed_Series, TokenLoan, TokenPosition,
     },
     provider::{DatabasePool, Grpc, HTTP},
+    settings::Settings,
+    template::Templates,
     types::{AdminProtocolExtendType, Currency, ProtocolContracts},
 };
 use bigdecimal::BigDecimal;
@@ -57,9 +73,11 @@ pub const CACHE_TTL_STANDARD: Duration = Duration::from_secs(300); // 5 minutes
 pub const CACHE_TTL_HOURLY: Duration = Duration::from_secs(3600); // 1 hour for aggregated state data (refreshed by aggregation task)
 
 fn build_cache<V: Clone + Send + Sync + 'static>(
+    name: &str,
     ttl: Duration,
 ) -> Cache<String, V> {
     Cache::builder()
+        .name(name)
         .time_to_live(ttl)
         .max_capacity(10_000)
         .build()
@@ -113,50 +131,89 @@ impl ApiCache {
             // HOURLY TTL - Data from *_State tables (updated by aggregation task)
             // =================================================================
             // From LS_State
-            positions: build_cache(CACHE_TTL_HOURLY),
-            open_position_value: build_cache(CACHE_TTL_HOURLY),
-            open_interest: build_cache(CACHE_TTL_HOURLY),
-            unrealized_pnl: build_cache(CACHE_TTL_HOURLY),
-            lease_value_stats: build_cache(CACHE_TTL_HOURLY),
-            position_buckets: build_cache(CACHE_TTL_HOURLY),
-            open_positions_by_token: build_cache(CACHE_TTL_HOURLY),
-            loans_by_token: build_cache(CACHE_TTL_HOURLY),
-            daily_positions: build_cache(CACHE_TTL_HOURLY),
+            positions: build_cache("positions", CACHE_TTL_HOURLY),
+            open_position_value: build_cache(
+                "open_position_value",
+                CACHE_TTL_HOURLY,
+            ),
+            open_interest: build_cache("open_interest", CACHE_TTL_HOURLY),
+            unrealized_pnl: build_cache("unrealized_pnl", CACHE_TTL_HOURLY),
+            lease_value_stats: build_cache(
+                "lease_value_stats",
+                CACHE_TTL_HOURLY,
+            ),
+            position_buckets: build_cache("position_buckets", CACHE_TTL_HOURLY),
+            open_positions_by_token: build_cache(
+                "open_positions_by_token",
+                CACHE_TTL_HOURLY,
+            ),
+            loans_by_token: build_cache("loans_by_token", CACHE_TTL_HOURLY),
+            daily_positions: build_cache("daily_positions", CACHE_TTL_HOURLY),
             // From LP_Pool_State
-            supplied_borrowed_history: build_cache(CACHE_TTL_HOURLY),
-            pools: build_cache(CACHE_TTL_HOURLY),
-            supplied_funds: build_cache(CACHE_TTL_HOURLY),
-            borrowed: build_cache(CACHE_TTL_HOURLY),
+            supplied_borrowed_history: build_cache(
+                "supplied_borrowed_history",
+                CACHE_TTL_HOURLY,
+            ),
+            pools: build_cache("pools", CACHE_TTL_HOURLY),
+            supplied_funds: build_cache("supplied_funds", CACHE_TTL_HOURLY),
+            borrowed: build_cache("borrowed", CACHE_TTL_HOURLY),
             // From LP_Lender_State
-            current_lenders: build_cache(CACHE_TTL_HOURLY),
+            current_lenders: build_cache("current_lenders", CACHE_TTL_HOURLY),
 
             // =================================================================
             // STANDARD TTL (5 min) - Real-time events or price-dependent data
             // =================================================================
             // Price-dependent (uses current market prices)
-            total_value_locked: build_cache(CACHE_TTL_STANDARD),
-            leased_assets: build_cache(CACHE_TTL_STANDARD),
+            total_value_locked: build_cache(
+                "total_value_locked",
+                CACHE_TTL_STANDARD,
+            ),
+            leased_assets: build_cache("leased_assets", CACHE_TTL_STANDARD),
             // Real-time event data
-            liquidations: build_cache(CACHE_TTL_STANDARD),
-            interest_repayments: build_cache(CACHE_TTL_STANDARD),
-            historical_lenders: build_cache(CACHE_TTL_STANDARD),
-            historically_opened: build_cache(CACHE_TTL_STANDARD),
-            historically_repaid: build_cache(CACHE_TTL_STANDARD),
-            historically_liquidated: build_cache(CACHE_TTL_STANDARD),
-            loans_granted: build_cache(CACHE_TTL_STANDARD),
-            realized_pnl_stats: build_cache(CACHE_TTL_STANDARD),
-            realized_pnl_wallet: build_cache(CACHE_TTL_STANDARD),
+            liquidations: build_cache("liquidations", CACHE_TTL_STANDARD),
+            interest_repayments: build_cache(
+                "interest_repayments",
+                CACHE_TTL_STANDARD,
+            ),
+            historical_lenders: build_cache(
+                "historical_lenders",
+                CACHE_TTL_STANDARD,
+            ),
+            historically_opened: build_cache(
+                "historically_opened",
+                CACHE_TTL_STANDARD,
+            ),
+            historically_repaid: build_cache(
+                "historically_repaid",
+                CACHE_TTL_STANDARD,
+            ),
+            historically_liquidated: build_cache(
+                "historically_liquidated",
+                CACHE_TTL_STANDARD,
+            ),
+            loans_granted: build_cache("loans_granted", CACHE_TTL_STANDARD),
+            realized_pnl_stats: build_cache(
+                "realized_pnl_stats",
+                CACHE_TTL_STANDARD,
+            ),
+            realized_pnl_wallet: build_cache(
+                "realized_pnl_wallet",
+                CACHE_TTL_STANDARD,
+            ),
             // Treasury (real-time events)
-            buyback: build_cache(CACHE_TTL_STANDARD),
-            buyback_total: build_cache(CACHE_TTL_STANDARD),
-            distributed: build_cache(CACHE_TTL_STANDARD),
-            incentives_pool: build_cache(CACHE_TTL_STANDARD),
-            revenue: build_cache(CACHE_TTL_STANDARD),
-            revenue_series: build_cache(CACHE_TTL_STANDARD),
+            buyback: build_cache("buyback", CACHE_TTL_STANDARD),
+            buyback_total: build_cache("buyback_total", CACHE_TTL_STANDARD),
+            distributed: build_cache("distributed", CACHE_TTL_STANDARD),
+            incentives_pool: build_cache("incentives_pool", CACHE_TTL_STANDARD),
+            revenue: build_cache("revenue", CACHE_TTL_STANDARD),
+            revenue_series: build_cache("revenue_series", CACHE_TTL_STANDARD),
             // Other real-time data
-            total_tx_value: build_cache(CACHE_TTL_STANDARD),
-            leases_monthly: build_cache(CACHE_TTL_STANDARD),
-            monthly_active_wallets: build_cache(CACHE_TTL_STANDARD),
+            total_tx_value: build_cache("total_tx_value", CACHE_TTL_STANDARD),
+            leases_monthly: build_cache("leases_monthly", CACHE_TTL_STANDARD),
+            monthly_active_wallets: build_cache(
+                "monthly_active_wallets",
+                CACHE_TTL_STANDARD,
+            ),
         }
     }
 }
@@ -177,16 +234,168 @@ pub type PriceCacheKey = (String, String);
 /// Updated every time prices are fetched from the oracle
 pub type LatestPricesCache = Arc<RwLock<HashMap<PriceCacheKey, BigDecimal>>>;
 
-pub struct State {
-    pub config: Config,
-    pub database: DatabasePool,
-    pub grpc: Grpc,
+/// Protocols, currencies and pools loaded from the registry tables. Swapped
+/// as a whole when the registry is reloaded, so readers holding a snapshot
+/// see consistent maps.
+#[derive(Debug, Default)]
+pub struct Registry {
     /// Active protocols only - used for price fetching
     pub protocols: HashMap<String, AdminProtocolExtendType>,
     /// All protocols (active + deprecated) - pool_id -> protocol_name mapping
     pub hash_map_pool_protocol: HashMap<String, String>,
+    /// All currencies (active + deprecated) by ticker
+    pub hash_map_currencies: HashMap<String, Currency>,
+    /// Ticker of an upper-cased bank denom
+    pub hash_map_denom_ticker: HashMap<String, String>,
+    /// LPN of a pool
+    pub hash_map_pool_currency: HashMap<String, Currency>,
+    /// Protocols a contract belonged to and over which heights
+    pub contract_history: HashMap<String, Vec<ProtocolContractHistory>>,
+    /// Treasury contract, from the platform query of the admin contract
+    pub treasury_contract: String,
+}
+
+impl Registry {
+    /// Load ALL data (active + deprecated) from the registry tables and add
+    /// the `LP_Pool` rows of new pools
+    pub async fn load(
+        database: &DatabasePool,
+        treasury_contract: String,
+    ) -> Result<Registry, Error> {
+        // Load ALL currencies for historical lookups
+        let all_currencies = database.currency_registry.get_all().await?;
+        let mut hash_map_currencies: HashMap<String, Currency> = HashMap::new();
+        for c in all_currencies {
+            hash_map_currencies
+                .insert(c.ticker.clone(), Currency(c.ticker, c.decimal_digits));
+        }
+
+        // Build denom -> ticker reverse lookup from all currency_protocol entries
+        let all_currency_protocols =
+            database.currency_protocol.get_all().await?;
+        let mut hash_map_denom_ticker: HashMap<String, String> = HashMap::new();
+        for cp in &all_currency_protocols {
+            if let Some(ref bank_symbol) = cp.bank_symbol {
+                hash_map_denom_ticker
+                    .insert(bank_symbol.to_uppercase(), cp.ticker.clone());
+            }
+        }
+
+        // Load ALL protocols for historical lookups
+        let all_protocols_db = database.protocol_registry.get_all().await?;
+
+        // Build pool_id -> protocol_name mapping (for get_protocol_by_pool_id)
+        let mut hash_map_pool_protocol: HashMap<String, String> =
+            HashMap::new();
+        let mut hash_map_pool_currency: HashMap<String, Currency> =
+            HashMap::new();
+        let mut protocols: HashMap<String, AdminProtocolExtendType> =
+            HashMap::new();
+
+        for p in &all_protocols_db {
+            if let Some(lpp) = &p.lpp_contract {
+                hash_map_pool_protocol
+                    .insert(lpp.clone(), p.protocol_name.clone());
+
+                // Also build pool -> currency mapping
+                if let Some(currency) = hash_map_currencies.get(&p.lpn_symbol) {
+                    hash_map_pool_currency
+                        .insert(lpp.clone(), currency.clone());
+                }
+
+                // Initialize LP_Pool table (for backward compatibility)
+                let pool = LP_Pool {
+                    LP_Pool_id: lpp.clone(),
+                    LP_symbol: p.lpn_symbol.clone(),
+                    LP_status: p.is_active,
+                };
+                database.lp_pool.insert(pool).await?;
+            }
+
+            if p.is_active {
+                protocols.insert(
+                    p.protocol_name.clone(),
+                    AdminProtocolExtendType {
+                        network: p.network.clone().unwrap_or_default(),
+                        protocol: p.protocol_name.clone(),
+                        contracts: ProtocolContracts {
+                            leaser: p
+                                .leaser_contract
+                                .clone()
+                                .unwrap_or_default(),
+                            lpp: p.lpp_contract.clone().unwrap_or_default(),
+                            oracle: p
+                                .oracle_contract
+                                .clone()
+                                .unwrap_or_default(),
+                            profit: p
+                                .profit_contract
+                                .clone()
+                                .unwrap_or_default(),
+                            reserve: p.reserve_contract.clone(),
+                        },
+                    },
+                );
+            }
+        }
+
+        let mut contract_history: HashMap<
+            String,
+            Vec<ProtocolContractHistory>,
+        > = HashMap::new();
+        for entry in database.protocol_contract_history.get_all().await? {
+            contract_history
+                .entry(entry.contract.clone())
+                .or_default()
+                .push(entry);
+        }
+
+        Ok(Registry {
+            protocols,
+            hash_map_pool_protocol,
+            hash_map_currencies,
+            hash_map_denom_ticker,
+            hash_map_pool_currency,
+            contract_history,
+            treasury_contract,
+        })
+    }
+}
+
+/// Swap in the registry of the registry tables, which the aggregator keeps
+/// in sync, possibly from another process
+pub async fn registry_reload_task(
+    app_state: AppState<State>,
+) -> Result<(), Error> {
+    let period = Duration::from_secs(app_state.config.registry_reload_interval);
+    let mut interval = time::interval_at(Instant::now() + period, period);
+    tokio::spawn(async move {
+        loop {
+            interval.tick().await;
+
+            if let Err(error) = app_state.reload_registry().await {
+                tracing::error!("Registry reload error {}", error);
+            }
+        }
+    })
+    .await?
+}
+
+/// Entries added to or deprecated from the registry by a sync
+pub type RegistryChanges = Vec<(RegistryEntity, String, RegistryChange)>;
+
+pub struct State {
+    pub config: Config,
+    pub database: DatabasePool,
+    pub grpc: Grpc,
+    /// Protocols, currencies and pools, reloaded while running
+    pub registry: ArcSwap<Registry>,
     pub api_cache: ApiCache,
     pub http: HTTP,
+    /// Notification channels of the push pipeline
+    pub channels: Channels,
+    /// Localized notification templates
+    pub templates: Templates,
     /// Semaphore to limit concurrent push notification tasks
     pub push_permits: Arc<Semaphore>,
     /// In-memory cache for the latest asset prices (updated every price fetch cycle)
@@ -199,10 +408,11 @@ impl std::fmt::Debug for State {
             .field("config", &self.config)
             .field("database", &self.database)
             .field("grpc", &self.grpc)
-            .field("protocols", &self.protocols)
-            .field("hash_map_pool_protocol", &self.hash_map_pool_protocol)
+            .field("registry", &self.registry.load())
             .field("api_cache", &"<ApiCache>")
             .field("http", &self.http)
+            .field("channels", &"<Channels>")
+            .field("templates", &"<Templates>")
             .field("push_permits", &"<Semaphore>")
             .field("latest_prices", &"<RwLock<HashMap>>")
             .finish()
@@ -211,22 +421,120 @@ impl std::fmt::Debug for State {
 
 impl State {
     pub async fn new(
-        mut config: Config,
+        config: Config,
         database: DatabasePool,
         grpc: Grpc,
         http: HTTP,
     ) -> Result<State, Error> {
+        let (treasury_contract, _) =
+            Self::sync_registry(&config, &database, &grpc).await?;
+        let registry = Registry::load(&database, treasury_contract).await?;
+
+        // Log summary
+        let (active_curr, deprecated_curr) =
+            database.currency_registry.count_by_status().await?;
+        let (active_proto, deprecated_proto) =
+            database.protocol_registry.count_by_status().await?;
+        tracing::info!(
+            "Configuration loaded: {} active currencies ({} deprecated), {} active protocols ({} deprecated)",
+            active_curr,
+            deprecated_curr,
+            active_proto,
+            deprecated_proto
+        );
+
+        let channels = Channels::new(&config, http.http.clone())?;
+        let templates = Templates::new(&config.app_url)?;
+
+        Ok(Self {
+            config,
+            database,
+            grpc,
+            http,
+            channels,
+            templates,
+            registry: ArcSwap::from_pointee(registry),
+            api_cache: ApiCache::new(),
+            push_permits: Arc::new(Semaphore::new(MAX_PUSH_TASKS)),
+            latest_prices: Arc::new(RwLock::new(HashMap::new())),
+        })
+    }
+
+    /// Current snapshot of the registry
+    pub fn registry(&self) -> Arc<Registry> {
+        self.registry.load_full()
+    }
+
+    /// Reload the registry from the registry tables and swap it in. The
+    /// treasury contract of the current registry is kept.
+    pub async fn reload_registry(&self) -> Result<Arc<Registry>, Error> {
+        let treasury_contract = self.registry().treasury_contract.to_owned();
+        self.swap_registry(treasury_contract).await
+    }
+
+    /// Sync the registry tables and the treasury contract with the admin
+    /// contract, then reload the registry. Returns the entries added or
+    /// deprecated.
+    pub async fn reconcile_registry(&self) -> Result<RegistryChanges, Error> {
+        let (treasury_contract, changes) =
+            Self::sync_registry(&self.config, &self.database, &self.grpc)
+                .await?;
+        self.swap_registry(treasury_contract).await?;
+        Ok(changes)
+    }
+
+    async fn swap_registry(
+        &self,
+        treasury_contract: String,
+    ) -> Result<Arc<Registry>, Error> {
+        let registry =
+            Arc::new(Registry::load(&self.database, treasury_contract).await?);
+        self.registry.store(Arc::clone(&registry));
+        Ok(registry)
+    }
+
+    /// Discover protocols and currencies from the admin contract and sync
+    /// them to the registry tables. Protocols, currencies and pools added
+    /// or deprecated since the last sync are recorded in `registry_audit`
+    /// and returned with the treasury contract; the runtime state is not
+    /// modified.
+    pub async fn sync_registry(
+        config: &Config,
+        database: &DatabasePool,
+        grpc: &Grpc,
+    ) -> Result<(String, RegistryChanges), Error> {
+        // Entries active before this sync
+        let known_protocols: HashSet<String> = database
+            .protocol_registry
+            .get_active()
+            .await?
+            .into_iter()
+            .map(|p| p.protocol_name)
+            .collect();
+        let known_currencies: HashSet<String> = database
+            .currency_registry
+            .get_active()
+            .await?
+            .into_iter()
+            .map(|c| c.ticker)
+            .collect();
+        let known_pools: HashSet<String> = database
+            .pool_config
+            .get_all()
+            .await?
+            .into_iter()
+            .filter(|p| p.is_active)
+            .map(|p| p.pool_id)
+            .collect();
+
         // =====================================================================
         // PHASE 1: Fetch active data from contracts
         // =====================================================================
 
         // Get platform info (treasury contract)
         let platform = grpc.get_platform(config.admin_contract.clone()).await?;
-        config.treasury_contract = platform.treasury;
-        tracing::info!(
-            "Loaded treasury contract: {}",
-            config.treasury_contract
-        );
+        let treasury_contract = platform.treasury;
+        tracing::info!("Loaded treasury contract: {}", treasury_contract);
 
         // Get all active protocols from admin contract
         let active_protocol_names =
@@ -410,6 +718,38 @@ impl State {
             database.protocol_registry.upsert_active(entry).await?;
         }
 
+        // Contracts replaced since the last sync are valid up to the last
+        // indexed block, the new ones from the next
+        let height = database.block.get_max_block().await?.unwrap_or(0);
+        for entry in &protocol_registry_entries {
+            let contracts = [
+                (ContractRole::Leaser, &entry.leaser_contract),
+                (ContractRole::Lpp, &entry.lpp_contract),
+                (ContractRole::Oracle, &entry.oracle_contract),
+                (ContractRole::Profit, &entry.profit_contract),
+                (ContractRole::Reserve, &entry.reserve_contract),
+            ];
+            for (role, contract) in contracts {
+                let Some(contract) = contract else {
+                    continue;
+                };
+                if let Some(replaced) = database
+                    .protocol_contract_history
+                    .record(&entry.protocol_name, role, contract, height + 1)
+                    .await?
+                {
+                    tracing::info!(
+                        "Protocol {} {} contract {} replaced by {} from height {}",
+                        entry.protocol_name,
+                        role,
+                        replaced,
+                        contract,
+                        height + 1
+                    );
+                }
+            }
+        }
+
         // Mark currencies NOT in active set as deprecated
         let active_tickers: Vec<String> =
             active_currencies.keys().cloned().collect();
@@ -437,6 +777,10 @@ impl State {
                 deprecated_protocols
             );
         }
+        database
+            .protocol_contract_history
+            .close_except(&active_proto_names, height)
+            .await?;
 
         // Mark pools NOT in active set as deprecated
         let deprecated_pools = database
@@ -448,94 +792,33 @@ impl State {
         }
 
         // =====================================================================
-        // PHASE 3: Load ALL data (active + deprecated) into runtime state
+        // PHASE 3: Audit added and deprecated entries
         // =====================================================================
 
-        // Load ALL currencies for historical lookups
-        let all_currencies = database.currency_registry.get_all().await?;
-        let mut hash_map_currencies: HashMap<String, Currency> = HashMap::new();
-        for c in all_currencies {
-            hash_map_currencies
-                .insert(c.ticker.clone(), Currency(c.ticker, c.decimal_digits));
-        }
-        config.hash_map_currencies = hash_map_currencies;
-
-        // Build denom -> ticker reverse lookup from all currency_protocol entries
-        let all_currency_protocols =
-            database.currency_protocol.get_all().await?;
-        let mut hash_map_denom_ticker: HashMap<String, String> = HashMap::new();
-        for cp in &all_currency_protocols {
-            if let Some(ref bank_symbol) = cp.bank_symbol {
-                hash_map_denom_ticker
-                    .insert(bank_symbol.to_uppercase(), cp.ticker.clone());
-            }
-        }
-        config.hash_map_denom_ticker = hash_map_denom_ticker;
-
-        // Load ALL protocols for historical lookups
-        let all_protocols_db = database.protocol_registry.get_all().await?;
-
-        // Build pool_id -> protocol_name mapping (for get_protocol_by_pool_id)
-        let mut hash_map_pool_protocol: HashMap<String, String> =
-            HashMap::new();
-        let mut hash_map_pool_currency: HashMap<String, Currency> =
-            HashMap::new();
-
-        for p in &all_protocols_db {
-            if let Some(lpp) = &p.lpp_contract {
-                hash_map_pool_protocol
-                    .insert(lpp.clone(), p.protocol_name.clone());
-
-                // Also build pool -> currency mapping
-                if let Some(currency) =
-                    config.hash_map_currencies.get(&p.lpn_symbol)
-                {
-                    hash_map_pool_currency
-                        .insert(lpp.clone(), currency.clone());
-                }
-            }
-        }
-        config.hash_map_pool_currency = hash_map_pool_currency;
-
-        // =====================================================================
-        // PHASE 4: Initialize LP_Pool table (for backward compatibility)
-        // =====================================================================
-
-        for p in &all_protocols_db {
-            if let Some(lpp) = &p.lpp_contract {
-                let pool = LP_Pool {
-                    LP_Pool_id: lpp.clone(),
-                    LP_symbol: p.lpn_symbol.clone(),
-                    LP_status: p.is_active,
-                };
-                database.lp_pool.insert(pool).await?;
+        let mut changes = registry_changes(
+            RegistryEntity::Protocol,
+            &known_protocols,
+            active_protocols.into_keys().collect(),
+        );
+        changes.extend(registry_changes(
+            RegistryEntity::Currency,
+            &known_currencies,
+            active_currencies.into_keys().collect(),
+        ));
+        changes.extend(registry_changes(
+            RegistryEntity::Pool,
+            &known_pools,
+            active_pool_ids.into_iter().collect(),
+        ));
+
+        if !changes.is_empty() {
+            for (entity, name, change) in &changes {
+                tracing::info!("Registry {} {} {}", entity, name, change);
             }
+            database.registry_audit.insert_many(&changes).await?;
         }
 
-        // Log summary
-        let (active_curr, deprecated_curr) =
-            database.currency_registry.count_by_status().await?;
-        let (active_proto, deprecated_proto) =
-            database.protocol_registry.count_by_status().await?;
-        tracing::info!(
-            "Configuration loaded: {} active currencies ({} deprecated), {} active protocols ({} deprecated)",
-            active_curr,
-            deprecated_curr,
-            active_proto,
-            deprecated_proto
-        );
-
-        Ok(Self {
-            config,
-            database,
-            grpc,
-            http,
-            protocols: active_protocols,
-            hash_map_pool_protocol,
-            api_cache: ApiCache::new(),
-            push_permits: Arc::new(Semaphore::new(MAX_PUSH_TASKS)),
-            latest_prices: Arc::new(RwLock::new(HashMap::new())),
-        })
+        Ok((treasury_contract, changes))
     }
 
     /// Get the latest price for a symbol, checking the in-memory cache first.
@@ -569,7 +852,7 @@ impl State {
     ) -> Result<BigDecimal, Error> {
         let currency = self.get_currency(currency_symbol)?;
         let Currency(symbol, _) = currency;
-        let stabe_price = self.get_cached_price(symbol, protocol).await?;
+        let stabe_price = self.get_cached_price(&symbol, protocol).await?;
         let val = self.in_stable_calc(&stabe_price, value)?;
 
         Ok(val)
@@ -588,7 +871,7 @@ impl State {
         let (stabe_price,) = self
             .database
             .mp_asset
-            .get_price_by_date(symbol, protocol, date_time)
+            .get_price_by_date(&symbol, protocol, date_time)
             .await?;
         let val = self.in_stable_calc(&stabe_price, value)?;
 
@@ -604,7 +887,7 @@ impl State {
         let Currency(symbol, _) = currency;
         let protocol = self.get_protocol_by_pool_id(pool_id);
 
-        let stabe_price = self.get_cached_price(symbol, protocol).await?;
+        let stabe_price = self.get_cached_price(&symbol, protocol).await?;
         let val = self.in_stable_calc(&stabe_price, value)?;
 
         Ok(val)
@@ -614,7 +897,25 @@ impl State {
     /// Uses hash_map_pool_protocol which includes ALL protocols (active + deprecated)
     /// This ensures historical lookups work even for deprecated protocols
     pub fn get_protocol_by_pool_id(&self, pool_id: &str) -> Option<String> {
-        self.hash_map_pool_protocol.get(pool_id).cloned()
+        self.registry().hash_map_pool_protocol.get(pool_id).cloned()
+    }
+
+    /// Get the protocol a pool (or any other protocol contract) belonged to
+    /// at a height, from the contract history. Falls back to
+    /// [`State::get_protocol_by_pool_id`] for heights the history does not
+    /// cover.
+    pub fn get_protocol_by_pool_id_at(
+        &self,
+        pool_id: &str,
+        height: i64,
+    ) -> Option<String> {
+        let registry = self.registry();
+        registry
+            .contract_history
+            .get(pool_id)
+            .and_then(|entries| entries.iter().find(|e| e.covers(height)))
+            .map(|entry| entry.protocol.clone())
+            .or_else(|| registry.hash_map_pool_protocol.get(pool_id).cloned())
     }
 
     pub fn in_stable_calc(
@@ -627,13 +928,25 @@ impl State {
         Ok(val)
     }
 
+    /// Amount in whole units of a currency, from its smallest denomination
+    pub fn in_whole_units(
+        &self,
+        currency_symbol: &str,
+        amount: &BigDecimal,
+    ) -> Result<BigDecimal, Error> {
+        let Currency(_, decimals) = self.get_currency(currency_symbol)?;
+        let divisor = BigDecimal::from(u64::pow(10, decimals.try_into()?));
+
+        Ok(amount / divisor)
+    }
+
     pub fn get_currency(
         &self,
         currency_symbol: &str,
-    ) -> Result<&Currency, Error> {
+    ) -> Result<Currency, Error> {
         let currency =
-            match self.config.hash_map_currencies.get(currency_symbol) {
-                Some(c) => c,
+            match self.registry().hash_map_currencies.get(currency_symbol) {
+                Some(c) => c.clone(),
                 None => {
                     return Err(Error::NotSupportedCurrency(format!(
                         "Currency {} not found",
@@ -648,9 +961,10 @@ impl State {
     pub fn get_currency_by_pool_id(
         &self,
         pool_id: &str,
-    ) -> Result<&Currency, Error> {
-        let currency = match self.config.hash_map_pool_currency.get(pool_id) {
-            Some(c) => c,
+    ) -> Result<Currency, Error> {
+        let currency = match self.registry().hash_map_pool_currency.get(pool_id)
+        {
+            Some(c) => c.clone(),
             None => {
                 return Err(Error::NotSupportedCurrency(format!(
                     "Pool with id {} not found",
@@ -665,12 +979,13 @@ impl State {
     /// Get the first (default) protocol name for treasury operations
     /// Returns None if no protocols are configured
     pub fn get_default_protocol(&self) -> Option<String> {
-        self.protocols.keys().next().cloned()
+        self.registry().protocols.keys().next().cloned()
     }
 
     /// Get all active LP pool IDs
     pub fn get_active_pool_ids(&self) -> Vec<String> {
-        self.protocols
+        self.registry()
+            .protocols
             .values()
             .map(|p| p.contracts.lpp.clone())
             .collect()
@@ -705,9 +1020,9 @@ impl State {
     ) -> Result<BigDecimal, Error> {
         use anyhow::Context as _;
 
+        let registry = self.registry();
         let symbol = &lease.LS_asset_symbol.to_owned();
-        let ctrl_currency = self
-            .config
+        let ctrl_currency = registry
             .hash_map_currencies
             .get(&lease.LS_cltr_symbol)
             .context(format!(
@@ -715,8 +1030,7 @@ impl State {
                 &lease.LS_cltr_symbol
             ))?;
 
-        let loan_currency = self
-            .config
+        let loan_currency = registry
             .hash_map_currencies
             .get(&symbol.to_owned())
             .context(format!("LS_asset_symbol not found {}", &symbol))?;
@@ -756,22 +1070,45 @@ impl State {
     }
 }
 
-#[derive(Debug, Clone)]
+/// Entries of `active` missing from `known` as added, and the other way
+/// round as deprecated, sorted by name
+fn registry_changes(
+    entity: RegistryEntity,
+    known: &HashSet<String>,
+    active: HashSet<String>,
+) -> RegistryChanges {
+    let mut added: Vec<&String> = active.difference(known).collect();
+    let mut deprecated: Vec<&String> = known.difference(&active).collect();
+    added.sort();
+    deprecated.sort();
+
+    added
+        .into_iter()
+        .map(|name| (entity, name.to_owned(), RegistryChange::Added))
+        .chain(
+            deprecated.into_iter().map(|name| {
+                (entity, name.to_owned(), RegistryChange::Deprecated)
+            }),
+        )
+        .collect()
+}
+
+/// Effective configuration. Serializes with its secrets redacted.
+#[derive(Debug, Clone, Serialize)]
 pub struct Config {
+    /// Configuration file read, if any
+    pub config_file: Option<String>,
     pub host: String,
     pub websocket_host: String,
+    #[serde(serialize_with = "redact_url")]
     pub database_url: String,
     pub sync_threads: i16,
     pub aggregation_interval: u8,
     pub mp_asset_interval: u8,
     pub cache_state_interval: u16,
+    /// Interval between two registry reloads, in seconds
+    pub registry_reload_interval: u64,
     pub timeout: u64,
-    // Dynamic configuration - populated from registry at startup
-    pub hash_map_currencies: HashMap<String, Currency>,
-    pub hash_map_denom_ticker: HashMap<String, String>,
-    pub hash_map_pool_currency: HashMap<String, Currency>,
-    // Treasury contract - loaded from admin contract's platform query
-    pub treasury_contract: String,
     pub server_host: String,
     pub port: u16,
     pub allowed_origins: Vec<String>,
@@ -782,15 +1119,36 @@ pub struct Config {
     pub ignore_protocols: Vec<String>,
     // Native currency symbol (NLS) - used for filtering
     pub native_currency: String,
+    /// Unbonding period of the chain, for undelegations indexed without
+    /// their completion time
+    pub staking_unbonding_days: i64,
     pub socket_reconnect_interval: u64,
     pub grpc_host: String,
     pub events_subscribe: Vec<String>,
     pub enable_sync: bool,
+    /// Roles the ingest process runs
+    #[serde(serialize_with = "display_seq")]
+    pub ingest_roles: Vec<IngestRole>,
+    /// Listener of `/metrics` in the ingest process, port 0 disables it
+    pub metrics_host: String,
+    pub metrics_port: u16,
     pub tasks_interval: u64,
     pub status_code_to_delete: Vec<u16>,
     pub mail_to: String,
-    pub vapid_private_key: Vec<u8>,
-    pub vapid_public_key: Vec<u8>,
+    /// Web app the deep links of notifications point to
+    pub app_url: String,
+    /// Minimum time between two notifications of a type for the same lease,
+    /// for subscriptions that do not set their own
+    pub push_min_interval_minutes: i32,
+    /// VAPID keys subscriptions may be signed with, by key id
+    #[serde(serialize_with = "vapid_key_ids")]
+    pub vapid_keys: Vec<VapidKey>,
+    /// Key id new subscriptions are created with
+    pub vapid_key_id: String,
+    /// Key ids whose subscriptions are deactivated. Only keys listed here
+    /// are retired, never a key that is merely missing from `vapid_keys`.
+    pub vapid_retired_key_ids: Vec<String>,
+    #[serde(serialize_with = "redact")]
     pub auth: String,
     pub grpc_connections: usize,
     pub grpc_permits: usize,
@@ -804,174 +1162,463 @@ pub struct Config {
     pub cache_refresh_interval_secs: u64,
     pub cache_max_concurrent_refreshes: usize,
     pub cache_max_concurrent_initial_refreshes: usize,
+    // Rate limits for requests without an API key, per client IP
+    pub api_rate_limit_per_minute: u32,
+    pub api_rate_limit_burst: u32,
+    /// Reverse proxies whose `X-Forwarded-For` names the client IP
+    pub api_trusted_proxies: Vec<IpAddr>,
+    // Notification channels besides Web Push, disabled when not configured
+    pub smtp: Option<SmtpConfig>,
+    pub bot: Option<BotConfig>,
+    pub ops_webhook: Option<WebhookConfig>,
 }
 
-impl Config {}
+/// SMTP relay of the email channel
+#[derive(Debug, Clone, Serialize)]
+pub struct SmtpConfig {
+    pub host: String,
+    pub port: u16,
+    pub username: Option<String>,
+    #[serde(serialize_with = "redact_option")]
+    pub password: Option<String>,
+    pub from: String,
+    /// Upgrade the connection with STARTTLS
+    pub starttls: bool,
+}
 
-/// Returns the workspace root directory path.
-/// Since etl-core lives at `crates/etl-core/`, we go two levels up.
-fn workspace_root() -> String {
-    format!("{}/../../", env!("CARGO_MANIFEST_DIR"))
+/// Bot-style HTTP API of the bot channel, e.g. `https://api.telegram.org`
+#[derive(Debug, Clone, Serialize)]
+pub struct BotConfig {
+    pub api_url: String,
+    #[serde(serialize_with = "redact")]
+    pub token: String,
+}
+
+/// Signed webhook receiving every notification
+#[derive(Debug, Clone, Serialize)]
+pub struct WebhookConfig {
+    pub url: String,
+    #[serde(serialize_with = "redact")]
+    pub secret: String,
+}
+
+/// VAPID keypair of one version. The `default` key is read from
+/// `vapid_private.pem` and `vapid_public.b64` of `cert_dir`, other keys from
+/// `vapid_private_{id}.pem` and `vapid_public_{id}.b64`.
+#[derive(Debug, Clone)]
+pub struct VapidKey {
+    pub id: String,
+    pub private_key: Vec<u8>,
+    pub public_key: Vec<u8>,
 }
 
-fn parse_config_vapid_keys() -> Result<(Vec<u8>, Vec<u8>), Error> {
-    let root = workspace_root();
-    let private_key_dir = format!("{}cert/vapid_private.pem", root);
-    let public_key_dir = format!("{}cert/vapid_public.b64", root);
+/// Key id of the VAPID keypair used before key versions
+pub const DEFAULT_VAPID_KEY_ID: &str = "default";
+
+/// Contract events subscribed to without `events_subscribe`
+const DEFAULT_EVENTS_SUBSCRIBE: [&str; 7] = [
+    "deposit",
+    "burn",
+    "open_lease",
+    "repay",
+    "claim_rewards",
+    "close_position",
+    "change_close_policy",
+];
+
+/// Stands in for secrets in the serialized configuration
+const REDACTED: &str = "********";
+
+fn redact<S: Serializer>(_: &str, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(REDACTED)
+}
 
-    let private_key = fs::read(private_key_dir)?;
-    let public_key = fs::read(public_key_dir)?;
+fn redact_option<S: Serializer>(
+    value: &Option<String>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match value {
+        Some(_) => serializer.serialize_some(REDACTED),
+        None => serializer.serialize_none(),
+    }
+}
 
-    Ok((private_key, public_key))
+/// Connection string without its password
+fn redact_url<S: Serializer>(
+    value: &str,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match Url::parse(value) {
+        Ok(mut url) => {
+            if url.password().is_some() {
+                let _ = url.set_password(Some(REDACTED));
+            }
+            serializer.serialize_str(url.as_str())
+        },
+        Err(_) => serializer.serialize_str(REDACTED),
+    }
 }
 
-pub fn get_configuration() -> Result<Config, Error> {
-    let host = env::var("HOST")?;
-    let websocket_host = env::var("WEBSOCKET_HOST")?;
-    let database_url = env::var("DATABASE_URL")?;
-    let sync_threads: i16 = env::var("SYNC_THREADS")?.parse()?;
-    let aggregation_interval = env::var("AGGREGATION_INTERVAL")?.parse()?;
-    let mp_asset_interval = env::var("MP_ASSET_INTERVAL_IN_SEC")?.parse()?;
-    let cache_state_interval =
-        env::var("CACHE_INTERVAL_IN_MINUTES")?.parse()?;
-    let timeout = env::var("TIMEOUT")?.parse()?;
-    let max_tasks = env::var("MAX_TASKS")?.parse()?;
-
-    // Admin contract is the bootstrap for dynamic configuration
-    let admin_contract = env::var("ADMIN_CONTRACT")?.parse()?;
-
-    let socket_reconnect_interval =
-        env::var("SOCKET_RECONNECT_INTERVAL")?.parse()?;
-    let grpc_host = env::var("GRPC_HOST")?.parse()?;
-    let events_subscribe: String = env::var("EVENTS_SUBSCRIBE")?.parse()?;
-
-    // Parse ignore protocols (optional, defaults to empty)
-    let ignore_protocols = env::var("IGNORE_PROTOCOLS")
-        .unwrap_or_default()
-        .split(',')
-        .filter(|s| !s.is_empty())
-        .map(|item| item.to_owned())
-        .collect::<Vec<String>>();
-
-    // Native currency (defaults to NLS)
-    let native_currency =
-        env::var("NATIVE_CURRENCY").unwrap_or_else(|_| "NLS".to_string());
-
-    let server_host = env::var("SERVER_HOST")?;
-    let port: u16 = env::var("PORT")?.parse()?;
-    let allowed_origins = env::var("ALLOWED_ORIGINS")?
-        .split(',')
-        .map(|item| item.to_owned())
-        .collect::<Vec<String>>();
-    let static_dir =
-        format!("{}{}", workspace_root(), env::var("STATIC_DIRECTORY")?);
-    let enable_sync = env::var("ENABLE_SYNC")?.parse()?;
-    let tasks_interval: u64 = env::var("TASKS_INTERVAL")?.parse()?;
-    let grpc_connections = env::var("GRPC_CONNECTIONS")?.parse()?;
-    let grpc_permits = env::var("GRPC_PERMITS")?.parse()?;
-
-    let events_subscribe = events_subscribe
-        .split(',')
-        .map(|item| item.to_owned())
-        .collect();
-
-    let codes = env::var("STATUS_COODE_TO_DELETE")?
-        .split(",")
-        .map(|item| item.to_string())
-        .collect::<Vec<String>>();
-    let mut status_code_to_delete = vec![];
-    let mail_to: String = env::var("MAIL_TO")?;
-
-    for code in codes {
-        status_code_to_delete.push(code.parse::<u16>()?);
-    }
-
-    let (vapid_private_key, vapid_public_key) = parse_config_vapid_keys()?;
-    let auth = env::var("AUTH")?.parse()?;
-
-    // Database pool settings with PgBouncer-friendly defaults
-    let db_max_connections: u32 = env::var("DB_MAX_CONNECTIONS")
-        .unwrap_or_else(|_| "5".to_string())
-        .parse()?;
-    let db_min_connections: u32 = env::var("DB_MIN_CONNECTIONS")
-        .unwrap_or_else(|_| "1".to_string())
-        .parse()?;
-    let db_acquire_timeout: u64 = env::var("DB_ACQUIRE_TIMEOUT")
-        .unwrap_or_else(|_| "30".to_string())
-        .parse()?;
-    let db_idle_timeout: u64 = env::var("DB_IDLE_TIMEOUT")
-        .unwrap_or_else(|_| "300".to_string())
-        .parse()?;
-    let db_statement_timeout: u64 = env::var("DB_STATEMENT_TIMEOUT")
-        .unwrap_or_else(|_| "60".to_string())
-        .parse()?;
+fn display_seq<S: Serializer, T: std::fmt::Display>(
+    values: &[T],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(values.iter().map(|value| value.to_string()))
+}
 
-    // Cache refresh settings
-    let cache_refresh_interval_secs: u64 =
-        env::var("CACHE_REFRESH_INTERVAL_SECS")
-            .unwrap_or_else(|_| "30".to_string())
-            .parse()?;
-    let cache_max_concurrent_refreshes: usize =
-        env::var("CACHE_MAX_CONCURRENT_REFRESHES")
-            .unwrap_or_else(|_| "4".to_string())
-            .parse()?;
-    let cache_max_concurrent_initial_refreshes: usize =
-        env::var("CACHE_MAX_CONCURRENT_INITIAL_REFRESHES")
-            .unwrap_or_else(|_| "6".to_string())
-            .parse()?;
-
-    let config = Config {
-        host,
-        websocket_host,
-        database_url,
-        sync_threads,
-        aggregation_interval,
-        mp_asset_interval,
-        cache_state_interval,
-        timeout,
-        // These will be populated dynamically from the registry in State::new()
-        hash_map_currencies: HashMap::new(),
-        hash_map_denom_ticker: HashMap::new(),
-        hash_map_pool_currency: HashMap::new(),
-        // Treasury contract will be loaded from admin contract's platform query
-        treasury_contract: String::new(),
-        server_host,
-        port,
-        allowed_origins,
-        static_dir,
-        max_tasks,
-        admin_contract,
-        ignore_protocols,
-        native_currency,
-        socket_reconnect_interval,
-        grpc_host,
-        events_subscribe,
-        enable_sync,
-        tasks_interval,
-        status_code_to_delete,
-        mail_to,
-        vapid_private_key,
-        vapid_public_key,
-        auth,
-        grpc_connections,
-        grpc_permits,
-        db_max_connections,
-        db_min_connections,
-        db_acquire_timeout,
-        db_idle_timeout,
-        db_statement_timeout,
-        cache_refresh_interval_secs,
-        cache_max_concurrent_refreshes,
-        cache_max_concurrent_initial_refreshes,
-    };
-
-    Ok(config)
+fn vapid_key_ids<S: Serializer>(
+    keys: &[VapidKey],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(keys.iter().map(|key| &key.id))
 }
 
+fn parse_config_vapid_keys(
+    settings: &Settings,
+    cert_dir: &Path,
+    ids: &[String],
+) -> Vec<VapidKey> {
+    let mut keys = vec![];
+
+    for id in ids {
+        let valid = (1..=32).contains(&id.len())
+            && id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !valid {
+            settings.error("vapid_key_ids", format_args!("invalid id {}", id));
+            continue;
+        }
+
+        let suffix = if id == DEFAULT_VAPID_KEY_ID {
+            String::new()
+        } else {
+            format!("_{}", id)
+        };
+        let read = |name: String| {
+            let path = cert_dir.join(name);
+            fs::read(&path)
+                .map_err(|e| {
+                    settings.error(
+                        "vapid_key_ids",
+                        format_args!("{}: {}", path.display(), e),
+                    )
+                })
+                .ok()
+        };
+        let private_key = read(format!("vapid_private{}.pem", suffix));
+        let public_key = read(format!("vapid_public{}.b64", suffix));
+
+        if let (Some(private_key), Some(public_key)) = (private_key, public_key)
+        {
+            keys.push(VapidKey {
+                id: id.to_owned(),
+                private_key,
+                public_key,
+            });
+        }
+    }
+
+    keys
+}
+
+/// Configuration of the file at `path`, if any, under the environment.
+/// See [`crate::settings`] for the layering.
+pub fn get_configuration(path: Option<&Path>) -> Result<Config, Error> {
+    Config::from_settings(&Settings::load(path)?)
+}
+
+impl Config {
+    /// Read and validate every setting, failing with all the invalid ones
+    pub fn from_settings(settings: &Settings) -> Result<Config, Error> {
+        let positive = |key: &str, valid: bool| {
+            settings.check(key, valid, "must be greater than 0")
+        };
+
+        let host: String = settings.required("host");
+        let websocket_host: String = settings.required("websocket_host");
+        settings.check_url("websocket_host", &websocket_host, &["ws", "wss"]);
+        let database_url: String = settings.required("database_url");
+        settings.check_url(
+            "database_url",
+            &database_url,
+            &["postgres", "postgresql"],
+        );
+        let grpc_host: String = settings.required("grpc_host");
+        settings.check_url("grpc_host", &grpc_host, &["http", "https"]);
+
+        let sync_threads: i16 = settings.get_or("sync_threads", 32);
+        positive("sync_threads", sync_threads > 0);
+        let aggregation_interval: u8 =
+            settings.get_or("aggregation_interval", 1);
+        positive("aggregation_interval", aggregation_interval > 0);
+        let mp_asset_interval: u8 =
+            settings.get_or("mp_asset_interval_in_sec", 20);
+        positive("mp_asset_interval_in_sec", mp_asset_interval > 0);
+        let cache_state_interval: u16 =
+            settings.get_or("cache_interval_in_minutes", 60);
+        positive("cache_interval_in_minutes", cache_state_interval > 0);
+        let registry_reload_interval: u64 =
+            settings.get_or("registry_reload_interval_secs", 300);
+        positive(
+            "registry_reload_interval_secs",
+            registry_reload_interval > 0,
+        );
+        let timeout: u64 = settings.get_or("timeout", 300);
+        positive("timeout", timeout > 0);
+        let max_tasks: usize = settings.get_or("max_tasks", 128);
+        positive("max_tasks", max_tasks > 0);
+
+        // Admin contract is the bootstrap for dynamic configuration
+        let admin_contract: String = settings.required("admin_contract");
+
+        let socket_reconnect_interval: u64 =
+            settings.get_or("socket_reconnect_interval", 5);
+        let events_subscribe =
+            settings.list("events_subscribe").unwrap_or_else(|| {
+                DEFAULT_EVENTS_SUBSCRIBE.map(String::from).to_vec()
+            });
+        settings.check(
+            "events_subscribe",
+            !events_subscribe.is_empty(),
+            "must not be empty",
+        );
+
+        let ignore_protocols =
+            settings.list("ignore_protocols").unwrap_or_default();
+
+        // Native currency (defaults to NLS)
+        let native_currency =
+            settings.get_or("native_currency", String::from("NLS"));
+        let staking_unbonding_days: i64 =
+            settings.get_or("staking_unbonding_days", 21);
+        positive("staking_unbonding_days", staking_unbonding_days > 0);
+
+        let server_host =
+            settings.get_or("server_host", String::from("127.0.0.1"));
+        let port: u16 = settings.get_or("port", 8080);
+        positive("port", port > 0);
+        let allowed_origins =
+            settings.list("allowed_origins").unwrap_or_default();
+        let static_dir = settings
+            .dir("static_directory", "static")
+            .to_string_lossy()
+            .into_owned();
+        let cert_dir = settings.dir("cert_dir", "cert");
+        let enable_sync = settings.get_or("enable_sync", true);
+        let ingest_roles = settings
+            .list("ingest_roles")
+            .unwrap_or_else(|| IngestRole::ALL.to_vec());
+        settings.check(
+            "ingest_roles",
+            !ingest_roles.is_empty(),
+            "must not be empty",
+        );
+        let metrics_host =
+            settings.get_or("metrics_host", String::from("127.0.0.1"));
+        let metrics_port: u16 = settings.get_or("metrics_port", 9100);
+        let tasks_interval: u64 = settings.get_or("tasks_interval", 3000);
+        positive("tasks_interval", tasks_interval > 0);
+        let grpc_connections: usize = settings.get_or("grpc_connections", 32);
+        positive("grpc_connections", grpc_connections > 0);
+        let grpc_permits: usize = settings.get_or("grpc_permits", 12);
+        positive("grpc_permits", grpc_permits > 0);
+
+        let status_code_to_delete: Vec<u16> = settings
+            .list("status_code_to_delete")
+            .unwrap_or_else(|| vec![410, 404]);
+        settings.check(
+            "status_code_to_delete",
+            status_code_to_delete
+                .iter()
+                .all(|code| (100..=599).contains(code)),
+            "HTTP status codes are between 100 and 599",
+        );
+        let mail_to: String = settings.required("mail_to");
+        let app_url =
+            settings.get_or("app_url", String::from("https://app.nolus.io"));
+        settings.check_url("app_url", &app_url, &["http", "https"]);
+        let push_min_interval_minutes: i32 =
+            settings.get_or("push_min_interval_minutes", 15);
+        settings.check(
+            "push_min_interval_minutes",
+            push_min_interval_minutes >= 0,
+            "must not be negative",
+        );
+
+        let vapid_key_ids: Vec<String> = settings
+            .list("vapid_key_ids")
+            .unwrap_or_else(|| vec![DEFAULT_VAPID_KEY_ID.to_string()]);
+        let vapid_keys =
+            parse_config_vapid_keys(settings, &cert_dir, &vapid_key_ids);
+        let vapid_key_id = settings
+            .get("vapid_key_id")
+            .or_else(|| vapid_key_ids.last().cloned())
+            .unwrap_or_default();
+        settings.check(
+            "vapid_key_id",
+            vapid_key_ids.contains(&vapid_key_id),
+            "not in vapid_key_ids",
+        );
+        let vapid_retired_key_ids: Vec<String> =
+            settings.list("vapid_retired_key_ids").unwrap_or_default();
+        settings.check(
+            "vapid_retired_key_ids",
+            !vapid_retired_key_ids
+                .iter()
+                .any(|id| vapid_key_ids.contains(id)),
+            "must not list keys of vapid_key_ids",
+        );
+        let auth: String = settings.required("auth");
+
+        // Database pool settings with PgBouncer-friendly defaults
+        let db_max_connections: u32 = settings.get_or("db_max_connections", 5);
+        positive("db_max_connections", db_max_connections > 0);
+        let db_min_connections: u32 = settings.get_or("db_min_connections", 1);
+        settings.check(
+            "db_min_connections",
+            db_min_connections <= db_max_connections,
+            "must not exceed db_max_connections",
+        );
+        let db_acquire_timeout: u64 = settings.get_or("db_acquire_timeout", 30);
+        positive("db_acquire_timeout", db_acquire_timeout > 0);
+        let db_idle_timeout: u64 = settings.get_or("db_idle_timeout", 300);
+        let db_statement_timeout: u64 =
+            settings.get_or("db_statement_timeout", 60);
+        positive("db_statement_timeout", db_statement_timeout > 0);
+
+        // Cache refresh settings
+        let cache_refresh_interval_secs: u64 =
+            settings.get_or("cache_refresh_interval_secs", 30);
+        positive(
+            "cache_refresh_interval_secs",
+            cache_refresh_interval_secs > 0,
+        );
+        let cache_max_concurrent_refreshes: usize =
+            settings.get_or("cache_max_concurrent_refreshes", 4);
+        positive(
+            "cache_max_concurrent_refreshes",
+            cache_max_concurrent_refreshes > 0,
+        );
+        let cache_max_concurrent_initial_refreshes: usize =
+            settings.get_or("cache_max_concurrent_initial_refreshes", 6);
+        positive(
+            "cache_max_concurrent_initial_refreshes",
+            cache_max_concurrent_initial_refreshes > 0,
+        );
+
+        // Anonymous rate limit settings
+        let api_rate_limit_per_minute: u32 =
+            settings.get_or("api_rate_limit_per_minute", 120);
+        positive("api_rate_limit_per_minute", api_rate_limit_per_minute > 0);
+        let api_rate_limit_burst: u32 =
+            settings.get_or("api_rate_limit_burst", 60);
+        positive("api_rate_limit_burst", api_rate_limit_burst > 0);
+        let api_trusted_proxies =
+            settings.list("api_trusted_proxies").unwrap_or_default();
+
+        let smtp = settings.get("smtp_host").map(|host| SmtpConfig {
+            host,
+            port: settings.get_or("smtp_port", 587),
+            username: settings.get("smtp_username"),
+            password: settings.get("smtp_password"),
+            from: settings.required("smtp_from"),
+            starttls: settings.get_or("smtp_starttls", true),
+        });
+
+        let bot = settings.get("bot_token").map(|token| BotConfig {
+            api_url: settings.get_or(
+                "bot_api_url",
+                String::from("https://api.telegram.org"),
+            ),
+            token,
+        });
+        if let Some(bot) = &bot {
+            settings.check_url("bot_api_url", &bot.api_url, &["http", "https"]);
+        }
+
+        let ops_webhook =
+            settings.get("ops_webhook_url").map(|url| WebhookConfig {
+                url,
+                secret: settings.required("ops_webhook_secret"),
+            });
+        if let Some(webhook) = &ops_webhook {
+            settings.check_url(
+                "ops_webhook_url",
+                &webhook.url,
+                &["http", "https"],
+            );
+        }
+
+        settings.finish()?;
+
+        Ok(Config {
+            config_file: settings
+                .path()
+                .map(|path| path.to_string_lossy().into_owned()),
+            host,
+            websocket_host,
+            database_url,
+            sync_threads,
+            aggregation_interval,
+            mp_asset_interval,
+            cache_state_interval,
+            registry_reload_interval,
+            timeout,
+            server_host,
+            port,
+            allowed_origins,
+            static_dir,
+            max_tasks,
+            admin_contract,
+            ignore_protocols,
+            native_currency,
+            staking_unbonding_days,
+            socket_reconnect_interval,
+            grpc_host,
+            events_subscribe,
+            enable_sync,
+            ingest_roles,
+            metrics_host,
+            metrics_port,
+            tasks_interval,
+            status_code_to_delete,
+            mail_to,
+            app_url,
+            push_min_interval_minutes,
+            vapid_keys,
+            vapid_key_id,
+            vapid_retired_key_ids,
+            auth,
+            grpc_connections,
+            grpc_permits,
+            db_max_connections,
+            db_min_connections,
+            db_acquire_timeout,
+            db_idle_timeout,
+            db_statement_timeout,
+            cache_refresh_interval_secs,
+            cache_max_concurrent_refreshes,
+            cache_max_concurrent_initial_refreshes,
+            api_rate_limit_per_minute,
+            api_rate_limit_burst,
+            api_trusted_proxies,
+            smtp,
+            bot,
+            ops_webhook,
+        })
+    }
+}
+
+/// Load the `.env` of the working directory, or of a parent, into the
+/// environment when there is one
 pub fn set_configuration() -> Result<(), Error> {
-    let path = format!("{}.env", workspace_root());
-    dotenvy::from_path(path).map_err(|e| {
-        Error::ConfigurationError(format!("Failed to load .env: {}", e))
-    })?;
-    Ok(())
+    match dotenvy::dotenv() {
+        Ok(_) => Ok(()),
+        Err(e) if e.not_found() => Ok(()),
+        Err(e) => Err(Error::ConfigurationError(format!(
+            "Failed to load .env: {}",
+            e
+        ))),
+    }
 }
@@ -1,7 +1,10 @@
 use chrono::{DateTime, Utc};
 use sqlx::Error;
 
-use crate::model::{Action_History, Table};
+use crate::{
+    model::{Action_History, Table},
+    telemetry::Timed as _,
+};
 
 use super::QueryResult;
 
@@ -20,6 +23,7 @@ impl Table<Action_History> {
         .bind(data.created_at)
         .persistent(true)
         .execute(&self.pool)
+        .timed("action_history", "insert")
         .await
     }
 
@@ -35,6 +39,7 @@ impl Table<Action_History> {
         .bind(action_type)
         .persistent(true)
         .fetch_optional(&self.pool)
+        .timed("action_history", "get_last_by_type")
         .await
     }
 
@@ -52,6 +57,27 @@ impl Table<Action_History> {
         .bind(timestamp)
         .persistent(true)
         .fetch_optional(&self.pool)
+        .timed("action_history", "get_last_by_type_before")
+        .await
+    }
+
+    pub async fn get_recent(
+        &self,
+        action_type: Option<String>,
+        limit: i64,
+    ) -> Result<Vec<Action_History>, Error> {
+        sqlx::query_as(
+            r#"
+            SELECT * FROM "action_history"
+            WHERE ($1::VARCHAR IS NULL OR "action_type" = $1)
+            ORDER BY "created_at" DESC
+            LIMIT $2
+            "#,
+        )
+        .bind(action_type)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .timed("action_history", "get_recent")
         .await
     }
 }
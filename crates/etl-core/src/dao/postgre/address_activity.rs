@@ -0,0 +1,164 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::{Error, FromRow, Transaction};
+
+use crate::{
+    helpers::Filter_Types,
+    model::{AddressActivity, CosmosTypes, Table},
+    telemetry::Timed as _,
+};
+
+use super::{DataBase, QueryResult};
+
+/// Activity of an address as sender, recipient or grantee of messages
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct AddressActivityRow {
+    pub messages: i64,
+    pub last_seen: Option<DateTime<Utc>>,
+}
+
+/// Derives the rows of the messages matching `condition`, binding from `$5`.
+/// Recipients equal to the sender and numeric ones (proposal ids) are
+/// skipped. Leases are found by the executed contract, or by the opening
+/// transaction for `open_lease`.
+fn derive(condition: &str) -> String {
+    format!(
+        r#"
+        INSERT INTO "address_activity" (
+            "address", "tx_hash", "index", "inner_index", "role", "type",
+            "kind", "lease", "pool", "to", "block", "timestamp", "code"
+        )
+        SELECT
+            r."address",
+            m."tx_hash",
+            m."index",
+            m."inner_index",
+            r."role",
+            m."type",
+            CASE
+                WHEN m."type" = ANY($1) THEN 'transfer'
+                WHEN m."type" = ANY($2) THEN 'staking'
+                WHEN m."type" = ANY($3) THEN 'governance'
+                WHEN m."type" <> $4 THEN 'other'
+                WHEN a."name" = 'open_lease' THEN 'lease_open'
+                WHEN a."name" IN ('close', 'close_position') THEN 'close'
+                WHEN a."name" = 'burn' THEN 'withdraw'
+                ELSE COALESCE(a."name", 'other')
+            END,
+            COALESCE(l."LS_contract_id", o."LS_contract_id"),
+            p."LP_Pool_id",
+            m."to",
+            m."block",
+            m."timestamp",
+            m."code"
+        FROM "raw_message" m
+        CROSS JOIN LATERAL (
+            VALUES ('sender', m."from"), ('recipient', m."to"), ('grantee', m."grantee")
+        ) AS r ("role", "address")
+        LEFT JOIN LATERAL (
+            SELECT "name"
+            FROM JSONB_OBJECT_KEYS(
+                CASE
+                    WHEN JSONB_TYPEOF(m."data" -> 'msg') = 'object' THEN m."data" -> 'msg'
+                    ELSE '{{}}'::JSONB
+                END
+            ) AS "name"
+            LIMIT 1
+        ) a ON true
+        LEFT JOIN "LS_Opening" l ON m."type" = $4 AND l."LS_contract_id" = m."to"
+        LEFT JOIN LATERAL (
+            SELECT "LS_contract_id"
+            FROM "LS_Opening"
+            WHERE a."name" = 'open_lease' AND "Tx_Hash" = m."tx_hash" AND "LS_address_id" = m."from"
+            LIMIT 1
+        ) o ON true
+        LEFT JOIN "LP_Pool" p ON m."type" = $4 AND p."LP_Pool_id" = m."to"
+        WHERE COALESCE(r."address", '') <> ''
+            AND NOT (r."role" = 'recipient' AND (r."address" = m."from" OR r."address" ~ '^[0-9]+$'))
+            AND {condition}
+        ON CONFLICT DO NOTHING
+        "#
+    )
+}
+
+fn kind_types() -> (Vec<String>, Vec<String>, Vec<String>, String) {
+    let governance = [
+        CosmosTypes::MsgVote,
+        CosmosTypes::MsgVoteLegacy,
+        CosmosTypes::MsgSubmitProposal,
+        CosmosTypes::MsgSubmitProposalLegacy,
+        CosmosTypes::MsgDeposit,
+        CosmosTypes::MsgDepositLegacy,
+    ];
+
+    (
+        Filter_Types::Transfers.into(),
+        Filter_Types::Staking.into(),
+        governance.iter().map(|t| t.to_string()).collect(),
+        CosmosTypes::MsgExecuteContract.to_string(),
+    )
+}
+
+impl Table<AddressActivity> {
+    /// Index the messages of a transaction. Run after its messages and the
+    /// lease openings of its events are inserted.
+    pub async fn insert_by_tx(
+        &self,
+        tx_hash: &str,
+        transaction: &mut Transaction<'_, DataBase>,
+    ) -> Result<QueryResult, Error> {
+        let (transfers, staking, governance, execute) = kind_types();
+
+        sqlx::query(&derive(r#"m."tx_hash" = $5"#))
+            .bind(transfers)
+            .bind(staking)
+            .bind(governance)
+            .bind(execute)
+            .bind(tx_hash)
+            .persistent(true)
+            .execute(&mut **transaction)
+            .timed("address_activity", "insert_by_tx")
+            .await
+    }
+
+    /// Index the messages of a range of blocks, both inclusive
+    pub async fn insert_by_blocks(
+        &self,
+        from: i64,
+        to: i64,
+    ) -> Result<QueryResult, Error> {
+        let (transfers, staking, governance, execute) = kind_types();
+
+        sqlx::query(&derive(r#"m."block" BETWEEN $5 AND $6"#))
+            .bind(transfers)
+            .bind(staking)
+            .bind(governance)
+            .bind(execute)
+            .bind(from)
+            .bind(to)
+            .persistent(true)
+            .execute(&self.pool)
+            .timed("address_activity", "insert_by_blocks")
+            .await
+    }
+
+    pub async fn get_summary(
+        &self,
+        address: String,
+    ) -> Result<AddressActivityRow, Error> {
+        sqlx::query_as(
+            r#"
+            SELECT
+                COUNT(DISTINCT ("tx_hash", "index", "inner_index")) AS "messages",
+                MAX("timestamp") AS "last_seen"
+            FROM "address_activity"
+            WHERE "address" = $1
+            "#,
+        )
+        .bind(address)
+        .persistent(true)
+        .fetch_one(&self.pool)
+        .timed("address_activity", "get_summary")
+        .await
+    }
+}
@@ -0,0 +1,132 @@
+use sqlx::Error;
+
+use crate::{
+    helpers::{AdminCommandStatus, AdminCommandType},
+    model::{AdminCommand, Table},
+    telemetry::Timed as _,
+};
+
+use super::QueryResult;
+
+impl Table<AdminCommand> {
+    pub async fn insert(
+        &self,
+        command: AdminCommandType,
+        from_height: Option<i64>,
+        to_height: Option<i64>,
+        requested_by: Option<String>,
+    ) -> Result<AdminCommand, Error> {
+        sqlx::query_as(
+            r#"
+            INSERT INTO "admin_command" ("command", "from_height", "to_height", "requested_by")
+            VALUES ($1, $2, $3, $4)
+            RETURNING *
+            "#,
+        )
+        .bind(command.to_string())
+        .bind(from_height)
+        .bind(to_height)
+        .bind(requested_by)
+        .fetch_one(&self.pool)
+        .timed("admin_command", "insert")
+        .await
+    }
+
+    pub async fn get_recent(
+        &self,
+        limit: i64,
+    ) -> Result<Vec<AdminCommand>, Error> {
+        sqlx::query_as(
+            r#"
+            SELECT * FROM "admin_command" ORDER BY "id" DESC LIMIT $1
+            "#,
+        )
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .timed("admin_command", "get_recent")
+        .await
+    }
+
+    /// Mark the oldest pending command, or running command whose lease
+    /// expired, as running for `lock_secs` under a new `claim` and return
+    /// it. Rows locked by another consumer are skipped.
+    pub async fn claim_next(
+        &self,
+        lock_secs: i64,
+    ) -> Result<Option<AdminCommand>, Error> {
+        sqlx::query_as(
+            r#"
+            UPDATE "admin_command"
+            SET
+                "status" = $1,
+                "started_at" = now(),
+                "claimed_until" = now() + make_interval(secs => $3),
+                "claim" = "claim" + 1
+            WHERE "id" = (
+                SELECT "id" FROM "admin_command"
+                WHERE "status" = $2 OR ("status" = $1 AND "claimed_until" < now())
+                ORDER BY "id" ASC
+                LIMIT 1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING *
+            "#,
+        )
+        .bind(AdminCommandStatus::Running.to_string())
+        .bind(AdminCommandStatus::Pending.to_string())
+        .bind(lock_secs as f64)
+        .persistent(true)
+        .fetch_optional(&self.pool)
+        .timed("admin_command", "claim_next")
+        .await
+    }
+
+    /// Extend the lease of a running command by `lock_secs` from now. No row
+    /// is updated once the command was claimed again.
+    pub async fn extend_claim(
+        &self,
+        id: i64,
+        claim: i64,
+        lock_secs: i64,
+    ) -> Result<QueryResult, Error> {
+        sqlx::query(
+            r#"
+            UPDATE "admin_command"
+            SET "claimed_until" = now() + make_interval(secs => $4)
+            WHERE "id" = $1 AND "claim" = $2 AND "status" = $3
+            "#,
+        )
+        .bind(id)
+        .bind(claim)
+        .bind(AdminCommandStatus::Running.to_string())
+        .bind(lock_secs as f64)
+        .execute(&self.pool)
+        .timed("admin_command", "extend_claim")
+        .await
+    }
+
+    /// Record the outcome of a run. No row is updated once the command was
+    /// claimed again.
+    pub async fn finish(
+        &self,
+        id: i64,
+        claim: i64,
+        status: AdminCommandStatus,
+        error: Option<String>,
+    ) -> Result<QueryResult, Error> {
+        sqlx::query(
+            r#"
+            UPDATE "admin_command" SET "status" = $1, "error" = $2, "finished_at" = now()
+            WHERE "id" = $3 AND "claim" = $4 AND "status" = $5
+            "#,
+        )
+        .bind(status.to_string())
+        .bind(error)
+        .bind(id)
+        .bind(claim)
+        .bind(AdminCommandStatus::Running.to_string())
+        .execute(&self.pool)
+        .timed("admin_command", "finish")
+        .await
+    }
+}
@@ -0,0 +1,289 @@
+use sqlx::{types::BigDecimal, Error, FromRow, Transaction};
+
+use crate::{
+    helpers::AlertRuleKind,
+    model::{AlertRule, Table},
+    telemetry::Timed as _,
+};
+
+use super::{DataBase, QueryResult};
+
+/// Condition of a rule, validated against its kind
+#[derive(Debug, Clone, Default)]
+pub struct AlertRuleParams {
+    pub kind: String,
+    pub lease: Option<String>,
+    pub symbol: Option<String>,
+    pub protocol: Option<String>,
+    pub comparison: Option<String>,
+    pub threshold: Option<BigDecimal>,
+    pub strategy: Option<String>,
+}
+
+/// LTV rule with the latest state of its lease. `debt` is the principal with
+/// the due margin and interest, in the pool currency.
+#[derive(Debug, FromRow)]
+pub struct LeaseAlertState {
+    #[sqlx(flatten)]
+    pub rule: AlertRule,
+    pub asset_symbol: String,
+    pub pool_id: String,
+    pub amount: BigDecimal,
+    pub debt: BigDecimal,
+}
+
+impl Table<AlertRule> {
+    pub async fn insert(
+        &self,
+        address: String,
+        auth: String,
+        params: AlertRuleParams,
+    ) -> Result<AlertRule, Error> {
+        sqlx::query_as(
+            r#"
+            INSERT INTO "alert_rule" (
+                "address", "auth", "kind", "lease", "symbol", "protocol",
+                "comparison", "threshold", "strategy"
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            RETURNING *
+            "#,
+        )
+        .bind(address)
+        .bind(auth)
+        .bind(params.kind)
+        .bind(params.lease)
+        .bind(params.symbol)
+        .bind(params.protocol)
+        .bind(params.comparison)
+        .bind(params.threshold)
+        .bind(params.strategy)
+        .fetch_one(&self.pool)
+        .timed("alert_rule", "insert")
+        .await
+    }
+
+    /// Replace the condition of a rule of the subscription. The rule is
+    /// re-armed, so it fires again if the new condition already holds.
+    pub async fn update(
+        &self,
+        id: i64,
+        address: String,
+        auth: String,
+        params: AlertRuleParams,
+        active: bool,
+    ) -> Result<Option<AlertRule>, Error> {
+        sqlx::query_as(
+            r#"
+            UPDATE "alert_rule"
+            SET
+                "kind" = $4,
+                "lease" = $5,
+                "symbol" = $6,
+                "protocol" = $7,
+                "comparison" = $8,
+                "threshold" = $9,
+                "strategy" = $10,
+                "active" = $11,
+                "triggered" = false,
+                "last_value" = NULL,
+                "updated_at" = now()
+            WHERE "id" = $1 AND "address" = $2 AND "auth" = $3
+            RETURNING *
+            "#,
+        )
+        .bind(id)
+        .bind(address)
+        .bind(auth)
+        .bind(params.kind)
+        .bind(params.lease)
+        .bind(params.symbol)
+        .bind(params.protocol)
+        .bind(params.comparison)
+        .bind(params.threshold)
+        .bind(params.strategy)
+        .bind(active)
+        .fetch_optional(&self.pool)
+        .timed("alert_rule", "update")
+        .await
+    }
+
+    pub async fn delete(
+        &self,
+        id: i64,
+        address: String,
+        auth: String,
+    ) -> Result<QueryResult, Error> {
+        sqlx::query(
+            r#"
+            DELETE FROM "alert_rule"
+            WHERE "id" = $1 AND "address" = $2 AND "auth" = $3
+            "#,
+        )
+        .bind(id)
+        .bind(address)
+        .bind(auth)
+        .execute(&self.pool)
+        .timed("alert_rule", "delete")
+        .await
+    }
+
+    pub async fn get(&self, id: i64) -> Result<Option<AlertRule>, Error> {
+        sqlx::query_as(
+            r#"
+            SELECT * FROM "alert_rule" WHERE "id" = $1
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .timed("alert_rule", "get")
+        .await
+    }
+
+    pub async fn get_by_subscription(
+        &self,
+        address: String,
+        auth: String,
+    ) -> Result<Vec<AlertRule>, Error> {
+        sqlx::query_as(
+            r#"
+            SELECT * FROM "alert_rule"
+            WHERE "address" = $1 AND "auth" = $2
+            ORDER BY "id" ASC
+            "#,
+        )
+        .bind(address)
+        .bind(auth)
+        .fetch_all(&self.pool)
+        .timed("alert_rule", "get_by_subscription")
+        .await
+    }
+
+    pub async fn count_by_subscription(
+        &self,
+        address: String,
+        auth: String,
+    ) -> Result<i64, Error> {
+        let (count,): (i64,) = sqlx::query_as(
+            r#"
+            SELECT COUNT(*) FROM "alert_rule"
+            WHERE "address" = $1 AND "auth" = $2
+            "#,
+        )
+        .bind(address)
+        .bind(auth)
+        .fetch_one(&self.pool)
+        .timed("alert_rule", "count_by_subscription")
+        .await?;
+
+        Ok(count)
+    }
+
+    pub async fn get_active_by_kind(
+        &self,
+        kind: AlertRuleKind,
+    ) -> Result<Vec<AlertRule>, Error> {
+        sqlx::query_as(
+            r#"
+            SELECT * FROM "alert_rule"
+            WHERE "kind" = $1 AND "active" = true
+            "#,
+        )
+        .bind(kind.to_string())
+        .fetch_all(&self.pool)
+        .timed("alert_rule", "get_active_by_kind")
+        .await
+    }
+
+    /// Active LTV rules whose lease has a state from the last hour
+    pub async fn get_lease_states(
+        &self,
+    ) -> Result<Vec<LeaseAlertState>, Error> {
+        sqlx::query_as(
+            r#"
+            SELECT
+                r.*,
+                o."LS_asset_symbol" AS asset_symbol,
+                o."LS_loan_pool_id" AS pool_id,
+                s."LS_amnt" AS amount,
+                (
+                    s."LS_principal_asset"
+                  + s."LS_prev_margin_asset"
+                  + s."LS_prev_interest_asset"
+                  + s."LS_current_margin_asset"
+                  + s."LS_current_interest_asset"
+                ) AS debt
+            FROM "alert_rule" r
+            INNER JOIN "LS_Opening" o ON o."LS_contract_id" = r."lease"
+            INNER JOIN LATERAL (
+                SELECT * FROM "LS_State" st
+                WHERE st."LS_contract_id" = r."lease"
+                AND st."LS_timestamp" > NOW() - INTERVAL '1 hour'
+                ORDER BY st."LS_timestamp" DESC
+                LIMIT 1
+            ) s ON true
+            WHERE r."kind" = $1 AND r."active" = true
+            "#,
+        )
+        .bind(AlertRuleKind::LeaseLtv.to_string())
+        .fetch_all(&self.pool)
+        .timed("alert_rule", "get_lease_states")
+        .await
+    }
+
+    /// Active close rules of a lease or of any lease of its owner. Rules
+    /// naming a strategy match auto-close events of that strategy, the others
+    /// match the lease closing.
+    pub async fn get_position_closed(
+        &self,
+        lease: String,
+        owner: Option<String>,
+        strategy: Option<String>,
+        transaction: &mut Transaction<'_, DataBase>,
+    ) -> Result<Vec<AlertRule>, Error> {
+        sqlx::query_as(
+            r#"
+            SELECT * FROM "alert_rule"
+            WHERE "kind" = $1 AND "active" = true
+            AND ("lease" = $2 OR ("lease" IS NULL AND "address" = $3))
+            AND "strategy" IS NOT DISTINCT FROM $4
+            "#,
+        )
+        .bind(AlertRuleKind::PositionClosed.to_string())
+        .bind(lease)
+        .bind(owner)
+        .bind(strategy)
+        .fetch_all(&mut **transaction)
+        .timed("alert_rule", "get_position_closed")
+        .await
+    }
+
+    /// Store the outcome of an evaluation, stamping the trigger time when the
+    /// rule fired
+    pub async fn set_state(
+        &self,
+        id: i64,
+        triggered: bool,
+        last_value: Option<BigDecimal>,
+        fired: bool,
+        transaction: &mut Transaction<'_, DataBase>,
+    ) -> Result<QueryResult, Error> {
+        sqlx::query(
+            r#"
+            UPDATE "alert_rule"
+            SET
+                "triggered" = $2,
+                "last_value" = COALESCE($3, "last_value"),
+                "last_triggered_at" = CASE WHEN $4 THEN now() ELSE "last_triggered_at" END
+            WHERE "id" = $1
+            "#,
+        )
+        .bind(id)
+        .bind(triggered)
+        .bind(last_value)
+        .bind(fired)
+        .execute(&mut **transaction)
+        .timed("alert_rule", "set_state")
+        .await
+    }
+}
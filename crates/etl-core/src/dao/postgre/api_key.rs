@@ -0,0 +1,139 @@
+use chrono::NaiveDate;
+use serde::Serialize;
+use sqlx::{Error, FromRow};
+
+use crate::{
+    model::{ApiKey, Table},
+    telemetry::Timed as _,
+};
+
+use super::QueryResult;
+
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct ApiKeyUsage {
+    pub key_prefix: String,
+    pub name: String,
+    pub day: NaiveDate,
+    pub requests: i64,
+    pub throttled: i64,
+}
+
+impl Table<ApiKey> {
+    pub async fn insert(&self, data: &ApiKey) -> Result<QueryResult, Error> {
+        sqlx::query(
+            r#"
+            INSERT INTO "api_key" ("key_hash", "key_prefix", "name", "scope", "rate_limit_per_minute", "burst", "active", "created_at")
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            "#,
+        )
+        .bind(&data.key_hash)
+        .bind(&data.key_prefix)
+        .bind(&data.name)
+        .bind(&data.scope)
+        .bind(data.rate_limit_per_minute)
+        .bind(data.burst)
+        .bind(data.active)
+        .bind(data.created_at)
+        .execute(&self.pool)
+        .timed("api_key", "insert")
+        .await
+    }
+
+    pub async fn get_active_by_hash(
+        &self,
+        key_hash: &str,
+    ) -> Result<Option<ApiKey>, Error> {
+        sqlx::query_as(
+            r#"
+            SELECT * FROM "api_key" WHERE "key_hash" = $1 AND "active" = true
+            "#,
+        )
+        .bind(key_hash)
+        .persistent(true)
+        .fetch_optional(&self.pool)
+        .timed("api_key", "get_active_by_hash")
+        .await
+    }
+
+    pub async fn get_all(&self) -> Result<Vec<ApiKey>, Error> {
+        sqlx::query_as(
+            r#"
+            SELECT * FROM "api_key" ORDER BY "created_at" DESC
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .timed("api_key", "get_all")
+        .await
+    }
+
+    pub async fn deactivate(
+        &self,
+        key_prefix: &str,
+    ) -> Result<QueryResult, Error> {
+        sqlx::query(
+            r#"
+            UPDATE "api_key" SET "active" = false WHERE "key_prefix" = $1
+            "#,
+        )
+        .bind(key_prefix)
+        .execute(&self.pool)
+        .timed("api_key", "deactivate")
+        .await
+    }
+
+    /// Add to the daily counters of a key, creating the row for the day
+    pub async fn add_usage(
+        &self,
+        key_hash: &str,
+        day: NaiveDate,
+        requests: i64,
+        throttled: i64,
+    ) -> Result<QueryResult, Error> {
+        sqlx::query(
+            r#"
+            INSERT INTO "api_key_usage" ("key_hash", "day", "requests", "throttled")
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT ("key_hash", "day") DO UPDATE SET
+                "requests" = "api_key_usage"."requests" + EXCLUDED."requests",
+                "throttled" = "api_key_usage"."throttled" + EXCLUDED."throttled"
+            "#,
+        )
+        .bind(key_hash)
+        .bind(day)
+        .bind(requests)
+        .bind(throttled)
+        .persistent(true)
+        .execute(&self.pool)
+        .timed("api_key", "add_usage")
+        .await
+    }
+
+    pub async fn get_usage(
+        &self,
+        from: NaiveDate,
+        to: NaiveDate,
+        key_prefix: Option<String>,
+    ) -> Result<Vec<ApiKeyUsage>, Error> {
+        sqlx::query_as(
+            r#"
+            SELECT
+                k."key_prefix",
+                k."name",
+                u."day",
+                u."requests",
+                u."throttled"
+            FROM "api_key_usage" u
+            INNER JOIN "api_key" k ON k."key_hash" = u."key_hash"
+            WHERE u."day" BETWEEN $1 AND $2
+                AND ($3::VARCHAR IS NULL OR k."key_prefix" = $3)
+            ORDER BY u."day" DESC, u."requests" DESC
+            "#,
+        )
+        .bind(from)
+        .bind(to)
+        .bind(key_prefix)
+        .fetch_all(&self.pool)
+        .timed("api_key", "get_usage")
+        .await
+    }
+}
@@ -1,6 +1,9 @@
 use sqlx::{Error, Transaction};
 
-use crate::model::{Block, Table};
+use crate::{
+    model::{Block, Table},
+    telemetry::Timed as _,
+};
 
 use super::{DataBase, QueryResult};
 
@@ -20,6 +23,7 @@ impl Table<Block> {
         .bind(block.id)
         .persistent(true)
         .execute(&mut **transaction)
+        .timed("block", "insert")
         .await
     }
 
@@ -42,6 +46,7 @@ impl Table<Block> {
         )
         .persistent(true)
         .fetch_all(&self.pool)
+        .timed("block", "get_all_missing_blocks")
         .await
     }
 
@@ -70,6 +75,7 @@ impl Table<Block> {
         )
         .persistent(true)
         .fetch_all(&self.pool)
+        .timed("block", "get_recent_missing_blocks")
         .await
     }
 
@@ -81,6 +87,7 @@ impl Table<Block> {
         )
         .persistent(true)
         .fetch_one(&self.pool)
+        .timed("block", "get_first_block")
         .await
     }
 
@@ -92,9 +99,25 @@ impl Table<Block> {
         )
         .persistent(true)
         .fetch_one(&self.pool)
+        .timed("block", "get_last_block")
         .await
     }
 
+    /// Highest indexed block, `None` before the first one
+    pub async fn get_max_block(&self) -> Result<Option<i64>, Error> {
+        let (id,): (Option<i64>,) = sqlx::query_as(
+            r#"
+            SELECT MAX(id) FROM block
+            "#,
+        )
+        .persistent(true)
+        .fetch_one(&self.pool)
+        .timed("block", "get_max_block")
+        .await?;
+
+        Ok(id)
+    }
+
     pub async fn get_one(&self, id: i64) -> Result<Option<Block>, Error> {
         sqlx::query_as(
             r#"
@@ -104,6 +127,7 @@ impl Table<Block> {
         .bind(id)
         .persistent(true)
         .fetch_optional(&self.pool)
+        .timed("block", "get_one")
         .await
     }
 
@@ -115,6 +139,7 @@ impl Table<Block> {
         )
         .persistent(true)
         .fetch_one(&self.pool)
+        .timed("block", "count")
         .await?;
         Ok(count)
     }
@@ -128,6 +153,7 @@ impl Table<Block> {
         .bind(block)
         .persistent(true)
         .fetch_one(&self.pool)
+        .timed("block", "is_synced_to_block")
         .await?;
 
         if block == count {
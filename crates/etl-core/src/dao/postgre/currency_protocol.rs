@@ -1,6 +1,9 @@
 use sqlx::Error;
 
-use crate::model::{CurrencyProtocol, Table};
+use crate::{
+    model::{CurrencyProtocol, Table},
+    telemetry::Timed as _,
+};
 
 impl Table<CurrencyProtocol> {
     /// Upsert a currency-protocol relationship with per-protocol denoms
@@ -28,6 +31,7 @@ impl Table<CurrencyProtocol> {
         .bind(bank_symbol)
         .bind(dex_symbol)
         .execute(&self.pool)
+        .timed("currency_protocol", "upsert")
         .await?;
         Ok(())
     }
@@ -43,6 +47,7 @@ impl Table<CurrencyProtocol> {
         )
         .persistent(true)
         .fetch_all(&self.pool)
+        .timed("currency_protocol", "get_all")
         .await
     }
 
@@ -62,6 +67,7 @@ impl Table<CurrencyProtocol> {
         .bind(ticker)
         .persistent(true)
         .fetch_all(&self.pool)
+        .timed("currency_protocol", "get_by_ticker")
         .await
     }
 }
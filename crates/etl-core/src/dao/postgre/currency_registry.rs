@@ -1,7 +1,10 @@
 use sqlx::Error;
 
-use crate::model::{CurrencyRegistry, Table};
 use crate::types::OracleCurrency;
+use crate::{
+    model::{CurrencyRegistry, Table},
+    telemetry::Timed as _,
+};
 
 impl Table<CurrencyRegistry> {
     /// Upsert an active currency from oracle
@@ -24,6 +27,7 @@ impl Table<CurrencyRegistry> {
         .bind(&currency.ticker)
         .bind(currency.decimal_digits)
         .execute(&self.pool)
+        .timed("currency_registry", "upsert_active")
         .await?;
 
         Ok(())
@@ -43,6 +47,7 @@ impl Table<CurrencyRegistry> {
         )
         .bind(active_tickers)
         .execute(&self.pool)
+        .timed("currency_registry", "mark_deprecated_except")
         .await?;
 
         Ok(result.rows_affected())
@@ -60,6 +65,7 @@ impl Table<CurrencyRegistry> {
         )
         .persistent(true)
         .fetch_all(&self.pool)
+        .timed("currency_registry", "get_all")
         .await
     }
 
@@ -76,6 +82,7 @@ impl Table<CurrencyRegistry> {
         )
         .persistent(true)
         .fetch_all(&self.pool)
+        .timed("currency_registry", "get_active")
         .await
     }
 
@@ -95,6 +102,7 @@ impl Table<CurrencyRegistry> {
         .bind(ticker)
         .persistent(true)
         .fetch_optional(&self.pool)
+        .timed("currency_registry", "get_by_ticker")
         .await
     }
 
@@ -104,12 +112,14 @@ impl Table<CurrencyRegistry> {
             r#"SELECT COUNT(*) FROM "currency_registry" WHERE "is_active" = true"#,
         )
         .fetch_one(&self.pool)
+        .timed("currency_registry", "count_by_status")
         .await?;
 
         let deprecated: (i64,) = sqlx::query_as(
             r#"SELECT COUNT(*) FROM "currency_registry" WHERE "is_active" = false"#,
         )
         .fetch_one(&self.pool)
+        .timed("currency_registry", "count_by_status")
         .await?;
 
         Ok((active.0, deprecated.0))
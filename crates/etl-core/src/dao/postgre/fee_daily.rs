@@ -0,0 +1,225 @@
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::{Error, FromRow, QueryBuilder, Transaction};
+
+use crate::{
+    helpers::FeeGroup,
+    model::{CosmosTypes, FeeDaily, Table},
+    telemetry::Timed as _,
+};
+
+use super::{DataBase, QueryResult};
+
+/// Fees and gas of a day for a message type, contract action or address
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct FeeSeriesRow {
+    pub day: DateTime<Utc>,
+    pub key: String,
+    pub fee_denom: String,
+    pub txs: i64,
+    pub failed: i64,
+    pub fee_amount: BigDecimal,
+    pub fee_per_tx: BigDecimal,
+    pub gas_wanted: BigDecimal,
+    pub gas_used: BigDecimal,
+}
+
+impl Table<FeeDaily> {
+    /// Day of the latest rollup, recomputed by the next one
+    pub async fn get_last_day(&self) -> Result<Option<DateTime<Utc>>, Error> {
+        let (day,): (Option<DateTime<Utc>>,) = sqlx::query_as(
+            r#"
+            SELECT MAX("day") FROM "fee_daily"
+            "#,
+        )
+        .persistent(true)
+        .fetch_one(&self.pool)
+        .timed("fee_daily", "get_last_day")
+        .await?;
+
+        Ok(day)
+    }
+
+    /// Mark the day of `timestamp` for recomputation when it is before the
+    /// latest rollup, which recomputes its own day and the next ones
+    pub async fn mark_day(
+        &self,
+        timestamp: DateTime<Utc>,
+        transaction: &mut Transaction<'_, DataBase>,
+    ) -> Result<QueryResult, Error> {
+        sqlx::query(
+            r#"
+            INSERT INTO "fee_daily_pending" ("day")
+            SELECT DATE_TRUNC('day', $1::TIMESTAMPTZ)
+            WHERE DATE_TRUNC('day', $1::TIMESTAMPTZ) < (SELECT MAX("day") FROM "fee_daily")
+            ON CONFLICT ("day") DO UPDATE SET
+                "version" = "fee_daily_pending"."version" + 1
+            "#,
+        )
+        .bind(timestamp)
+        .persistent(true)
+        .execute(&mut **transaction)
+        .timed("fee_daily", "mark_day")
+        .await
+    }
+
+    /// Marked days with the version of their mark
+    pub async fn get_pending_days(
+        &self,
+    ) -> Result<Vec<(DateTime<Utc>, i64)>, Error> {
+        sqlx::query_as(
+            r#"
+            SELECT "day", "version" FROM "fee_daily_pending" ORDER BY "day"
+            "#,
+        )
+        .persistent(true)
+        .fetch_all(&self.pool)
+        .timed("fee_daily", "get_pending_days")
+        .await
+    }
+
+    /// Remove the mark of a day unless it was marked again since `version`
+    pub async fn clear_pending_day(
+        &self,
+        day: DateTime<Utc>,
+        version: i64,
+    ) -> Result<QueryResult, Error> {
+        sqlx::query(
+            r#"
+            DELETE FROM "fee_daily_pending" WHERE "day" = $1 AND "version" = $2
+            "#,
+        )
+        .bind(day)
+        .bind(version)
+        .persistent(true)
+        .execute(&self.pool)
+        .timed("fee_daily", "clear_pending_day")
+        .await
+    }
+
+    /// Roll up the transactions of the days from the one of `from`, or the
+    /// first, to the one of `to`, or the last
+    pub async fn refresh(
+        &self,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    ) -> Result<QueryResult, Error> {
+        sqlx::query(
+            r#"
+            INSERT INTO "fee_daily" (
+                "day", "type", "action", "address", "fee_denom", "txs",
+                "failed", "fee_amount", "gas_wanted", "gas_used"
+            )
+            SELECT
+                DATE_TRUNC('day', "timestamp") AS "day",
+                "type",
+                "action",
+                "address",
+                "fee_denom",
+                COUNT(*),
+                COUNT(*) FILTER (WHERE "failed"),
+                COALESCE(SUM("fee_amount"), 0),
+                COALESCE(SUM("gas_wanted"), 0),
+                COALESCE(SUM("gas_used"), 0)
+            FROM (
+                SELECT DISTINCT ON (m."tx_hash")
+                    m."timestamp",
+                    m."type",
+                    CASE
+                        WHEN m."type" <> $2 THEN ''
+                        WHEN a."name" = 'open_lease' THEN 'lease_open'
+                        WHEN a."name" IN ('close', 'close_position') THEN 'close'
+                        WHEN a."name" = 'burn' THEN 'withdraw'
+                        ELSE COALESCE(a."name", '')
+                    END AS "action",
+                    COALESCE(m."grantee", m."from") AS "address",
+                    COALESCE(m."fee_denom", '') AS "fee_denom",
+                    m."fee_amount",
+                    m."gas_wanted",
+                    m."gas_used",
+                    COALESCE(m."code", 0) <> 0 AS "failed"
+                FROM "raw_message" m
+                LEFT JOIN LATERAL (
+                    SELECT "name"
+                    FROM JSONB_OBJECT_KEYS(
+                        CASE
+                            WHEN JSONB_TYPEOF(m."data" -> 'msg') = 'object' THEN m."data" -> 'msg'
+                            ELSE '{}'::JSONB
+                        END
+                    ) AS "name"
+                    LIMIT 1
+                ) a ON true
+                WHERE ($1::TIMESTAMPTZ IS NULL OR m."timestamp" >= DATE_TRUNC('day', $1::TIMESTAMPTZ))
+                    AND ($3::TIMESTAMPTZ IS NULL OR m."timestamp" < DATE_TRUNC('day', $3::TIMESTAMPTZ) + INTERVAL '1 day')
+                ORDER BY m."tx_hash", m."index", m."inner_index"
+            ) txs
+            GROUP BY 1, 2, 3, 4, 5
+            ON CONFLICT ("day", "type", "action", "address", "fee_denom") DO UPDATE SET
+                "txs" = EXCLUDED."txs",
+                "failed" = EXCLUDED."failed",
+                "fee_amount" = EXCLUDED."fee_amount",
+                "gas_wanted" = EXCLUDED."gas_wanted",
+                "gas_used" = EXCLUDED."gas_used"
+            "#,
+        )
+        .bind(from)
+        .bind(CosmosTypes::MsgExecuteContract.to_string())
+        .bind(to)
+        .persistent(true)
+        .execute(&self.pool)
+        .timed("fee_daily", "refresh")
+        .await
+    }
+
+    /// Daily fees and gas broken down by a dimension, oldest first.
+    /// Messages other than contract executions are left out of the action
+    /// breakdown.
+    pub async fn get_series(
+        &self,
+        group: FeeGroup,
+        address: Option<String>,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<FeeSeriesRow>, Error> {
+        let key = match group {
+            FeeGroup::Type => r#""type""#,
+            FeeGroup::Action => r#""action""#,
+            FeeGroup::Address => r#""address""#,
+        };
+
+        let mut qb: QueryBuilder<DataBase> =
+            QueryBuilder::new("SELECT \"day\", ");
+        qb.push(key).push(
+            r#" AS "key",
+                "fee_denom",
+                SUM("txs")::BIGINT AS "txs",
+                SUM("failed")::BIGINT AS "failed",
+                SUM("fee_amount") AS "fee_amount",
+                ROUND(SUM("fee_amount") / SUM("txs")) AS "fee_per_tx",
+                SUM("gas_wanted") AS "gas_wanted",
+                SUM("gas_used") AS "gas_used"
+            FROM "fee_daily"
+            WHERE "day" >= DATE_TRUNC('day', "#,
+        );
+        qb.push_bind(from)
+            .push(r#"::TIMESTAMPTZ) AND "day" <= "#)
+            .push_bind(to);
+
+        if let Some(address) = address {
+            qb.push(r#" AND "address" = "#).push_bind(address);
+        }
+
+        if group == FeeGroup::Action {
+            qb.push(r#" AND "action" <> ''"#);
+        }
+
+        qb.push(r#" GROUP BY "day", "key", "fee_denom" ORDER BY "day", "key""#);
+
+        qb.build_query_as()
+            .persistent(false)
+            .fetch_all(&self.pool)
+            .timed("fee_daily", "get_series")
+            .await
+    }
+}
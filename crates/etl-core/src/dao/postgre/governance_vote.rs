@@ -0,0 +1,85 @@
+use bigdecimal::BigDecimal;
+use serde::Serialize;
+use sqlx::{Error, FromRow, Transaction};
+
+use crate::{
+    model::{GovernanceVote, Table},
+    telemetry::Timed as _,
+};
+
+use super::{DataBase, QueryResult};
+
+/// Votes cast for an option of a proposal and the stake indexed for the
+/// voters
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct VoteTallyRow {
+    pub option: String,
+    pub votes: i64,
+    pub stake: BigDecimal,
+}
+
+impl Table<GovernanceVote> {
+    /// Record a vote. A later vote of the same voter replaces it, an
+    /// earlier one indexed afterwards does not.
+    pub async fn upsert(
+        &self,
+        data: GovernanceVote,
+        transaction: &mut Transaction<'_, DataBase>,
+    ) -> Result<QueryResult, Error> {
+        sqlx::query(
+            r#"
+            INSERT INTO "governance_vote" ("proposal_id", "voter", "option", "tx_hash", "block", "timestamp")
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT ("proposal_id", "voter") DO UPDATE SET
+                "option" = EXCLUDED."option",
+                "tx_hash" = EXCLUDED."tx_hash",
+                "block" = EXCLUDED."block",
+                "timestamp" = EXCLUDED."timestamp"
+            WHERE EXCLUDED."block" >= "governance_vote"."block"
+            "#,
+        )
+        .bind(data.proposal_id)
+        .bind(&data.voter)
+        .bind(&data.option)
+        .bind(&data.tx_hash)
+        .bind(data.block)
+        .bind(data.timestamp)
+        .persistent(true)
+        .execute(&mut **transaction)
+        .timed("governance_vote", "upsert")
+        .await
+    }
+
+    /// Tally of a proposal per option. The stake of a voter is what they
+    /// had delegated at the time of their vote, as the end of the voting
+    /// period is not indexed; changes of the delegation after the vote are
+    /// not counted, and votes that validators cast for their delegators are
+    /// not inherited.
+    pub async fn get_tally(
+        &self,
+        proposal_id: i64,
+    ) -> Result<Vec<VoteTallyRow>, Error> {
+        sqlx::query_as(
+            r#"
+            SELECT
+                v."option",
+                COUNT(*) AS "votes",
+                COALESCE(SUM(s."stake"), 0) AS "stake"
+            FROM "governance_vote" v
+            LEFT JOIN LATERAL (
+                SELECT SUM("amount") AS "stake"
+                FROM "staking_event"
+                WHERE "delegator" = v."voter" AND "timestamp" <= v."timestamp"
+            ) s ON true
+            WHERE v."proposal_id" = $1
+            GROUP BY v."option"
+            ORDER BY v."option"
+            "#,
+        )
+        .bind(proposal_id)
+        .persistent(true)
+        .fetch_all(&self.pool)
+        .timed("governance_vote", "get_tally")
+        .await
+    }
+}
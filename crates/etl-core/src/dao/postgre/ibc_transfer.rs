@@ -0,0 +1,126 @@
+use sqlx::{Error, QueryBuilder, Transaction};
+
+use crate::{
+    model::{IbcTransfer, Table},
+    telemetry::Timed as _,
+};
+
+use super::{DataBase, QueryResult};
+
+impl Table<IbcTransfer> {
+    /// Record an outgoing transfer. A transfer already completed by an
+    /// acknowledgement or timeout indexed first keeps its state.
+    pub async fn insert_sent(
+        &self,
+        data: IbcTransfer,
+        transaction: &mut Transaction<'_, DataBase>,
+    ) -> Result<QueryResult, Error> {
+        self.upsert(
+            data,
+            r#"
+            ON CONFLICT ("source_channel", "destination_channel", "sequence", "direction") DO UPDATE SET
+                "tx_hash" = EXCLUDED."tx_hash",
+                "sent_at" = EXCLUDED."sent_at"
+            "#,
+            transaction,
+        )
+        .await
+    }
+
+    /// Record a received transfer, or move a pending outgoing transfer to
+    /// its final state. Transfers that are no longer pending are left as is.
+    pub async fn complete(
+        &self,
+        data: IbcTransfer,
+        transaction: &mut Transaction<'_, DataBase>,
+    ) -> Result<QueryResult, Error> {
+        self.upsert(
+            data,
+            r#"
+            ON CONFLICT ("source_channel", "destination_channel", "sequence", "direction") DO UPDATE SET
+                "status" = EXCLUDED."status",
+                "error" = EXCLUDED."error",
+                "completion_tx_hash" = EXCLUDED."completion_tx_hash",
+                "completed_at" = EXCLUDED."completed_at"
+            WHERE "ibc_transfer"."status" = 'pending'
+            "#,
+            transaction,
+        )
+        .await
+    }
+
+    async fn upsert(
+        &self,
+        data: IbcTransfer,
+        conflict: &str,
+        transaction: &mut Transaction<'_, DataBase>,
+    ) -> Result<QueryResult, Error> {
+        let mut query_builder: QueryBuilder<DataBase> = QueryBuilder::new(
+            r#"
+            INSERT INTO "ibc_transfer" (
+                "source_channel", "sequence", "direction", "source_port",
+                "destination_port", "destination_channel", "sender",
+                "receiver", "denom", "packet_denom", "amount", "status",
+                "error", "timeout_at", "tx_hash", "sent_at",
+                "completion_tx_hash", "completed_at"
+            )"#,
+        );
+
+        query_builder.push_values([data], |mut b, data| {
+            b.push_bind(data.source_channel)
+                .push_bind(data.sequence)
+                .push_bind(data.direction)
+                .push_bind(data.source_port)
+                .push_bind(data.destination_port)
+                .push_bind(data.destination_channel)
+                .push_bind(data.sender)
+                .push_bind(data.receiver)
+                .push_bind(data.denom)
+                .push_bind(data.packet_denom)
+                .push_bind(data.amount)
+                .push_bind(data.status)
+                .push_bind(data.error)
+                .push_bind(data.timeout_at)
+                .push_bind(data.tx_hash)
+                .push_bind(data.sent_at)
+                .push_bind(data.completion_tx_hash)
+                .push_bind(data.completed_at);
+        });
+        query_builder.push(conflict);
+
+        query_builder
+            .build()
+            .persistent(true)
+            .execute(&mut **transaction)
+            .timed("ibc_transfer", "upsert")
+            .await
+    }
+
+    /// Transfers sent or received by an address in the given states, most
+    /// recent first
+    pub async fn get_by_address(
+        &self,
+        address: String,
+        statuses: Vec<String>,
+        skip: i64,
+        limit: i64,
+    ) -> Result<Vec<IbcTransfer>, Error> {
+        sqlx::query_as(
+            r#"
+            SELECT * FROM "ibc_transfer"
+            WHERE ("sender" = $1 OR "receiver" = $1)
+            AND "status" = ANY($2)
+            ORDER BY COALESCE("sent_at", "completed_at") DESC
+            OFFSET $3 LIMIT $4
+            "#,
+        )
+        .bind(address)
+        .bind(statuses)
+        .bind(skip)
+        .bind(limit)
+        .persistent(true)
+        .fetch_all(&self.pool)
+        .timed("ibc_transfer", "get_by_address")
+        .await
+    }
+}
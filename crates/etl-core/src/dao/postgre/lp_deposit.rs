@@ -1,9 +1,13 @@
 use std::str::FromStr as _;
 
 use chrono::{DateTime, Utc};
+use futures::stream::BoxStream;
 use sqlx::{types::BigDecimal, Error, FromRow, QueryBuilder, Transaction};
 
-use crate::model::{LP_Deposit, Table};
+use crate::{
+    model::{LP_Deposit, Table},
+    telemetry::Timed as _,
+};
 
 use super::{DataBase, QueryResult};
 
@@ -49,6 +53,7 @@ impl Table<LP_Deposit> {
         .bind(&data.Tx_Hash)
         .persistent(true)
         .execute(&mut **transaction)
+        .timed("lp_deposit", "insert_if_not_exists")
         .await
     }
 
@@ -87,7 +92,10 @@ impl Table<LP_Deposit> {
         });
 
         let query = query_builder.build().persistent(true);
-        query.execute(&mut **transaction).await?;
+        query
+            .execute(&mut **transaction)
+            .timed("lp_deposit", "insert_many")
+            .await?;
 
         Ok(())
     }
@@ -108,6 +116,7 @@ impl Table<LP_Deposit> {
         .bind(to)
         .persistent(true)
         .fetch_one(&self.pool)
+        .timed("lp_deposit", "count")
         .await?;
         Ok(value)
     }
@@ -128,6 +137,7 @@ impl Table<LP_Deposit> {
         .bind(to)
         .persistent(true)
         .fetch_one(&self.pool)
+        .timed("lp_deposit", "get_amnt_stable")
         .await?;
         let (amnt,) = value;
         let amnt = amnt.unwrap_or(BigDecimal::from_str("0")?);
@@ -135,11 +145,8 @@ impl Table<LP_Deposit> {
         Ok(amnt)
     }
 
-    pub async fn get_historical_lenders_with_window(
-        &self,
-        months: Option<i32>,
-        from: Option<chrono::DateTime<chrono::Utc>>,
-    ) -> Result<Vec<HistoricalLender>, crate::error::Error> {
+    /// SQL of [`Self::get_historical_lenders_with_window`], `from` adds a `$1` lower bound
+    fn historical_lenders_query(months: Option<i32>, from: bool) -> String {
         // Build time conditions dynamically
         let mut conditions = Vec::new();
 
@@ -148,7 +155,7 @@ impl Table<LP_Deposit> {
                 .push(format!("timestamp > NOW() - INTERVAL '{} months'", m));
         }
 
-        if from.is_some() {
+        if from {
             conditions.push("timestamp > $1".to_string());
         }
 
@@ -158,7 +165,7 @@ impl Table<LP_Deposit> {
             format!("WHERE {}", conditions.join(" AND "))
         };
 
-        let query = format!(
+        format!(
             r#"
             SELECT * FROM (
                 SELECT 
@@ -187,7 +194,15 @@ impl Table<LP_Deposit> {
             ORDER BY timestamp DESC
             "#,
             time_condition
-        );
+        )
+    }
+
+    pub async fn get_historical_lenders_with_window(
+        &self,
+        months: Option<i32>,
+        from: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> Result<Vec<HistoricalLender>, crate::error::Error> {
+        let query = Self::historical_lenders_query(months, from.is_some());
 
         let mut query_builder = sqlx::query_as::<_, HistoricalLender>(&query);
 
@@ -195,11 +210,27 @@ impl Table<LP_Deposit> {
             query_builder = query_builder.bind(from_ts);
         }
 
-        let data = query_builder.persistent(true).fetch_all(&self.pool).await?;
+        let data = query_builder
+            .persistent(true)
+            .fetch_all(&self.pool)
+            .timed("lp_deposit", "get_historical_lenders_with_window")
+            .await?;
 
         Ok(data)
     }
 
+    /// Rows of [`Self::get_historical_lenders_with_window`] streamed from a database cursor
+    pub fn stream_historical_lenders(
+        &self,
+        months: Option<i32>,
+        from: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> BoxStream<'static, Result<HistoricalLender, Error>> {
+        self.stream_rows(
+            Self::historical_lenders_query(months, from.is_some()),
+            from,
+        )
+    }
+
     pub async fn get_all_historical_lenders(
         &self,
     ) -> Result<Vec<HistoricalLender>, crate::error::Error> {
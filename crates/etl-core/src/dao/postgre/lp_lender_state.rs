@@ -1,7 +1,10 @@
 use chrono::{DateTime, Utc};
 use sqlx::{types::BigDecimal, Error, FromRow, QueryBuilder};
 
-use crate::model::{LP_Lender_State, Table};
+use crate::{
+    model::{LP_Lender_State, Table},
+    telemetry::Timed as _,
+};
 
 use super::{DataBase, QueryResult};
 
@@ -39,6 +42,7 @@ impl Table<LP_Lender_State> {
         .bind(&data.LP_Lender_receipts)
         .persistent(true)
         .execute(&self.pool)
+        .timed("lp_lender_state", "insert")
         .await
     }
 
@@ -63,6 +67,7 @@ impl Table<LP_Lender_State> {
         )
         .persistent(true)
         .fetch_all(&self.pool)
+        .timed("lp_lender_state", "get_active_states")
         .await
     }
 
@@ -96,7 +101,10 @@ impl Table<LP_Lender_State> {
         });
 
         let query = query_builder.build().persistent(true);
-        query.execute(&self.pool).await?;
+        query
+            .execute(&self.pool)
+            .timed("lp_lender_state", "insert_many")
+            .await?;
         Ok(())
     }
 
@@ -114,6 +122,7 @@ impl Table<LP_Lender_State> {
         .bind(timestamp)
         .persistent(true)
         .fetch_one(&self.pool)
+        .timed("lp_lender_state", "count")
         .await?;
         Ok(value)
     }
@@ -122,6 +131,7 @@ impl Table<LP_Lender_State> {
         sqlx::query_as(r#"SELECT * FROM "LP_Lender_State""#)
             .persistent(true)
             .fetch_all(&self.pool)
+            .timed("lp_lender_state", "get_all")
             .await
     }
 
@@ -152,6 +162,7 @@ impl Table<LP_Lender_State> {
         .bind(data.LP_timestamp)
         .persistent(true)
         .execute(&self.pool)
+        .timed("lp_lender_state", "update")
         .await
     }
 
@@ -186,6 +197,7 @@ impl Table<LP_Lender_State> {
         )
         .persistent(true)
         .fetch_all(&self.pool)
+        .timed("lp_lender_state", "get_current_lenders")
         .await?;
 
         Ok(data)
@@ -1,6 +1,9 @@
 use sqlx::Error;
 
-use crate::model::{LP_Pool, Table};
+use crate::{
+    model::{LP_Pool, Table},
+    telemetry::Timed as _,
+};
 
 use super::QueryResult;
 
@@ -18,6 +21,7 @@ impl Table<LP_Pool> {
         .bind(data.LP_status)
         .persistent(true)
         .execute(&self.pool)
+        .timed("lp_pool", "insert")
         .await
     }
 
@@ -25,6 +29,7 @@ impl Table<LP_Pool> {
         sqlx::query_as(r#"SELECT * FROM "LP_Pool""#)
             .persistent(true)
             .fetch_all(&self.pool)
+            .timed("lp_pool", "get_all")
             .await
     }
 }
@@ -3,7 +3,10 @@ use std::str::FromStr as _;
 use chrono::{DateTime, Utc};
 use sqlx::{types::BigDecimal, Error, QueryBuilder};
 
-use crate::model::{LP_Pool_State, Supplied_Borrowed_Series, Table};
+use crate::{
+    model::{LP_Pool_State, Supplied_Borrowed_Series, Table},
+    telemetry::Timed as _,
+};
 
 use super::{DataBase, QueryResult};
 
@@ -40,6 +43,7 @@ impl Table<LP_Pool_State> {
         .bind(&data.LP_Pool_total_yield_asset)
         .persistent(true)
         .execute(&self.pool)
+        .timed("lp_pool_state", "insert")
         .await
     }
 
@@ -81,7 +85,10 @@ impl Table<LP_Pool_State> {
         });
 
         let query = query_builder.build().persistent(true);
-        query.execute(&self.pool).await?;
+        query
+            .execute(&self.pool)
+            .timed("lp_pool_state", "insert_many")
+            .await?;
         Ok(())
     }
 
@@ -105,6 +112,7 @@ impl Table<LP_Pool_State> {
         .bind(datetime)
         .persistent(true)
         .fetch_one(&self.pool)
+        .timed("lp_pool_state", "get_total_value_locked_stable")
         .await?;
         let (locked, borrowed, yield_amount) = value;
         let locked = locked.unwrap_or(BigDecimal::from_str("0")?);
@@ -137,6 +145,7 @@ impl Table<LP_Pool_State> {
         .bind(protocol)
         .persistent(true)
         .fetch_all(&self.pool)
+        .timed("lp_pool_state", "get_supplied_borrowed_series")
         .await?;
         Ok(data)
     }
@@ -176,7 +185,11 @@ impl Table<LP_Pool_State> {
             query = query.bind(i);
         }
 
-        let data = query.persistent(true).fetch_all(&self.pool).await?;
+        let data = query
+            .persistent(true)
+            .fetch_all(&self.pool)
+            .timed("lp_pool_state", "get_supplied_borrowed_series_total")
+            .await?;
         Ok(data)
     }
 
@@ -221,6 +234,7 @@ impl Table<LP_Pool_State> {
             .bind(protocol)
             .persistent(true)
             .fetch_all(&self.pool)
+            .timed("lp_pool_state", "get_supplied_borrowed_series_with_window")
             .await?;
         Ok(data)
     }
@@ -275,7 +289,14 @@ impl Table<LP_Pool_State> {
             query = query.bind(i);
         }
 
-        let data = query.persistent(true).fetch_all(&self.pool).await?;
+        let data = query
+            .persistent(true)
+            .fetch_all(&self.pool)
+            .timed(
+                "lp_pool_state",
+                "get_supplied_borrowed_series_total_with_window",
+            )
+            .await?;
         Ok(data)
     }
 
@@ -301,6 +322,7 @@ impl Table<LP_Pool_State> {
         )
         .persistent(true)
         .fetch_one(&self.pool)
+        .timed("lp_pool_state", "get_supplied_funds")
         .await?;
         let (amnt,) = value;
         let amnt = amnt.unwrap_or(BigDecimal::from_str("0")?);
@@ -395,6 +417,7 @@ impl Table<LP_Pool_State> {
         .persistent(true)
         .bind(address)
         .fetch_one(&self.pool)
+        .timed("lp_pool_state", "get_earnings")
         .await?;
         let (amnt,) = value;
         let amnt = amnt.unwrap_or(BigDecimal::from_str("0")?);
@@ -423,6 +446,7 @@ impl Table<LP_Pool_State> {
         .bind(date_time)
         .persistent(true)
         .fetch_one(&self.pool)
+        .timed("lp_pool_state", "get_by_date")
         .await
     }
 
@@ -526,6 +550,7 @@ impl Table<LP_Pool_State> {
         )
         .persistent(true)
         .fetch_all(&self.pool)
+        .timed("lp_pool_state", "get_all_utilization_levels")
         .await?;
 
         Ok(data)
@@ -3,7 +3,10 @@ use std::str::FromStr as _;
 use chrono::{DateTime, Utc};
 use sqlx::{types::BigDecimal, Error, QueryBuilder, Transaction};
 
-use crate::model::{LP_Withdraw, Table};
+use crate::{
+    model::{LP_Withdraw, Table},
+    telemetry::Timed as _,
+};
 
 use super::{DataBase, QueryResult};
 
@@ -42,6 +45,7 @@ impl Table<LP_Withdraw> {
         .bind(data.Tx_Hash)
         .persistent(true)
         .execute(&mut **transaction)
+        .timed("lp_withdraw", "insert_if_not_exists")
         .await
     }
 
@@ -82,7 +86,10 @@ impl Table<LP_Withdraw> {
         });
 
         let query = query_builder.build().persistent(true);
-        query.execute(&mut **transaction).await?;
+        query
+            .execute(&mut **transaction)
+            .timed("lp_withdraw", "insert_many")
+            .await?;
         Ok(())
     }
 
@@ -102,6 +109,7 @@ impl Table<LP_Withdraw> {
         .bind(to)
         .persistent(true)
         .fetch_one(&self.pool)
+        .timed("lp_withdraw", "count_closed")
         .await?;
         Ok(value)
     }
@@ -122,6 +130,7 @@ impl Table<LP_Withdraw> {
         .bind(to)
         .persistent(true)
         .fetch_one(&self.pool)
+        .timed("lp_withdraw", "get_amnt_stable")
         .await?;
         let (amnt,) = value;
         let amnt = amnt.unwrap_or(BigDecimal::from_str("0")?);
@@ -141,6 +150,7 @@ impl Table<LP_Withdraw> {
         .bind(tx)
         .persistent(true)
         .fetch_optional(&self.pool)
+        .timed("lp_withdraw", "get_by_tx")
         .await
     }
 }
@@ -1,6 +1,9 @@
 use sqlx::{Error, Transaction};
 
-use crate::model::{LS_Auto_Close_Position, Table};
+use crate::{
+    model::{LS_Auto_Close_Position, Table},
+    telemetry::Timed as _,
+};
 
 use super::{DataBase, QueryResult};
 
@@ -30,6 +33,7 @@ impl Table<LS_Auto_Close_Position> {
         .bind(data.LS_timestamp)
         .persistent(true)
         .execute(&mut **transaction)
+        .timed("ls_auto_close_position", "insert_if_not_exists")
         .await
     }
 }
@@ -1,6 +1,9 @@
 use sqlx::{Error, QueryBuilder, Transaction};
 
-use crate::model::{LS_Close_Position, Table};
+use crate::{
+    model::{LS_Close_Position, Table},
+    telemetry::Timed as _,
+};
 
 use super::{DataBase, QueryResult};
 
@@ -54,6 +57,7 @@ impl Table<LS_Close_Position> {
         .bind(&data.LS_payment_symbol)
         .persistent(true)
         .execute(&mut **transaction)
+        .timed("ls_close_position", "insert_if_not_exists")
         .await
     }
 
@@ -110,7 +114,10 @@ impl Table<LS_Close_Position> {
         });
 
         let query = query_builder.build().persistent(true);
-        query.execute(&mut **transaction).await?;
+        query
+            .execute(&mut **transaction)
+            .timed("ls_close_position", "insert_many")
+            .await?;
         Ok(())
     }
 
@@ -126,6 +133,7 @@ impl Table<LS_Close_Position> {
         .bind(&contract)
         .persistent(true)
         .fetch_all(&self.pool)
+        .timed("ls_close_position", "get_by_contract")
         .await?;
         Ok(data)
     }
@@ -142,6 +150,7 @@ impl Table<LS_Close_Position> {
         .bind(&contract)
         .persistent(true)
         .fetch_one(&self.pool)
+        .timed("ls_close_position", "get_closed_by_contract")
         .await
     }
 }
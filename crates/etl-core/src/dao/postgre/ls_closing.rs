@@ -1,7 +1,10 @@
 use chrono::{DateTime, Utc};
 use sqlx::{Error, QueryBuilder, Transaction};
 
-use crate::model::{LS_Closing, Table};
+use crate::{
+    model::{LS_Closing, Table},
+    telemetry::Timed as _,
+};
 
 use super::{DataBase, QueryResult};
 
@@ -24,6 +27,7 @@ impl Table<LS_Closing> {
         .bind(data.Tx_Hash)
         .persistent(true)
         .execute(&mut **transaction)
+        .timed("ls_closing", "insert_if_not_exists")
         .await
     }
 
@@ -50,7 +54,10 @@ impl Table<LS_Closing> {
         });
 
         let query = query_builder.build().persistent(true);
-        query.execute(&mut **transaction).await?;
+        query
+            .execute(&mut **transaction)
+            .timed("ls_closing", "insert_many")
+            .await?;
         Ok(())
     }
 
@@ -70,6 +77,7 @@ impl Table<LS_Closing> {
         .bind(to)
         .persistent(true)
         .fetch_one(&self.pool)
+        .timed("ls_closing", "count")
         .await?;
         Ok(value)
     }
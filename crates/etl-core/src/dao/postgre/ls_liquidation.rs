@@ -1,7 +1,11 @@
 use chrono::{DateTime, Utc};
+use futures::stream::BoxStream;
 use sqlx::{types::BigDecimal, Error, FromRow, QueryBuilder, Transaction};
 
-use crate::model::{LS_Liquidation, Table};
+use crate::{
+    model::{LS_Liquidation, Table},
+    telemetry::Timed as _,
+};
 
 use super::{DataBase, QueryResult};
 
@@ -79,6 +83,7 @@ impl Table<LS_Liquidation> {
         .bind(&data.LS_liquidation_price)
         .persistent(true)
         .execute(&mut **transaction)
+        .timed("ls_liquidation", "insert_if_not_exists")
         .await
     }
 
@@ -137,7 +142,10 @@ impl Table<LS_Liquidation> {
         });
 
         let query = query_builder.build().persistent(true);
-        query.execute(&mut **transaction).await?;
+        query
+            .execute(&mut **transaction)
+            .timed("ls_liquidation", "insert_many")
+            .await?;
         Ok(())
     }
 
@@ -153,15 +161,13 @@ impl Table<LS_Liquidation> {
         .bind(&contract)
         .persistent(true)
         .fetch_all(&self.pool)
+        .timed("ls_liquidation", "get_by_contract")
         .await?;
         Ok(data)
     }
 
-    pub async fn get_liquidations_with_window(
-        &self,
-        months: Option<i32>,
-        from: Option<chrono::DateTime<chrono::Utc>>,
-    ) -> Result<Vec<LiquidationData>, crate::error::Error> {
+    /// SQL of [`Self::get_liquidations_with_window`], `from` adds a `$1` lower bound
+    fn liquidations_query(months: Option<i32>, from: bool) -> String {
         // Build time conditions dynamically
         let mut conditions = Vec::new();
 
@@ -172,7 +178,7 @@ impl Table<LS_Liquidation> {
             ));
         }
 
-        if from.is_some() {
+        if from {
             conditions.push("liq.\"LS_timestamp\" > $1".to_string());
         }
 
@@ -183,7 +189,7 @@ impl Table<LS_Liquidation> {
         };
 
         // Simplified query using stored LS_liquidation_price instead of expensive CTE
-        let query = format!(
+        format!(
             r#"
             SELECT
                 liq."LS_timestamp" AS timestamp,
@@ -206,7 +212,15 @@ impl Table<LS_Liquidation> {
                 liq."LS_timestamp" DESC
             "#,
             time_condition
-        );
+        )
+    }
+
+    pub async fn get_liquidations_with_window(
+        &self,
+        months: Option<i32>,
+        from: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> Result<Vec<LiquidationData>, crate::error::Error> {
+        let query = Self::liquidations_query(months, from.is_some());
 
         let mut query_builder = sqlx::query_as::<_, LiquidationData>(&query);
 
@@ -214,11 +228,24 @@ impl Table<LS_Liquidation> {
             query_builder = query_builder.bind(from_ts);
         }
 
-        let data = query_builder.persistent(true).fetch_all(&self.pool).await?;
+        let data = query_builder
+            .persistent(true)
+            .fetch_all(&self.pool)
+            .timed("ls_liquidation", "get_liquidations_with_window")
+            .await?;
 
         Ok(data)
     }
 
+    /// Rows of [`Self::get_liquidations_with_window`] streamed from a database cursor
+    pub fn stream_liquidations(
+        &self,
+        months: Option<i32>,
+        from: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> BoxStream<'static, Result<LiquidationData, Error>> {
+        self.stream_rows(Self::liquidations_query(months, from.is_some()), from)
+    }
+
     pub async fn get_all_liquidations(
         &self,
     ) -> Result<Vec<LiquidationData>, crate::error::Error> {
@@ -260,17 +287,17 @@ impl Table<LS_Liquidation> {
         )
         .persistent(true)
         .fetch_all(&self.pool)
+        .timed("ls_liquidation", "get_historically_liquidated")
         .await?;
 
         Ok(data)
     }
 
-    /// Get historically liquidated positions with optional time window filter
-    pub async fn get_historically_liquidated_with_window(
-        &self,
+    /// SQL of [`Self::get_historically_liquidated_with_window`], `from` adds a `$1` lower bound
+    fn historically_liquidated_query(
         months: Option<i32>,
-        from: Option<chrono::DateTime<chrono::Utc>>,
-    ) -> Result<Vec<HistoricallyLiquidated>, crate::error::Error> {
+        from: bool,
+    ) -> String {
         // Build time conditions dynamically
         let mut conditions = Vec::new();
 
@@ -281,7 +308,7 @@ impl Table<LS_Liquidation> {
             ));
         }
 
-        if from.is_some() {
+        if from {
             conditions.push("lso.\"LS_timestamp\" > $1".to_string());
         }
 
@@ -291,7 +318,7 @@ impl Table<LS_Liquidation> {
             format!("WHERE {}", conditions.join(" AND "))
         };
 
-        let query = format!(
+        format!(
             r#"
             WITH LiquidationAmounts AS (
                 SELECT
@@ -322,7 +349,16 @@ impl Table<LS_Liquidation> {
                 "Loan"
             "#,
             time_condition
-        );
+        )
+    }
+
+    /// Get historically liquidated positions with optional time window filter
+    pub async fn get_historically_liquidated_with_window(
+        &self,
+        months: Option<i32>,
+        from: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> Result<Vec<HistoricallyLiquidated>, crate::error::Error> {
+        let query = Self::historically_liquidated_query(months, from.is_some());
 
         let mut query_builder =
             sqlx::query_as::<_, HistoricallyLiquidated>(&query);
@@ -331,8 +367,24 @@ impl Table<LS_Liquidation> {
             query_builder = query_builder.bind(from_ts);
         }
 
-        let data = query_builder.persistent(true).fetch_all(&self.pool).await?;
+        let data = query_builder
+            .persistent(true)
+            .fetch_all(&self.pool)
+            .timed("ls_liquidation", "get_historically_liquidated_with_window")
+            .await?;
 
         Ok(data)
     }
+
+    /// Rows of [`Self::get_historically_liquidated_with_window`] streamed from a database cursor
+    pub fn stream_historically_liquidated(
+        &self,
+        months: Option<i32>,
+        from: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> BoxStream<'static, Result<HistoricallyLiquidated, Error>> {
+        self.stream_rows(
+            Self::historically_liquidated_query(months, from.is_some()),
+            from,
+        )
+    }
 }
@@ -1,6 +1,9 @@
 use sqlx::{Error, Transaction};
 
-use crate::model::{LS_Liquidation_Warning, Table};
+use crate::{
+    model::{LS_Liquidation_Warning, Table},
+    telemetry::Timed as _,
+};
 
 use super::{DataBase, QueryResult};
 
@@ -34,6 +37,7 @@ impl Table<LS_Liquidation_Warning> {
         .bind(data.LS_timestamp)
         .persistent(true)
         .execute(&mut **transaction)
+        .timed("ls_liquidation_warning", "insert_if_not_exists")
         .await
     }
 }
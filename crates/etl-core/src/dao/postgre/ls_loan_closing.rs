@@ -3,7 +3,10 @@ use std::str::FromStr as _;
 use bigdecimal::BigDecimal;
 use sqlx::{Error, Transaction};
 
-use crate::model::{LS_Loan_Closing, Pnl_Result, Table};
+use crate::{
+    model::{LS_Loan_Closing, Pnl_Result, Table},
+    telemetry::Timed as _,
+};
 
 use super::{DataBase, QueryResult};
 
@@ -40,6 +43,7 @@ impl Table<LS_Loan_Closing> {
         .bind(data.Active)
         .persistent(true)
         .execute(&mut **transaction)
+        .timed("ls_loan_closing", "insert_if_not_exists")
         .await
     }
 
@@ -70,6 +74,7 @@ impl Table<LS_Loan_Closing> {
         .bind(contract_id)
         .persistent(true)
         .fetch_one(&self.pool)
+        .timed("ls_loan_closing", "get_lease_amount")
         .await?;
         let (amnt,) = value;
         let amnt = amnt.unwrap_or(BigDecimal::from_str("0")?);
@@ -97,6 +102,7 @@ impl Table<LS_Loan_Closing> {
         )
         .persistent(true)
         .fetch_one(&self.pool)
+        .timed("ls_loan_closing", "get_realized_pnl_stats")
         .await?;
         let (amnt,) = value;
         let amnt = amnt.unwrap_or(BigDecimal::from_str("0")?);
@@ -129,6 +135,7 @@ impl Table<LS_Loan_Closing> {
         .bind(&data.LS_contract_id)
         .persistent(true)
         .execute(&self.pool)
+        .timed("ls_loan_closing", "update")
         .await
     }
 
@@ -142,6 +149,7 @@ impl Table<LS_Loan_Closing> {
         )
         .persistent(true)
         .fetch_all(&self.pool)
+        .timed("ls_loan_closing", "get_leases_to_proceed")
         .await?;
         Ok(data)
     }
@@ -222,6 +230,7 @@ impl Table<LS_Loan_Closing> {
         .bind(limit)
         .persistent(true)
         .fetch_all(&self.pool)
+        .timed("ls_loan_closing", "get_leases")
         .await?;
         Ok(data)
     }
@@ -286,6 +295,7 @@ impl Table<LS_Loan_Closing> {
         .bind(address)
         .persistent(true)
         .fetch_one(&self.pool)
+        .timed("ls_loan_closing", "get_realized_pnl")
         .await?;
 
         let (amnt,) = value;
@@ -300,6 +310,7 @@ impl Table<LS_Loan_Closing> {
         )
         .persistent(true)
         .fetch_all(&self.pool)
+        .timed("ls_loan_closing", "get_all")
         .await
     }
 
@@ -313,6 +324,7 @@ impl Table<LS_Loan_Closing> {
         .bind(contract)
         .persistent(true)
         .fetch_one(&self.pool)
+        .timed("ls_loan_closing", "get")
         .await
     }
 }
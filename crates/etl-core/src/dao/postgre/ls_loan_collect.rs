@@ -1,5 +1,8 @@
 use super::{DataBase, QueryResult};
-use crate::model::{LS_Loan_Collect, Table};
+use crate::{
+    model::{LS_Loan_Collect, Table},
+    telemetry::Timed as _,
+};
 use bigdecimal::BigDecimal;
 use sqlx::{Error, QueryBuilder, Transaction};
 
@@ -31,7 +34,10 @@ impl Table<LS_Loan_Collect> {
         });
 
         let query = query_builder.build().persistent(true);
-        query.execute(&mut **transaction).await?;
+        query
+            .execute(&mut **transaction)
+            .timed("ls_loan_collect", "insert_many_transaction")
+            .await?;
         Ok(())
     }
 
@@ -61,7 +67,10 @@ impl Table<LS_Loan_Collect> {
         });
 
         let query = query_builder.build().persistent(true);
-        query.execute(&self.pool).await?;
+        query
+            .execute(&self.pool)
+            .timed("ls_loan_collect", "insert_many")
+            .await?;
         Ok(())
     }
 
@@ -69,6 +78,7 @@ impl Table<LS_Loan_Collect> {
         sqlx::query_as(r#"SELECT * FROM "LS_Loan_Collect""#)
             .persistent(true)
             .fetch_all(&self.pool)
+            .timed("ls_loan_collect", "get_all")
             .await
     }
 
@@ -94,6 +104,7 @@ impl Table<LS_Loan_Collect> {
         .bind(&data.LS_symbol)
         .persistent(true)
         .execute(&self.pool)
+        .timed("ls_loan_collect", "update_stable_amount")
         .await
     }
 }
@@ -1,6 +1,7 @@
 use std::{collections::HashMap, str::FromStr as _};
 
 use chrono::{DateTime, Utc};
+use futures::stream::BoxStream;
 use serde::Serialize;
 use sqlx::{types::BigDecimal, Error, FromRow, QueryBuilder, Transaction};
 
@@ -57,9 +58,12 @@ pub struct RealizedPnlWallet {
     pub realized_pnl_stable: Option<BigDecimal>,
 }
 
-use crate::model::{
-    Borrow_APR, LS_Amount, LS_History, LS_Opening, LS_Realized_Pnl_Data,
-    Leased_Asset, Leases_Monthly, Table,
+use crate::{
+    model::{
+        Borrow_APR, LS_Amount, LS_History, LS_Opening, LS_Realized_Pnl_Data,
+        Leased_Asset, Leases_Monthly, Table,
+    },
+    telemetry::Timed as _,
 };
 
 use super::{DataBase, QueryResult};
@@ -123,6 +127,7 @@ impl Table<LS_Opening> {
         .bind(&data.LS_liquidation_price_at_open)
         .persistent(true)
         .execute(&mut **transaction)
+        .timed("ls_opening", "insert_if_not_exists")
         .await
     }
 
@@ -187,7 +192,10 @@ impl Table<LS_Opening> {
         });
 
         let query = query_builder.build().persistent(true);
-        query.execute(&mut **transaction).await?;
+        query
+            .execute(&mut **transaction)
+            .timed("ls_opening", "insert_many")
+            .await?;
         Ok(())
     }
 
@@ -207,6 +215,7 @@ impl Table<LS_Opening> {
         .bind(to)
         .persistent(true)
         .fetch_one(&self.pool)
+        .timed("ls_opening", "count")
         .await?;
         Ok(value)
     }
@@ -227,6 +236,7 @@ impl Table<LS_Opening> {
         .bind(to)
         .persistent(true)
         .fetch_one(&self.pool)
+        .timed("ls_opening", "get_cltr_amnt_opened_stable_sum")
         .await?;
         let (amnt,) = value;
         let amnt = amnt.unwrap_or(BigDecimal::from_str("0")?);
@@ -250,6 +260,7 @@ impl Table<LS_Opening> {
         .bind(to)
         .persistent(true)
         .fetch_one(&self.pool)
+        .timed("ls_opening", "get_loan_amnt_stable_sum")
         .await?;
         let (amnt,) = value;
         let amnt = amnt.unwrap_or(BigDecimal::from_str("0")?);
@@ -278,6 +289,7 @@ impl Table<LS_Opening> {
         .bind(to)
         .persistent(true)
         .fetch_one(&self.pool)
+        .timed("ls_opening", "get_ls_cltr_amnt_stable_sum")
         .await?;
         let (amnt,) = value;
         let amnt = amnt.unwrap_or(BigDecimal::from_str("0")?);
@@ -306,6 +318,7 @@ impl Table<LS_Opening> {
         .bind(to)
         .persistent(true)
         .fetch_one(&self.pool)
+        .timed("ls_opening", "get_ls_amnt_stable_sum")
         .await?;
         let (amnt,) = value;
         let amnt = amnt.unwrap_or(BigDecimal::from_str("0")?);
@@ -329,6 +342,7 @@ impl Table<LS_Opening> {
         .bind(limit)
         .persistent(true)
         .fetch_all(&self.pool)
+        .timed("ls_opening", "get_borrow_apr")
         .await?;
         Ok(data)
     }
@@ -372,7 +386,11 @@ impl Table<LS_Opening> {
             query_builder = query_builder.bind(from_ts);
         }
 
-        let data = query_builder.persistent(true).fetch_all(&self.pool).await?;
+        let data = query_builder
+            .persistent(true)
+            .fetch_all(&self.pool)
+            .timed("ls_opening", "get_borrow_apr_with_window")
+            .await?;
         Ok(data)
     }
 
@@ -392,6 +410,7 @@ impl Table<LS_Opening> {
         .bind(protocol)
         .persistent(true)
         .fetch_all(&self.pool)
+        .timed("ls_opening", "get_leased_assets")
         .await?;
         Ok(data)
     }
@@ -448,6 +467,7 @@ impl Table<LS_Opening> {
         )
         .persistent(true)
         .fetch_all(&self.pool)
+        .timed("ls_opening", "get_leased_assets_total")
         .await?;
         Ok(data)
     }
@@ -510,6 +530,7 @@ impl Table<LS_Opening> {
         let value: Option<(BigDecimal,)> = sqlx::query_as(&sql)
             .persistent(true)
             .fetch_optional(&self.pool)
+            .timed("ls_opening", "get_earn_apr_interest")
             .await?;
 
         let amnt = value.unwrap_or((BigDecimal::from_str("0")?,));
@@ -570,6 +591,7 @@ impl Table<LS_Opening> {
         .bind(&protocol)
         .persistent(true)
         .fetch_optional(&self.pool)
+        .timed("ls_opening", "get_earn_apr")
         .await?;
         let amnt = value.unwrap_or((BigDecimal::from_str("0")?,));
 
@@ -588,9 +610,29 @@ impl Table<LS_Opening> {
         .bind(LS_contract_id)
         .persistent(true)
         .fetch_optional(&self.pool)
+        .timed("ls_opening", "get")
         .await
     }
 
+    /// Wallet that opened the lease
+    pub async fn get_owner(
+        &self,
+        LS_contract_id: String,
+    ) -> Result<Option<String>, Error> {
+        let value: Option<(String,)> = sqlx::query_as(
+            r#"
+            SELECT "LS_address_id" FROM "LS_Opening" WHERE "LS_contract_id" = $1
+            "#,
+        )
+        .bind(LS_contract_id)
+        .persistent(true)
+        .fetch_optional(&self.pool)
+        .timed("ls_opening", "get_owner")
+        .await?;
+
+        Ok(value.map(|(owner,)| owner))
+    }
+
     pub async fn get_borrowed(
         &self,
         protocol: String,
@@ -606,6 +648,7 @@ impl Table<LS_Opening> {
         .bind(protocol)
         .persistent(true)
         .fetch_optional(&self.pool)
+        .timed("ls_opening", "get_borrowed")
         .await?;
         let amnt = value.unwrap_or((BigDecimal::from_str("0")?,));
 
@@ -624,6 +667,7 @@ impl Table<LS_Opening> {
         )
         .persistent(true)
         .fetch_optional(&self.pool)
+        .timed("ls_opening", "get_borrowed_total")
         .await?;
         let amnt = value.unwrap_or((BigDecimal::from_str("0")?,));
 
@@ -647,6 +691,7 @@ impl Table<LS_Opening> {
         )
         .persistent(true)
         .fetch_all(&self.pool)
+        .timed("ls_opening", "get_borrowed_by_protocols")
         .await?;
 
         let mut result = HashMap::new();
@@ -679,7 +724,11 @@ impl Table<LS_Opening> {
             query = query.bind(i);
         }
 
-        let data = query.persistent(true).fetch_all(&self.pool).await?;
+        let data = query
+            .persistent(true)
+            .fetch_all(&self.pool)
+            .timed("ls_opening", "get_leases")
+            .await?;
         Ok(data)
     }
 
@@ -733,6 +782,7 @@ impl Table<LS_Opening> {
         )
         .persistent(true)
         .fetch_optional(&self.pool)
+        .timed("ls_opening", "get_total_tx_value")
         .await?;
 
         let default = BigDecimal::from_str("0")?;
@@ -773,6 +823,7 @@ impl Table<LS_Opening> {
         .bind(limit)
         .persistent(true)
         .fetch_all(&self.pool)
+        .timed("ls_opening", "get_leases_addresses")
         .await?;
         Ok(data)
     }
@@ -795,6 +846,7 @@ impl Table<LS_Opening> {
         .bind(&ls_opening.LS_contract_id)
         .persistent(true)
         .execute(&self.pool)
+        .timed("ls_opening", "update_ls_loan_amnt")
         .await?;
         Ok(())
     }
@@ -817,6 +869,7 @@ impl Table<LS_Opening> {
         .bind(&ls_opening.LS_contract_id)
         .persistent(true)
         .execute(&self.pool)
+        .timed("ls_opening", "update_ls_lpn_loan_amnt")
         .await?;
         Ok(())
     }
@@ -873,6 +926,7 @@ impl Table<LS_Opening> {
         .bind(contract_id)
         .persistent(true)
         .fetch_all(&self.pool)
+        .timed("ls_opening", "get_lease_history")
         .await?;
 
         Ok(data)
@@ -914,6 +968,7 @@ impl Table<LS_Opening> {
         )
         .persistent(true)
         .fetch_all(&self.pool)
+        .timed("ls_opening", "get_leases_monthly")
         .await?;
         Ok(data)
     }
@@ -939,6 +994,7 @@ impl Table<LS_Opening> {
         .bind(address)
         .persistent(true)
         .fetch_all(&self.pool)
+        .timed("ls_opening", "get_position_value")
         .await?;
         Ok(data)
     }
@@ -973,6 +1029,7 @@ impl Table<LS_Opening> {
         .bind(address)
         .persistent(true)
         .fetch_all(&self.pool)
+        .timed("ls_opening", "get_debt_value")
         .await?;
         Ok(data)
     }
@@ -1154,6 +1211,7 @@ impl Table<LS_Opening> {
         .bind(address)
         .persistent(true)
         .fetch_all(&self.pool)
+        .timed("ls_opening", "get_realized_pnl_data")
         .await?;
         Ok(data)
     }
@@ -1170,6 +1228,7 @@ impl Table<LS_Opening> {
         .bind(address)
         .persistent(true)
         .fetch_all(&self.pool)
+        .timed("ls_opening", "get_addresses")
         .await?;
         Ok(data)
     }
@@ -1257,9 +1316,13 @@ impl Table<LS_Opening> {
             sqlx::query_as(&query)
                 .bind(from_ts)
                 .fetch_all(&self.pool)
+                .timed("ls_opening", "get_monthly_active_wallets_with_window")
                 .await?
         } else {
-            sqlx::query_as(&query).fetch_all(&self.pool).await?
+            sqlx::query_as(&query)
+                .fetch_all(&self.pool)
+                .timed("ls_opening", "get_monthly_active_wallets_with_window")
+                .await?
         };
 
         Ok(data)
@@ -1393,7 +1456,11 @@ impl Table<LS_Opening> {
             query_builder = query_builder.bind(from_ts);
         }
 
-        let data = query_builder.persistent(true).fetch_all(&self.pool).await?;
+        let data = query_builder
+            .persistent(true)
+            .fetch_all(&self.pool)
+            .timed("ls_opening", "get_daily_opened_closed_with_window")
+            .await?;
         Ok(data)
     }
 
@@ -1416,6 +1483,7 @@ impl Table<LS_Opening> {
         )
         .persistent(true)
         .fetch_all(&self.pool)
+        .timed("ls_opening", "get_loans_granted")
         .await?;
 
         Ok(data)
@@ -1491,22 +1559,14 @@ impl Table<LS_Opening> {
         .bind(limit)
         .persistent(true)
         .fetch_all(&self.pool)
+        .timed("ls_opening", "get_historically_opened")
         .await?;
 
         Ok(data)
     }
 
-    /// Get historically opened positions with optional time window filter
-    /// If months is Some(n), only returns positions opened in the last n months
-    /// If months is None, returns all positions
-    ///
-    /// OPTIMIZED: Uses pre-computed columns (LS_position_type, LS_opening_price, LS_liquidation_price_at_open)
-    /// when available, with fallback to computed values for rows not yet backfilled.
-    pub async fn get_historically_opened_with_window(
-        &self,
-        months: Option<i32>,
-        from: Option<DateTime<Utc>>,
-    ) -> Result<Vec<HistoricallyOpened>, crate::error::Error> {
+    /// SQL of [`Self::get_historically_opened_with_window`], `from` adds a `$1` lower bound
+    fn historically_opened_query(months: Option<i32>, from: bool) -> String {
         // Build time conditions dynamically
         let mut conditions = Vec::new();
 
@@ -1517,7 +1577,7 @@ impl Table<LS_Opening> {
             ));
         }
 
-        if from.is_some() {
+        if from {
             conditions.push("o.\"LS_timestamp\" > $1".to_string());
         }
 
@@ -1528,7 +1588,7 @@ impl Table<LS_Opening> {
         };
 
         // Optimized query using pre-computed columns with fallbacks
-        let query = format!(
+        format!(
             r#"
             SELECT
                 o."LS_contract_id" AS contract_id,
@@ -1594,7 +1654,21 @@ impl Table<LS_Opening> {
             ORDER BY o."LS_timestamp" DESC
             "#,
             time_condition
-        );
+        )
+    }
+
+    /// Get historically opened positions with optional time window filter
+    /// If months is Some(n), only returns positions opened in the last n months
+    /// If months is None, returns all positions
+    ///
+    /// OPTIMIZED: Uses pre-computed columns (LS_position_type, LS_opening_price, LS_liquidation_price_at_open)
+    /// when available, with fallback to computed values for rows not yet backfilled.
+    pub async fn get_historically_opened_with_window(
+        &self,
+        months: Option<i32>,
+        from: Option<DateTime<Utc>>,
+    ) -> Result<Vec<HistoricallyOpened>, crate::error::Error> {
+        let query = Self::historically_opened_query(months, from.is_some());
 
         let mut query_builder = sqlx::query_as::<_, HistoricallyOpened>(&query);
 
@@ -1602,11 +1676,27 @@ impl Table<LS_Opening> {
             query_builder = query_builder.bind(from_ts);
         }
 
-        let data = query_builder.persistent(true).fetch_all(&self.pool).await?;
+        let data = query_builder
+            .persistent(true)
+            .fetch_all(&self.pool)
+            .timed("ls_opening", "get_historically_opened_with_window")
+            .await?;
 
         Ok(data)
     }
 
+    /// Rows of [`Self::get_historically_opened_with_window`] streamed from a database cursor
+    pub fn stream_historically_opened(
+        &self,
+        months: Option<i32>,
+        from: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> BoxStream<'static, Result<HistoricallyOpened, Error>> {
+        self.stream_rows(
+            Self::historically_opened_query(months, from.is_some()),
+            from,
+        )
+    }
+
     /// Get all historically opened positions without pagination - for streaming CSV export
     /// OPTIMIZED: Uses pre-computed LS_opening_price and LS_liquidation_price_at_open
     /// instead of expensive LATERAL JOINs to MP_Asset
@@ -1672,6 +1762,7 @@ impl Table<LS_Opening> {
         )
         .persistent(true)
         .fetch_all(&self.pool)
+        .timed("ls_opening", "get_all_historically_opened")
         .await?;
 
         Ok(data)
@@ -1785,18 +1876,14 @@ impl Table<LS_Opening> {
         .bind(limit)
         .persistent(true)
         .fetch_all(&self.pool)
+        .timed("ls_opening", "get_realized_pnl_by_wallet")
         .await?;
 
         Ok(data)
     }
 
-    /// Get realized PnL by wallet with time window filtering - OPTIMIZED
-    /// Uses pre-computed _stable values instead of LATERAL JOINs to MP_Asset
-    pub async fn get_realized_pnl_by_wallet_with_window(
-        &self,
-        months: Option<i32>,
-        from: Option<DateTime<Utc>>,
-    ) -> Result<Vec<RealizedPnlWallet>, crate::error::Error> {
+    /// SQL of [`Self::get_realized_pnl_by_wallet_with_window`], `from` adds a `$1` lower bound
+    fn realized_pnl_by_wallet_query(months: Option<i32>, from: bool) -> String {
         // Build time conditions dynamically
         let mut conditions = Vec::new();
 
@@ -1807,7 +1894,7 @@ impl Table<LS_Opening> {
             ));
         }
 
-        if from.is_some() {
+        if from {
             conditions.push("o.\"LS_timestamp\" > $1".to_string());
         }
 
@@ -1817,7 +1904,7 @@ impl Table<LS_Opening> {
             format!("WHERE {}", conditions.join(" AND "))
         };
 
-        let query = format!(
+        format!(
             r#"
             WITH openings AS (
                 SELECT
@@ -1912,7 +1999,17 @@ impl Table<LS_Opening> {
             ORDER BY lc."Close Timestamp" DESC
             "#,
             time_condition
-        );
+        )
+    }
+
+    /// Get realized PnL by wallet with time window filtering - OPTIMIZED
+    /// Uses pre-computed _stable values instead of LATERAL JOINs to MP_Asset
+    pub async fn get_realized_pnl_by_wallet_with_window(
+        &self,
+        months: Option<i32>,
+        from: Option<DateTime<Utc>>,
+    ) -> Result<Vec<RealizedPnlWallet>, crate::error::Error> {
+        let query = Self::realized_pnl_by_wallet_query(months, from.is_some());
 
         let mut query_builder = sqlx::query_as::<_, RealizedPnlWallet>(&query);
 
@@ -1920,8 +2017,24 @@ impl Table<LS_Opening> {
             query_builder = query_builder.bind(from_ts);
         }
 
-        let data = query_builder.persistent(true).fetch_all(&self.pool).await?;
+        let data = query_builder
+            .persistent(true)
+            .fetch_all(&self.pool)
+            .timed("ls_opening", "get_realized_pnl_by_wallet_with_window")
+            .await?;
 
         Ok(data)
     }
+
+    /// Rows of [`Self::get_realized_pnl_by_wallet_with_window`] streamed from a database cursor
+    pub fn stream_realized_pnl_by_wallet(
+        &self,
+        months: Option<i32>,
+        from: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> BoxStream<'static, Result<RealizedPnlWallet, Error>> {
+        self.stream_rows(
+            Self::realized_pnl_by_wallet_query(months, from.is_some()),
+            from,
+        )
+    }
 }
@@ -1,9 +1,13 @@
 use std::str::FromStr as _;
 
 use chrono::{DateTime, Utc};
+use futures::stream::BoxStream;
 use sqlx::{types::BigDecimal, Error, FromRow, QueryBuilder, Transaction};
 
-use crate::model::{LS_Repayment, Table};
+use crate::{
+    model::{LS_Repayment, Table},
+    telemetry::Timed as _,
+};
 
 use super::{DataBase, QueryResult};
 
@@ -72,6 +76,7 @@ impl Table<LS_Repayment> {
         .bind(&data.Tx_Hash)
         .persistent(true)
         .execute(&mut **transaction)
+        .timed("ls_repayment", "insert_if_not_exists")
         .await
     }
 
@@ -120,7 +125,10 @@ impl Table<LS_Repayment> {
         });
 
         let query = query_builder.build().persistent(true);
-        query.execute(&mut **transaction).await?;
+        query
+            .execute(&mut **transaction)
+            .timed("ls_repayment", "insert_many")
+            .await?;
         Ok(())
     }
 
@@ -153,6 +161,7 @@ impl Table<LS_Repayment> {
         .bind(to)
         .persistent(true)
         .fetch_one(&self.pool)
+        .timed("ls_repayment", "get_sum")
         .await?;
         let (
             prev_margin_stable,
@@ -212,6 +221,7 @@ impl Table<LS_Repayment> {
         .bind(contract)
         .persistent(true)
         .fetch_all(&self.pool)
+        .timed("ls_repayment", "get_by_contract")
         .await?;
         Ok(data)
     }
@@ -287,17 +297,14 @@ impl Table<LS_Repayment> {
         )
         .persistent(true)
         .fetch_all(&self.pool)
+        .timed("ls_repayment", "get_historically_repaid")
         .await?;
 
         Ok(data)
     }
 
-    /// Get historically repaid positions with optional time window filter
-    pub async fn get_historically_repaid_with_window(
-        &self,
-        months: Option<i32>,
-        from: Option<DateTime<Utc>>,
-    ) -> Result<Vec<HistoricallyRepaid>, crate::error::Error> {
+    /// SQL of [`Self::get_historically_repaid_with_window`], `from` adds a `$1` lower bound
+    fn historically_repaid_query(months: Option<i32>, from: bool) -> String {
         // Build time conditions dynamically
         let mut conditions = Vec::new();
 
@@ -308,7 +315,7 @@ impl Table<LS_Repayment> {
             ));
         }
 
-        if from.is_some() {
+        if from {
             conditions.push("lso.\"LS_timestamp\" > $1".to_string());
         }
 
@@ -318,7 +325,7 @@ impl Table<LS_Repayment> {
             format!("WHERE {}", conditions.join(" AND "))
         };
 
-        let query = format!(
+        format!(
             r#"
             WITH Closed_Loans AS (
                 SELECT
@@ -385,7 +392,16 @@ impl Table<LS_Repayment> {
                 RepaidLeases rl
             "#,
             time_condition
-        );
+        )
+    }
+
+    /// Get historically repaid positions with optional time window filter
+    pub async fn get_historically_repaid_with_window(
+        &self,
+        months: Option<i32>,
+        from: Option<DateTime<Utc>>,
+    ) -> Result<Vec<HistoricallyRepaid>, crate::error::Error> {
+        let query = Self::historically_repaid_query(months, from.is_some());
 
         let mut query_builder = sqlx::query_as::<_, HistoricallyRepaid>(&query);
 
@@ -393,11 +409,27 @@ impl Table<LS_Repayment> {
             query_builder = query_builder.bind(from_ts);
         }
 
-        let data = query_builder.persistent(true).fetch_all(&self.pool).await?;
+        let data = query_builder
+            .persistent(true)
+            .fetch_all(&self.pool)
+            .timed("ls_repayment", "get_historically_repaid_with_window")
+            .await?;
 
         Ok(data)
     }
 
+    /// Rows of [`Self::get_historically_repaid_with_window`] streamed from a database cursor
+    pub fn stream_historically_repaid(
+        &self,
+        months: Option<i32>,
+        from: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> BoxStream<'static, Result<HistoricallyRepaid, Error>> {
+        self.stream_rows(
+            Self::historically_repaid_query(months, from.is_some()),
+            from,
+        )
+    }
+
     pub async fn get_interest_repayments(
         &self,
         skip: i64,
@@ -481,26 +513,21 @@ impl Table<LS_Repayment> {
         .bind(from_timestamp)
         .persistent(true)
         .fetch_all(&self.pool)
+        .timed("ls_repayment", "get_interest_repayments")
         .await?;
 
         Ok(data)
     }
 
-    /// Get interest repayments with time window filtering
-    /// - months: number of months to look back (None = all time)
-    /// - from: only return records after this timestamp (exclusive)
-    pub async fn get_interest_repayments_with_window(
-        &self,
-        months: Option<i32>,
-        from: Option<DateTime<Utc>>,
-    ) -> Result<Vec<InterestRepaymentData>, crate::error::Error> {
+    /// SQL of [`Self::get_interest_repayments_with_window`], `from` adds a `$1` lower bound
+    fn interest_repayments_query(months: Option<i32>, from: bool) -> String {
         // Build time conditions
         let mut conditions = Vec::new();
         if let Some(m) = months {
             conditions
                 .push(format!("timestamp >= NOW() - INTERVAL '{} months'", m));
         }
-        if from.is_some() {
+        if from {
             conditions.push("timestamp > $1".to_string());
         }
 
@@ -510,7 +537,7 @@ impl Table<LS_Repayment> {
             format!("WHERE {}", conditions.join(" AND "))
         };
 
-        let query = format!(
+        format!(
             r#"
             WITH ContractInfo AS (
                 SELECT
@@ -566,21 +593,46 @@ impl Table<LS_Repayment> {
             ORDER BY e.timestamp DESC
             "#,
             where_clause
-        );
+        )
+    }
+
+    /// Get interest repayments with time window filtering
+    /// - months: number of months to look back (None = all time)
+    /// - from: only return records after this timestamp (exclusive)
+    pub async fn get_interest_repayments_with_window(
+        &self,
+        months: Option<i32>,
+        from: Option<DateTime<Utc>>,
+    ) -> Result<Vec<InterestRepaymentData>, crate::error::Error> {
+        let query = Self::interest_repayments_query(months, from.is_some());
 
         let data = if let Some(from_ts) = from {
             sqlx::query_as(&query)
                 .bind(from_ts)
                 .persistent(true)
                 .fetch_all(&self.pool)
+                .timed("ls_repayment", "get_interest_repayments_with_window")
                 .await?
         } else {
             sqlx::query_as(&query)
                 .persistent(true)
                 .fetch_all(&self.pool)
+                .timed("ls_repayment", "get_interest_repayments_with_window")
                 .await?
         };
 
         Ok(data)
     }
+
+    /// Rows of [`Self::get_interest_repayments_with_window`] streamed from a database cursor
+    pub fn stream_interest_repayments(
+        &self,
+        months: Option<i32>,
+        from: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> BoxStream<'static, Result<InterestRepaymentData, Error>> {
+        self.stream_rows(
+            Self::interest_repayments_query(months, from.is_some()),
+            from,
+        )
+    }
 }
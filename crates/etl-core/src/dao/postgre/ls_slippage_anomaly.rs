@@ -1,6 +1,9 @@
 use sqlx::{Error, Transaction};
 
-use crate::model::{LS_Slippage_Anomaly, Table};
+use crate::{
+    model::{LS_Slippage_Anomaly, Table},
+    telemetry::Timed as _,
+};
 
 use super::{DataBase, QueryResult};
 
@@ -32,6 +35,7 @@ impl Table<LS_Slippage_Anomaly> {
         .bind(data.LS_timestamp)
         .persistent(true)
         .execute(&mut **transaction)
+        .timed("ls_slippage_anomaly", "insert_if_not_exists")
         .await
     }
 }
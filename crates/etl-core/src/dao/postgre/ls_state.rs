@@ -1,6 +1,10 @@
 use super::{DataBase, QueryResult};
-use crate::model::{LS_Opening, LS_State, Pnl_Over_Time, Table};
+use crate::{
+    model::{LS_Opening, LS_State, Pnl_Over_Time, Table},
+    telemetry::Timed as _,
+};
 use chrono::{DateTime, Utc};
+use futures::stream::BoxStream;
 use sqlx::{types::BigDecimal, Error, FromRow, QueryBuilder};
 use std::str::FromStr as _;
 
@@ -66,6 +70,7 @@ impl Table<LS_State> {
         .bind(&data.LS_lpn_loan_amnt)
         .persistent(true)
         .execute(&self.pool)
+        .timed("ls_state", "insert")
         .await
     }
 
@@ -122,6 +127,7 @@ impl Table<LS_State> {
         )
         .persistent(true)
         .fetch_all(&self.pool)
+        .timed("ls_state", "get_active_states")
         .await
     }
 
@@ -170,10 +176,34 @@ impl Table<LS_State> {
         });
 
         let query = query_builder.build().persistent(true);
-        query.execute(&self.pool).await?;
+        query
+            .execute(&self.pool)
+            .timed("ls_state", "insert_many")
+            .await?;
         Ok(())
     }
 
+    /// Position value in stable of the latest state of a lease
+    pub async fn get_latest_amount_stable(
+        &self,
+        contract_id: String,
+    ) -> Result<Option<BigDecimal>, Error> {
+        let value: Option<(BigDecimal,)> = sqlx::query_as(
+            r#"
+            SELECT "LS_amnt_stable" FROM "LS_State"
+            WHERE "LS_contract_id" = $1
+            ORDER BY "LS_timestamp" DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(contract_id)
+        .fetch_optional(&self.pool)
+        .timed("ls_state", "get_latest_amount_stable")
+        .await?;
+
+        Ok(value.map(|(amount,)| amount))
+    }
+
     pub async fn count(
         &self,
         timestamp: DateTime<Utc>,
@@ -188,6 +218,7 @@ impl Table<LS_State> {
         .bind(timestamp)
         .persistent(true)
         .fetch_one(&self.pool)
+        .timed("ls_state", "count")
         .await?;
         Ok(value)
     }
@@ -235,6 +266,7 @@ impl Table<LS_State> {
         )
         .persistent(true)
         .fetch_all(&self.pool)
+        .timed("ls_state", "get_loans_by_token")
         .await?;
 
         Ok(data)
@@ -283,6 +315,7 @@ impl Table<LS_State> {
         )
         .persistent(true)
         .fetch_all(&self.pool)
+        .timed("ls_state", "get_position_buckets")
         .await?;
 
         Ok(data)
@@ -340,6 +373,7 @@ impl Table<LS_State> {
         )
         .persistent(true)
         .fetch_all(&self.pool)
+        .timed("ls_state", "get_open_positions_by_token")
         .await?;
 
         Ok(data)
@@ -389,6 +423,7 @@ impl Table<LS_State> {
         )
         .persistent(true)
         .fetch_optional(&self.pool)
+        .timed("ls_state", "get_open_position_value")
         .await?;
 
         let default = BigDecimal::from_str("0")?;
@@ -444,6 +479,7 @@ impl Table<LS_State> {
       )
       .persistent(true)
       .fetch_optional(&self.pool)
+      .timed("ls_state", "get_open_interest")
       .await?;
 
         let default = BigDecimal::from_str("0")?;
@@ -522,6 +558,7 @@ impl Table<LS_State> {
     )
     .persistent(true)
     .fetch_optional(&self.pool)
+    .timed("ls_state", "get_unrealized_pnl")
     .await?;
 
         let default = BigDecimal::from_str("0")?;
@@ -615,6 +652,7 @@ impl Table<LS_State> {
       "#, contract_id.to_owned(), contract_id.to_owned(), contract_id.to_owned(), contract_id.to_owned()))
       .persistent(true)
     .fetch_all(&self.pool)
+    .timed("ls_state", "get_pnl_over_time")
   .await?;
 
         Ok(value)
@@ -696,6 +734,7 @@ impl Table<LS_State> {
         .bind(address)
         .persistent(true)
         .fetch_optional(&self.pool)
+        .timed("ls_state", "get_current_unrealized_pnl_by_address")
         .await?;
 
         Ok(result
@@ -746,6 +785,7 @@ impl Table<LS_State> {
         .bind(&pool_ids)
         .persistent(true)
         .fetch_optional(&self.pool)
+        .timed("ls_state", "get_total_value_locked")
         .await?;
 
         let default = BigDecimal::from_str("0")?;
@@ -791,6 +831,7 @@ impl Table<LS_State> {
         )
         .persistent(true)
         .fetch_all(&self.pool)
+        .timed("ls_state", "get_lease_value_stats")
         .await?;
 
         Ok(data)
@@ -935,19 +976,14 @@ impl Table<LS_State> {
         .bind(limit)
         .persistent(true)
         .fetch_all(&self.pool)
+        .timed("ls_state", "get_positions")
         .await?;
 
         Ok(data)
     }
 
-    /// Get all positions without pagination - uses single cache key pattern
-    /// Uses pool_config table instead of hardcoded CTE and extended timeout
-    /// for background cache refresh
-    pub async fn get_all_positions(
-        &self,
-    ) -> Result<Vec<crate::model::Position>, Error> {
-        let data = sqlx::query_as(
-            r#"
+    /// SQL of [`Self::get_all_positions`] and [`Self::stream_all_positions`]
+    const ALL_POSITIONS_QUERY: &'static str = r#"
             WITH Latest_States AS (
               SELECT DISTINCT ON ("LS_contract_id") *
               FROM "LS_State"
@@ -1073,12 +1109,27 @@ impl Table<LS_State> {
             FROM Joined_States js
             LEFT JOIN Latest_Prices lp ON js."Symbol" = lp."MP_asset_symbol"
             LEFT JOIN Repayments rp ON js."Contract ID" = rp."LS_contract_id"
-            "#,
-        )
-        .persistent(true)
-        .fetch_all(&self.pool)
-        .await?;
+            "#;
+
+    /// Get all positions without pagination - uses single cache key pattern
+    /// Uses pool_config table instead of hardcoded CTE and extended timeout
+    /// for background cache refresh
+    pub async fn get_all_positions(
+        &self,
+    ) -> Result<Vec<crate::model::Position>, Error> {
+        let data = sqlx::query_as(Self::ALL_POSITIONS_QUERY)
+            .persistent(true)
+            .fetch_all(&self.pool)
+            .timed("ls_state", "get_all_positions")
+            .await?;
 
         Ok(data)
     }
+
+    /// Rows of [`Self::get_all_positions`] streamed from a database cursor
+    pub fn stream_all_positions(
+        &self,
+    ) -> BoxStream<'static, Result<crate::model::Position, Error>> {
+        self.stream_rows(Self::ALL_POSITIONS_QUERY.to_owned(), None)
+    }
 }
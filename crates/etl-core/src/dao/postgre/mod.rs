@@ -3,9 +3,16 @@ pub use self::types::{
 };
 
 mod action_history;
+pub mod address_activity;
+mod admin_command;
+pub mod alert_rule;
+pub mod api_key;
 mod block;
 mod currency_protocol;
 mod currency_registry;
+pub mod fee_daily;
+pub mod governance_vote;
+mod ibc_transfer;
 pub mod lp_deposit;
 pub mod lp_lender_state;
 mod lp_pool;
@@ -26,10 +33,21 @@ mod mp_asset;
 mod mp_yield;
 mod pl_state;
 mod pool_config;
+mod protocol_contract_history;
 mod protocol_registry;
-mod raw_message;
+pub mod push_delivery;
+mod push_digest;
+mod push_notification;
+mod push_outbox;
+mod push_throttle;
+pub mod raw_message;
+mod registry_audit;
 mod reserve_cover_loss;
-mod subscription;
+pub mod staking_event;
+pub mod subscription;
+mod subscription_channel;
+mod subscription_lease;
+pub mod subscription_preference;
 mod tr_profit;
 mod tr_rewards_distribution;
 mod tr_state;
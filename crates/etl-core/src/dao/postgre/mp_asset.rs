@@ -1,7 +1,10 @@
 use chrono::{DateTime, Utc};
 use sqlx::{types::BigDecimal, Error, QueryBuilder};
 
-use crate::model::{MP_Asset, Table};
+use crate::{
+    model::{MP_Asset, Table},
+    telemetry::Timed as _,
+};
 
 use super::DataBase;
 
@@ -32,7 +35,10 @@ impl Table<MP_Asset> {
             .push(r#" ON CONFLICT ("MP_asset_symbol", "MP_asset_timestamp", "Protocol") DO NOTHING"#);
 
         let query = query_builder.build().persistent(true);
-        query.execute(&self.pool).await?;
+        query
+            .execute(&self.pool)
+            .timed("mp_asset", "insert_many")
+            .await?;
 
         Ok(())
     }
@@ -55,6 +61,7 @@ impl Table<MP_Asset> {
         .bind(to)
         .persistent(true)
         .fetch_optional(&self.pool)
+        .timed("mp_asset", "get_min_max_from_range")
         .await
     }
 
@@ -83,6 +90,7 @@ impl Table<MP_Asset> {
         .bind(date_time)
         .persistent(true)
         .fetch_all(&self.pool)
+        .timed("mp_asset", "get_prices")
         .await
     }
 
@@ -104,6 +112,7 @@ impl Table<MP_Asset> {
                 .bind(protocol)
                 .persistent(true)
                 .fetch_one(&self.pool)
+                .timed("mp_asset", "get_price")
                 .await
             },
             None => {
@@ -117,6 +126,7 @@ impl Table<MP_Asset> {
                 .bind(key)
                 .persistent(true)
                 .fetch_one(&self.pool)
+                .timed("mp_asset", "get_price")
                 .await
             },
         }
@@ -149,6 +159,7 @@ impl Table<MP_Asset> {
                 .bind(date_time)
                 .persistent(true)
                 .fetch_one(&self.pool)
+                .timed("mp_asset", "get_price_by_date")
                 .await
             },
             None => {
@@ -168,6 +179,7 @@ impl Table<MP_Asset> {
                 .bind(date_time)
                 .persistent(true)
                 .fetch_one(&self.pool)
+                .timed("mp_asset", "get_price_by_date")
                 .await
             },
         };
@@ -1,6 +1,9 @@
 use sqlx::Error;
 
-use crate::model::{MP_Yield, Table};
+use crate::{
+    model::{MP_Yield, Table},
+    telemetry::Timed as _,
+};
 
 use super::QueryResult;
 
@@ -21,6 +24,7 @@ impl Table<MP_Yield> {
         .bind(data.MP_apy_permilles)
         .persistent(true)
         .execute(&self.pool)
+        .timed("mp_yield", "insert")
         .await
     }
 }
@@ -1,6 +1,9 @@
 use sqlx::Error;
 
-use crate::model::{PL_State, Table};
+use crate::{
+    model::{PL_State, Table},
+    telemetry::Timed as _,
+};
 
 use super::QueryResult;
 
@@ -75,6 +78,7 @@ impl Table<PL_State> {
         .bind(&data.PL_OUT_TR_rewards_amnt_nls)
         .persistent(true)
         .execute(&self.pool)
+        .timed("pl_state", "insert")
         .await
     }
 }
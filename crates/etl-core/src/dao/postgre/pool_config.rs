@@ -1,6 +1,9 @@
 use sqlx::Error;
 
-use crate::model::{PoolConfigUpsert, Pool_Config, Table};
+use crate::{
+    model::{PoolConfigUpsert, Pool_Config, Table},
+    telemetry::Timed as _,
+};
 
 impl Table<Pool_Config> {
     /// Upsert an active pool configuration from blockchain data
@@ -37,6 +40,7 @@ impl Table<Pool_Config> {
         .bind(data.stable_currency_symbol)
         .bind(data.stable_currency_decimals)
         .execute(&self.pool)
+        .timed("pool_config", "upsert")
         .await?;
         Ok(())
     }
@@ -55,6 +59,7 @@ impl Table<Pool_Config> {
         )
         .bind(active_pool_ids)
         .execute(&self.pool)
+        .timed("pool_config", "mark_deprecated_except")
         .await?;
 
         Ok(result.rows_affected())
@@ -77,6 +82,7 @@ impl Table<Pool_Config> {
         .bind(pool_id)
         .persistent(true)
         .fetch_optional(&self.pool)
+        .timed("pool_config", "get_by_pool_id")
         .await
     }
 
@@ -93,6 +99,7 @@ impl Table<Pool_Config> {
         )
         .persistent(true)
         .fetch_all(&self.pool)
+        .timed("pool_config", "get_all")
         .await
     }
 
@@ -110,6 +117,7 @@ impl Table<Pool_Config> {
         )
         .persistent(true)
         .fetch_all(&self.pool)
+        .timed("pool_config", "get_long_pools")
         .await
     }
 
@@ -127,6 +135,7 @@ impl Table<Pool_Config> {
         )
         .persistent(true)
         .fetch_all(&self.pool)
+        .timed("pool_config", "get_short_pools")
         .await
     }
 
@@ -136,12 +145,14 @@ impl Table<Pool_Config> {
             r#"SELECT COUNT(*) FROM "pool_config" WHERE "is_active" = true"#,
         )
         .fetch_one(&self.pool)
+        .timed("pool_config", "count_by_status")
         .await?;
 
         let deprecated: (i64,) = sqlx::query_as(
             r#"SELECT COUNT(*) FROM "pool_config" WHERE "is_active" = false"#,
         )
         .fetch_one(&self.pool)
+        .timed("pool_config", "count_by_status")
         .await?;
 
         Ok((active.0, deprecated.0))
@@ -0,0 +1,117 @@
+use sqlx::Error;
+
+use crate::{
+    helpers::ContractRole,
+    model::{ProtocolContractHistory, Table},
+    telemetry::Timed as _,
+};
+
+impl Table<ProtocolContractHistory> {
+    /// Record the current contract of a role of a protocol. A different
+    /// contract still current for the role is closed at `height - 1` and the
+    /// new one is valid from `height`, or from 0 for the first contract of
+    /// the role. Returns the contract replaced, if any.
+    pub async fn record(
+        &self,
+        protocol: &str,
+        role: ContractRole,
+        contract: &str,
+        height: i64,
+    ) -> Result<Option<String>, Error> {
+        let (replaced,): (Option<String>,) = sqlx::query_as(
+            r#"
+            WITH "current" AS (
+                SELECT "contract"
+                FROM "protocol_contract_history"
+                WHERE "protocol" = $1 AND "role" = $2 AND "valid_to" IS NULL
+            ),
+            "closed" AS (
+                UPDATE "protocol_contract_history"
+                SET "valid_to" = $4 - 1
+                WHERE "protocol" = $1 AND "role" = $2 AND "valid_to" IS NULL AND "contract" <> $3
+                RETURNING "contract"
+            ),
+            "opened" AS (
+                INSERT INTO "protocol_contract_history" ("protocol", "role", "contract", "valid_from")
+                SELECT
+                    $1, $2, $3,
+                    CASE
+                        WHEN EXISTS (
+                            SELECT 1 FROM "protocol_contract_history"
+                            WHERE "protocol" = $1 AND "role" = $2
+                        ) THEN $4
+                        ELSE 0
+                    END
+                WHERE NOT EXISTS (SELECT 1 FROM "current" WHERE "contract" = $3)
+                ON CONFLICT DO NOTHING
+            )
+            SELECT MAX("contract") FROM "closed"
+            "#,
+        )
+        .bind(protocol)
+        .bind(role.to_string())
+        .bind(contract)
+        .bind(height)
+        .persistent(true)
+        .fetch_one(&self.pool)
+        .timed("protocol_contract_history", "record")
+        .await?;
+
+        Ok(replaced)
+    }
+
+    /// Close the current contracts of the protocols not in `active` at
+    /// `height`
+    pub async fn close_except(
+        &self,
+        active: &[String],
+        height: i64,
+    ) -> Result<u64, Error> {
+        let result = sqlx::query(
+            r#"
+            UPDATE "protocol_contract_history"
+            SET "valid_to" = $2
+            WHERE "protocol" != ALL($1) AND "valid_to" IS NULL
+            "#,
+        )
+        .bind(active)
+        .bind(height)
+        .persistent(true)
+        .execute(&self.pool)
+        .timed("protocol_contract_history", "close_except")
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    pub async fn get_all(&self) -> Result<Vec<ProtocolContractHistory>, Error> {
+        sqlx::query_as(
+            r#"
+            SELECT * FROM "protocol_contract_history"
+            ORDER BY "protocol", "role", "valid_from"
+            "#,
+        )
+        .persistent(true)
+        .fetch_all(&self.pool)
+        .timed("protocol_contract_history", "get_all")
+        .await
+    }
+
+    pub async fn get_by_protocol(
+        &self,
+        protocol: &str,
+    ) -> Result<Vec<ProtocolContractHistory>, Error> {
+        sqlx::query_as(
+            r#"
+            SELECT * FROM "protocol_contract_history"
+            WHERE "protocol" = $1
+            ORDER BY "role", "valid_from"
+            "#,
+        )
+        .bind(protocol)
+        .persistent(true)
+        .fetch_all(&self.pool)
+        .timed("protocol_contract_history", "get_by_protocol")
+        .await
+    }
+}
@@ -1,6 +1,9 @@
 use sqlx::Error;
 
-use crate::model::{ProtocolRegistry, Table};
+use crate::{
+    model::{ProtocolRegistry, Table},
+    telemetry::Timed as _,
+};
 
 impl Table<ProtocolRegistry> {
     /// Upsert an active protocol from admin contract
@@ -41,6 +44,7 @@ impl Table<ProtocolRegistry> {
         .bind(&protocol.lpn_symbol)
         .bind(&protocol.position_type)
         .execute(&self.pool)
+        .timed("protocol_registry", "upsert_active")
         .await?;
 
         Ok(())
@@ -60,6 +64,7 @@ impl Table<ProtocolRegistry> {
         )
         .bind(active_names)
         .execute(&self.pool)
+        .timed("protocol_registry", "mark_deprecated_except")
         .await?;
 
         Ok(result.rows_affected())
@@ -78,6 +83,7 @@ impl Table<ProtocolRegistry> {
         )
         .persistent(true)
         .fetch_all(&self.pool)
+        .timed("protocol_registry", "get_all")
         .await
     }
 
@@ -95,6 +101,7 @@ impl Table<ProtocolRegistry> {
         )
         .persistent(true)
         .fetch_all(&self.pool)
+        .timed("protocol_registry", "get_active")
         .await
     }
 
@@ -115,6 +122,55 @@ impl Table<ProtocolRegistry> {
         .bind(name)
         .persistent(true)
         .fetch_optional(&self.pool)
+        .timed("protocol_registry", "get_by_name")
+        .await
+    }
+
+    /// Get the protocol one of whose contracts is the given address
+    pub async fn get_by_contract(
+        &self,
+        contract: &str,
+    ) -> Result<Option<ProtocolRegistry>, Error> {
+        sqlx::query_as(
+            r#"
+            SELECT "protocol_name", "network", "dex", "leaser_contract", "lpp_contract",
+                   "oracle_contract", "profit_contract", "reserve_contract",
+                   "lpn_symbol", "position_type", "is_active", "first_seen_at", "deprecated_at"
+            FROM "protocol_registry"
+            WHERE $1 IN (
+                "leaser_contract", "lpp_contract", "oracle_contract",
+                "profit_contract", "reserve_contract"
+            )
+            ORDER BY "is_active" DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(contract)
+        .persistent(true)
+        .fetch_optional(&self.pool)
+        .timed("protocol_registry", "get_by_contract")
+        .await
+    }
+
+    /// Get protocols whose name starts with the given text, ignoring case
+    pub async fn search_by_name(
+        &self,
+        prefix: &str,
+    ) -> Result<Vec<ProtocolRegistry>, Error> {
+        sqlx::query_as(
+            r#"
+            SELECT "protocol_name", "network", "dex", "leaser_contract", "lpp_contract",
+                   "oracle_contract", "profit_contract", "reserve_contract",
+                   "lpn_symbol", "position_type", "is_active", "first_seen_at", "deprecated_at"
+            FROM "protocol_registry"
+            WHERE STARTS_WITH(UPPER("protocol_name"), UPPER($1))
+            ORDER BY "is_active" DESC, "protocol_name"
+            "#,
+        )
+        .bind(prefix)
+        .persistent(true)
+        .fetch_all(&self.pool)
+        .timed("protocol_registry", "search_by_name")
         .await
     }
 
@@ -135,6 +191,7 @@ impl Table<ProtocolRegistry> {
         .bind(lpp_contract)
         .persistent(true)
         .fetch_optional(&self.pool)
+        .timed("protocol_registry", "get_by_lpp_contract")
         .await
     }
 
@@ -144,12 +201,14 @@ impl Table<ProtocolRegistry> {
             r#"SELECT COUNT(*) FROM "protocol_registry" WHERE "is_active" = true"#,
         )
         .fetch_one(&self.pool)
+        .timed("protocol_registry", "count_by_status")
         .await?;
 
         let deprecated: (i64,) = sqlx::query_as(
             r#"SELECT COUNT(*) FROM "protocol_registry" WHERE "is_active" = false"#,
         )
         .fetch_one(&self.pool)
+        .timed("protocol_registry", "count_by_status")
         .await?;
 
         Ok((active.0, deprecated.0))
@@ -0,0 +1,176 @@
+use chrono::{DateTime, Utc};
+use sqlx::{Error, FromRow};
+
+use crate::{
+    model::{PushDelivery, Table},
+    telemetry::Timed as _,
+};
+
+use super::QueryResult;
+
+#[derive(Debug, Clone, FromRow)]
+pub struct DeliveryStats {
+    pub total: i64,
+    pub delivered: i64,
+    pub failed: i64,
+    pub avg_attempts: Option<f64>,
+    pub avg_latency_ms: Option<f64>,
+    pub p95_latency_ms: Option<f64>,
+}
+
+/// Outcome of one attempt to deliver a notification through a channel
+#[derive(Debug, Clone)]
+pub struct DeliveryAttempt {
+    pub notification_id: i64,
+    pub address: String,
+    pub channel: String,
+    pub endpoint: String,
+    pub status_code: Option<i32>,
+    pub latency_ms: i32,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct ChannelStats {
+    pub channel: String,
+    pub total: i64,
+    pub delivered: i64,
+}
+
+impl Table<PushDelivery> {
+    /// Record an attempt to deliver a notification, marked delivered when the
+    /// remote service answered with a 2xx status
+    pub async fn record(
+        &self,
+        attempt: DeliveryAttempt,
+    ) -> Result<QueryResult, Error> {
+        sqlx::query(
+            r#"
+            INSERT INTO "push_delivery" (
+                "notification_id", "address", "channel", "endpoint",
+                "status_code", "latency_ms", "error", "delivered_at"
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, CASE WHEN $5 BETWEEN 200 AND 299 THEN now() END)
+            ON CONFLICT ("notification_id", "channel", "endpoint") DO UPDATE SET
+                "status_code" = EXCLUDED."status_code",
+                "latency_ms" = EXCLUDED."latency_ms",
+                "error" = EXCLUDED."error",
+                "delivered_at" = EXCLUDED."delivered_at",
+                "attempts" = "push_delivery"."attempts" + 1,
+                "last_attempt_at" = now()
+            "#,
+        )
+        .bind(attempt.notification_id)
+        .bind(attempt.address)
+        .bind(attempt.channel)
+        .bind(attempt.endpoint)
+        .bind(attempt.status_code)
+        .bind(attempt.latency_ms)
+        .bind(attempt.error)
+        .execute(&self.pool)
+        .timed("push_delivery", "record")
+        .await
+    }
+
+    /// Channels and endpoints a notification was already delivered to
+    pub async fn get_delivered(
+        &self,
+        notification_id: i64,
+    ) -> Result<Vec<(String, String)>, Error> {
+        sqlx::query_as(
+            r#"
+            SELECT "channel", "endpoint" FROM "push_delivery"
+            WHERE "notification_id" = $1 AND "delivered_at" IS NOT NULL
+            "#,
+        )
+        .bind(notification_id)
+        .fetch_all(&self.pool)
+        .timed("push_delivery", "get_delivered")
+        .await
+    }
+
+    /// Deliveries of the given notifications
+    pub async fn get_by_notifications(
+        &self,
+        notification_ids: &[i64],
+    ) -> Result<Vec<PushDelivery>, Error> {
+        sqlx::query_as(
+            r#"
+            SELECT * FROM "push_delivery"
+            WHERE "notification_id" = ANY($1)
+            ORDER BY "notification_id", "last_attempt_at"
+            "#,
+        )
+        .bind(notification_ids)
+        .fetch_all(&self.pool)
+        .timed("push_delivery", "get_by_notifications")
+        .await
+    }
+
+    /// Outcome of the deliveries attempted since `from`
+    pub async fn get_stats(
+        &self,
+        from: DateTime<Utc>,
+    ) -> Result<DeliveryStats, Error> {
+        sqlx::query_as(
+            r#"
+            SELECT
+                COUNT(*) AS total,
+                COUNT(*) FILTER (WHERE "delivered_at" IS NOT NULL) AS delivered,
+                COUNT(*) FILTER (WHERE "delivered_at" IS NULL) AS failed,
+                AVG("attempts")::DOUBLE PRECISION AS avg_attempts,
+                AVG("latency_ms")::DOUBLE PRECISION AS avg_latency_ms,
+                PERCENTILE_CONT(0.95) WITHIN GROUP (ORDER BY "latency_ms") AS p95_latency_ms
+            FROM "push_delivery"
+            WHERE "last_attempt_at" >= $1
+            "#,
+        )
+        .bind(from)
+        .fetch_one(&self.pool)
+        .timed("push_delivery", "get_stats")
+        .await
+    }
+
+    /// Deliveries attempted since `from` per channel
+    pub async fn count_by_channel(
+        &self,
+        from: DateTime<Utc>,
+    ) -> Result<Vec<ChannelStats>, Error> {
+        sqlx::query_as(
+            r#"
+            SELECT
+                "channel",
+                COUNT(*) AS total,
+                COUNT(*) FILTER (WHERE "delivered_at" IS NOT NULL) AS delivered
+            FROM "push_delivery"
+            WHERE "last_attempt_at" >= $1
+            GROUP BY "channel"
+            ORDER BY "channel"
+            "#,
+        )
+        .bind(from)
+        .fetch_all(&self.pool)
+        .timed("push_delivery", "count_by_channel")
+        .await
+    }
+
+    /// Number of deliveries per last status since `from`, `None` when the
+    /// remote service could not be reached
+    pub async fn count_by_status_code(
+        &self,
+        from: DateTime<Utc>,
+    ) -> Result<Vec<(Option<i32>, i64)>, Error> {
+        sqlx::query_as(
+            r#"
+            SELECT "status_code", COUNT(*) FROM "push_delivery"
+            WHERE "last_attempt_at" >= $1
+            GROUP BY "status_code"
+            ORDER BY "status_code"
+            "#,
+        )
+        .bind(from)
+        .fetch_all(&self.pool)
+        .timed("push_delivery", "count_by_status_code")
+        .await
+    }
+}
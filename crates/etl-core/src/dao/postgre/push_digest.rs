@@ -0,0 +1,76 @@
+use chrono::{DateTime, Utc};
+use sqlx::{Error, Transaction};
+
+use crate::{
+    model::{PushDigest, Table},
+    telemetry::Timed as _,
+};
+
+use super::{DataBase, QueryResult};
+
+impl Table<PushDigest> {
+    /// Hold a notification back from a subscription. Held again on a retry
+    /// of the notification, it is kept once.
+    pub async fn insert(
+        &self,
+        address: String,
+        auth: String,
+        notification_id: i64,
+        push_type: String,
+        lease: Option<String>,
+    ) -> Result<QueryResult, Error> {
+        sqlx::query(
+            r#"
+            INSERT INTO "push_digest" ("address", "auth", "notification_id", "push_type", "lease")
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT DO NOTHING
+            "#,
+        )
+        .bind(address)
+        .bind(auth)
+        .bind(notification_id)
+        .bind(push_type)
+        .bind(lease)
+        .execute(&self.pool)
+        .timed("push_digest", "insert")
+        .await
+    }
+
+    /// Subscriptions with held notifications and the time the oldest was
+    /// held
+    pub async fn get_pending(
+        &self,
+    ) -> Result<Vec<(String, String, DateTime<Utc>)>, Error> {
+        sqlx::query_as(
+            r#"
+            SELECT "address", "auth", MIN("created_at")
+            FROM "push_digest"
+            GROUP BY "address", "auth"
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .timed("push_digest", "get_pending")
+        .await
+    }
+
+    /// Remove and return the held notifications of a subscription
+    pub async fn take(
+        &self,
+        address: String,
+        auth: String,
+        transaction: &mut Transaction<'_, DataBase>,
+    ) -> Result<Vec<PushDigest>, Error> {
+        sqlx::query_as(
+            r#"
+            DELETE FROM "push_digest"
+            WHERE "address" = $1 AND "auth" = $2
+            RETURNING *
+            "#,
+        )
+        .bind(address)
+        .bind(auth)
+        .fetch_all(&mut **transaction)
+        .timed("push_digest", "take")
+        .await
+    }
+}
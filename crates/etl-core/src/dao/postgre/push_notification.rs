@@ -0,0 +1,51 @@
+use sqlx::Error;
+
+use crate::{
+    model::{PushNotification, Table},
+    telemetry::Timed as _,
+};
+
+impl Table<PushNotification> {
+    pub async fn insert(
+        &self,
+        push_type: String,
+        lease: Option<String>,
+        owner: Option<String>,
+        recipients: i32,
+    ) -> Result<PushNotification, Error> {
+        sqlx::query_as(
+            r#"
+            INSERT INTO "push_notification" ("push_type", "lease", "owner", "recipients")
+            VALUES ($1, $2, $3, $4)
+            RETURNING *
+            "#,
+        )
+        .bind(push_type)
+        .bind(lease)
+        .bind(owner)
+        .bind(recipients)
+        .fetch_one(&self.pool)
+        .timed("push_notification", "insert")
+        .await
+    }
+
+    pub async fn get_recent(
+        &self,
+        lease: Option<String>,
+        limit: i64,
+    ) -> Result<Vec<PushNotification>, Error> {
+        sqlx::query_as(
+            r#"
+            SELECT * FROM "push_notification"
+            WHERE $1::VARCHAR IS NULL OR "lease" = $1
+            ORDER BY "id" DESC
+            LIMIT $2
+            "#,
+        )
+        .bind(lease)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .timed("push_notification", "get_recent")
+        .await
+    }
+}
@@ -0,0 +1,143 @@
+use chrono::{DateTime, Utc};
+use sqlx::{Error, Transaction};
+
+use crate::{
+    helpers::PushOutboxStatus,
+    model::{PushOutbox, Table},
+    telemetry::Timed as _,
+};
+
+use super::{DataBase, QueryResult};
+
+impl Table<PushOutbox> {
+    pub async fn insert(
+        &self,
+        push_type: String,
+        body: String,
+        recipient_type: &str,
+        recipient: String,
+        transaction: &mut Transaction<'_, DataBase>,
+    ) -> Result<QueryResult, Error> {
+        sqlx::query(
+            r#"
+            INSERT INTO "push_outbox" ("push_type", "body", "recipient_type", "recipient")
+            VALUES ($1, $2, $3, $4)
+            "#,
+        )
+        .bind(push_type)
+        .bind(body)
+        .bind(recipient_type)
+        .bind(recipient)
+        .execute(&mut **transaction)
+        .timed("push_outbox", "insert")
+        .await
+    }
+
+    /// Take up to `limit` due notifications and count the attempt. Claimed
+    /// rows are hidden for `lock_secs`, so a crashed worker's rows are
+    /// retried afterwards.
+    pub async fn claim_due(
+        &self,
+        limit: i64,
+        lock_secs: i64,
+    ) -> Result<Vec<PushOutbox>, Error> {
+        sqlx::query_as(
+            r#"
+            UPDATE "push_outbox"
+            SET
+                "attempts" = "attempts" + 1,
+                "next_attempt_at" = now() + make_interval(secs => $3)
+            WHERE "id" IN (
+                SELECT "id" FROM "push_outbox"
+                WHERE "status" = $1 AND "next_attempt_at" <= now()
+                ORDER BY "next_attempt_at" ASC
+                LIMIT $2
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING *
+            "#,
+        )
+        .bind(PushOutboxStatus::Pending.to_string())
+        .bind(limit)
+        .bind(lock_secs as f64)
+        .fetch_all(&self.pool)
+        .timed("push_outbox", "claim_due")
+        .await
+    }
+
+    pub async fn set_notification(
+        &self,
+        id: i64,
+        notification_id: i64,
+    ) -> Result<QueryResult, Error> {
+        sqlx::query(
+            r#"
+            UPDATE "push_outbox" SET "notification_id" = $2 WHERE "id" = $1
+            "#,
+        )
+        .bind(id)
+        .bind(notification_id)
+        .execute(&self.pool)
+        .timed("push_outbox", "set_notification")
+        .await
+    }
+
+    pub async fn retry(
+        &self,
+        id: i64,
+        next_attempt_at: DateTime<Utc>,
+        error: String,
+    ) -> Result<QueryResult, Error> {
+        sqlx::query(
+            r#"
+            UPDATE "push_outbox" SET "next_attempt_at" = $2, "last_error" = $3
+            WHERE "id" = $1
+            "#,
+        )
+        .bind(id)
+        .bind(next_attempt_at)
+        .bind(error)
+        .execute(&self.pool)
+        .timed("push_outbox", "retry")
+        .await
+    }
+
+    pub async fn finish(
+        &self,
+        id: i64,
+        status: PushOutboxStatus,
+        error: Option<String>,
+    ) -> Result<QueryResult, Error> {
+        sqlx::query(
+            r#"
+            UPDATE "push_outbox"
+            SET "status" = $2, "last_error" = COALESCE($3, "last_error"), "processed_at" = now()
+            WHERE "id" = $1
+            "#,
+        )
+        .bind(id)
+        .bind(status.to_string())
+        .bind(error)
+        .execute(&self.pool)
+        .timed("push_outbox", "finish")
+        .await
+    }
+
+    /// Number of notifications per status queued since `from`
+    pub async fn count_by_status(
+        &self,
+        from: DateTime<Utc>,
+    ) -> Result<Vec<(String, i64)>, Error> {
+        sqlx::query_as(
+            r#"
+            SELECT "status", COUNT(*) FROM "push_outbox"
+            WHERE "created_at" >= $1
+            GROUP BY "status"
+            "#,
+        )
+        .bind(from)
+        .fetch_all(&self.pool)
+        .timed("push_outbox", "count_by_status")
+        .await
+    }
+}
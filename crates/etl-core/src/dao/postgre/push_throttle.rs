@@ -0,0 +1,63 @@
+use sqlx::Error;
+
+use crate::{
+    model::{PushThrottle, Table},
+    telemetry::Timed as _,
+};
+
+use super::QueryResult;
+
+impl Table<PushThrottle> {
+    /// Last notifications of a type for a lease delivered to the given
+    /// subscriptions, as pairs of address and auth key
+    pub async fn get_by_subscriptions(
+        &self,
+        addresses: Vec<String>,
+        auths: Vec<String>,
+        lease: String,
+        push_type: String,
+    ) -> Result<Vec<PushThrottle>, Error> {
+        sqlx::query_as(
+            r#"
+            SELECT t.* FROM "push_throttle" t
+            INNER JOIN UNNEST($1::VARCHAR[], $2::VARCHAR[]) AS s ("address", "auth")
+                ON s."address" = t."address" AND s."auth" = t."auth"
+            WHERE t."lease" = $3 AND t."push_type" = $4
+            "#,
+        )
+        .bind(addresses)
+        .bind(auths)
+        .bind(lease)
+        .bind(push_type)
+        .fetch_all(&self.pool)
+        .timed("push_throttle", "get_by_subscriptions")
+        .await
+    }
+
+    pub async fn record(
+        &self,
+        address: String,
+        auth: String,
+        lease: String,
+        push_type: String,
+        notification_id: i64,
+    ) -> Result<QueryResult, Error> {
+        sqlx::query(
+            r#"
+            INSERT INTO "push_throttle" ("address", "auth", "lease", "push_type", "notification_id")
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT ("address", "auth", "lease", "push_type") DO UPDATE SET
+                "notification_id" = EXCLUDED."notification_id",
+                "sent_at" = now()
+            "#,
+        )
+        .bind(address)
+        .bind(auth)
+        .bind(lease)
+        .bind(push_type)
+        .bind(notification_id)
+        .execute(&self.pool)
+        .timed("push_throttle", "record")
+        .await
+    }
+}
@@ -1,14 +1,119 @@
-use std::str::FromStr;
+use std::{io, str::FromStr};
 
-use sqlx::{Error, QueryBuilder, Transaction};
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value;
+use sqlx::{Error, FromRow, QueryBuilder, Transaction};
 
 use crate::{
+    helpers::TxStatus,
     model::{CosmosTypes, Raw_Message, Table},
+    telemetry::Timed as _,
     types::Bucket_Type,
 };
 
 use super::{DataBase, QueryResult};
 
+/// Matching messages scanned per transaction a memo search returns
+const MEMO_SCAN_FACTOR: i64 = 10;
+
+/// Bound of a memo search, whose pattern may match most messages
+const MEMO_SEARCH_TIMEOUT: &str = "SET LOCAL statement_timeout = '5s'";
+
+/// Single ledger line of a wallet statement. Asset amounts are normalized
+/// by the asset decimals and stable values by the stable currency decimals.
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct WalletStatementRow {
+    pub timestamp: DateTime<Utc>,
+    pub event: String,
+    pub reference: String,
+    pub tx_hash: Option<String>,
+    pub asset: Option<String>,
+    pub amount: Option<BigDecimal>,
+    pub receipts: Option<BigDecimal>,
+    pub cost_basis_stable: Option<BigDecimal>,
+    pub proceeds_stable: Option<BigDecimal>,
+    pub realized_gain_stable: Option<BigDecimal>,
+    pub rewards: Option<String>,
+    pub fee_amount: Option<BigDecimal>,
+    pub fee_denom: Option<String>,
+}
+
+/// Messages of an address listed by `/api/txs`. `filter` holds message
+/// types, `to` the recipients, leases and pools of the executions to
+/// include, and `combine` includes both instead of requiring both. A
+/// `cursor` starts the page after the given message instead of skipping.
+#[derive(Debug, Clone)]
+pub struct TxsParams {
+    pub address: String,
+    pub skip: i64,
+    pub limit: i64,
+    pub filter: Vec<String>,
+    pub to: Vec<String>,
+    pub combine: bool,
+    pub status: Option<TxStatus>,
+    pub cursor: Option<TxsCursor>,
+}
+
+/// Last message of a page of `/api/txs`, as `block:tx_hash:index:inner_index`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TxsCursor {
+    pub block: i64,
+    pub tx_hash: String,
+    pub index: i32,
+    pub inner_index: i32,
+}
+
+impl FromStr for TxsCursor {
+    type Err = io::Error;
+
+    fn from_str(value: &str) -> Result<TxsCursor, Self::Err> {
+        let invalid = || io::Error::other("cursor not supported");
+        let mut parts = value.split(':');
+        let mut next = || parts.next().ok_or_else(invalid);
+
+        let cursor = TxsCursor {
+            block: next()?.parse().map_err(|_| invalid())?,
+            tx_hash: next()?.to_uppercase(),
+            index: next()?.parse().map_err(|_| invalid())?,
+            inner_index: next()?.parse().map_err(|_| invalid())?,
+        };
+        if parts.next().is_some() {
+            return Err(invalid());
+        }
+
+        Ok(cursor)
+    }
+}
+
+/// Transaction found by its hash
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct TxSummaryRow {
+    pub tx_hash: String,
+    pub block: i64,
+    pub timestamp: DateTime<Utc>,
+    pub messages: i64,
+    pub memo: Option<String>,
+    pub code: Option<i32>,
+}
+
+/// Contract executions of a protocol over a range and how many of their
+/// transactions failed, per reason
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct FailureRateRow {
+    pub protocol: String,
+    pub txs: i64,
+    pub failed: i64,
+    pub failure_rate: BigDecimal,
+    pub out_of_gas: i64,
+    pub insufficient_funds: i64,
+    pub insufficient_fee: i64,
+    pub slippage: i64,
+    pub contract_error: i64,
+    pub other: i64,
+}
+
 impl Table<Raw_Message> {
     pub async fn insert_if_not_exists(
         &self,
@@ -17,9 +122,9 @@ impl Table<Raw_Message> {
     ) -> Result<QueryResult, Error> {
         sqlx::query(
             r#"
-            INSERT INTO "raw_message" ("index", "from", "to", "tx_hash", "type", "value", "block", "fee_amount", "fee_denom", "memo", "timestamp", "rewards", "code")
-            VALUES($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
-            ON CONFLICT ("index", "tx_hash") DO NOTHING
+            INSERT INTO "raw_message" ("index", "from", "to", "tx_hash", "type", "value", "block", "fee_amount", "fee_denom", "memo", "timestamp", "rewards", "code", "data", "inner_index", "grantee", "codespace", "raw_log", "failure", "gas_wanted", "gas_used")
+            VALUES($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20, $21)
+            ON CONFLICT ("index", "inner_index", "tx_hash") DO NOTHING
             "#,
         )
         .bind(data.index)
@@ -35,20 +140,37 @@ impl Table<Raw_Message> {
         .bind(data.timestamp)
         .bind(&data.rewards)
 		.bind(data.code)
+        .bind(&data.data)
+        .bind(data.inner_index)
+        .bind(&data.grantee)
+        .bind(&data.codespace)
+        .bind(&data.raw_log)
+        .bind(&data.failure)
+        .bind(data.gas_wanted)
+        .bind(data.gas_used)
         .persistent(true)
         .execute(&mut **transaction)
+        .timed("raw_message", "insert_if_not_exists")
         .await
     }
 
+    /// Messages of an address, newest first, read through the address
+    /// activity index. A message appears once whatever the roles of the
+    /// address in it.
     pub async fn get(
         &self,
-        address: String,
-        skip: i64,
-        limit: i64,
-        filter: Vec<String>,
-        to: Vec<String>,
-        combine: bool,
+        params: TxsParams,
     ) -> Result<Vec<Raw_Message>, Error> {
+        let TxsParams {
+            address,
+            skip,
+            limit,
+            filter,
+            to,
+            combine,
+            status,
+            cursor,
+        } = params;
         let mut filters: Vec<String> = Vec::new();
 
         for f in filter {
@@ -60,58 +182,97 @@ impl Table<Raw_Message> {
 
         let mut qb = QueryBuilder::new(
             r#"
-        SELECT *
-        FROM "raw_message"
-        WHERE ("from" = "#,
+        SELECT m.*
+        FROM (
+            SELECT DISTINCT ON (a."block", a."tx_hash", a."index", a."inner_index")
+                a."block", a."tx_hash", a."index", a."inner_index"
+            FROM "address_activity" a
+            WHERE a."address" = "#,
         );
 
-        qb.push_bind(&address)
-            .push(r#" OR "to" = "#)
-            .push_bind(&address)
-            .push(")");
+        qb.push_bind(&address);
 
         let has_filters = !filters.is_empty();
         let has_to = !to.is_empty();
-        let earn_type = CosmosTypes::MsgExecuteContract.to_string();
 
         if has_filters && has_to && combine {
             qb.push(" AND (");
 
-            qb.push(" \"type\" = ANY(")
+            qb.push(" a.\"type\" = ANY(")
                 .push_bind(filters.as_slice())
                 .push(")");
 
-            qb.push(" OR (");
-
-            qb.push(" \"type\" = ").push_bind(&earn_type);
-
-            qb.push(" AND \"to\" = ANY(")
+            qb.push(" OR a.\"to\" = ANY(")
+                .push_bind(to.as_slice())
+                .push(") OR a.\"lease\" = ANY(")
+                .push_bind(to.as_slice())
+                .push(") OR a.\"pool\" = ANY(")
                 .push_bind(to.as_slice())
                 .push(")");
 
             qb.push(")");
-            qb.push(")");
         } else {
             if has_filters {
-                qb.push(" AND \"type\" = ANY(")
+                qb.push(" AND a.\"type\" = ANY(")
                     .push_bind(filters.as_slice())
                     .push(")");
             }
 
             if has_to {
-                qb.push(" AND \"to\" = ANY(")
+                qb.push(" AND (a.\"to\" = ANY(")
                     .push_bind(to.as_slice())
-                    .push(")");
+                    .push(") OR a.\"lease\" = ANY(")
+                    .push_bind(to.as_slice())
+                    .push(") OR a.\"pool\" = ANY(")
+                    .push_bind(to.as_slice())
+                    .push("))");
             }
         }
 
-        qb.push(r#" ORDER BY "timestamp" DESC OFFSET "#)
-            .push_bind(skip)
-            .push(" LIMIT ")
-            .push_bind(limit);
+        match status {
+            Some(TxStatus::Success) => {
+                qb.push(r#" AND COALESCE(a."code", 0) = 0"#);
+            },
+            Some(TxStatus::Failed) => {
+                qb.push(r#" AND COALESCE(a."code", 0) <> 0"#);
+            },
+            None => {},
+        }
+
+        if let Some(cursor) = &cursor {
+            qb.push(r#" AND (a."block", a."tx_hash", a."index", a."inner_index") < ("#)
+                .push_bind(cursor.block)
+                .push(", ")
+                .push_bind(&cursor.tx_hash)
+                .push(", ")
+                .push_bind(cursor.index)
+                .push(", ")
+                .push_bind(cursor.inner_index)
+                .push(")");
+        }
+
+        qb.push(
+            r#" ORDER BY a."block" DESC, a."tx_hash" DESC, a."index" DESC, a."inner_index" DESC"#,
+        );
+
+        if cursor.is_none() {
+            qb.push(" OFFSET ").push_bind(skip);
+        }
+
+        qb.push(" LIMIT ").push_bind(limit).push(
+            r#"
+        ) p
+        INNER JOIN "raw_message" m
+            ON m."tx_hash" = p."tx_hash" AND m."index" = p."index" AND m."inner_index" = p."inner_index"
+        ORDER BY p."block" DESC, p."tx_hash" DESC, p."index" DESC, p."inner_index" DESC"#,
+        );
 
         let query = qb.build_query_as::<Raw_Message>();
-        let rows = query.persistent(true).fetch_all(&self.pool).await?;
+        let rows = query
+            .persistent(true)
+            .fetch_all(&self.pool)
+            .timed("raw_message", "get")
+            .await?;
 
         Ok(rows)
     }
@@ -164,6 +325,7 @@ impl Table<Raw_Message> {
         .persistent(true)
         .bind(address)
         .fetch_one(&self.pool)
+        .timed("raw_message", "get_tx_volume")
         .await?;
         let (amnt,) = value;
         let amnt = amnt.unwrap_or(0.0);
@@ -233,6 +395,7 @@ impl Table<Raw_Message> {
         .persistent(true)
         .bind(address)
         .fetch_one(&self.pool)
+        .timed("raw_message", "get_win_rate")
         .await?;
         let (amnt,) = value;
         let amnt = amnt.unwrap_or(0.0);
@@ -345,6 +508,7 @@ impl Table<Raw_Message> {
         .bind(&address)
         .persistent(true)
         .fetch_all(&self.pool)
+        .timed("raw_message", "get_buckets")
         .await?;
         Ok(data)
     }
@@ -353,6 +517,7 @@ impl Table<Raw_Message> {
         sqlx::query_as(r#"SELECT * FROM "raw_message" where code is null"#)
             .persistent(true)
             .fetch_all(&self.pool)
+            .timed("raw_message", "get_all")
             .await
     }
 
@@ -369,15 +534,479 @@ impl Table<Raw_Message> {
             WHERE
 				"index" = $2
 			AND
-				"tx_hash" = $3
+				"inner_index" = $3
+			AND
+				"tx_hash" = $4
 
         "#,
         )
         .bind(data.code)
         .bind(data.index)
+        .bind(data.inner_index)
         .bind(&data.tx_hash)
         .persistent(true)
         .execute(&self.pool)
+        .timed("raw_message", "update")
+        .await
+    }
+
+    /// Messages without a decoded body, in
+    /// `("block", "tx_hash", "index", "inner_index")` order after the given
+    /// key
+    pub async fn get_undecoded(
+        &self,
+        after: (i64, String, i32, i32),
+        limit: i64,
+    ) -> Result<Vec<Raw_Message>, Error> {
+        sqlx::query_as(
+            r#"
+            SELECT * FROM "raw_message"
+            WHERE "data" IS NULL
+            AND ("block", "tx_hash", "index", "inner_index") > ($1, $2, $3, $4)
+            ORDER BY "block", "tx_hash", "index", "inner_index"
+            LIMIT $5
+            "#,
+        )
+        .bind(after.0)
+        .bind(&after.1)
+        .bind(after.2)
+        .bind(after.3)
+        .bind(limit)
+        .persistent(true)
+        .fetch_all(&self.pool)
+        .timed("raw_message", "get_undecoded")
+        .await
+    }
+
+    /// Messages of successful transactions with one of the given types, in
+    /// `("block", "tx_hash", "index", "inner_index")` order after the given
+    /// key
+    pub async fn get_by_types(
+        &self,
+        types: Vec<String>,
+        after: (i64, String, i32, i32),
+        limit: i64,
+    ) -> Result<Vec<Raw_Message>, Error> {
+        sqlx::query_as(
+            r#"
+            SELECT * FROM "raw_message"
+            WHERE "type" = ANY($1)
+            AND COALESCE("code", 0) = 0
+            AND ("block", "tx_hash", "index", "inner_index") > ($2, $3, $4, $5)
+            ORDER BY "block", "tx_hash", "index", "inner_index"
+            LIMIT $6
+            "#,
+        )
+        .bind(types)
+        .bind(after.0)
+        .bind(&after.1)
+        .bind(after.2)
+        .bind(after.3)
+        .bind(limit)
+        .persistent(true)
+        .fetch_all(&self.pool)
+        .timed("raw_message", "get_by_types")
+        .await
+    }
+
+    pub async fn get_tx_summary(
+        &self,
+        tx_hash: String,
+    ) -> Result<Option<TxSummaryRow>, Error> {
+        sqlx::query_as(
+            r#"
+            SELECT
+                "tx_hash",
+                MIN("block") AS "block",
+                MIN("timestamp") AS "timestamp",
+                COUNT(*) AS "messages",
+                MIN("memo") AS "memo",
+                MAX("code") AS "code"
+            FROM "raw_message"
+            WHERE "tx_hash" = $1
+            GROUP BY "tx_hash"
+            "#,
+        )
+        .bind(tx_hash)
+        .persistent(true)
+        .fetch_optional(&self.pool)
+        .timed("raw_message", "get_tx_summary")
+        .await
+    }
+
+    /// Latest transactions whose memo contains the given text, ignoring
+    /// case. Served by the trigram index on `memo`; only the latest
+    /// `MEMO_SCAN_FACTOR` matching messages per transaction requested are
+    /// grouped, and the search gives up after 5 seconds.
+    pub async fn search_memo(
+        &self,
+        text: &str,
+        limit: i64,
+    ) -> Result<Vec<TxSummaryRow>, Error> {
+        let pattern = format!(
+            "%{}%",
+            text.replace('\\', "\\\\")
+                .replace('%', "\\%")
+                .replace('_', "\\_")
+        );
+
+        let mut transaction = self.pool.begin().await?;
+        sqlx::query(MEMO_SEARCH_TIMEOUT)
+            .execute(&mut *transaction)
+            .await?;
+
+        let rows = sqlx::query_as(
+            r#"
+            SELECT
+                "tx_hash",
+                MIN("block") AS "block",
+                MIN("timestamp") AS "timestamp",
+                COUNT(*) AS "messages",
+                MIN("memo") AS "memo",
+                MAX("code") AS "code"
+            FROM (
+                SELECT "tx_hash", "block", "timestamp", "memo", "code"
+                FROM "raw_message"
+                WHERE "memo" <> '' AND "memo" ILIKE $1
+                ORDER BY "block" DESC
+                LIMIT $2 * $3
+            ) matches
+            GROUP BY "tx_hash"
+            ORDER BY MIN("block") DESC
+            LIMIT $2
+            "#,
+        )
+        .bind(pattern)
+        .bind(limit)
+        .bind(MEMO_SCAN_FACTOR)
+        .persistent(true)
+        .fetch_all(&mut *transaction)
+        .timed("raw_message", "search_memo")
+        .await?;
+        transaction.commit().await?;
+
+        Ok(rows)
+    }
+
+    /// Failure rate of the transactions executing the contracts of each
+    /// protocol: its registry contracts and its leases. A transaction counts
+    /// once, for the first contract it executes. Failures ingested before
+    /// they were classified count as `other`.
+    pub async fn get_failure_rates(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<FailureRateRow>, Error> {
+        sqlx::query_as(
+            r#"
+            WITH contracts AS (
+                SELECT pr."protocol_name" AS "protocol", c."contract"
+                FROM "protocol_registry" pr
+                CROSS JOIN LATERAL UNNEST(ARRAY[
+                    pr."leaser_contract", pr."lpp_contract", pr."oracle_contract",
+                    pr."profit_contract", pr."reserve_contract"
+                ]) AS c("contract")
+                WHERE c."contract" IS NOT NULL
+                UNION
+                SELECT pr."protocol_name", o."LS_contract_id"
+                FROM "LS_Opening" o
+                INNER JOIN "protocol_registry" pr ON pr."lpp_contract" = o."LS_loan_pool_id"
+            ),
+            txs AS (
+                SELECT DISTINCT ON (m."tx_hash")
+                    c."protocol",
+                    COALESCE(m."code", 0) <> 0 AS "failed",
+                    COALESCE(m."failure", 'other') AS "failure"
+                FROM "raw_message" m
+                INNER JOIN contracts c ON c."contract" = m."to"
+                WHERE m."type" = $1
+                AND m."timestamp" >= $2
+                AND m."timestamp" < $3
+                ORDER BY m."tx_hash", m."index", m."inner_index"
+            )
+            SELECT
+                "protocol",
+                COUNT(*) AS "txs",
+                COUNT(*) FILTER (WHERE "failed") AS "failed",
+                ROUND(COUNT(*) FILTER (WHERE "failed") * 100.0 / COUNT(*), 2) AS "failure_rate",
+                COUNT(*) FILTER (WHERE "failed" AND "failure" = 'out_of_gas') AS "out_of_gas",
+                COUNT(*) FILTER (WHERE "failed" AND "failure" = 'insufficient_funds') AS "insufficient_funds",
+                COUNT(*) FILTER (WHERE "failed" AND "failure" = 'insufficient_fee') AS "insufficient_fee",
+                COUNT(*) FILTER (WHERE "failed" AND "failure" = 'slippage') AS "slippage",
+                COUNT(*) FILTER (WHERE "failed" AND "failure" = 'contract_error') AS "contract_error",
+                COUNT(*) FILTER (WHERE "failed" AND "failure" = 'other') AS "other"
+            FROM txs
+            GROUP BY "protocol"
+            ORDER BY "protocol"
+            "#,
+        )
+        .bind(CosmosTypes::MsgExecuteContract.to_string())
+        .bind(from)
+        .bind(to)
+        .persistent(true)
+        .fetch_all(&self.pool)
+        .timed("raw_message", "get_failure_rates")
+        .await
+    }
+
+    /// Stores the decoded bodies of messages, keyed by index, inner index
+    /// and tx hash
+    pub async fn set_data(
+        &self,
+        data: Vec<(i32, i32, String, Value)>,
+    ) -> Result<QueryResult, Error> {
+        let mut indexes = Vec::with_capacity(data.len());
+        let mut inner_indexes = Vec::with_capacity(data.len());
+        let mut tx_hashes = Vec::with_capacity(data.len());
+        let mut values = Vec::with_capacity(data.len());
+        for (index, inner_index, tx_hash, value) in data {
+            indexes.push(index);
+            inner_indexes.push(inner_index);
+            tx_hashes.push(tx_hash);
+            values.push(value);
+        }
+
+        sqlx::query(
+            r#"
+            UPDATE "raw_message" AS r
+            SET "data" = d."data"
+            FROM UNNEST($1::INT[], $2::INT[], $3::VARCHAR[], $4::JSONB[]) AS d ("index", "inner_index", "tx_hash", "data")
+            WHERE r."index" = d."index" AND r."inner_index" = d."inner_index" AND r."tx_hash" = d."tx_hash"
+            "#,
+        )
+        .bind(indexes)
+        .bind(inner_indexes)
+        .bind(tx_hashes)
+        .bind(values)
+        .persistent(true)
+        .execute(&self.pool)
+        .timed("raw_message", "set_data")
+        .await
+    }
+
+    /// Accounting history of a wallet up to `to` in chronological order:
+    /// lease openings, repayments, partial closes, liquidations and
+    /// closings, LP deposits and withdrawals, and claimed rewards. Rows
+    /// before `from` are left out except LP deposits and withdrawals, which
+    /// carry the cost basis of the later withdrawals, and partial closes and
+    /// liquidations, whose proceeds count towards the gain of the closing.
+    /// The gains of closings and withdrawals are left to the caller. The
+    /// network fee of the underlying transaction is attached when the
+    /// wallet signed it.
+    pub async fn get_wallet_statement(
+        &self,
+        address: String,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    ) -> Result<Vec<WalletStatementRow>, Error> {
+        sqlx::query_as(
+            r#"
+            WITH
+            openings AS (
+                SELECT
+                    o."LS_contract_id",
+                    o."LS_timestamp",
+                    o."LS_loan_pool_id",
+                    o."LS_cltr_symbol",
+                    o."LS_cltr_amnt_asset",
+                    o."LS_cltr_amnt_stable",
+                    o."Tx_Hash"
+                FROM "LS_Opening" o
+                WHERE o."LS_address_id" = $1
+            ),
+            repayments AS (
+                SELECT
+                    r."LS_contract_id",
+                    SUM(r."LS_payment_amnt_stable" / pc.stable_currency_decimals::numeric) AS total_repaid
+                FROM "LS_Repayment" r
+                INNER JOIN openings o ON o."LS_contract_id" = r."LS_contract_id"
+                INNER JOIN pool_config pc ON pc.pool_id = o."LS_loan_pool_id"
+                GROUP BY r."LS_contract_id"
+            ),
+            collects AS (
+                SELECT
+                    lc."LS_contract_id",
+                    SUM(lc."LS_amount_stable" / POWER(10, cr.decimal_digits)::NUMERIC) AS total_collected
+                FROM "LS_Loan_Collect" lc
+                INNER JOIN openings o ON o."LS_contract_id" = lc."LS_contract_id"
+                INNER JOIN currency_registry cr ON cr.ticker = lc."LS_symbol"
+                GROUP BY lc."LS_contract_id"
+            ),
+            events AS (
+                SELECT
+                    o."LS_timestamp" AS timestamp,
+                    'lease_open' AS event,
+                    o."LS_contract_id" AS reference,
+                    o."Tx_Hash" AS tx_hash,
+                    o."LS_cltr_symbol" AS asset,
+                    o."LS_cltr_amnt_asset" / POWER(10, cr.decimal_digits)::NUMERIC AS amount,
+                    NULL::NUMERIC AS receipts,
+                    o."LS_cltr_amnt_stable" / POWER(10, cr.decimal_digits)::NUMERIC AS cost_basis_stable,
+                    NULL::NUMERIC AS proceeds_stable,
+                    NULL::NUMERIC AS realized_gain_stable,
+                    NULL::TEXT AS rewards
+                FROM openings o
+                INNER JOIN currency_registry cr ON cr.ticker = o."LS_cltr_symbol"
+
+                UNION ALL
+
+                SELECT
+                    r."LS_timestamp",
+                    'lease_repayment',
+                    r."LS_contract_id",
+                    r."Tx_Hash",
+                    r."LS_payment_symbol",
+                    r."LS_payment_amnt" / POWER(10, cr.decimal_digits)::NUMERIC,
+                    NULL,
+                    r."LS_payment_amnt_stable" / pc.stable_currency_decimals::numeric,
+                    NULL,
+                    NULL,
+                    NULL
+                FROM "LS_Repayment" r
+                INNER JOIN openings o ON o."LS_contract_id" = r."LS_contract_id"
+                INNER JOIN pool_config pc ON pc.pool_id = o."LS_loan_pool_id"
+                INNER JOIN currency_registry cr ON cr.ticker = r."LS_payment_symbol"
+
+                UNION ALL
+
+                SELECT
+                    c."LS_timestamp",
+                    'lease_partial_close',
+                    c."LS_contract_id",
+                    c."Tx_Hash",
+                    c."LS_amnt_symbol",
+                    c."LS_amnt" / POWER(10, cr.decimal_digits)::NUMERIC,
+                    NULL,
+                    NULL,
+                    c."LS_amnt_stable" / POWER(10, cr.decimal_digits)::NUMERIC,
+                    NULL,
+                    NULL
+                FROM "LS_Close_Position" c
+                INNER JOIN openings o ON o."LS_contract_id" = c."LS_contract_id"
+                INNER JOIN currency_registry cr ON cr.ticker = c."LS_amnt_symbol"
+
+                UNION ALL
+
+                SELECT
+                    l."LS_timestamp",
+                    'lease_liquidation',
+                    l."LS_contract_id",
+                    l."Tx_Hash",
+                    l."LS_amnt_symbol",
+                    l."LS_amnt" / POWER(10, cr.decimal_digits)::NUMERIC,
+                    NULL,
+                    NULL,
+                    l."LS_amnt_stable" / POWER(10, cr.decimal_digits)::NUMERIC,
+                    NULL,
+                    NULL
+                FROM "LS_Liquidation" l
+                INNER JOIN openings o ON o."LS_contract_id" = l."LS_contract_id"
+                INNER JOIN currency_registry cr ON cr.ticker = l."LS_amnt_symbol"
+
+                UNION ALL
+
+                SELECT
+                    lc."LS_timestamp",
+                    'lease_close',
+                    o."LS_contract_id",
+                    NULL,
+                    NULL,
+                    NULL,
+                    NULL,
+                    o."LS_cltr_amnt_stable" / POWER(10, cr.decimal_digits)::NUMERIC
+                        + COALESCE(r.total_repaid, 0),
+                    COALESCE(c.total_collected, 0),
+                    NULL,
+                    NULL
+                FROM openings o
+                INNER JOIN "LS_Loan_Closing" lc ON lc."LS_contract_id" = o."LS_contract_id"
+                INNER JOIN currency_registry cr ON cr.ticker = o."LS_cltr_symbol"
+                LEFT JOIN repayments r ON r."LS_contract_id" = o."LS_contract_id"
+                LEFT JOIN collects c ON c."LS_contract_id" = o."LS_contract_id"
+
+                UNION ALL
+
+                SELECT
+                    d."LP_timestamp",
+                    'lp_deposit',
+                    d."LP_Pool_id",
+                    d."Tx_Hash",
+                    pc.lpn_symbol,
+                    d."LP_amnt_asset" / pc.lpn_decimals::numeric,
+                    d."LP_amnt_receipts",
+                    d."LP_amnt_stable" / pc.lpn_decimals::numeric,
+                    NULL,
+                    NULL,
+                    NULL
+                FROM "LP_Deposit" d
+                INNER JOIN pool_config pc ON pc.pool_id = d."LP_Pool_id"
+                WHERE d."LP_address_id" = $1
+
+                UNION ALL
+
+                SELECT
+                    w."LP_timestamp",
+                    'lp_withdraw',
+                    w."LP_Pool_id",
+                    w."Tx_Hash",
+                    pc.lpn_symbol,
+                    w."LP_amnt_asset" / pc.lpn_decimals::numeric,
+                    w."LP_amnt_receipts",
+                    NULL,
+                    w."LP_amnt_stable" / pc.lpn_decimals::numeric,
+                    NULL,
+                    NULL
+                FROM "LP_Withdraw" w
+                INNER JOIN pool_config pc ON pc.pool_id = w."LP_Pool_id"
+                WHERE w."LP_address_id" = $1
+
+                UNION ALL
+
+                SELECT
+                    rm."timestamp",
+                    CASE
+                        WHEN rm."type" = $2 THEN 'staking_reward'
+                        ELSE 'reward_claim'
+                    END,
+                    rm."to",
+                    rm."tx_hash",
+                    NULL,
+                    NULL,
+                    NULL,
+                    NULL,
+                    NULL,
+                    NULL,
+                    rm."rewards"
+                FROM "address_activity" a
+                INNER JOIN "raw_message" rm
+                    ON rm."tx_hash" = a."tx_hash" AND rm."index" = a."index" AND rm."inner_index" = a."inner_index"
+                WHERE a."address" = $1 AND a."role" = 'sender' AND rm."rewards" IS NOT NULL
+            )
+            SELECT
+                e.*,
+                f."fee_amount",
+                f."fee_denom"
+            FROM events e
+            LEFT JOIN LATERAL (
+                SELECT rm."fee_amount", rm."fee_denom"
+                FROM "raw_message" rm
+                WHERE rm."tx_hash" = e.tx_hash AND rm."from" = $1
+                ORDER BY rm."index"
+                LIMIT 1
+            ) f ON TRUE
+            WHERE ($3::TIMESTAMPTZ IS NULL OR e.timestamp >= $3
+                    OR e.event IN ('lp_deposit', 'lp_withdraw', 'lease_partial_close', 'lease_liquidation'))
+                AND ($4::TIMESTAMPTZ IS NULL OR e.timestamp <= $4)
+            ORDER BY e.timestamp ASC
+            "#,
+        )
+        .bind(address)
+        .bind(CosmosTypes::MsgWithdrawDelegatorReward.to_string())
+        .bind(from)
+        .bind(to)
+        .persistent(true)
+        .fetch_all(&self.pool)
+        .timed("raw_message", "get_wallet_statement")
         .await
     }
 }
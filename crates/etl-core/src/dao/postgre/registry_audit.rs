@@ -0,0 +1,61 @@
+use sqlx::Error;
+
+use crate::{
+    helpers::{RegistryChange, RegistryEntity},
+    model::{RegistryAudit, Table},
+    telemetry::Timed as _,
+};
+
+use super::QueryResult;
+
+impl Table<RegistryAudit> {
+    pub async fn insert_many(
+        &self,
+        data: &[(RegistryEntity, String, RegistryChange)],
+    ) -> Result<QueryResult, Error> {
+        let mut entities = Vec::with_capacity(data.len());
+        let mut names = Vec::with_capacity(data.len());
+        let mut changes = Vec::with_capacity(data.len());
+        for (entity, name, change) in data {
+            entities.push(entity.to_string());
+            names.push(name.to_owned());
+            changes.push(change.to_string());
+        }
+
+        sqlx::query(
+            r#"
+            INSERT INTO "registry_audit" ("entity", "name", "change")
+            SELECT * FROM UNNEST($1::VARCHAR[], $2::VARCHAR[], $3::VARCHAR[])
+            "#,
+        )
+        .bind(entities)
+        .bind(names)
+        .bind(changes)
+        .persistent(true)
+        .execute(&self.pool)
+        .timed("registry_audit", "insert_many")
+        .await
+    }
+
+    /// Most recent changes first
+    pub async fn get_recent(
+        &self,
+        entity: Option<RegistryEntity>,
+        limit: i64,
+    ) -> Result<Vec<RegistryAudit>, Error> {
+        sqlx::query_as(
+            r#"
+            SELECT * FROM "registry_audit"
+            WHERE ($1::VARCHAR IS NULL OR "entity" = $1)
+            ORDER BY "created_at" DESC, "id" DESC
+            LIMIT $2
+            "#,
+        )
+        .bind(entity.map(String::from))
+        .bind(limit)
+        .persistent(true)
+        .fetch_all(&self.pool)
+        .timed("registry_audit", "get_recent")
+        .await
+    }
+}
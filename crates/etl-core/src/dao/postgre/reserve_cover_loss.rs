@@ -1,6 +1,9 @@
 use sqlx::{Error, Transaction};
 
-use crate::model::{Reserve_Cover_Loss, Table};
+use crate::{
+    model::{Reserve_Cover_Loss, Table},
+    telemetry::Timed as _,
+};
 
 use super::{DataBase, QueryResult};
 
@@ -32,6 +35,7 @@ impl Table<Reserve_Cover_Loss> {
         .bind(data.LS_timestamp)
         .persistent(true)
         .execute(&mut **transaction)
+        .timed("reserve_cover_loss", "insert_if_not_exists")
         .await
     }
 }
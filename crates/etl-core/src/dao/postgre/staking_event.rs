@@ -0,0 +1,271 @@
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::{Error, FromRow, QueryBuilder, Transaction};
+
+use crate::{
+    model::{StakingEvent, Table},
+    telemetry::Timed as _,
+};
+
+use super::DataBase;
+
+/// Change of a delegation with the staked balance after it
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct StakedBalanceRow {
+    pub timestamp: DateTime<Utc>,
+    pub tx_hash: String,
+    pub delegator: String,
+    pub validator: String,
+    pub action: String,
+    pub amount: BigDecimal,
+    pub balance: BigDecimal,
+}
+
+/// Reward claim with the rewards claimed up to and including it
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct ClaimedRewardsRow {
+    pub timestamp: DateTime<Utc>,
+    pub tx_hash: String,
+    pub validator: String,
+    pub rewards: BigDecimal,
+    pub rewards_stable: Option<BigDecimal>,
+    pub cumulative_rewards: BigDecimal,
+    pub cumulative_rewards_stable: BigDecimal,
+}
+
+/// Undelegation still unbonding, less the part cancelled since
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct UnbondingRow {
+    pub validator: String,
+    pub amount: BigDecimal,
+    pub completion_time: DateTime<Utc>,
+    pub block: i64,
+    pub tx_hash: String,
+}
+
+/// Stake delegated to a validator at the end of a day and its share of
+/// all the indexed stake
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct ValidatorShareRow {
+    pub day: DateTime<Utc>,
+    pub validator: String,
+    pub balance: BigDecimal,
+    pub share: BigDecimal,
+}
+
+impl Table<StakingEvent> {
+    /// Record the changes of a message. Rows already indexed are kept.
+    pub async fn insert_many(
+        &self,
+        data: Vec<StakingEvent>,
+        transaction: &mut Transaction<'_, DataBase>,
+    ) -> Result<(), Error> {
+        if data.is_empty() {
+            return Ok(());
+        }
+
+        let mut query_builder: QueryBuilder<DataBase> = QueryBuilder::new(
+            r#"
+            INSERT INTO "staking_event" (
+                "tx_hash", "index", "inner_index", "validator", "delegator",
+                "action", "amount", "rewards", "rewards_stable",
+                "completion_time", "creation_height", "block", "timestamp"
+            )"#,
+        );
+
+        query_builder.push_values(data, |mut b, data| {
+            b.push_bind(data.tx_hash)
+                .push_bind(data.index)
+                .push_bind(data.inner_index)
+                .push_bind(data.validator)
+                .push_bind(data.delegator)
+                .push_bind(data.action)
+                .push_bind(data.amount)
+                .push_bind(data.rewards)
+                .push_bind(data.rewards_stable)
+                .push_bind(data.completion_time)
+                .push_bind(data.creation_height)
+                .push_bind(data.block)
+                .push_bind(data.timestamp);
+        });
+        query_builder.push(
+            r#"
+            ON CONFLICT ("tx_hash", "index", "inner_index", "validator") DO NOTHING
+            "#,
+        );
+
+        query_builder
+            .build()
+            .persistent(false)
+            .execute(&mut **transaction)
+            .timed("staking_event", "insert_many")
+            .await?;
+
+        Ok(())
+    }
+
+    /// Delegation changes of a delegator, a validator or both, oldest first.
+    /// Balances are per validator, so with no delegator they are the total
+    /// stake of the validator.
+    pub async fn get_balance_history(
+        &self,
+        delegator: Option<String>,
+        validator: Option<String>,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    ) -> Result<Vec<StakedBalanceRow>, Error> {
+        sqlx::query_as(
+            r#"
+            SELECT * FROM (
+                SELECT
+                    "timestamp",
+                    "tx_hash",
+                    "delegator",
+                    "validator",
+                    "action",
+                    "amount",
+                    SUM("amount") OVER (
+                        PARTITION BY "validator"
+                        ORDER BY "block", "tx_hash", "index", "inner_index", "delegator"
+                    ) AS "balance"
+                FROM "staking_event"
+                WHERE ($1::VARCHAR IS NULL OR "delegator" = $1)
+                AND ($2::VARCHAR IS NULL OR "validator" = $2)
+                AND "action" <> 'withdraw_rewards'
+            ) AS history
+            WHERE ($3::TIMESTAMPTZ IS NULL OR "timestamp" >= $3)
+            AND ($4::TIMESTAMPTZ IS NULL OR "timestamp" <= $4)
+            ORDER BY "timestamp", "validator"
+            "#,
+        )
+        .bind(delegator)
+        .bind(validator)
+        .bind(from)
+        .bind(to)
+        .persistent(true)
+        .fetch_all(&self.pool)
+        .timed("staking_event", "get_balance_history")
+        .await
+    }
+
+    /// Reward claims of a delegator with their running totals, oldest first.
+    /// Claims whose stable value is unknown add nothing to the stable total.
+    pub async fn get_claimed_rewards(
+        &self,
+        delegator: String,
+    ) -> Result<Vec<ClaimedRewardsRow>, Error> {
+        sqlx::query_as(
+            r#"
+            SELECT
+                "timestamp",
+                "tx_hash",
+                "validator",
+                "rewards",
+                "rewards_stable",
+                SUM("rewards") OVER w AS "cumulative_rewards",
+                SUM(COALESCE("rewards_stable", 0)) OVER w AS "cumulative_rewards_stable"
+            FROM "staking_event"
+            WHERE "delegator" = $1 AND "action" = 'withdraw_rewards'
+            WINDOW w AS (ORDER BY "block", "tx_hash", "index", "inner_index", "validator")
+            ORDER BY "block", "tx_hash", "index", "inner_index", "validator"
+            "#,
+        )
+        .bind(delegator)
+        .persistent(true)
+        .fetch_all(&self.pool)
+        .timed("staking_event", "get_claimed_rewards")
+        .await
+    }
+
+    /// Undelegations of a delegator that have not completed, by completion
+    /// time. Cancelled unbondings are matched to the undelegation by the
+    /// block it was created at.
+    pub async fn get_unbonding(
+        &self,
+        delegator: String,
+    ) -> Result<Vec<UnbondingRow>, Error> {
+        sqlx::query_as(
+            r#"
+            SELECT
+                u."validator",
+                -u."amount" - COALESCE(c."amount", 0) AS "amount",
+                u."completion_time",
+                u."block",
+                u."tx_hash"
+            FROM "staking_event" u
+            LEFT JOIN (
+                SELECT "validator", "creation_height", SUM("amount") AS "amount"
+                FROM "staking_event"
+                WHERE "delegator" = $1 AND "action" = 'cancel_unbonding'
+                GROUP BY "validator", "creation_height"
+            ) c ON c."validator" = u."validator" AND c."creation_height" = u."block"
+            WHERE u."delegator" = $1
+            AND u."action" = 'undelegate'
+            AND u."completion_time" > NOW()
+            AND -u."amount" - COALESCE(c."amount", 0) > 0
+            ORDER BY u."completion_time", u."validator"
+            "#,
+        )
+        .bind(delegator)
+        .persistent(true)
+        .fetch_all(&self.pool)
+        .timed("staking_event", "get_unbonding")
+        .await
+    }
+
+    /// Daily stake and share of each validator over a range of days. The
+    /// changes before the range are folded into its first day and the
+    /// balances are a running sum of the daily changes.
+    pub async fn get_validator_shares(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<ValidatorShareRow>, Error> {
+        sqlx::query_as(
+            r#"
+            WITH days AS (
+                SELECT generate_series(
+                    DATE_TRUNC('day', $1::TIMESTAMPTZ),
+                    DATE_TRUNC('day', $2::TIMESTAMPTZ),
+                    INTERVAL '1 day'
+                ) AS "day"
+            ),
+            changes AS (
+                SELECT
+                    "validator",
+                    GREATEST(DATE_TRUNC('day', "timestamp"), DATE_TRUNC('day', $1::TIMESTAMPTZ)) AS "day",
+                    SUM("amount") AS "amount"
+                FROM "staking_event"
+                WHERE "timestamp" < DATE_TRUNC('day', $2::TIMESTAMPTZ) + INTERVAL '1 day'
+                GROUP BY 1, 2
+            ),
+            balances AS (
+                SELECT
+                    d."day",
+                    v."validator",
+                    SUM(COALESCE(c."amount", 0)) OVER (
+                        PARTITION BY v."validator" ORDER BY d."day"
+                    ) AS "balance"
+                FROM days d
+                CROSS JOIN (SELECT DISTINCT "validator" FROM changes) v
+                LEFT JOIN changes c ON c."validator" = v."validator" AND c."day" = d."day"
+            )
+            SELECT
+                "day",
+                "validator",
+                "balance",
+                "balance" / SUM("balance") OVER (PARTITION BY "day") AS "share"
+            FROM balances
+            WHERE "balance" > 0
+            ORDER BY "day", "balance" DESC
+            "#,
+        )
+        .bind(from)
+        .bind(to)
+        .persistent(true)
+        .fetch_all(&self.pool)
+        .timed("staking_event", "get_validator_shares")
+        .await
+    }
+}
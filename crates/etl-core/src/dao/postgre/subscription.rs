@@ -1,6 +1,20 @@
 use super::QueryResult;
-use crate::model::{Subscription, Table};
-use sqlx::error::Error;
+use crate::{
+    model::{Subscription, Table},
+    telemetry::Timed as _,
+};
+use chrono::{DateTime, Utc};
+use sqlx::{error::Error, FromRow};
+
+/// Subscriptions of one browser family with their Web Push deliveries
+#[derive(Debug, Clone, FromRow)]
+pub struct BrowserStats {
+    pub browser: String,
+    pub active: i64,
+    pub inactive: i64,
+    pub deliveries: i64,
+    pub failed: i64,
+}
 
 impl Table<Subscription> {
     pub async fn insert(
@@ -9,8 +23,8 @@ impl Table<Subscription> {
     ) -> Result<QueryResult, Error> {
         sqlx::query(
             r#"
-            INSERT INTO subscription (address, p256dh, auth, endpoint, expiration, ip, user_agent)
-            VALUES($1, $2, $3, $4, $5, $6, $7)
+            INSERT INTO subscription (address, p256dh, auth, endpoint, expiration, ip, user_agent, locale, vapid_key_id)
+            VALUES($1, $2, $3, $4, $5, $6, $7, $8, $9)
             "#,
         )
         .bind(&subscription.address)
@@ -20,7 +34,10 @@ impl Table<Subscription> {
         .bind(subscription.expiration)
         .bind(&subscription.ip)
         .bind(&subscription.user_agent)
+        .bind(&subscription.locale)
+        .bind(&subscription.vapid_key_id)
         .execute(&self.pool)
+        .timed("subscription", "insert")
         .await
     }
 
@@ -35,6 +52,27 @@ impl Table<Subscription> {
         )
         .bind(address)
         .fetch_all(&self.pool)
+        .timed("subscription", "get_by_address")
+        .await?;
+        Ok(data)
+    }
+
+    /// Active subscriptions of browsers subscribed to a single lease
+    pub async fn get_by_lease(
+        &self,
+        lease: String,
+    ) -> Result<Vec<Subscription>, Error> {
+        let data = sqlx::query_as(
+            r#"
+            SELECT s.* FROM subscription s
+            INNER JOIN subscription_lease sl
+                ON sl.address = s.address AND sl.auth = s.auth
+            WHERE sl.lease = $1 AND sl.active = true AND s.active = true
+            "#,
+        )
+        .bind(lease)
+        .fetch_all(&self.pool)
+        .timed("subscription", "get_by_lease")
         .await?;
         Ok(data)
     }
@@ -50,6 +88,22 @@ impl Table<Subscription> {
         )
         .bind(endpoint)
         .execute(&self.pool)
+        .timed("subscription", "deactivate")
+        .await
+    }
+
+    pub async fn deactivate_by_address(
+        &self,
+        address: String,
+    ) -> Result<QueryResult, Error> {
+        sqlx::query(
+            r#"
+            UPDATE subscription SET active = false WHERE address=$1 AND active = true
+            "#,
+        )
+        .bind(address)
+        .execute(&self.pool)
+        .timed("subscription", "deactivate_by_address")
         .await
     }
 
@@ -66,6 +120,7 @@ impl Table<Subscription> {
         .bind(address)
         .bind(auth)
         .execute(&self.pool)
+        .timed("subscription", "deactivate_by_auth_and_ne_address")
         .await
     }
 
@@ -84,6 +139,47 @@ impl Table<Subscription> {
         .bind(address)
         .bind(auth)
         .execute(&self.pool)
+        .timed("subscription", "update")
+        .await
+    }
+
+    /// Whether the browser itself receives the notifications of the
+    /// subscription
+    pub async fn set_web_push(
+        &self,
+        address: String,
+        auth: String,
+        web_push: bool,
+    ) -> Result<QueryResult, Error> {
+        sqlx::query(
+            r#"
+            UPDATE subscription SET web_push=$1 WHERE address=$2 AND auth=$3
+            "#,
+        )
+        .bind(web_push)
+        .bind(address)
+        .bind(auth)
+        .execute(&self.pool)
+        .timed("subscription", "set_web_push")
+        .await
+    }
+
+    pub async fn set_locale(
+        &self,
+        address: String,
+        auth: String,
+        locale: String,
+    ) -> Result<QueryResult, Error> {
+        sqlx::query(
+            r#"
+            UPDATE subscription SET locale=$1 WHERE address=$2 AND auth=$3
+            "#,
+        )
+        .bind(locale)
+        .bind(address)
+        .bind(auth)
+        .execute(&self.pool)
+        .timed("subscription", "set_locale")
         .await
     }
 
@@ -107,6 +203,7 @@ impl Table<Subscription> {
         .bind(auth)
         .persistent(true)
         .fetch_optional(&self.pool)
+        .timed("subscription", "get_one")
         .await
     }
 
@@ -130,6 +227,7 @@ impl Table<Subscription> {
         .bind(auth)
         .persistent(true)
         .fetch_one(&self.pool)
+        .timed("subscription", "isExists")
         .await?;
 
         if value > 0 {
@@ -138,4 +236,151 @@ impl Table<Subscription> {
 
         Ok(false)
     }
+
+    /// Deactivate subscriptions past the expiration time of the push service
+    pub async fn deactivate_expired(&self) -> Result<QueryResult, Error> {
+        sqlx::query(
+            r#"
+            UPDATE subscription SET active = false
+            WHERE active = true AND expiration IS NOT NULL AND expiration < now()
+            "#,
+        )
+        .execute(&self.pool)
+        .timed("subscription", "deactivate_expired")
+        .await
+    }
+
+    /// Deactivate subscriptions that had at least `min_failures` Web Push
+    /// deliveries in the last `days` days and none of them delivered
+    pub async fn deactivate_failing(
+        &self,
+        days: i32,
+        min_failures: i64,
+    ) -> Result<QueryResult, Error> {
+        sqlx::query(
+            r#"
+            UPDATE subscription SET active = false
+            WHERE active = true AND endpoint IN (
+                SELECT endpoint FROM push_delivery
+                WHERE channel = 'web_push'
+                AND last_attempt_at >= now() - make_interval(days => $1)
+                GROUP BY endpoint
+                HAVING COUNT(delivered_at) = 0 AND COUNT(*) >= $2
+            )
+            "#,
+        )
+        .bind(days)
+        .bind(min_failures)
+        .execute(&self.pool)
+        .timed("subscription", "deactivate_failing")
+        .await
+    }
+
+    /// Deactivate subscriptions signed with one of the retired VAPID keys
+    pub async fn deactivate_retired_keys(
+        &self,
+        retired_key_ids: Vec<String>,
+    ) -> Result<QueryResult, Error> {
+        sqlx::query(
+            r#"
+            UPDATE subscription SET active = false
+            WHERE active = true AND vapid_key_id = ANY($1)
+            "#,
+        )
+        .bind(retired_key_ids)
+        .execute(&self.pool)
+        .timed("subscription", "deactivate_retired_keys")
+        .await
+    }
+
+    /// Subscriptions per browser family, from the user agent, with their Web
+    /// Push deliveries since `from`
+    pub async fn count_by_browser(
+        &self,
+        from: DateTime<Utc>,
+    ) -> Result<Vec<BrowserStats>, Error> {
+        sqlx::query_as(
+            r#"
+            WITH s AS (
+                SELECT
+                    endpoint,
+                    active IS TRUE AS active,
+                    CASE
+                        WHEN user_agent IS NULL THEN 'unknown'
+                        WHEN user_agent ILIKE '%edg/%' THEN 'edge'
+                        WHEN user_agent ILIKE '%opr/%' THEN 'opera'
+                        WHEN user_agent ILIKE '%firefox/%' THEN 'firefox'
+                        WHEN user_agent ILIKE '%chrome/%' OR user_agent ILIKE '%crios/%' THEN 'chrome'
+                        WHEN user_agent ILIKE '%safari/%' THEN 'safari'
+                        ELSE 'other'
+                    END AS browser
+                FROM subscription
+            ),
+            d AS (
+                SELECT
+                    endpoint,
+                    COUNT(*) AS total,
+                    COUNT(*) FILTER (WHERE delivered_at IS NULL) AS failed
+                FROM push_delivery
+                WHERE channel = 'web_push' AND last_attempt_at >= $1
+                GROUP BY endpoint
+            )
+            SELECT
+                s.browser,
+                COUNT(*) FILTER (WHERE s.active) AS active,
+                COUNT(*) FILTER (WHERE NOT s.active) AS inactive,
+                COALESCE(SUM(d.total), 0)::BIGINT AS deliveries,
+                COALESCE(SUM(d.failed), 0)::BIGINT AS failed
+            FROM s
+            LEFT JOIN d ON d.endpoint = s.endpoint
+            GROUP BY s.browser
+            ORDER BY active DESC
+            "#,
+        )
+        .bind(from)
+        .fetch_all(&self.pool)
+        .timed("subscription", "count_by_browser")
+        .await
+    }
+
+    /// Active subscriptions per age bucket, youngest first
+    pub async fn count_by_age(&self) -> Result<Vec<(String, i64)>, Error> {
+        sqlx::query_as(
+            r#"
+            SELECT
+                CASE
+                    WHEN created_at >= now() - INTERVAL '1 day' THEN '<1d'
+                    WHEN created_at >= now() - INTERVAL '7 days' THEN '1-7d'
+                    WHEN created_at >= now() - INTERVAL '30 days' THEN '7-30d'
+                    WHEN created_at >= now() - INTERVAL '90 days' THEN '30-90d'
+                    ELSE '>90d'
+                END AS age,
+                COUNT(*)
+            FROM subscription
+            WHERE active = true
+            GROUP BY 1
+            ORDER BY MAX(created_at) DESC
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .timed("subscription", "count_by_age")
+        .await
+    }
+
+    /// Active subscriptions per VAPID key
+    pub async fn count_by_vapid_key(
+        &self,
+    ) -> Result<Vec<(String, i64)>, Error> {
+        sqlx::query_as(
+            r#"
+            SELECT vapid_key_id, COUNT(*) FROM subscription
+            WHERE active = true
+            GROUP BY vapid_key_id
+            ORDER BY vapid_key_id
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .timed("subscription", "count_by_vapid_key")
+        .await
+    }
 }
@@ -0,0 +1,193 @@
+use chrono::{DateTime, Utc};
+use sqlx::Error;
+
+use crate::{
+    model::{SubscriptionChannel, Table},
+    telemetry::Timed as _,
+};
+
+use super::QueryResult;
+
+impl Table<SubscriptionChannel> {
+    /// Add a channel to a subscription or update its state. The secret of an
+    /// existing webhook and a confirmation are kept; a new channel is
+    /// confirmed unless it is an email address.
+    pub async fn upsert(
+        &self,
+        address: String,
+        auth: String,
+        channel: String,
+        target: String,
+        secret: Option<String>,
+        active: bool,
+    ) -> Result<SubscriptionChannel, Error> {
+        sqlx::query_as(
+            r#"
+            INSERT INTO "subscription_channel" ("address", "auth", "channel", "target", "secret", "active", "confirmed_at")
+            VALUES ($1, $2, $3, $4, $5, $6, CASE WHEN $3 = 'email' THEN NULL ELSE now() END)
+            ON CONFLICT ("auth", "channel", "target") DO UPDATE SET
+                "address" = EXCLUDED."address",
+                "active" = EXCLUDED."active",
+                "secret" = COALESCE("subscription_channel"."secret", EXCLUDED."secret"),
+                "confirmed_at" = COALESCE("subscription_channel"."confirmed_at", EXCLUDED."confirmed_at")
+            RETURNING *
+            "#,
+        )
+        .bind(address)
+        .bind(auth)
+        .bind(channel)
+        .bind(target)
+        .bind(secret)
+        .bind(active)
+        .fetch_one(&self.pool)
+        .timed("subscription_channel", "upsert")
+        .await
+    }
+
+    pub async fn get_by_subscription(
+        &self,
+        address: String,
+        auth: String,
+    ) -> Result<Vec<SubscriptionChannel>, Error> {
+        sqlx::query_as(
+            r#"
+            SELECT * FROM "subscription_channel"
+            WHERE "address" = $1 AND "auth" = $2
+            ORDER BY "created_at" ASC
+            "#,
+        )
+        .bind(address)
+        .bind(auth)
+        .fetch_all(&self.pool)
+        .timed("subscription_channel", "get_by_subscription")
+        .await
+    }
+
+    /// Active channels of the given subscriptions, as pairs of address and
+    /// auth key
+    pub async fn get_active_by_subscriptions(
+        &self,
+        addresses: Vec<String>,
+        auths: Vec<String>,
+    ) -> Result<Vec<SubscriptionChannel>, Error> {
+        sqlx::query_as(
+            r#"
+            SELECT c.* FROM "subscription_channel" c
+            INNER JOIN UNNEST($1::VARCHAR[], $2::VARCHAR[]) AS s ("address", "auth")
+                ON s."address" = c."address" AND s."auth" = c."auth"
+            WHERE c."active" = true AND c."confirmed_at" IS NOT NULL
+            "#,
+        )
+        .bind(addresses)
+        .bind(auths)
+        .fetch_all(&self.pool)
+        .timed("subscription_channel", "get_active_by_subscriptions")
+        .await
+    }
+
+    pub async fn delete(
+        &self,
+        address: String,
+        auth: String,
+        channel: String,
+        target: String,
+    ) -> Result<QueryResult, Error> {
+        sqlx::query(
+            r#"
+            DELETE FROM "subscription_channel"
+            WHERE "address" = $1 AND "auth" = $2 AND "channel" = $3 AND "target" = $4
+            "#,
+        )
+        .bind(address)
+        .bind(auth)
+        .bind(channel)
+        .bind(target)
+        .execute(&self.pool)
+        .timed("subscription_channel", "delete")
+        .await
+    }
+
+    /// Stop delivering to a target the remote service reported as gone
+    pub async fn deactivate(
+        &self,
+        channel: String,
+        target: String,
+    ) -> Result<QueryResult, Error> {
+        sqlx::query(
+            r#"
+            UPDATE "subscription_channel" SET "active" = false
+            WHERE "channel" = $1 AND "target" = $2
+            "#,
+        )
+        .bind(channel)
+        .bind(target)
+        .execute(&self.pool)
+        .timed("subscription_channel", "deactivate")
+        .await
+    }
+
+    /// Store a new confirmation code of an unconfirmed channel, unless one
+    /// was sent after `resend_after`. Returns whether the code is to be
+    /// sent.
+    pub async fn start_confirmation(
+        &self,
+        auth: String,
+        channel: String,
+        target: String,
+        code: String,
+        resend_after: DateTime<Utc>,
+    ) -> Result<bool, Error> {
+        let result = sqlx::query(
+            r#"
+            UPDATE "subscription_channel" SET
+                "confirmation_code" = $4,
+                "confirmation_sent_at" = now()
+            WHERE "auth" = $1 AND "channel" = $2 AND "target" = $3
+                AND "confirmed_at" IS NULL
+                AND ("confirmation_sent_at" IS NULL OR "confirmation_sent_at" < $5)
+            "#,
+        )
+        .bind(auth)
+        .bind(channel)
+        .bind(target)
+        .bind(code)
+        .bind(resend_after)
+        .execute(&self.pool)
+        .timed("subscription_channel", "start_confirmation")
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Confirm a channel with the code sent to it after `sent_after`
+    pub async fn confirm(
+        &self,
+        address: String,
+        auth: String,
+        channel: String,
+        target: String,
+        code: String,
+        sent_after: DateTime<Utc>,
+    ) -> Result<QueryResult, Error> {
+        sqlx::query(
+            r#"
+            UPDATE "subscription_channel" SET
+                "confirmed_at" = now(),
+                "confirmation_code" = NULL
+            WHERE "address" = $1 AND "auth" = $2 AND "channel" = $3 AND "target" = $4
+                AND "confirmed_at" IS NULL
+                AND "confirmation_code" = $5
+                AND "confirmation_sent_at" > $6
+            "#,
+        )
+        .bind(address)
+        .bind(auth)
+        .bind(channel)
+        .bind(target)
+        .bind(code)
+        .bind(sent_after)
+        .execute(&self.pool)
+        .timed("subscription_channel", "confirm")
+        .await
+    }
+}
@@ -0,0 +1,67 @@
+use sqlx::Error;
+
+use crate::{
+    model::{LeaseSubscription, Table},
+    telemetry::Timed as _,
+};
+
+use super::QueryResult;
+
+impl Table<LeaseSubscription> {
+    pub async fn insert(
+        &self,
+        address: String,
+        auth: String,
+        lease: String,
+    ) -> Result<QueryResult, Error> {
+        sqlx::query(
+            r#"
+            INSERT INTO "subscription_lease" ("address", "auth", "lease")
+            VALUES ($1, $2, $3)
+            "#,
+        )
+        .bind(address)
+        .bind(auth)
+        .bind(lease)
+        .execute(&self.pool)
+        .timed("subscription_lease", "insert")
+        .await
+    }
+
+    pub async fn get_one(
+        &self,
+        auth: String,
+        lease: String,
+    ) -> Result<Option<LeaseSubscription>, Error> {
+        sqlx::query_as(
+            r#"
+            SELECT * FROM "subscription_lease" WHERE "auth" = $1 AND "lease" = $2
+            "#,
+        )
+        .bind(auth)
+        .bind(lease)
+        .persistent(true)
+        .fetch_optional(&self.pool)
+        .timed("subscription_lease", "get_one")
+        .await
+    }
+
+    pub async fn update(
+        &self,
+        active: bool,
+        auth: String,
+        lease: String,
+    ) -> Result<QueryResult, Error> {
+        sqlx::query(
+            r#"
+            UPDATE "subscription_lease" SET "active" = $1 WHERE "auth" = $2 AND "lease" = $3
+            "#,
+        )
+        .bind(active)
+        .bind(auth)
+        .bind(lease)
+        .execute(&self.pool)
+        .timed("subscription_lease", "update")
+        .await
+    }
+}
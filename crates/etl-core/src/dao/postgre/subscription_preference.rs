@@ -0,0 +1,113 @@
+use chrono::NaiveTime;
+use sqlx::{Error, FromRow};
+
+use crate::{
+    model::{SubscriptionPreference, Table},
+    telemetry::Timed as _,
+};
+
+/// Preferences of a subscription with the current time in its time zone
+#[derive(Debug, Clone, FromRow)]
+pub struct PreferenceState {
+    #[sqlx(flatten)]
+    pub preference: SubscriptionPreference,
+    pub local_time: NaiveTime,
+}
+
+impl Table<SubscriptionPreference> {
+    pub async fn get(
+        &self,
+        address: String,
+        auth: String,
+    ) -> Result<Option<SubscriptionPreference>, Error> {
+        sqlx::query_as(
+            r#"
+            SELECT * FROM "subscription_preference"
+            WHERE "address" = $1 AND "auth" = $2
+            "#,
+        )
+        .bind(address)
+        .bind(auth)
+        .fetch_optional(&self.pool)
+        .timed("subscription_preference", "get")
+        .await
+    }
+
+    pub async fn upsert(
+        &self,
+        preference: SubscriptionPreference,
+    ) -> Result<SubscriptionPreference, Error> {
+        sqlx::query_as(
+            r#"
+            INSERT INTO "subscription_preference" (
+                "address",
+                "auth",
+                "muted_types",
+                "min_interval_minutes",
+                "quiet_start",
+                "quiet_end",
+                "timezone",
+                "digest"
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            ON CONFLICT ("address", "auth") DO UPDATE SET
+                "muted_types" = EXCLUDED."muted_types",
+                "min_interval_minutes" = EXCLUDED."min_interval_minutes",
+                "quiet_start" = EXCLUDED."quiet_start",
+                "quiet_end" = EXCLUDED."quiet_end",
+                "timezone" = EXCLUDED."timezone",
+                "digest" = EXCLUDED."digest",
+                "updated_at" = now()
+            RETURNING *
+            "#,
+        )
+        .bind(preference.address)
+        .bind(preference.auth)
+        .bind(preference.muted_types)
+        .bind(preference.min_interval_minutes)
+        .bind(preference.quiet_start)
+        .bind(preference.quiet_end)
+        .bind(preference.timezone)
+        .bind(preference.digest)
+        .fetch_one(&self.pool)
+        .timed("subscription_preference", "upsert")
+        .await
+    }
+
+    /// Preferences of the given subscriptions, as pairs of address and auth
+    /// key
+    pub async fn get_by_subscriptions(
+        &self,
+        addresses: Vec<String>,
+        auths: Vec<String>,
+    ) -> Result<Vec<PreferenceState>, Error> {
+        sqlx::query_as(
+            r#"
+            SELECT p.*, (now() AT TIME ZONE p."timezone")::TIME AS "local_time"
+            FROM "subscription_preference" p
+            INNER JOIN UNNEST($1::VARCHAR[], $2::VARCHAR[]) AS s ("address", "auth")
+                ON s."address" = p."address" AND s."auth" = p."auth"
+            "#,
+        )
+        .bind(addresses)
+        .bind(auths)
+        .fetch_all(&self.pool)
+        .timed("subscription_preference", "get_by_subscriptions")
+        .await
+    }
+
+    /// Whether Postgres knows the IANA time zone
+    pub async fn is_timezone(&self, name: String) -> Result<bool, Error> {
+        let (exists,): (bool,) = sqlx::query_as(
+            r#"
+            SELECT EXISTS (SELECT 1 FROM pg_timezone_names WHERE "name" = $1)
+            "#,
+        )
+        .bind(name)
+        .fetch_one(&self.pool)
+        .timed("subscription_preference", "is_timezone")
+        .await?;
+
+        Ok(exists)
+    }
+}
@@ -3,7 +3,10 @@ use std::str::FromStr as _;
 use chrono::{DateTime, Utc};
 use sqlx::{types::BigDecimal, Error, QueryBuilder, Transaction};
 
-use crate::model::{Buyback, TR_Profit, Table};
+use crate::{
+    model::{Buyback, TR_Profit, Table},
+    telemetry::Timed as _,
+};
 
 use super::{DataBase, QueryResult};
 
@@ -41,6 +44,7 @@ impl Table<TR_Profit> {
         .bind(&data.Tx_Hash)
         .persistent(true)
         .execute(&mut **transaction)
+        .timed("tr_profit", "insert_if_not_exists")
         .await
     }
 
@@ -73,7 +77,10 @@ impl Table<TR_Profit> {
         });
 
         let query = query_builder.build().persistent(true);
-        query.execute(&mut **transaction).await?;
+        query
+            .execute(&mut **transaction)
+            .timed("tr_profit", "insert_many")
+            .await?;
         Ok(())
     }
 
@@ -94,6 +101,7 @@ impl Table<TR_Profit> {
         .bind(to)
         .persistent(true)
         .fetch_one(&self.pool)
+        .timed("tr_profit", "get_amnt_stable")
         .await?;
         let (amnt, amnt_nolus) = value;
         let amnt = amnt.unwrap_or(BigDecimal::from_str("0")?);
@@ -116,6 +124,7 @@ impl Table<TR_Profit> {
         .bind(limit)
         .persistent(true)
         .fetch_all(&self.pool)
+        .timed("tr_profit", "get_buyback")
         .await?;
         Ok(data)
     }
@@ -162,11 +171,13 @@ impl Table<TR_Profit> {
                 .bind(from_ts)
                 .persistent(true)
                 .fetch_all(&self.pool)
+                .timed("tr_profit", "get_buyback_with_window")
                 .await?
         } else {
             sqlx::query_as(&query)
                 .persistent(true)
                 .fetch_all(&self.pool)
+                .timed("tr_profit", "get_buyback_with_window")
                 .await?
         };
 
@@ -183,6 +194,7 @@ impl Table<TR_Profit> {
         )
         .persistent(true)
         .fetch_one(&self.pool)
+        .timed("tr_profit", "get_buyback_total")
         .await?;
 
         let (amnt,) = value;
@@ -199,6 +211,7 @@ impl Table<TR_Profit> {
         )
         .persistent(true)
         .fetch_one(&self.pool)
+        .timed("tr_profit", "get_revenue")
         .await?;
 
         let (amnt,) = value;
@@ -225,6 +238,7 @@ impl Table<TR_Profit> {
         )
         .persistent(true)
         .fetch_all(&self.pool)
+        .timed("tr_profit", "get_revenue_series")
         .await?;
 
         Ok(data)
@@ -267,6 +281,7 @@ impl Table<TR_Profit> {
             sqlx::query_as(&query_str)
                 .persistent(true)
                 .fetch_all(&self.pool)
+                .timed("tr_profit", "get_revenue_series_with_window")
                 .await?;
 
         Ok(data)
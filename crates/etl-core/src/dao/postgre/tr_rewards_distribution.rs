@@ -3,7 +3,10 @@ use std::str::FromStr as _;
 use chrono::{DateTime, Utc};
 use sqlx::{types::BigDecimal, Error, QueryBuilder, Transaction};
 
-use crate::model::{TR_Rewards_Distribution, Table};
+use crate::{
+    model::{TR_Rewards_Distribution, Table},
+    telemetry::Timed as _,
+};
 
 use super::{DataBase, QueryResult};
 
@@ -38,6 +41,7 @@ impl Table<TR_Rewards_Distribution> {
         .bind(data.Tx_Hash)
         .persistent(true)
         .execute(&mut **transaction)
+        .timed("tr_rewards_distribution", "insert_if_not_exists")
         .await
     }
 
@@ -74,7 +78,10 @@ impl Table<TR_Rewards_Distribution> {
         });
 
         let query = query_builder.build().persistent(true);
-        query.execute(&mut **transaction).await?;
+        query
+            .execute(&mut **transaction)
+            .timed("tr_rewards_distribution", "insert_many")
+            .await?;
         Ok(())
     }
 
@@ -94,6 +101,7 @@ impl Table<TR_Rewards_Distribution> {
         .bind(to)
         .persistent(true)
         .fetch_one(&self.pool)
+        .timed("tr_rewards_distribution", "get_amnt_stable")
         .await?;
         let (amnt,) = value;
         let amnt = amnt.unwrap_or(BigDecimal::from_str("0")?);
@@ -117,6 +125,7 @@ impl Table<TR_Rewards_Distribution> {
         .bind(to)
         .persistent(true)
         .fetch_one(&self.pool)
+        .timed("tr_rewards_distribution", "get_amnt_nls")
         .await?;
         let (amnt,) = value;
         let amnt = amnt.unwrap_or(BigDecimal::from_str("0")?);
@@ -134,6 +143,7 @@ impl Table<TR_Rewards_Distribution> {
         )
         .persistent(true)
         .fetch_one(&self.pool)
+        .timed("tr_rewards_distribution", "get_distributed")
         .await?;
 
         let (amnt,) = value;
@@ -3,7 +3,10 @@ use std::str::FromStr as _;
 use chrono::{DateTime, Utc};
 use sqlx::{types::BigDecimal, Error, QueryBuilder};
 
-use crate::model::{TR_State, Table};
+use crate::{
+    model::{TR_State, Table},
+    telemetry::Timed as _,
+};
 
 use super::{DataBase, QueryResult};
 
@@ -20,6 +23,7 @@ impl Table<TR_State> {
         .bind(&data.TR_amnt_nls)
         .persistent(true)
         .execute(&self.pool)
+        .timed("tr_state", "insert")
         .await
     }
 
@@ -44,7 +48,10 @@ impl Table<TR_State> {
         });
 
         let query = query_builder.build().persistent(true);
-        query.execute(&self.pool).await?;
+        query
+            .execute(&self.pool)
+            .timed("tr_state", "insert_many")
+            .await?;
         Ok(())
     }
 
@@ -64,6 +71,7 @@ impl Table<TR_State> {
         .bind(to)
         .persistent(true)
         .fetch_one(&self.pool)
+        .timed("tr_state", "get_amnt_stable")
         .await?;
         let (amnt,) = value;
         let amnt = amnt.unwrap_or(BigDecimal::from_str("0")?);
@@ -87,6 +95,7 @@ impl Table<TR_State> {
         .bind(to)
         .persistent(true)
         .fetch_one(&self.pool)
+        .timed("tr_state", "get_amnt_nls")
         .await?;
         let (amnt,) = value;
         let amnt = amnt.unwrap_or(BigDecimal::from_str("0")?);
@@ -104,6 +113,7 @@ impl Table<TR_State> {
         )
         .persistent(true)
         .fetch_optional(&self.pool)
+        .timed("tr_state", "get_incentives_pool")
         .await?;
         let amnt = value.unwrap_or((BigDecimal::from_str("0")?,));
 
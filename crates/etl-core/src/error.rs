@@ -18,6 +18,10 @@ use cosmrs::{
 };
 use ece::Error as ECE_ERROR;
 use jsonwebtoken::errors::Error as JWT_ERROR;
+use lettre::{
+    address::AddressError as EMAIL_ADDRESS_ERROR, error::Error as EMAIL_ERROR,
+    transport::smtp::Error as SMTP_ERROR,
+};
 use reqwest::header::{
     InvalidHeaderName as INVALID_HEADER_NAME,
     InvalidHeaderValue as INVALID_HEADER_VALUE,
@@ -158,14 +162,35 @@ pub enum Error {
     #[error("{0}")]
     JWT(#[from] JWT_ERROR),
 
+    #[error("{0}")]
+    SmtpError(#[from] SMTP_ERROR),
+
+    #[error("Invalid email address: {0}")]
+    EmailAddressError(#[from] EMAIL_ADDRESS_ERROR),
+
+    #[error("{0}")]
+    EmailError(#[from] EMAIL_ERROR),
+
     #[error("InvalidHeader error: {0}")]
     InvalidHeader(String),
 
     #[error("Missing params: {0}")]
     MissingParams(String),
 
+    #[error("Not found: {0}")]
+    NotFound(String),
+
     #[error("{0}")]
     AcquireError(#[from] ACQUIRE_ERROR),
+
+    #[error("Unauthorized: {0}")]
+    Unauthorized(String),
+
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
+
+    #[error("Rate limit exceeded, retry after {retry_after}s")]
+    RateLimited { retry_after: u64 },
 }
 
 impl From<Status> for Error {
@@ -244,6 +244,8 @@ impl From<Filter_Types> for Vec<String> {
                     CosmosTypes::MsgSend.to_string(),
                     CosmosTypes::MsgTransfer.to_string(),
                     CosmosTypes::MsgRecvPacket.to_string(),
+                    CosmosTypes::MsgAcknowledgement.to_string(),
+                    CosmosTypes::MsgTimeout.to_string(),
                 ]
             },
             Filter_Types::Earn => {
@@ -255,6 +257,7 @@ impl From<Filter_Types> for Vec<String> {
                     CosmosTypes::MsgUndelegate.to_string(),
                     CosmosTypes::MsgBeginRedelegate.to_string(),
                     CosmosTypes::MsgWithdrawDelegatorReward.to_string(),
+                    CosmosTypes::MsgCancelUnbondingDelegation.to_string(),
                 ]
             },
             Filter_Types::Positions => {
@@ -266,3 +269,582 @@ impl From<Filter_Types> for Vec<String> {
         }
     }
 }
+
+/// Access scope of an API key. Scopes are ordered, a key grants access to
+/// every route requiring its own scope or a lower one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ApiKeyScope {
+    Public,
+    Partner,
+    Admin,
+}
+
+impl fmt::Display for ApiKeyScope {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ApiKeyScope::Public => write!(f, "public"),
+            ApiKeyScope::Partner => write!(f, "partner"),
+            ApiKeyScope::Admin => write!(f, "admin"),
+        }
+    }
+}
+
+impl From<ApiKeyScope> for String {
+    fn from(value: ApiKeyScope) -> Self {
+        value.to_string()
+    }
+}
+
+impl FromStr for ApiKeyScope {
+    type Err = io::Error;
+
+    fn from_str(value: &str) -> Result<ApiKeyScope, Self::Err> {
+        match value {
+            "public" => Ok(ApiKeyScope::Public),
+            "partner" => Ok(ApiKeyScope::Partner),
+            "admin" => Ok(ApiKeyScope::Admin),
+            _ => Err(io::Error::other("ApiKeyScope not supported")),
+        }
+    }
+}
+
+/// Operation queued in `admin_command` for the ingest process
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AdminCommandType {
+    Aggregation,
+    Resync,
+    DecodeMessages,
+    StakingBackfill,
+    ActivityBackfill,
+}
+
+impl fmt::Display for AdminCommandType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AdminCommandType::Aggregation => write!(f, "aggregation"),
+            AdminCommandType::Resync => write!(f, "resync"),
+            AdminCommandType::DecodeMessages => write!(f, "decode_messages"),
+            AdminCommandType::StakingBackfill => write!(f, "staking_backfill"),
+            AdminCommandType::ActivityBackfill => {
+                write!(f, "activity_backfill")
+            },
+        }
+    }
+}
+
+impl From<AdminCommandType> for String {
+    fn from(value: AdminCommandType) -> Self {
+        value.to_string()
+    }
+}
+
+impl FromStr for AdminCommandType {
+    type Err = io::Error;
+
+    fn from_str(value: &str) -> Result<AdminCommandType, Self::Err> {
+        match value {
+            "aggregation" => Ok(AdminCommandType::Aggregation),
+            "resync" => Ok(AdminCommandType::Resync),
+            "decode_messages" => Ok(AdminCommandType::DecodeMessages),
+            "staking_backfill" => Ok(AdminCommandType::StakingBackfill),
+            "activity_backfill" => Ok(AdminCommandType::ActivityBackfill),
+            _ => Err(io::Error::other("AdminCommandType not supported")),
+        }
+    }
+}
+
+/// Part of the ingest process a deployment runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IngestRole {
+    /// Index new blocks from the WebSocket
+    Live,
+    /// Index the blocks missing from the block table, or a given range
+    Backfill,
+    /// Aggregation, admin commands, registry sync and the scheduled
+    /// notification jobs, on one instance at a time
+    Aggregator,
+    /// Collect prices from the oracles
+    Prices,
+}
+
+impl IngestRole {
+    pub const ALL: [IngestRole; 4] = [
+        IngestRole::Live,
+        IngestRole::Backfill,
+        IngestRole::Aggregator,
+        IngestRole::Prices,
+    ];
+}
+
+impl fmt::Display for IngestRole {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            IngestRole::Live => write!(f, "live"),
+            IngestRole::Backfill => write!(f, "backfill"),
+            IngestRole::Aggregator => write!(f, "aggregator"),
+            IngestRole::Prices => write!(f, "prices"),
+        }
+    }
+}
+
+impl From<IngestRole> for String {
+    fn from(value: IngestRole) -> Self {
+        value.to_string()
+    }
+}
+
+impl FromStr for IngestRole {
+    type Err = io::Error;
+
+    fn from_str(value: &str) -> Result<IngestRole, Self::Err> {
+        match value {
+            "live" => Ok(IngestRole::Live),
+            "backfill" => Ok(IngestRole::Backfill),
+            "aggregator" => Ok(IngestRole::Aggregator),
+            "prices" => Ok(IngestRole::Prices),
+            _ => Err(io::Error::other("IngestRole not supported")),
+        }
+    }
+}
+
+/// Direction of an ICS-20 transfer seen from this chain
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IbcTransferDirection {
+    Outgoing,
+    Incoming,
+}
+
+impl fmt::Display for IbcTransferDirection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            IbcTransferDirection::Outgoing => write!(f, "outgoing"),
+            IbcTransferDirection::Incoming => write!(f, "incoming"),
+        }
+    }
+}
+
+/// State of an ICS-20 transfer. Outgoing transfers are `Pending` until
+/// acknowledged or timed out; `Failed` and `TimedOut` ones were refunded to
+/// the sender. Incoming transfers are `Received`, or `Failed` when this chain
+/// wrote an error acknowledgement. Only pending transfers change state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IbcTransferStatus {
+    Pending,
+    Acknowledged,
+    Failed,
+    TimedOut,
+    Received,
+}
+
+impl fmt::Display for IbcTransferStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            IbcTransferStatus::Pending => write!(f, "pending"),
+            IbcTransferStatus::Acknowledged => write!(f, "acknowledged"),
+            IbcTransferStatus::Failed => write!(f, "failed"),
+            IbcTransferStatus::TimedOut => write!(f, "timed_out"),
+            IbcTransferStatus::Received => write!(f, "received"),
+        }
+    }
+}
+
+impl From<IbcTransferStatus> for String {
+    fn from(value: IbcTransferStatus) -> Self {
+        value.to_string()
+    }
+}
+
+impl FromStr for IbcTransferStatus {
+    type Err = io::Error;
+
+    fn from_str(value: &str) -> Result<IbcTransferStatus, Self::Err> {
+        match value {
+            "pending" => Ok(IbcTransferStatus::Pending),
+            "acknowledged" => Ok(IbcTransferStatus::Acknowledged),
+            "failed" => Ok(IbcTransferStatus::Failed),
+            "timed_out" => Ok(IbcTransferStatus::TimedOut),
+            "received" => Ok(IbcTransferStatus::Received),
+            _ => Err(io::Error::other("IbcTransferStatus not supported")),
+        }
+    }
+}
+
+/// Change of a delegation recorded in `staking_event`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StakingAction {
+    Delegate,
+    Undelegate,
+    Redelegate,
+    CancelUnbonding,
+    WithdrawRewards,
+}
+
+impl fmt::Display for StakingAction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StakingAction::Delegate => write!(f, "delegate"),
+            StakingAction::Undelegate => write!(f, "undelegate"),
+            StakingAction::Redelegate => write!(f, "redelegate"),
+            StakingAction::CancelUnbonding => write!(f, "cancel_unbonding"),
+            StakingAction::WithdrawRewards => write!(f, "withdraw_rewards"),
+        }
+    }
+}
+
+impl From<StakingAction> for String {
+    fn from(value: StakingAction) -> Self {
+        value.to_string()
+    }
+}
+
+/// Outcome filter of transaction listings
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TxStatus {
+    Success,
+    Failed,
+}
+
+impl FromStr for TxStatus {
+    type Err = io::Error;
+
+    fn from_str(value: &str) -> Result<TxStatus, Self::Err> {
+        match value {
+            "success" => Ok(TxStatus::Success),
+            "failed" => Ok(TxStatus::Failed),
+            _ => Err(io::Error::other("TxStatus not supported")),
+        }
+    }
+}
+
+/// Reason a transaction failed, from its result code, codespace and log
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TxFailure {
+    OutOfGas,
+    InsufficientFunds,
+    InsufficientFee,
+    Slippage,
+    ContractError,
+    Other,
+}
+
+impl fmt::Display for TxFailure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TxFailure::OutOfGas => write!(f, "out_of_gas"),
+            TxFailure::InsufficientFunds => write!(f, "insufficient_funds"),
+            TxFailure::InsufficientFee => write!(f, "insufficient_fee"),
+            TxFailure::Slippage => write!(f, "slippage"),
+            TxFailure::ContractError => write!(f, "contract_error"),
+            TxFailure::Other => write!(f, "other"),
+        }
+    }
+}
+
+impl From<TxFailure> for String {
+    fn from(value: TxFailure) -> Self {
+        value.to_string()
+    }
+}
+
+/// Dimension the daily fee series is broken down by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeeGroup {
+    Type,
+    Action,
+    Address,
+}
+
+impl FromStr for FeeGroup {
+    type Err = io::Error;
+
+    fn from_str(value: &str) -> Result<FeeGroup, Self::Err> {
+        match value {
+            "type" => Ok(FeeGroup::Type),
+            "action" => Ok(FeeGroup::Action),
+            "address" => Ok(FeeGroup::Address),
+            _ => Err(io::Error::other("FeeGroup not supported")),
+        }
+    }
+}
+
+/// Kind of a result of the search endpoint
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchHitKind {
+    Wallet,
+    Lease,
+    Pool,
+    Protocol,
+    Tx,
+    Block,
+}
+
+impl fmt::Display for SearchHitKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SearchHitKind::Wallet => write!(f, "wallet"),
+            SearchHitKind::Lease => write!(f, "lease"),
+            SearchHitKind::Pool => write!(f, "pool"),
+            SearchHitKind::Protocol => write!(f, "protocol"),
+            SearchHitKind::Tx => write!(f, "tx"),
+            SearchHitKind::Block => write!(f, "block"),
+        }
+    }
+}
+
+impl From<SearchHitKind> for String {
+    fn from(value: SearchHitKind) -> Self {
+        value.to_string()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AdminCommandStatus {
+    Pending,
+    Running,
+    Done,
+    Failed,
+}
+
+impl fmt::Display for AdminCommandStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AdminCommandStatus::Pending => write!(f, "pending"),
+            AdminCommandStatus::Running => write!(f, "running"),
+            AdminCommandStatus::Done => write!(f, "done"),
+            AdminCommandStatus::Failed => write!(f, "failed"),
+        }
+    }
+}
+
+impl From<AdminCommandStatus> for String {
+    fn from(value: AdminCommandStatus) -> Self {
+        value.to_string()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PushOutboxStatus {
+    Pending,
+    Done,
+    Failed,
+}
+
+impl fmt::Display for PushOutboxStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PushOutboxStatus::Pending => write!(f, "pending"),
+            PushOutboxStatus::Done => write!(f, "done"),
+            PushOutboxStatus::Failed => write!(f, "failed"),
+        }
+    }
+}
+
+impl From<PushOutboxStatus> for String {
+    fn from(value: PushOutboxStatus) -> Self {
+        value.to_string()
+    }
+}
+
+/// Condition checked by an `alert_rule`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlertRuleKind {
+    /// LTV of a lease, in percent
+    LeaseLtv,
+    /// Price of a currency on a protocol
+    Price,
+    /// Earnings of the wallet LP deposits, in stable
+    LpEarnings,
+    /// Lease closed, optionally by a given auto-close strategy
+    PositionClosed,
+}
+
+impl fmt::Display for AlertRuleKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AlertRuleKind::LeaseLtv => write!(f, "lease_ltv"),
+            AlertRuleKind::Price => write!(f, "price"),
+            AlertRuleKind::LpEarnings => write!(f, "lp_earnings"),
+            AlertRuleKind::PositionClosed => write!(f, "position_closed"),
+        }
+    }
+}
+
+impl From<AlertRuleKind> for String {
+    fn from(value: AlertRuleKind) -> Self {
+        value.to_string()
+    }
+}
+
+impl FromStr for AlertRuleKind {
+    type Err = io::Error;
+
+    fn from_str(value: &str) -> Result<AlertRuleKind, Self::Err> {
+        match value {
+            "lease_ltv" => Ok(AlertRuleKind::LeaseLtv),
+            "price" => Ok(AlertRuleKind::Price),
+            "lp_earnings" => Ok(AlertRuleKind::LpEarnings),
+            "position_closed" => Ok(AlertRuleKind::PositionClosed),
+            _ => Err(io::Error::other("AlertRuleKind not supported")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlertComparison {
+    Above,
+    Below,
+}
+
+impl fmt::Display for AlertComparison {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AlertComparison::Above => write!(f, "above"),
+            AlertComparison::Below => write!(f, "below"),
+        }
+    }
+}
+
+impl From<AlertComparison> for String {
+    fn from(value: AlertComparison) -> Self {
+        value.to_string()
+    }
+}
+
+impl FromStr for AlertComparison {
+    type Err = io::Error;
+
+    fn from_str(value: &str) -> Result<AlertComparison, Self::Err> {
+        match value {
+            "above" => Ok(AlertComparison::Above),
+            "below" => Ok(AlertComparison::Below),
+            _ => Err(io::Error::other("AlertComparison not supported")),
+        }
+    }
+}
+
+/// Channel a notification is delivered through
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChannelKind {
+    WebPush,
+    Webhook,
+    Email,
+    Bot,
+}
+
+impl fmt::Display for ChannelKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ChannelKind::WebPush => write!(f, "web_push"),
+            ChannelKind::Webhook => write!(f, "webhook"),
+            ChannelKind::Email => write!(f, "email"),
+            ChannelKind::Bot => write!(f, "bot"),
+        }
+    }
+}
+
+impl From<ChannelKind> for String {
+    fn from(value: ChannelKind) -> Self {
+        value.to_string()
+    }
+}
+
+impl FromStr for ChannelKind {
+    type Err = io::Error;
+
+    fn from_str(value: &str) -> Result<ChannelKind, Self::Err> {
+        match value {
+            "web_push" => Ok(ChannelKind::WebPush),
+            "webhook" => Ok(ChannelKind::Webhook),
+            "email" => Ok(ChannelKind::Email),
+            "bot" => Ok(ChannelKind::Bot),
+            _ => Err(io::Error::other("ChannelKind not supported")),
+        }
+    }
+}
+
+/// Kind of registry entry recorded in `registry_audit`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegistryEntity {
+    Protocol,
+    Currency,
+    Pool,
+}
+
+impl fmt::Display for RegistryEntity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RegistryEntity::Protocol => write!(f, "protocol"),
+            RegistryEntity::Currency => write!(f, "currency"),
+            RegistryEntity::Pool => write!(f, "pool"),
+        }
+    }
+}
+
+impl From<RegistryEntity> for String {
+    fn from(value: RegistryEntity) -> Self {
+        value.to_string()
+    }
+}
+
+impl FromStr for RegistryEntity {
+    type Err = io::Error;
+
+    fn from_str(value: &str) -> Result<RegistryEntity, Self::Err> {
+        match value {
+            "protocol" => Ok(RegistryEntity::Protocol),
+            "currency" => Ok(RegistryEntity::Currency),
+            "pool" => Ok(RegistryEntity::Pool),
+            _ => Err(io::Error::other("RegistryEntity not supported")),
+        }
+    }
+}
+
+/// Change of a registry entry found by a registry sync
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegistryChange {
+    Added,
+    Deprecated,
+}
+
+impl fmt::Display for RegistryChange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RegistryChange::Added => write!(f, "added"),
+            RegistryChange::Deprecated => write!(f, "deprecated"),
+        }
+    }
+}
+
+impl From<RegistryChange> for String {
+    fn from(value: RegistryChange) -> Self {
+        value.to_string()
+    }
+}
+
+/// Contract of a protocol tracked in `protocol_contract_history`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContractRole {
+    Leaser,
+    Lpp,
+    Oracle,
+    Profit,
+    Reserve,
+}
+
+impl fmt::Display for ContractRole {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ContractRole::Leaser => write!(f, "leaser"),
+            ContractRole::Lpp => write!(f, "lpp"),
+            ContractRole::Oracle => write!(f, "oracle"),
+            ContractRole::Profit => write!(f, "profit"),
+            ContractRole::Reserve => write!(f, "reserve"),
+        }
+    }
+}
+
+impl From<ContractRole> for String {
+    fn from(value: ContractRole) -> Self {
+        value.to_string()
+    }
+}
@@ -1,5 +1,7 @@
 mod enums;
+mod search;
 mod time_window;
 
 pub use enums::*;
+pub use search::*;
 pub use time_window::*;
@@ -0,0 +1,86 @@
+use std::str::FromStr as _;
+
+use cosmrs::AccountId;
+
+/// Shortest text matched against transaction memos
+pub const MIN_MEMO_QUERY: usize = 3;
+
+/// What a search input looks like, deciding where it is looked up
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SearchTerm {
+    /// Hex transaction hash, upper-cased as stored
+    TxHash(String),
+    /// Block height
+    Height(i64),
+    /// Bech32 account address, lower-cased
+    Account(String),
+    /// Bech32 contract address, lower-cased
+    Contract(String),
+    /// Anything else: protocol names and memo fragments
+    Text(String),
+}
+
+impl SearchTerm {
+    pub fn classify(query: &str) -> SearchTerm {
+        let query = query.trim();
+
+        if query.len() == 64 && query.chars().all(|c| c.is_ascii_hexdigit()) {
+            return SearchTerm::TxHash(query.to_uppercase());
+        }
+
+        if let Ok(height) = query.parse::<i64>() {
+            if height > 0 {
+                return SearchTerm::Height(height);
+            }
+        }
+
+        let address = query.to_lowercase();
+        if let Ok(account) = AccountId::from_str(&address) {
+            return match account.to_bytes().len() {
+                32 => SearchTerm::Contract(address),
+                _ => SearchTerm::Account(address),
+            };
+        }
+
+        SearchTerm::Text(query.to_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_hashes_and_heights() {
+        let hash =
+            "3f9a0c7b1e2d4f5a6b7c8d9e0f1a2b3c4d5e6f7a8b9c0d1e2f3a4b5c6d7e8f90";
+        assert_eq!(
+            SearchTerm::classify(hash),
+            SearchTerm::TxHash(hash.to_uppercase())
+        );
+        assert_eq!(SearchTerm::classify(" 1234 "), SearchTerm::Height(1234));
+        assert_eq!(
+            SearchTerm::classify("-5"),
+            SearchTerm::Text(String::from("-5"))
+        );
+    }
+
+    #[test]
+    fn classifies_addresses_by_length() {
+        let account = AccountId::new("nolus", &[7; 20]).unwrap().to_string();
+        let contract = AccountId::new("nolus", &[7; 32]).unwrap().to_string();
+
+        assert_eq!(
+            SearchTerm::classify(&account.to_uppercase()),
+            SearchTerm::Account(account)
+        );
+        assert_eq!(
+            SearchTerm::classify(&contract),
+            SearchTerm::Contract(contract)
+        );
+        assert_eq!(
+            SearchTerm::classify("nolus1notanaddress"),
+            SearchTerm::Text(String::from("nolus1notanaddress"))
+        );
+    }
+}
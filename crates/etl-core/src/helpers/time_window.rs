@@ -2,11 +2,12 @@ use chrono::{DateTime, Utc};
 use moka::future::Cache;
 use std::future::Future;
 
-use crate::error::Error;
+use crate::{error::Error, telemetry::metrics};
 
 /// Fetches a cached value or computes it using the provided async function.
 /// Uses Moka's built-in stampede protection: only one caller executes
 /// the fetch on a cache miss; concurrent callers wait for the result.
+/// Hits and misses are counted under the name of the cache.
 pub async fn cached_fetch<T, F, Fut>(
     cache: &Cache<String, T>,
     key: &str,
@@ -17,6 +18,19 @@ where
     F: FnOnce() -> Fut,
     Fut: Future<Output = Result<T, Error>>,
 {
+    let name = cache.name().unwrap_or("unnamed");
+    if let Some(value) = cache.get(key).await {
+        metrics()
+            .cache_requests
+            .with_label_values(&[name, "hit"])
+            .inc();
+        return Ok(value);
+    }
+
+    metrics()
+        .cache_requests
+        .with_label_values(&[name, "miss"])
+        .inc();
     cache
         .try_get_with_by_ref(key, fetch_fn())
         .await
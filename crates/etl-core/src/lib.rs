@@ -1,7 +1,9 @@
 #![allow(non_camel_case_types)]
 #![allow(non_snake_case)]
 
+pub mod alerts;
 pub mod cache_keys;
+pub mod channel;
 pub mod configuration;
 pub mod dao;
 pub mod error;
@@ -10,4 +12,7 @@ pub mod migration;
 pub mod model;
 pub mod provider;
 pub mod push;
+pub mod settings;
+pub mod telemetry;
+pub mod template;
 pub mod types;
@@ -98,8 +98,8 @@ mod tests {
             prev_version = *version;
         }
 
-        // Verify we have all expected migrations (V001 through V017)
-        assert_eq!(sorted_versions.len(), 17, "Expected 17 migrations");
+        // Verify we have all expected migrations (V001 through V043)
+        assert_eq!(sorted_versions.len(), 43, "Expected 43 migrations");
         assert_eq!(
             sorted_versions.first(),
             Some(&1),
@@ -107,8 +107,8 @@ mod tests {
         );
         assert_eq!(
             sorted_versions.last(),
-            Some(&17),
-            "Last migration should be V017"
+            Some(&43),
+            "Last migration should be V043"
         );
     }
 }
@@ -11,7 +11,10 @@ mod table;
 pub use models::*;
 
 // Re-export from raw_message
-pub use raw_message::{CosmosTypes, Raw_Message};
+pub use raw_message::{
+    classify_failure, decode_message, local_denom, received_denom, CosmosTypes,
+    MessageCoin, MessageData, Raw_Message, TransferPacket,
+};
 
 // Re-export from table
 pub use table::Table;
@@ -2,10 +2,10 @@
 //!
 //! All database entity structs organized by domain sections.
 
-use std::{fmt, io, str::FromStr};
+use std::{collections::HashMap, fmt, io, str::FromStr};
 
 use bigdecimal::BigDecimal;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveTime, Utc};
 use cosmrs::proto::{tendermint::abci::Event, Timestamp};
 use cosmrs::{tx::Fee, Any};
 use serde::{Deserialize, Serialize};
@@ -485,7 +485,7 @@ pub struct Block {
     pub id: i64,
 }
 
-#[derive(Debug, FromRow)]
+#[derive(Debug, FromRow, Serialize)]
 pub struct Action_History {
     pub action_type: String,
     pub created_at: DateTime<Utc>,
@@ -629,7 +629,7 @@ pub struct ProtocolRegistry {
     pub deprecated_at: Option<DateTime<Utc>>,
 }
 
-#[derive(Debug, FromRow)]
+#[derive(Debug, Clone, FromRow)]
 pub struct Subscription {
     pub active: Option<bool>,
     pub address: String,
@@ -639,6 +639,329 @@ pub struct Subscription {
     pub expiration: Option<DateTime<Utc>>,
     pub ip: Option<String>,
     pub user_agent: Option<String>,
+    /// Deliver to the browser itself, besides the channels of the
+    /// subscription
+    pub web_push: bool,
+    /// Locale notifications are rendered in
+    pub locale: String,
+    /// VAPID key the browser subscribed with
+    pub vapid_key_id: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Notification channel of a browser subscription besides Web Push. `target`
+/// is the webhook URL, email address or bot chat id.
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct SubscriptionChannel {
+    pub address: String,
+    #[serde(skip)]
+    pub auth: String,
+    pub channel: String,
+    pub target: String,
+    /// HMAC key of webhook signatures
+    #[serde(skip)]
+    pub secret: Option<String>,
+    pub active: bool,
+    pub created_at: DateTime<Utc>,
+    /// Unset for an email address that did not confirm the code mailed to
+    /// it yet; nothing else is delivered to it until then
+    pub confirmed_at: Option<DateTime<Utc>>,
+    #[serde(skip)]
+    pub confirmation_code: Option<String>,
+    #[serde(skip)]
+    pub confirmation_sent_at: Option<DateTime<Utc>>,
+}
+
+/// Notification preferences of a browser subscription. Subscriptions without
+/// a row get every type, the configured minimum interval and no quiet hours.
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct SubscriptionPreference {
+    pub address: String,
+    #[serde(skip)]
+    pub auth: String,
+    /// `PUSH_TYPES` that are not delivered
+    pub muted_types: Vec<String>,
+    /// Minimum time between two notifications of a type for the same lease,
+    /// the configured default when null
+    pub min_interval_minutes: Option<i32>,
+    /// Start and end of the quiet hours in `timezone`; the end may be on the
+    /// next day
+    pub quiet_start: Option<NaiveTime>,
+    pub quiet_end: Option<NaiveTime>,
+    /// IANA time zone name
+    pub timezone: String,
+    /// Send notifications held back as a digest instead of dropping them
+    pub digest: bool,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// ICS-20 transfer to or from this chain, see
+/// [`crate::helpers::IbcTransferStatus`]. `denom` is the denom of the funds
+/// on this chain, `tx_hash` the transaction that sent an outgoing transfer
+/// and `completion_tx_hash` the one that received, acknowledged or timed it
+/// out.
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct IbcTransfer {
+    pub source_channel: String,
+    pub sequence: i64,
+    pub direction: String,
+    pub source_port: String,
+    pub destination_port: String,
+    pub destination_channel: String,
+    pub sender: String,
+    pub receiver: String,
+    pub denom: String,
+    pub packet_denom: String,
+    pub amount: BigDecimal,
+    pub status: String,
+    pub error: Option<String>,
+    pub timeout_at: Option<DateTime<Utc>>,
+    pub tx_hash: Option<String>,
+    pub sent_at: Option<DateTime<Utc>>,
+    pub completion_tx_hash: Option<String>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+impl IbcTransfer {
+    /// Primary key of the row. Incoming packets carry the source channel of
+    /// the counterparty, so the channel they arrived on is part of it.
+    pub fn key(&self) -> (&str, &str, i64, &str) {
+        (
+            &self.source_channel,
+            &self.destination_channel,
+            self.sequence,
+            &self.direction,
+        )
+    }
+}
+
+/// Change of a delegation, see [`crate::helpers::StakingAction`]. `amount`
+/// is the signed change of the staked balance; `completion_time` is set on
+/// undelegations and `creation_height` on cancelled unbondings, pointing to
+/// the block of the undelegation.
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct StakingEvent {
+    pub tx_hash: String,
+    pub index: i32,
+    pub inner_index: i32,
+    pub validator: String,
+    pub delegator: String,
+    pub action: String,
+    pub amount: BigDecimal,
+    pub rewards: BigDecimal,
+    pub rewards_stable: Option<BigDecimal>,
+    pub completion_time: Option<DateTime<Utc>>,
+    pub creation_height: Option<i64>,
+    pub block: i64,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Latest vote of a voter on a governance proposal
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct GovernanceVote {
+    pub proposal_id: i64,
+    pub voter: String,
+    pub option: String,
+    pub tx_hash: String,
+    pub block: i64,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Fees and gas of the transactions of a day by the type and contract
+/// action of their first message and their fee payer
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct FeeDaily {
+    pub day: DateTime<Utc>,
+    pub r#type: String,
+    pub action: String,
+    pub address: String,
+    pub fee_denom: String,
+    pub txs: i64,
+    pub failed: i64,
+    pub fee_amount: BigDecimal,
+    pub gas_wanted: BigDecimal,
+    pub gas_used: BigDecimal,
+}
+
+/// Part taken by an address in a message, with what the message did and the
+/// lease or pool it touched
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct AddressActivity {
+    pub address: String,
+    pub tx_hash: String,
+    pub index: i32,
+    pub inner_index: i32,
+    pub role: String,
+    pub r#type: String,
+    pub kind: String,
+    pub lease: Option<String>,
+    pub pool: Option<String>,
+    pub to: Option<String>,
+    pub block: i64,
+    pub timestamp: DateTime<Utc>,
+    pub code: Option<i32>,
+}
+
+/// Protocol, currency or pool added to or deprecated from the registry
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct RegistryAudit {
+    pub id: i64,
+    pub entity: String,
+    pub name: String,
+    pub change: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Contract of a role of a protocol and the heights it was valid over, both
+/// inclusive. `valid_to` is `None` while the contract is current.
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct ProtocolContractHistory {
+    pub protocol: String,
+    pub role: String,
+    pub contract: String,
+    pub valid_from: i64,
+    pub valid_to: Option<i64>,
+    pub recorded_at: DateTime<Utc>,
+}
+
+impl ProtocolContractHistory {
+    pub fn covers(&self, height: i64) -> bool {
+        self.valid_from <= height
+            && self.valid_to.map_or(true, |to| height <= to)
+    }
+}
+
+/// Notification held back from a subscription by its quiet hours or minimum
+/// interval, until sent in a digest
+#[derive(Debug, Clone, FromRow)]
+pub struct PushDigest {
+    pub address: String,
+    pub auth: String,
+    pub notification_id: i64,
+    pub push_type: String,
+    pub lease: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Last notification of a type for a lease delivered to a subscription.
+/// `lease` is empty for notifications of a wallet.
+#[derive(Debug, Clone, FromRow)]
+pub struct PushThrottle {
+    pub address: String,
+    pub auth: String,
+    pub lease: String,
+    pub push_type: String,
+    pub notification_id: i64,
+    pub sent_at: DateTime<Utc>,
+}
+
+/// Browser subscription to the notifications of a single lease
+#[derive(Debug, Clone, FromRow)]
+pub struct LeaseSubscription {
+    pub address: String,
+    pub auth: String,
+    pub lease: String,
+    pub active: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Push notification sent for a wallet or a lease
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct PushNotification {
+    pub id: i64,
+    pub push_type: String,
+    pub lease: Option<String>,
+    pub owner: Option<String>,
+    pub recipients: i32,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Delivery of a push notification through one channel of a subscription.
+/// `delivered_at` is set once the remote service accepted it.
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct PushDelivery {
+    pub notification_id: i64,
+    pub address: String,
+    pub channel: String,
+    pub endpoint: String,
+    pub status_code: Option<i32>,
+    pub delivered_at: Option<DateTime<Utc>>,
+    pub attempts: i32,
+    pub latency_ms: Option<i32>,
+    pub error: Option<String>,
+    pub last_attempt_at: DateTime<Utc>,
+}
+
+/// Push notification queued with the event that triggered it
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct PushOutbox {
+    pub id: i64,
+    pub push_type: String,
+    pub body: String,
+    pub recipient_type: String,
+    pub recipient: String,
+    pub notification_id: Option<i64>,
+    pub status: String,
+    pub attempts: i32,
+    pub next_attempt_at: DateTime<Utc>,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub processed_at: Option<DateTime<Utc>>,
+}
+
+/// Condition of a subscription that triggers a push notification. Fields
+/// that do not apply to the rule `kind` are null.
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct AlertRule {
+    pub id: i64,
+    pub address: String,
+    #[serde(skip)]
+    pub auth: String,
+    pub kind: String,
+    pub lease: Option<String>,
+    pub symbol: Option<String>,
+    pub protocol: Option<String>,
+    pub comparison: Option<String>,
+    pub threshold: Option<SqlxBigDecimal>,
+    pub strategy: Option<String>,
+    pub active: bool,
+    /// Whether the condition held on the last evaluation
+    pub triggered: bool,
+    pub last_value: Option<SqlxBigDecimal>,
+    pub last_triggered_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// API key - only the SHA-256 hash of the key is persisted
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct ApiKey {
+    #[serde(skip)]
+    pub key_hash: String,
+    pub key_prefix: String,
+    pub name: String,
+    pub scope: String,
+    pub rate_limit_per_minute: i32,
+    pub burst: i32,
+    pub active: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Operation requested through the admin API, executed by the ingest process
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct AdminCommand {
+    pub id: i64,
+    pub command: String,
+    pub from_height: Option<i64>,
+    pub to_height: Option<i64>,
+    pub status: String,
+    pub error: Option<String>,
+    pub requested_by: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub finished_at: Option<DateTime<Utc>>,
+    /// Number of times the command was claimed, identifying the current run
+    pub claim: i64,
 }
 
 // =============================================================================
@@ -780,6 +1103,10 @@ pub struct RawTxParams<'a> {
     pub tx_data: Any,
     pub height: i64,
     pub code: u32,
+    pub codespace: String,
+    pub raw_log: String,
+    pub gas_wanted: i64,
+    pub gas_used: i64,
     pub time_stamp: Timestamp,
     pub tx_events: &'a [Event],
 }
@@ -795,4 +1122,6 @@ pub struct RawMsgParams<'a> {
     pub events: Vec<String>,
     pub tx_events: &'a [Event],
     pub code: u32,
+    /// Bank symbol (uppercase) to ticker, for the decoded amounts
+    pub denom_tickers: &'a HashMap<String, String>,
 }
@@ -1,4 +1,4 @@
-use std::{fmt, io, str::FromStr};
+use std::{collections::HashMap, fmt, io, str::FromStr};
 
 use anyhow::{anyhow, Context as _};
 use base64::engine::{general_purpose::STANDARD as BASE64_STANDARD, Engine};
@@ -6,29 +6,54 @@ use bigdecimal::BigDecimal;
 use chrono::{DateTime, Utc};
 use cosmrs::proto::{
     cosmos::{
+        authz::v1beta1::{MsgExec, MsgGrant},
         bank::v1beta1::MsgSend,
         distribution::v1beta1::MsgWithdrawDelegatorReward,
-        gov::{v1::MsgVote, v1beta1::MsgVote as MsgVoteLegacy},
-        staking::v1beta1::{MsgBeginRedelegate, MsgDelegate, MsgUndelegate},
+        feegrant::v1beta1::MsgGrantAllowance,
+        gov::{
+            v1::{MsgDeposit, MsgSubmitProposal, MsgVote, VoteOption},
+            v1beta1::{
+                MsgDeposit as MsgDepositLegacy,
+                MsgSubmitProposal as MsgSubmitProposalLegacy,
+                MsgVote as MsgVoteLegacy,
+            },
+        },
+        staking::v1beta1::{
+            MsgBeginRedelegate, MsgCancelUnbondingDelegation, MsgDelegate,
+            MsgUndelegate,
+        },
+    },
+    cosmwasm::wasm::v1::{
+        MsgExecuteContract, MsgInstantiateContract, MsgMigrateContract,
     },
-    cosmwasm::wasm::v1::MsgExecuteContract,
     tendermint::abci::Event,
 };
+use cosmrs::Any;
 use ibc_proto::ibc::{
-    applications::transfer::v1::MsgTransfer, core::channel::v1::MsgRecvPacket,
+    applications::transfer::v1::MsgTransfer,
+    core::channel::v1::{
+        MsgAcknowledgement, MsgRecvPacket, MsgTimeout, Packet,
+    },
 };
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use sqlx::FromRow;
 
-use crate::{error::Error, model::RawMsgParams, types::MsgReceivePacket};
+use crate::{
+    error::Error, helpers::TxFailure, model::RawMsgParams,
+    types::MsgReceivePacket,
+};
 
 #[derive(Debug, FromRow, Default, Serialize, Deserialize)]
 pub struct Raw_Message {
     pub index: i32,
+    /// Position within an authz `MsgExec` from 1, 0 for top-level messages
+    pub inner_index: i32,
     pub from: String,
     pub to: String,
     pub r#type: String,
+    /// Base64 protobuf body, superseded by `data` in responses
+    #[serde(skip_serializing)]
     pub value: String,
     pub tx_hash: String,
     pub block: i64,
@@ -38,11 +63,67 @@ pub struct Raw_Message {
     pub timestamp: DateTime<Utc>,
     pub rewards: Option<String>,
     pub code: Option<i32>,
+    /// Grantee that executed the message through authz
+    pub grantee: Option<String>,
+    /// Decoded body, see [`MessageData`]
+    pub data: Option<Value>,
+    /// Module the result code of a failed transaction belongs to
+    pub codespace: Option<String>,
+    /// Error log of a failed transaction
+    pub raw_log: Option<String>,
+    /// Reason a transaction failed, see [`TxFailure`]
+    pub failure: Option<String>,
+    pub gas_wanted: Option<i64>,
+    pub gas_used: Option<i64>,
 }
 
 impl Raw_Message {
+    /// Rows of a message. An authz `MsgExec` is unwrapped into a row per
+    /// supported inner message, attributed to the granter and recording the
+    /// grantee; any other message is a single row.
     pub fn from_any(
         params: RawMsgParams<'_>,
+    ) -> Result<Vec<Raw_Message>, anyhow::Error> {
+        if params.value.type_url != CosmosTypes::MsgExec.to_string() {
+            return Ok(vec![Self::decoded(params)?]);
+        }
+
+        let exec = params.value.to_msg::<MsgExec>()?;
+        let mut messages = Vec::with_capacity(exec.msgs.len());
+        for (inner_index, msg) in exec.msgs.into_iter().enumerate() {
+            let inner = Self::decoded(RawMsgParams {
+                value: msg,
+                tx_hash: params.tx_hash.to_owned(),
+                fee: params.fee.clone(),
+                memo: params.memo.to_owned(),
+                events: params.events.clone(),
+                ..params
+            });
+
+            if let Ok(mut message) = inner {
+                message.inner_index = (inner_index + 1).try_into()?;
+                message.grantee = Some(exec.grantee.to_owned());
+                messages.push(message);
+            }
+        }
+
+        Ok(messages)
+    }
+
+    fn decoded(params: RawMsgParams<'_>) -> Result<Raw_Message, anyhow::Error> {
+        let value = params.value.clone();
+        let denom_tickers = params.denom_tickers;
+
+        let mut message = Self::parse_any(params)?;
+        let data =
+            decode_message(&value, message.rewards.as_deref(), denom_tickers)?;
+        message.data = Some(serde_json::to_value(data)?);
+
+        Ok(message)
+    }
+
+    fn parse_any(
+        params: RawMsgParams<'_>,
     ) -> Result<Raw_Message, anyhow::Error> {
         let RawMsgParams {
             index,
@@ -55,6 +136,7 @@ impl Raw_Message {
             events,
             tx_events,
             code,
+            ..
         } = params;
 
         let k = CosmosTypes::from_str(&value.type_url)?;
@@ -65,106 +147,67 @@ impl Raw_Message {
             Some(f) => (f.amount, Some(f.denom.to_string())),
             None => (0, None),
         };
+        let timestamp = DateTime::from_timestamp(seconds, nanos)
+            .context("Could not parse time stamp")?;
+        let code = Some(code.try_into()?);
+
+        let row = |from: String, to: String, rewards: Option<String>| {
+            Ok(Raw_Message {
+                index,
+                inner_index: 0,
+                from,
+                to,
+                r#type: value.type_url.to_owned(),
+                value: BASE64_STANDARD.encode(&value.value),
+                tx_hash: tx_hash.to_owned(),
+                block,
+                fee_amount: BigDecimal::from(fee_amount),
+                fee_denom: fee_denom.to_owned(),
+                memo: memo.to_owned(),
+                timestamp,
+                rewards,
+                code,
+                grantee: None,
+                data: None,
+                codespace: None,
+                raw_log: None,
+                failure: None,
+                gas_wanted: None,
+                gas_used: None,
+            })
+        };
 
         match k {
             CosmosTypes::MsgSend => {
                 let m = value.to_msg::<MsgSend>()?;
-                Ok(Raw_Message {
-                    index,
-                    from: m.from_address,
-                    to: m.to_address,
-                    r#type: value.type_url,
-                    tx_hash,
-                    block,
-                    fee_amount: BigDecimal::from(fee_amount),
-                    fee_denom,
-                    timestamp: DateTime::from_timestamp(seconds, nanos)
-                        .context("Could not parse time stamp")?,
-                    value: BASE64_STANDARD.encode(value.value),
-                    memo,
-                    rewards: None,
-                    code: Some(code.try_into()?),
-                })
+                row(m.from_address, m.to_address, None)
             },
             CosmosTypes::MsgTransfer => {
                 let m = value.to_msg::<MsgTransfer>()?;
-                Ok(Raw_Message {
-                    index,
-                    from: m.sender,
-                    to: m.receiver,
-                    r#type: value.type_url,
-                    tx_hash,
-                    block,
-                    fee_amount: BigDecimal::from(fee_amount),
-                    fee_denom,
-                    timestamp: DateTime::from_timestamp(seconds, nanos)
-                        .context("Could not parse time stamp")?,
-                    value: BASE64_STANDARD.encode(value.value),
-                    memo,
-                    rewards: None,
-                    code: Some(code.try_into()?),
-                })
+                row(m.sender, m.receiver, None)
             },
             CosmosTypes::MsgVoteLegacy => {
                 let m = value.to_msg::<MsgVoteLegacy>()?;
-                Ok(Raw_Message {
-                    index,
-                    from: m.voter,
-                    to: m.proposal_id.to_string(),
-                    r#type: value.type_url,
-                    tx_hash,
-                    block,
-                    fee_amount: BigDecimal::from(fee_amount),
-                    fee_denom,
-                    timestamp: DateTime::from_timestamp(seconds, nanos)
-                        .context("Could not parse time stamp")?,
-                    value: BASE64_STANDARD.encode(value.value),
-                    memo,
-                    rewards: None,
-                    code: Some(code.try_into()?),
-                })
+                row(m.voter, m.proposal_id.to_string(), None)
             },
             CosmosTypes::MsgVote => {
                 let m = value.to_msg::<MsgVote>()?;
-                Ok(Raw_Message {
-                    index,
-                    from: m.voter,
-                    to: m.proposal_id.to_string(),
-                    r#type: value.type_url,
-                    tx_hash,
-                    block,
-                    fee_amount: BigDecimal::from(fee_amount),
-                    fee_denom,
-                    timestamp: DateTime::from_timestamp(seconds, nanos)
-                        .context("Could not parse time stamp")?,
-                    value: BASE64_STANDARD.encode(value.value),
-                    memo,
-                    rewards: None,
-                    code: Some(code.try_into()?),
-                })
+                row(m.voter, m.proposal_id.to_string(), None)
             },
             CosmosTypes::MsgRecvPacket => {
                 let m = value.to_msg::<MsgRecvPacket>()?;
-                let packet = m.packet.context("unable to get packets")?;
-                let data =
-                    serde_json::from_slice::<MsgReceivePacket>(&packet.data)?;
-
-                Ok(Raw_Message {
-                    index,
-                    from: data.sender,
-                    to: data.receiver,
-                    r#type: value.type_url,
-                    tx_hash,
-                    block,
-                    fee_amount: BigDecimal::from(fee_amount),
-                    fee_denom,
-                    timestamp: DateTime::from_timestamp(seconds, nanos)
-                        .context("Could not parse time stamp")?,
-                    value: BASE64_STANDARD.encode(value.value),
-                    memo,
-                    rewards: None,
-                    code: Some(code.try_into()?),
-                })
+                let data = transfer_packet(m.packet)?.1;
+                row(data.sender, data.receiver, None)
+            },
+            CosmosTypes::MsgAcknowledgement => {
+                let m = value.to_msg::<MsgAcknowledgement>()?;
+                let data = transfer_packet(m.packet)?.1;
+                row(data.sender, data.receiver, None)
+            },
+            CosmosTypes::MsgTimeout => {
+                let m = value.to_msg::<MsgTimeout>()?;
+                let data = transfer_packet(m.packet)?.1;
+                row(data.sender, data.receiver, None)
             },
             CosmosTypes::MsgWithdrawDelegatorReward => {
                 let m = value.to_msg::<MsgWithdrawDelegatorReward>()?;
@@ -173,79 +216,58 @@ impl Raw_Message {
                     m.delegator_address.to_owned(),
                     tx_events,
                 )?;
-                Ok(Raw_Message {
-                    index,
-                    from: m.delegator_address,
-                    to: m.validator_address,
-                    r#type: value.type_url,
-                    tx_hash,
-                    block,
-                    fee_amount: BigDecimal::from(fee_amount),
-                    fee_denom,
-                    timestamp: DateTime::from_timestamp(seconds, nanos)
-                        .context("Could not parse time stamp")?,
-                    value: BASE64_STANDARD.encode(value.value),
-                    memo,
-                    rewards: amount,
-                    code: Some(code.try_into()?),
-                })
+                row(m.delegator_address, m.validator_address, amount)
             },
             CosmosTypes::MsgDelegate => {
                 let m = value.to_msg::<MsgDelegate>()?;
-                Ok(Raw_Message {
-                    index,
-                    from: m.delegator_address,
-                    to: m.validator_address,
-                    r#type: value.type_url,
-                    tx_hash,
-                    block,
-                    fee_amount: BigDecimal::from(fee_amount),
-                    fee_denom,
-                    timestamp: DateTime::from_timestamp(seconds, nanos)
-                        .context("Could not parse time stamp")?,
-                    value: BASE64_STANDARD.encode(value.value),
-                    memo,
-                    rewards: None,
-                    code: Some(code.try_into()?),
-                })
+                row(m.delegator_address, m.validator_address, None)
             },
             CosmosTypes::MsgBeginRedelegate => {
                 let m = value.to_msg::<MsgBeginRedelegate>()?;
-                Ok(Raw_Message {
-                    index,
-                    from: m.delegator_address,
-                    to: m.validator_dst_address,
-                    r#type: value.type_url,
-                    tx_hash,
-                    block,
-                    fee_amount: BigDecimal::from(fee_amount),
-                    fee_denom,
-                    timestamp: DateTime::from_timestamp(seconds, nanos)
-                        .context("Could not parse time stamp")?,
-                    value: BASE64_STANDARD.encode(value.value),
-                    memo,
-                    rewards: None,
-                    code: Some(code.try_into()?),
-                })
+                row(m.delegator_address, m.validator_dst_address, None)
             },
             CosmosTypes::MsgUndelegate => {
                 let m = value.to_msg::<MsgUndelegate>()?;
-                Ok(Raw_Message {
-                    index,
-                    from: m.delegator_address,
-                    to: m.validator_address,
-                    r#type: value.type_url,
-                    tx_hash,
-                    block,
-                    fee_amount: BigDecimal::from(fee_amount),
-                    fee_denom,
-                    timestamp: DateTime::from_timestamp(seconds, nanos)
-                        .context("Could not parse time stamp")?,
-                    value: BASE64_STANDARD.encode(value.value),
-                    memo,
-                    rewards: None,
-                    code: Some(code.try_into()?),
-                })
+                row(m.delegator_address, m.validator_address, None)
+            },
+            CosmosTypes::MsgCancelUnbondingDelegation => {
+                let m = value.to_msg::<MsgCancelUnbondingDelegation>()?;
+                row(m.delegator_address, m.validator_address, None)
+            },
+            CosmosTypes::MsgSubmitProposal => {
+                let m = value.to_msg::<MsgSubmitProposal>()?;
+                row(m.proposer, submitted_proposal(tx_events), None)
+            },
+            CosmosTypes::MsgSubmitProposalLegacy => {
+                let m = value.to_msg::<MsgSubmitProposalLegacy>()?;
+                row(m.proposer, submitted_proposal(tx_events), None)
+            },
+            CosmosTypes::MsgDeposit => {
+                let m = value.to_msg::<MsgDeposit>()?;
+                row(m.depositor, m.proposal_id.to_string(), None)
+            },
+            CosmosTypes::MsgDepositLegacy => {
+                let m = value.to_msg::<MsgDepositLegacy>()?;
+                row(m.depositor, m.proposal_id.to_string(), None)
+            },
+            CosmosTypes::MsgGrant => {
+                let m = value.to_msg::<MsgGrant>()?;
+                row(m.granter, m.grantee, None)
+            },
+            CosmosTypes::MsgGrantAllowance => {
+                let m = value.to_msg::<MsgGrantAllowance>()?;
+                row(m.granter, m.grantee, None)
+            },
+            CosmosTypes::MsgExec => {
+                Err(anyhow!("Nested CosmosTypes::MsgExec is not unwrapped"))
+            },
+            CosmosTypes::MsgInstantiateContract => {
+                let m = value.to_msg::<MsgInstantiateContract>()?;
+                row(m.sender, instantiated_contract(tx_events, m.code_id), None)
+            },
+            CosmosTypes::MsgMigrateContract => {
+                let m = value.to_msg::<MsgMigrateContract>()?;
+                row(m.sender, m.contract, None)
             },
             CosmosTypes::MsgExecuteContract => {
                 let m = value.to_msg::<MsgExecuteContract>()?;
@@ -265,22 +287,7 @@ impl Raw_Message {
                             }
                         };
 
-                        return Ok(Raw_Message {
-                            index,
-                            from: m.sender,
-                            to: m.contract,
-                            r#type: value.type_url,
-                            tx_hash,
-                            block,
-                            fee_amount: BigDecimal::from(fee_amount),
-                            fee_denom,
-                            timestamp: DateTime::from_timestamp(seconds, nanos)
-                                .context("Could not parse time stamp")?,
-                            value: BASE64_STANDARD.encode(value.value),
-                            memo,
-                            rewards,
-                            code: Some(code.try_into()?),
-                        });
+                        return row(m.sender, m.contract, rewards);
                     }
                 }
                 Err(anyhow!("Missing event for subscribe in CosmosTypes::MsgExecuteContract"))
@@ -289,6 +296,507 @@ impl Raw_Message {
     }
 }
 
+/// Token amount of a decoded message. The ticker is resolved from the bank
+/// symbols of the registered currencies and left out for unknown denoms.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MessageCoin {
+    pub amount: String,
+    pub denom: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ticker: Option<String>,
+}
+
+/// ICS-20 transfer carried by an IBC packet. `token` is in the local denom
+/// of the funds, `packet_denom` as written in the packet.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TransferPacket {
+    pub sender: String,
+    pub receiver: String,
+    pub token: MessageCoin,
+    pub packet_denom: String,
+    pub sequence: u64,
+    pub source_port: String,
+    pub source_channel: String,
+    pub destination_port: String,
+    pub destination_channel: String,
+}
+
+/// Structured body of a supported message, stored in `raw_message.data`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum MessageData {
+    Send {
+        from_address: String,
+        to_address: String,
+        amount: Vec<MessageCoin>,
+    },
+    Transfer {
+        sender: String,
+        receiver: String,
+        token: Option<MessageCoin>,
+        source_port: String,
+        source_channel: String,
+        timeout_timestamp: u64,
+        memo: String,
+    },
+    /// Incoming transfer
+    RecvPacket(TransferPacket),
+    /// Counterparty acknowledgement of an outgoing transfer. Failed
+    /// transfers are refunded.
+    Acknowledgement {
+        #[serde(flatten)]
+        packet: TransferPacket,
+        success: bool,
+        error: Option<String>,
+    },
+    /// Outgoing transfer that timed out and was refunded
+    Timeout(TransferPacket),
+    Vote {
+        voter: String,
+        proposal_id: u64,
+        option: String,
+    },
+    WithdrawDelegatorReward {
+        delegator_address: String,
+        validator_address: String,
+        rewards: Vec<MessageCoin>,
+    },
+    /// Delegation or undelegation
+    Delegate {
+        delegator_address: String,
+        validator_address: String,
+        amount: Option<MessageCoin>,
+    },
+    CancelUnbondingDelegation {
+        delegator_address: String,
+        validator_address: String,
+        amount: Option<MessageCoin>,
+        creation_height: i64,
+    },
+    Redelegate {
+        delegator_address: String,
+        validator_src_address: String,
+        validator_dst_address: String,
+        amount: Option<MessageCoin>,
+    },
+    ExecuteContract {
+        sender: String,
+        contract: String,
+        msg: Value,
+        funds: Vec<MessageCoin>,
+    },
+    InstantiateContract {
+        sender: String,
+        admin: String,
+        code_id: u64,
+        label: String,
+        msg: Value,
+        funds: Vec<MessageCoin>,
+    },
+    MigrateContract {
+        sender: String,
+        contract: String,
+        code_id: u64,
+        msg: Value,
+    },
+    /// Governance proposal. `messages` are the type URLs of the proposed
+    /// messages, or of the content of a legacy proposal, which has no title
+    /// or summary of its own.
+    SubmitProposal {
+        proposer: String,
+        title: Option<String>,
+        summary: Option<String>,
+        messages: Vec<String>,
+        initial_deposit: Vec<MessageCoin>,
+        expedited: bool,
+    },
+    Deposit {
+        depositor: String,
+        proposal_id: u64,
+        amount: Vec<MessageCoin>,
+    },
+    /// Authz grant, `authorization` is the type URL of the authorization
+    Grant {
+        granter: String,
+        grantee: String,
+        authorization: Option<String>,
+        expiration: Option<DateTime<Utc>>,
+    },
+    /// Fee allowance, `allowance` is the type URL of the allowance
+    GrantAllowance {
+        granter: String,
+        grantee: String,
+        allowance: Option<String>,
+    },
+}
+
+/// Decodes the protobuf body of a supported message. `rewards` is the
+/// amount withdrawn by a `MsgWithdrawDelegatorReward`, taken from the
+/// transaction events.
+pub fn decode_message(
+    value: &Any,
+    rewards: Option<&str>,
+    denom_tickers: &HashMap<String, String>,
+) -> Result<MessageData, anyhow::Error> {
+    let coin = |amount: &str, denom: &str| MessageCoin {
+        amount: amount.to_owned(),
+        denom: denom.to_owned(),
+        ticker: denom_tickers.get(&denom.to_uppercase()).cloned(),
+    };
+
+    let data = match CosmosTypes::from_str(&value.type_url)? {
+        CosmosTypes::MsgSend => {
+            let m = value.to_msg::<MsgSend>()?;
+            MessageData::Send {
+                amount: m
+                    .amount
+                    .iter()
+                    .map(|c| coin(&c.amount, &c.denom))
+                    .collect(),
+                from_address: m.from_address,
+                to_address: m.to_address,
+            }
+        },
+        CosmosTypes::MsgTransfer => {
+            let m = value.to_msg::<MsgTransfer>()?;
+            MessageData::Transfer {
+                token: m.token.map(|c| coin(&c.amount, &c.denom)),
+                sender: m.sender,
+                receiver: m.receiver,
+                source_port: m.source_port,
+                source_channel: m.source_channel,
+                timeout_timestamp: m.timeout_timestamp,
+                memo: m.memo,
+            }
+        },
+        CosmosTypes::MsgVoteLegacy => {
+            let m = value.to_msg::<MsgVoteLegacy>()?;
+            MessageData::Vote {
+                option: vote_option(m.option),
+                voter: m.voter,
+                proposal_id: m.proposal_id,
+            }
+        },
+        CosmosTypes::MsgVote => {
+            let m = value.to_msg::<MsgVote>()?;
+            MessageData::Vote {
+                option: vote_option(m.option),
+                voter: m.voter,
+                proposal_id: m.proposal_id,
+            }
+        },
+        CosmosTypes::MsgRecvPacket => {
+            let m = value.to_msg::<MsgRecvPacket>()?;
+            let (packet, data) = transfer_packet(m.packet)?;
+            let denom = received_denom(
+                &packet.source_port,
+                &packet.source_channel,
+                &packet.destination_port,
+                &packet.destination_channel,
+                &data.denom,
+            );
+
+            let token = coin(&data.amount, &denom);
+
+            MessageData::RecvPacket(TransferPacket::new(packet, data, token))
+        },
+        CosmosTypes::MsgAcknowledgement => {
+            let m = value.to_msg::<MsgAcknowledgement>()?;
+            let (packet, data) = transfer_packet(m.packet)?;
+            let token = coin(&data.amount, &local_denom(&data.denom));
+            let acknowledgement: Value =
+                serde_json::from_slice(&m.acknowledgement)?;
+
+            MessageData::Acknowledgement {
+                packet: TransferPacket::new(packet, data, token),
+                success: acknowledgement.get("result").is_some(),
+                error: acknowledgement
+                    .get("error")
+                    .and_then(Value::as_str)
+                    .map(str::to_owned),
+            }
+        },
+        CosmosTypes::MsgTimeout => {
+            let m = value.to_msg::<MsgTimeout>()?;
+            let (packet, data) = transfer_packet(m.packet)?;
+            let token = coin(&data.amount, &local_denom(&data.denom));
+
+            MessageData::Timeout(TransferPacket::new(packet, data, token))
+        },
+        CosmosTypes::MsgWithdrawDelegatorReward => {
+            let m = value.to_msg::<MsgWithdrawDelegatorReward>()?;
+            MessageData::WithdrawDelegatorReward {
+                delegator_address: m.delegator_address,
+                validator_address: m.validator_address,
+                rewards: parse_coins(rewards.unwrap_or_default())
+                    .into_iter()
+                    .map(|(amount, denom)| coin(amount, denom))
+                    .collect(),
+            }
+        },
+        CosmosTypes::MsgDelegate => {
+            let m = value.to_msg::<MsgDelegate>()?;
+            MessageData::Delegate {
+                amount: m.amount.map(|c| coin(&c.amount, &c.denom)),
+                delegator_address: m.delegator_address,
+                validator_address: m.validator_address,
+            }
+        },
+        CosmosTypes::MsgUndelegate => {
+            let m = value.to_msg::<MsgUndelegate>()?;
+            MessageData::Delegate {
+                amount: m.amount.map(|c| coin(&c.amount, &c.denom)),
+                delegator_address: m.delegator_address,
+                validator_address: m.validator_address,
+            }
+        },
+        CosmosTypes::MsgCancelUnbondingDelegation => {
+            let m = value.to_msg::<MsgCancelUnbondingDelegation>()?;
+            MessageData::CancelUnbondingDelegation {
+                amount: m.amount.map(|c| coin(&c.amount, &c.denom)),
+                delegator_address: m.delegator_address,
+                validator_address: m.validator_address,
+                creation_height: m.creation_height,
+            }
+        },
+        CosmosTypes::MsgBeginRedelegate => {
+            let m = value.to_msg::<MsgBeginRedelegate>()?;
+            MessageData::Redelegate {
+                amount: m.amount.map(|c| coin(&c.amount, &c.denom)),
+                delegator_address: m.delegator_address,
+                validator_src_address: m.validator_src_address,
+                validator_dst_address: m.validator_dst_address,
+            }
+        },
+        CosmosTypes::MsgExecuteContract => {
+            let m = value.to_msg::<MsgExecuteContract>()?;
+            MessageData::ExecuteContract {
+                msg: serde_json::from_slice(&m.msg)?,
+                funds: m
+                    .funds
+                    .iter()
+                    .map(|c| coin(&c.amount, &c.denom))
+                    .collect(),
+                sender: m.sender,
+                contract: m.contract,
+            }
+        },
+        CosmosTypes::MsgInstantiateContract => {
+            let m = value.to_msg::<MsgInstantiateContract>()?;
+            MessageData::InstantiateContract {
+                msg: serde_json::from_slice(&m.msg)?,
+                funds: m
+                    .funds
+                    .iter()
+                    .map(|c| coin(&c.amount, &c.denom))
+                    .collect(),
+                sender: m.sender,
+                admin: m.admin,
+                code_id: m.code_id,
+                label: m.label,
+            }
+        },
+        CosmosTypes::MsgMigrateContract => {
+            let m = value.to_msg::<MsgMigrateContract>()?;
+            MessageData::MigrateContract {
+                msg: serde_json::from_slice(&m.msg)?,
+                sender: m.sender,
+                contract: m.contract,
+                code_id: m.code_id,
+            }
+        },
+        CosmosTypes::MsgSubmitProposal => {
+            let m = value.to_msg::<MsgSubmitProposal>()?;
+            MessageData::SubmitProposal {
+                messages: m.messages.into_iter().map(|m| m.type_url).collect(),
+                initial_deposit: m
+                    .initial_deposit
+                    .iter()
+                    .map(|c| coin(&c.amount, &c.denom))
+                    .collect(),
+                proposer: m.proposer,
+                title: Some(m.title),
+                summary: Some(m.summary),
+                expedited: m.expedited,
+            }
+        },
+        CosmosTypes::MsgSubmitProposalLegacy => {
+            let m = value.to_msg::<MsgSubmitProposalLegacy>()?;
+            MessageData::SubmitProposal {
+                messages: m.content.into_iter().map(|c| c.type_url).collect(),
+                initial_deposit: m
+                    .initial_deposit
+                    .iter()
+                    .map(|c| coin(&c.amount, &c.denom))
+                    .collect(),
+                proposer: m.proposer,
+                title: None,
+                summary: None,
+                expedited: false,
+            }
+        },
+        CosmosTypes::MsgDeposit => {
+            let m = value.to_msg::<MsgDeposit>()?;
+            MessageData::Deposit {
+                amount: m
+                    .amount
+                    .iter()
+                    .map(|c| coin(&c.amount, &c.denom))
+                    .collect(),
+                depositor: m.depositor,
+                proposal_id: m.proposal_id,
+            }
+        },
+        CosmosTypes::MsgDepositLegacy => {
+            let m = value.to_msg::<MsgDepositLegacy>()?;
+            MessageData::Deposit {
+                amount: m
+                    .amount
+                    .iter()
+                    .map(|c| coin(&c.amount, &c.denom))
+                    .collect(),
+                depositor: m.depositor,
+                proposal_id: m.proposal_id,
+            }
+        },
+        CosmosTypes::MsgGrant => {
+            let m = value.to_msg::<MsgGrant>()?;
+            let grant = m.grant.unwrap_or_default();
+            MessageData::Grant {
+                granter: m.granter,
+                grantee: m.grantee,
+                authorization: grant.authorization.map(|a| a.type_url),
+                expiration: grant.expiration.and_then(|t| {
+                    DateTime::from_timestamp(
+                        t.seconds,
+                        t.nanos.try_into().ok()?,
+                    )
+                }),
+            }
+        },
+        CosmosTypes::MsgGrantAllowance => {
+            let m = value.to_msg::<MsgGrantAllowance>()?;
+            MessageData::GrantAllowance {
+                granter: m.granter,
+                grantee: m.grantee,
+                allowance: m.allowance.map(|a| a.type_url),
+            }
+        },
+        CosmosTypes::MsgExec => {
+            return Err(anyhow!(
+                "CosmosTypes::MsgExec is decoded per inner message"
+            ));
+        },
+    };
+
+    Ok(data)
+}
+
+impl TransferPacket {
+    fn new(packet: Packet, data: MsgReceivePacket, token: MessageCoin) -> Self {
+        Self {
+            sender: data.sender,
+            receiver: data.receiver,
+            token,
+            packet_denom: data.denom,
+            sequence: packet.sequence,
+            source_port: packet.source_port,
+            source_channel: packet.source_channel,
+            destination_port: packet.destination_port,
+            destination_channel: packet.destination_channel,
+        }
+    }
+}
+
+/// Packet of an IBC message and its ICS-20 data. Packets of other
+/// applications, such as interchain accounts, are not supported.
+fn transfer_packet(
+    packet: Option<Packet>,
+) -> Result<(Packet, MsgReceivePacket), anyhow::Error> {
+    let packet = packet.context("unable to get packets")?;
+    let data = serde_json::from_slice::<MsgReceivePacket>(&packet.data)?;
+    Ok((packet, data))
+}
+
+/// Id of the proposal created in a transaction, from its events
+fn submitted_proposal(tx_events: &[Event]) -> String {
+    tx_events
+        .iter()
+        .filter(|event| event.r#type == "submit_proposal")
+        .flat_map(|event| event.attributes.iter())
+        .find(|attribute| attribute.key == "proposal_id")
+        .map(|attribute| attribute.value.to_owned())
+        .unwrap_or_default()
+}
+
+/// Address of the contract instantiated from `code_id` in a transaction,
+/// from its events
+fn instantiated_contract(tx_events: &[Event], code_id: u64) -> String {
+    let code_id = code_id.to_string();
+
+    tx_events
+        .iter()
+        .filter(|event| event.r#type == "instantiate")
+        .filter(|event| {
+            event.attributes.iter().any(|attribute| {
+                attribute.key == "code_id" && attribute.value == code_id
+            })
+        })
+        .flat_map(|event| event.attributes.iter())
+        .find(|attribute| attribute.key == "_contract_address")
+        .map(|attribute| attribute.value.to_owned())
+        .unwrap_or_default()
+}
+
+fn vote_option(option: i32) -> String {
+    VoteOption::try_from(option)
+        .map(|o| o.as_str_name().to_owned())
+        .unwrap_or_else(|_| option.to_string())
+}
+
+/// Denom of ICS-20 funds on the receiving chain. Tokens coming back through
+/// the channel they left by lose the hop prefix, anything else is vouchered
+/// as `ibc/{SHA256(trace path)}`.
+pub fn received_denom(
+    source_port: &str,
+    source_channel: &str,
+    destination_port: &str,
+    destination_channel: &str,
+    denom: &str,
+) -> String {
+    let returning = format!("{}/{}/", source_port, source_channel);
+    match denom.strip_prefix(&returning) {
+        Some(unwound) => local_denom(unwound),
+        None => local_denom(&format!(
+            "{}/{}/{}",
+            destination_port, destination_channel, denom
+        )),
+    }
+}
+
+/// Denom of a trace path on this chain: the base denom when it has no hops,
+/// `ibc/{SHA256(trace path)}` otherwise
+pub fn local_denom(trace: &str) -> String {
+    if trace.contains('/') {
+        format!("ibc/{}", sha256::digest(trace).to_uppercase())
+    } else {
+        trace.to_owned()
+    }
+}
+
+/// Splits an SDK coin list such as `12unls,5ibc/ABC` into amounts and denoms
+fn parse_coins(coins: &str) -> Vec<(&str, &str)> {
+    coins
+        .split(',')
+        .filter_map(|c| {
+            let c = c.trim();
+            let split = c.find(|ch: char| !ch.is_ascii_digit())?;
+            let (amount, denom) = c.split_at(split);
+            (!amount.is_empty()).then_some((amount, denom))
+        })
+        .collect()
+}
+
 pub fn get_withdraw_delegator_rewards(
     validator: String,
     delegator: String,
@@ -360,7 +868,48 @@ pub fn get_msg_execute_contract_rewards(
     Ok(None)
 }
 
-#[derive(Debug)]
+/// Codespace of the result codes of the SDK modules
+const SDK_CODESPACE: &str = "sdk";
+/// Codespace of the result codes of the wasm module
+const WASM_CODESPACE: &str = "wasm";
+
+/// Sort a failed transaction by its result code, codespace and log. Swaps
+/// rejected for their price are told by the contract error in the log.
+pub fn classify_failure(
+    code: u32,
+    codespace: &str,
+    raw_log: &str,
+) -> TxFailure {
+    let log = raw_log.to_lowercase();
+
+    if codespace == SDK_CODESPACE {
+        match code {
+            11 => return TxFailure::OutOfGas,
+            5 => return TxFailure::InsufficientFunds,
+            13 => return TxFailure::InsufficientFee,
+            _ => {},
+        }
+    }
+
+    if ["slippage", "max spread", "minimum receive", "min_output"]
+        .iter()
+        .any(|pattern| log.contains(pattern))
+    {
+        return TxFailure::Slippage;
+    }
+
+    if log.contains("insufficient funds") {
+        return TxFailure::InsufficientFunds;
+    }
+
+    if codespace == WASM_CODESPACE {
+        return TxFailure::ContractError;
+    }
+
+    TxFailure::Other
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CosmosTypes {
     MsgSend,
     MsgTransfer,
@@ -372,6 +921,18 @@ pub enum CosmosTypes {
     MsgBeginRedelegate,
     MsgUndelegate,
     MsgExecuteContract,
+    MsgAcknowledgement,
+    MsgTimeout,
+    MsgCancelUnbondingDelegation,
+    MsgSubmitProposal,
+    MsgSubmitProposalLegacy,
+    MsgDeposit,
+    MsgDepositLegacy,
+    MsgExec,
+    MsgGrant,
+    MsgGrantAllowance,
+    MsgInstantiateContract,
+    MsgMigrateContract,
 }
 
 impl fmt::Display for CosmosTypes {
@@ -410,6 +971,45 @@ impl fmt::Display for CosmosTypes {
             CosmosTypes::MsgExecuteContract => {
                 write!(f, "/cosmwasm.wasm.v1.MsgExecuteContract")
             },
+            CosmosTypes::MsgAcknowledgement => {
+                write!(f, "/ibc.core.channel.v1.MsgAcknowledgement")
+            },
+            CosmosTypes::MsgTimeout => {
+                write!(f, "/ibc.core.channel.v1.MsgTimeout")
+            },
+            CosmosTypes::MsgCancelUnbondingDelegation => {
+                write!(
+                    f,
+                    "/cosmos.staking.v1beta1.MsgCancelUnbondingDelegation"
+                )
+            },
+            CosmosTypes::MsgSubmitProposal => {
+                write!(f, "/cosmos.gov.v1.MsgSubmitProposal")
+            },
+            CosmosTypes::MsgSubmitProposalLegacy => {
+                write!(f, "/cosmos.gov.v1beta1.MsgSubmitProposal")
+            },
+            CosmosTypes::MsgDeposit => {
+                write!(f, "/cosmos.gov.v1.MsgDeposit")
+            },
+            CosmosTypes::MsgDepositLegacy => {
+                write!(f, "/cosmos.gov.v1beta1.MsgDeposit")
+            },
+            CosmosTypes::MsgExec => {
+                write!(f, "/cosmos.authz.v1beta1.MsgExec")
+            },
+            CosmosTypes::MsgGrant => {
+                write!(f, "/cosmos.authz.v1beta1.MsgGrant")
+            },
+            CosmosTypes::MsgGrantAllowance => {
+                write!(f, "/cosmos.feegrant.v1beta1.MsgGrantAllowance")
+            },
+            CosmosTypes::MsgInstantiateContract => {
+                write!(f, "/cosmwasm.wasm.v1.MsgInstantiateContract")
+            },
+            CosmosTypes::MsgMigrateContract => {
+                write!(f, "/cosmwasm.wasm.v1.MsgMigrateContract")
+            },
         }
     }
 }
@@ -445,6 +1045,42 @@ impl From<CosmosTypes> for String {
             CosmosTypes::MsgExecuteContract => {
                 String::from("/cosmwasm.wasm.v1.MsgExecuteContract")
             },
+            CosmosTypes::MsgAcknowledgement => {
+                String::from("/ibc.core.channel.v1.MsgAcknowledgement")
+            },
+            CosmosTypes::MsgTimeout => {
+                String::from("/ibc.core.channel.v1.MsgTimeout")
+            },
+            CosmosTypes::MsgCancelUnbondingDelegation => String::from(
+                "/cosmos.staking.v1beta1.MsgCancelUnbondingDelegation",
+            ),
+            CosmosTypes::MsgSubmitProposal => {
+                String::from("/cosmos.gov.v1.MsgSubmitProposal")
+            },
+            CosmosTypes::MsgSubmitProposalLegacy => {
+                String::from("/cosmos.gov.v1beta1.MsgSubmitProposal")
+            },
+            CosmosTypes::MsgDeposit => {
+                String::from("/cosmos.gov.v1.MsgDeposit")
+            },
+            CosmosTypes::MsgDepositLegacy => {
+                String::from("/cosmos.gov.v1beta1.MsgDeposit")
+            },
+            CosmosTypes::MsgExec => {
+                String::from("/cosmos.authz.v1beta1.MsgExec")
+            },
+            CosmosTypes::MsgGrant => {
+                String::from("/cosmos.authz.v1beta1.MsgGrant")
+            },
+            CosmosTypes::MsgGrantAllowance => {
+                String::from("/cosmos.feegrant.v1beta1.MsgGrantAllowance")
+            },
+            CosmosTypes::MsgInstantiateContract => {
+                String::from("/cosmwasm.wasm.v1.MsgInstantiateContract")
+            },
+            CosmosTypes::MsgMigrateContract => {
+                String::from("/cosmwasm.wasm.v1.MsgMigrateContract")
+            },
         }
     }
 }
@@ -478,6 +1114,34 @@ impl FromStr for CosmosTypes {
             "/cosmwasm.wasm.v1.MsgExecuteContract" => {
                 Ok(CosmosTypes::MsgExecuteContract)
             },
+            "/ibc.core.channel.v1.MsgAcknowledgement" => {
+                Ok(CosmosTypes::MsgAcknowledgement)
+            },
+            "/ibc.core.channel.v1.MsgTimeout" => Ok(CosmosTypes::MsgTimeout),
+            "/cosmos.staking.v1beta1.MsgCancelUnbondingDelegation" => {
+                Ok(CosmosTypes::MsgCancelUnbondingDelegation)
+            },
+            "/cosmos.gov.v1.MsgSubmitProposal" => {
+                Ok(CosmosTypes::MsgSubmitProposal)
+            },
+            "/cosmos.gov.v1beta1.MsgSubmitProposal" => {
+                Ok(CosmosTypes::MsgSubmitProposalLegacy)
+            },
+            "/cosmos.gov.v1.MsgDeposit" => Ok(CosmosTypes::MsgDeposit),
+            "/cosmos.gov.v1beta1.MsgDeposit" => {
+                Ok(CosmosTypes::MsgDepositLegacy)
+            },
+            "/cosmos.authz.v1beta1.MsgExec" => Ok(CosmosTypes::MsgExec),
+            "/cosmos.authz.v1beta1.MsgGrant" => Ok(CosmosTypes::MsgGrant),
+            "/cosmos.feegrant.v1beta1.MsgGrantAllowance" => {
+                Ok(CosmosTypes::MsgGrantAllowance)
+            },
+            "/cosmwasm.wasm.v1.MsgInstantiateContract" => {
+                Ok(CosmosTypes::MsgInstantiateContract)
+            },
+            "/cosmwasm.wasm.v1.MsgMigrateContract" => {
+                Ok(CosmosTypes::MsgMigrateContract)
+            },
             _ => Err(io::Error::other(format!(
                 "CosmosTypes message not supported: {}",
                 &value
@@ -485,3 +1149,204 @@ impl FromStr for CosmosTypes {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use cosmrs::proto::{
+        cosmos::base::v1beta1::Coin, prost::Message as _,
+        tendermint::abci::EventAttribute,
+    };
+
+    use super::*;
+
+    #[test]
+    fn received_denom_follows_ics20() {
+        assert_eq!(
+            received_denom(
+                "transfer",
+                "channel-141",
+                "transfer",
+                "channel-0",
+                "uatom"
+            ),
+            "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2"
+        );
+        assert_eq!(
+            received_denom(
+                "transfer",
+                "channel-783",
+                "transfer",
+                "channel-0",
+                "transfer/channel-783/unls"
+            ),
+            "unls"
+        );
+        assert_eq!(
+            local_denom("transfer/channel-0/uatom"),
+            "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2"
+        );
+    }
+
+    #[test]
+    fn classifies_failures() {
+        assert_eq!(
+            classify_failure(11, "sdk", "out of gas in location: WritePerByte"),
+            TxFailure::OutOfGas
+        );
+        assert_eq!(
+            classify_failure(
+                5,
+                "wasm",
+                "failed to execute message; message index: 0: \
+                 Operation exceeds max spread limit: execute wasm contract failed"
+            ),
+            TxFailure::Slippage
+        );
+        assert_eq!(
+            classify_failure(
+                5,
+                "wasm",
+                "failed to execute message; message index: 0: 1unls is \
+                 smaller than 5unls: insufficient funds"
+            ),
+            TxFailure::InsufficientFunds
+        );
+        assert_eq!(
+            classify_failure(
+                5,
+                "wasm",
+                "failed to execute message; message index: 0: \
+                 [Leaser] The transaction amount should worth at least 50: \
+                 execute wasm contract failed"
+            ),
+            TxFailure::ContractError
+        );
+        assert_eq!(
+            classify_failure(32, "sdk", "account sequence mismatch"),
+            TxFailure::Other
+        );
+    }
+
+    #[test]
+    fn exec_is_unwrapped_per_message() {
+        let send = MsgSend {
+            from_address: String::from("nolus1granter"),
+            to_address: String::from("nolus1to"),
+            amount: vec![Coin {
+                denom: String::from("unls"),
+                amount: String::from("10"),
+            }],
+        };
+        let exec = MsgExec {
+            grantee: String::from("nolus1grantee"),
+            msgs: vec![
+                Any {
+                    type_url: String::from("/cosmos.unknown.v1.MsgUnknown"),
+                    value: vec![],
+                },
+                Any {
+                    type_url: CosmosTypes::MsgSend.to_string(),
+                    value: send.encode_to_vec(),
+                },
+            ],
+        };
+        let fee = cosmrs::tx::Fee::from_amount_and_gas(
+            cosmrs::Coin::new(500, "unls").unwrap(),
+            100_000u64,
+        );
+
+        let messages = Raw_Message::from_any(RawMsgParams {
+            index: 2,
+            value: Any {
+                type_url: CosmosTypes::MsgExec.to_string(),
+                value: exec.encode_to_vec(),
+            },
+            tx_hash: String::from("HASH"),
+            block: 10,
+            time_stamp: cosmrs::proto::Timestamp {
+                seconds: 1_700_000_000,
+                nanos: 0,
+            },
+            fee,
+            memo: String::new(),
+            events: vec![],
+            tx_events: &[],
+            code: 0,
+            denom_tickers: &HashMap::new(),
+        })
+        .unwrap();
+
+        assert_eq!(messages.len(), 1);
+        let message = &messages[0];
+        assert_eq!((message.index, message.inner_index), (2, 2));
+        assert_eq!(message.from, "nolus1granter");
+        assert_eq!(message.grantee.as_deref(), Some("nolus1grantee"));
+        assert_eq!(message.r#type, CosmosTypes::MsgSend.to_string());
+        assert_eq!(message.data.as_ref().unwrap()["amount"][0]["amount"], "10");
+    }
+
+    #[test]
+    fn instantiation_is_sent_to_the_new_contract() {
+        let attribute = |key: &str, value: &str| EventAttribute {
+            key: key.to_owned(),
+            value: value.to_owned(),
+            index: true,
+        };
+        let instantiate = |code_id: &str, contract: &str| Event {
+            r#type: String::from("instantiate"),
+            attributes: vec![
+                attribute("_contract_address", contract),
+                attribute("code_id", code_id),
+            ],
+        };
+        let tx_events = [
+            instantiate("12", "nolus1other"),
+            instantiate("7", "nolus1contract"),
+        ];
+
+        assert_eq!(instantiated_contract(&tx_events, 7), "nolus1contract");
+        assert_eq!(instantiated_contract(&tx_events, 8), "");
+    }
+
+    #[test]
+    fn decodes_amounts_with_tickers() {
+        let msg = MsgSend {
+            from_address: String::from("nolus1from"),
+            to_address: String::from("nolus1to"),
+            amount: vec![
+                Coin {
+                    denom: String::from("unls"),
+                    amount: String::from("1500"),
+                },
+                Coin {
+                    denom: String::from("ibc/ABC"),
+                    amount: String::from("7"),
+                },
+            ],
+        };
+        let value = Any {
+            type_url: CosmosTypes::MsgSend.to_string(),
+            value: msg.encode_to_vec(),
+        };
+        let tickers =
+            HashMap::from([(String::from("UNLS"), String::from("NLS"))]);
+
+        let data = decode_message(&value, None, &tickers).unwrap();
+        assert_eq!(
+            serde_json::to_value(data).unwrap(),
+            serde_json::json!({
+                "from_address": "nolus1from",
+                "to_address": "nolus1to",
+                "amount": [
+                    {"amount": "1500", "denom": "unls", "ticker": "NLS"},
+                    {"amount": "7", "denom": "ibc/ABC"},
+                ],
+            })
+        );
+
+        assert_eq!(
+            parse_coins("12unls,5ibc/ABC"),
+            vec![("12", "unls"), ("5", "ibc/ABC")]
+        );
+    }
+}
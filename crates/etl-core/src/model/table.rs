@@ -1,5 +1,9 @@
 use std::marker::{self, PhantomData};
 
+use chrono::{DateTime, Utc};
+use futures::{stream::BoxStream, TryStreamExt as _};
+use sqlx::{postgres::PgRow, FromRow};
+
 use crate::dao::PoolType;
 
 #[derive(Debug)]
@@ -15,4 +19,30 @@ impl<T> Table<T> {
             _phantomdata: PhantomData,
         }
     }
+
+    /// Stream the rows of a query from a database cursor instead of
+    /// collecting them. `from` is bound as `$1` when present.
+    pub fn stream_rows<R>(
+        &self,
+        query: String,
+        from: Option<DateTime<Utc>>,
+    ) -> BoxStream<'static, Result<R, sqlx::Error>>
+    where
+        R: for<'r> FromRow<'r, PgRow> + Send + Unpin + 'static,
+    {
+        let pool = self.pool.clone();
+
+        Box::pin(async_stream::try_stream! {
+            let mut query_builder = sqlx::query_as::<_, R>(&query);
+
+            if let Some(from_ts) = from {
+                query_builder = query_builder.bind(from_ts);
+            }
+
+            let mut rows = query_builder.fetch(&pool);
+            while let Some(row) = rows.try_next().await? {
+                yield row;
+            }
+        })
+    }
 }
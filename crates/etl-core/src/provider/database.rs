@@ -5,14 +5,18 @@ use crate::{
     dao::{PoolOption, PoolType},
     error::Error,
     model::{
-        Action_History, Block, CurrencyProtocol, CurrencyRegistry, LP_Deposit,
-        LP_Lender_State, LP_Pool, LP_Pool_State, LP_Withdraw,
-        LS_Auto_Close_Position, LS_Close_Position, LS_Closing, LS_Liquidation,
-        LS_Liquidation_Warning, LS_Loan_Closing, LS_Loan_Collect, LS_Opening,
-        LS_Repayment, LS_Slippage_Anomaly, LS_State, MP_Asset, MP_Yield,
-        PL_State, Pool_Config, ProtocolRegistry, Raw_Message,
-        Reserve_Cover_Loss, Subscription, TR_Profit, TR_Rewards_Distribution,
-        TR_State, Table,
+        Action_History, AddressActivity, AdminCommand, AlertRule, ApiKey,
+        Block, CurrencyProtocol, CurrencyRegistry, FeeDaily, GovernanceVote,
+        IbcTransfer, LP_Deposit, LP_Lender_State, LP_Pool, LP_Pool_State,
+        LP_Withdraw, LS_Auto_Close_Position, LS_Close_Position, LS_Closing,
+        LS_Liquidation, LS_Liquidation_Warning, LS_Loan_Closing,
+        LS_Loan_Collect, LS_Opening, LS_Repayment, LS_Slippage_Anomaly,
+        LS_State, LeaseSubscription, MP_Asset, MP_Yield, PL_State, Pool_Config,
+        ProtocolContractHistory, ProtocolRegistry, PushDelivery, PushDigest,
+        PushNotification, PushOutbox, PushThrottle, Raw_Message, RegistryAudit,
+        Reserve_Cover_Loss, StakingEvent, Subscription, SubscriptionChannel,
+        SubscriptionPreference, TR_Profit, TR_Rewards_Distribution, TR_State,
+        Table,
     },
 };
 
@@ -41,14 +45,32 @@ pub struct DatabasePool {
     pub ls_close_position: Table<LS_Close_Position>,
     pub reserve_cover_loss: Table<Reserve_Cover_Loss>,
     pub raw_message: Table<Raw_Message>,
+    pub ibc_transfer: Table<IbcTransfer>,
+    pub staking_event: Table<StakingEvent>,
+    pub governance_vote: Table<GovernanceVote>,
+    pub fee_daily: Table<FeeDaily>,
+    pub address_activity: Table<AddressActivity>,
+    pub registry_audit: Table<RegistryAudit>,
+    pub protocol_contract_history: Table<ProtocolContractHistory>,
     pub ls_loan_closing: Table<LS_Loan_Closing>,
     pub ls_slippage_anomaly: Table<LS_Slippage_Anomaly>,
     pub subscription: Table<Subscription>,
+    pub subscription_lease: Table<LeaseSubscription>,
+    pub subscription_channel: Table<SubscriptionChannel>,
+    pub subscription_preference: Table<SubscriptionPreference>,
+    pub push_notification: Table<PushNotification>,
+    pub push_delivery: Table<PushDelivery>,
+    pub push_outbox: Table<PushOutbox>,
+    pub push_throttle: Table<PushThrottle>,
+    pub push_digest: Table<PushDigest>,
+    pub alert_rule: Table<AlertRule>,
     pub ls_loan_collect: Table<LS_Loan_Collect>,
     pub pool_config: Table<Pool_Config>,
     pub currency_registry: Table<CurrencyRegistry>,
     pub currency_protocol: Table<CurrencyProtocol>,
     pub protocol_registry: Table<ProtocolRegistry>,
+    pub api_key: Table<ApiKey>,
+    pub admin_command: Table<AdminCommand>,
     pub pool: PoolType,
 }
 
@@ -105,11 +127,29 @@ impl DatabasePool {
             ls_loan_closing: Table::new(pool.clone()),
             ls_slippage_anomaly: Table::new(pool.clone()),
             subscription: Table::new(pool.clone()),
+            subscription_lease: Table::new(pool.clone()),
+            subscription_channel: Table::new(pool.clone()),
+            subscription_preference: Table::new(pool.clone()),
+            push_notification: Table::new(pool.clone()),
+            push_delivery: Table::new(pool.clone()),
+            push_outbox: Table::new(pool.clone()),
+            push_throttle: Table::new(pool.clone()),
+            push_digest: Table::new(pool.clone()),
+            alert_rule: Table::new(pool.clone()),
             ls_loan_collect: Table::new(pool.clone()),
             pool_config: Table::new(pool.clone()),
             currency_registry: Table::new(pool.clone()),
             currency_protocol: Table::new(pool.clone()),
             protocol_registry: Table::new(pool.clone()),
+            api_key: Table::new(pool.clone()),
+            admin_command: Table::new(pool.clone()),
+            ibc_transfer: Table::new(pool.clone()),
+            staking_event: Table::new(pool.clone()),
+            governance_vote: Table::new(pool.clone()),
+            fee_daily: Table::new(pool.clone()),
+            address_activity: Table::new(pool.clone()),
+            registry_audit: Table::new(pool.clone()),
+            protocol_contract_history: Table::new(pool.clone()),
             raw_message: Table::new(pool),
         })
     }
@@ -1,10 +1,15 @@
 use std::{
-    fmt::Debug, future::Future, str::FromStr, sync::Arc, time::Duration,
+    fmt::Debug,
+    future::Future,
+    str::FromStr,
+    sync::Arc,
+    time::{Duration, Instant},
 };
 
 use crate::{
     configuration::Config,
     error::Error,
+    telemetry::metrics,
     types::{
         AdminProtocolExtendType, AdminProtocolFullType, AdminProtocolType,
         Balance, LPP_Price, LP_Pool_Config_State_Type, LP_Pool_State_Type,
@@ -133,6 +138,7 @@ impl Grpc {
 
     async fn with_retry<C, F, Fut, T>(
         &self,
+        rpc: &'static str,
         client_factory: impl Fn() -> C + Send + Sync,
         mut f: F,
     ) -> Result<T, Error>
@@ -144,16 +150,38 @@ impl Grpc {
     {
         let max_attempts: u32 = 8;
         let permit_timeout = Duration::from_secs(60);
+        let metrics = metrics();
+        let waiting = Instant::now();
         let _permit =
             timeout(permit_timeout, self.permits.clone().acquire_owned())
                 .await
                 .map_err(|_| {
                     Error::GrpsError("gRPC permit acquisition timed out".into())
                 })??;
+        metrics
+            .grpc_permit_wait
+            .observe(waiting.elapsed().as_secs_f64());
 
         for attempt in 0..=max_attempts {
             let client = client_factory();
+            let started = Instant::now();
             let res = f(client).await;
+            metrics
+                .grpc_duration
+                .with_label_values(&[rpc])
+                .observe(started.elapsed().as_secs_f64());
+            let outcome = match &res {
+                Ok(_) => "ok",
+                Err(e) if is_retryable(e.code()) && attempt < max_attempts => {
+                    "retry"
+                },
+                Err(_) => "error",
+            };
+            metrics
+                .grpc_attempts
+                .with_label_values(&[rpc, outcome])
+                .inc();
+
             match res {
                 Ok(v) => return Ok(v),
                 Err(e) if is_retryable(e.code()) => {
@@ -185,6 +213,7 @@ impl Grpc {
 
         let data = self
             .with_retry(
+                "get_latest_block",
                 || self.tendermint_client.clone(),
                 |mut client| async move {
                     client.get_latest_block(GetLatestBlockRequest {}).await.map(
@@ -205,6 +234,7 @@ impl Grpc {
                     .map(|header| header.height)
                     .context(MISSING_BLOCK_HEADER_INFO_ERROR)
             })?;
+        metrics().chain_head_height.set(data);
 
         Ok(data)
     }
@@ -223,6 +253,7 @@ impl Grpc {
 
         let data = self
             .with_retry(
+                "get_block",
                 || self.tendermint_client.clone(),
                 |mut client| async move {
                     client
@@ -271,6 +302,7 @@ impl Grpc {
 
         let new_result = self
             .with_retry(
+                "get_lease_state",
                 || self.wasm_query_client.clone(),
                 |mut client| {
                     let contract = contract.to_owned();
@@ -294,6 +326,7 @@ impl Grpc {
             Err(_) => {
                 // Old contract version — try legacy empty query
                 self.with_retry(
+                    "get_lease_state",
                     || self.wasm_query_client.clone(),
                     |mut client| {
                         let contract = contract.to_owned();
@@ -331,6 +364,7 @@ impl Grpc {
 
         let tx = self
             .with_retry(
+                "get_tx",
                 || self.tx_service_client.clone(),
                 |mut client| {
                     let hash = tx_hash.to_owned();
@@ -356,6 +390,7 @@ impl Grpc {
 
         let data = self
             .with_retry(
+                "get_balances",
                 || self.bank_query_client.clone(),
                 |mut client| {
                     let address = address.to_owned();
@@ -395,6 +430,7 @@ impl Grpc {
 
         let data = self
             .with_retry(
+                "get_balances_by_block",
                 || self.bank_query_client.clone(),
                 |mut client| {
                     let address = address.to_owned();
@@ -446,6 +482,7 @@ impl Grpc {
             "Failed to parse message query against contract!";
         let data = self
             .with_retry(
+                "get_protocol_config",
                 || self.wasm_query_client.clone(),
                 |mut client| {
                     let contract = contract.to_owned();
@@ -497,6 +534,7 @@ impl Grpc {
 
         let data = self
             .with_retry(
+                "get_prices",
                 || self.wasm_query_client.clone(),
                 |mut client| {
                     let contract = contract.to_owned();
@@ -537,6 +575,7 @@ impl Grpc {
 
         let data = self
             .with_retry(
+                "get_base_currency",
                 || self.wasm_query_client.clone(),
                 |mut client| {
                     let contract = contract.to_owned();
@@ -578,6 +617,7 @@ impl Grpc {
 
         let data = self
             .with_retry(
+                "get_stable_price",
                 || self.wasm_query_client.clone(),
                 |mut client| {
                     let ticker = ticker.to_owned();
@@ -625,6 +665,7 @@ impl Grpc {
 
         let data = self
             .with_retry(
+                "get_admin_config",
                 || self.wasm_query_client.clone(),
                 |mut client| {
                     let contract = contract.to_owned();
@@ -671,6 +712,7 @@ impl Grpc {
 
         let data = self
             .with_retry(
+                "get_lease_state_by_block",
                 || self.wasm_query_client.clone(),
                 |mut client| {
                     let contract = contract.to_owned();
@@ -728,6 +770,7 @@ impl Grpc {
 
         let data = self
             .with_retry(
+                "get_lease_raw_state_by_block",
                 || self.wasm_query_client.clone(),
                 |mut client| {
                     let contract = contract.to_owned();
@@ -778,6 +821,7 @@ impl Grpc {
 
         let data = self
             .with_retry(
+                "get_balance_state",
                 || self.wasm_query_client.clone(),
                 |mut client| {
                     let contract = contract.to_owned();
@@ -824,6 +868,7 @@ impl Grpc {
 
         let data = self
             .with_retry(
+                "get_lpp_price",
                 || self.wasm_query_client.clone(),
                 |mut client| {
                     let bytes = b"{\"price\": []}";
@@ -864,6 +909,7 @@ impl Grpc {
 
         let data = self
             .with_retry(
+                "get_lpp_balance_state",
                 || self.wasm_query_client.clone(),
                 |mut client| {
                     let bytes = b"{\"lpp_balance\": []}";
@@ -904,6 +950,7 @@ impl Grpc {
 
         let data = self
             .with_retry(
+                "get_lpp_config_state",
                 || self.wasm_query_client.clone(),
                 |mut client| {
                     let bytes = b"{\"config\": []}";
@@ -945,6 +992,7 @@ impl Grpc {
 
         let data = self
             .with_retry(
+                "get_platform",
                 || self.wasm_query_client.clone(),
                 |mut client| {
                     let contract = contract.to_owned();
@@ -985,6 +1033,7 @@ impl Grpc {
 
         let data = self
             .with_retry(
+                "get_protocol_config_full",
                 || self.wasm_query_client.clone(),
                 |mut client| {
                     let contract = contract.to_owned();
@@ -1027,6 +1076,7 @@ impl Grpc {
 
         let data = self
             .with_retry(
+                "get_currencies",
                 || self.wasm_query_client.clone(),
                 |mut client| {
                     let contract = contract.to_owned();
@@ -1063,6 +1113,7 @@ impl Grpc {
 
         let data = self
             .with_retry(
+                "get_lpn",
                 || self.wasm_query_client.clone(),
                 |mut client| {
                     let contract = contract.to_owned();
@@ -1102,6 +1153,7 @@ impl Grpc {
 
         let data = self
             .with_retry(
+                "get_stable_currency",
                 || self.wasm_query_client.clone(),
                 |mut client| {
                     let contract = oracle_contract.to_owned();
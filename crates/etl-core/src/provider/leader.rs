@@ -0,0 +1,51 @@
+use std::time::Duration;
+
+use sqlx::{Connection as _, PgConnection};
+use tokio::time::sleep;
+
+use crate::error::Error;
+
+/// Session-level advisory lock electing one process to run a role. The lock
+/// lives on a connection of its own, outside the pool, and is released by
+/// Postgres when that connection closes, so `database_url` must reach the
+/// server directly or through a pooler in session mode.
+#[derive(Debug)]
+pub struct LeaderLock {
+    connection: PgConnection,
+}
+
+impl LeaderLock {
+    /// Wait until the lock of `key` is free and take it, trying again
+    /// every `retry`
+    pub async fn acquire(
+        database_url: &str,
+        key: i64,
+        retry: Duration,
+    ) -> Result<LeaderLock, Error> {
+        let mut connection = PgConnection::connect(database_url).await?;
+
+        loop {
+            let (acquired,): (bool,) =
+                sqlx::query_as("SELECT pg_try_advisory_lock($1)")
+                    .bind(key)
+                    .fetch_one(&mut connection)
+                    .await?;
+            if acquired {
+                return Ok(LeaderLock { connection });
+            }
+
+            sleep(retry).await;
+        }
+    }
+
+    /// Keep the lock, checking its connection every `check`. Fails once the
+    /// connection, and with it the lock, is lost.
+    pub async fn hold(mut self, check: Duration) -> Result<(), Error> {
+        loop {
+            sleep(check).await;
+            self.connection.ping().await.map_err(|e| {
+                Error::TaskError(format!("Leader lock lost: {}", e))
+            })?;
+        }
+    }
+}
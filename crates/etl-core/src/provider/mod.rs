@@ -1,5 +1,8 @@
-pub use self::{database::DatabasePool, grpc::Grpc, http::HTTP};
+pub use self::{
+    database::DatabasePool, grpc::Grpc, http::HTTP, leader::LeaderLock,
+};
 
 mod database;
 mod grpc;
 mod http;
+mod leader;
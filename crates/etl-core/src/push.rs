@@ -1,105 +1,870 @@
-use base64::{engine::general_purpose::URL_SAFE_NO_PAD as BASE64_URL, Engine};
-use chrono::Local;
-use jsonwebtoken::{encode, Algorithm, EncodingKey, Header};
-use reqwest::Url;
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    time::Instant,
+};
+
+use chrono::{DateTime, NaiveTime, TimeDelta, Utc};
+use futures::future::join_all;
+use sqlx::Transaction;
 
 use crate::{
+    channel::{Destination, Notification, Target},
     configuration::{AppState, State},
+    dao::{
+        postgre::{
+            push_delivery::DeliveryAttempt,
+            subscription_preference::PreferenceState,
+        },
+        DataBase,
+    },
     error::Error,
-    model::Subscription,
-    types::{Claims, PushData, PushHeader, Urgency},
+    helpers::ChannelKind,
+    model::{PushDigest, PushOutbox, PushThrottle},
+    telemetry::metrics,
+    template::DEFAULT_LOCALE,
+    types::{
+        DigestItem, DigestPush, PushData, PushHeader, Urgency, PUSH_TYPES,
+    },
 };
 
-pub async fn send(
-    app_state: AppState<State>,
-    address: String,
+/// Who a notification is addressed to
+#[derive(Debug, Clone)]
+pub enum Recipient {
+    /// Subscriptions of a wallet
+    Wallet(String),
+    /// Subscriptions of the lease owner and of browsers subscribed to the
+    /// lease itself
+    Lease(String),
+    /// Subscription that owns an alert rule
+    AlertRule(i64),
+    /// Single subscription, by address and auth key
+    Subscription(String, String),
+}
+
+impl Recipient {
+    /// Value of `push_outbox.recipient_type`
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Recipient::Wallet(_) => "wallet",
+            Recipient::Lease(_) => "lease",
+            Recipient::AlertRule(_) => "alert_rule",
+            Recipient::Subscription(_, _) => "subscription",
+        }
+    }
+
+    /// Value of `push_outbox.recipient`
+    pub fn target(&self) -> String {
+        match self {
+            Recipient::Wallet(address) | Recipient::Lease(address) => {
+                address.to_owned()
+            },
+            Recipient::AlertRule(id) => id.to_string(),
+            Recipient::Subscription(address, auth) => {
+                format!("{}:{}", address, auth)
+            },
+        }
+    }
+
+    pub fn parse(kind: &str, target: &str) -> Result<Self, Error> {
+        match kind {
+            "wallet" => Ok(Recipient::Wallet(target.to_owned())),
+            "lease" => Ok(Recipient::Lease(target.to_owned())),
+            "alert_rule" => Ok(Recipient::AlertRule(target.parse()?)),
+            "subscription" => match target.split_once(':') {
+                Some((address, auth)) => Ok(Recipient::Subscription(
+                    address.to_owned(),
+                    auth.to_owned(),
+                )),
+                None => Err(Error::InvalidOption {
+                    option: target.to_owned(),
+                }),
+            },
+            _ => Err(Error::InvalidOption {
+                option: kind.to_owned(),
+            }),
+        }
+    }
+}
+
+/// Destinations a notification goes to, with the lease and its owner when
+/// addressed to a lease
+pub struct Recipients {
+    pub lease: Option<String>,
+    pub owner: Option<String>,
+    pub targets: Vec<Target>,
+}
+
+/// Address of the deliveries to the ops webhook
+const OPS_ADDRESS: &str = "ops";
+
+/// Resolves a lease to its owner through `LS_Opening`, or an alert rule to
+/// its subscription, and collects the destinations of the active
+/// subscriptions: the browser, unless Web Push is turned off, and the active
+/// channels of the subscription. Every notification of a wallet or lease also
+/// goes to the ops webhook when configured. Destinations are unique per
+/// channel and endpoint.
+pub async fn resolve(
+    app_state: &AppState<State>,
+    recipient: Recipient,
+) -> Result<Recipients, Error> {
+    let subscription = &app_state.database.subscription;
+    let ops = matches!(recipient, Recipient::Wallet(_) | Recipient::Lease(_));
+
+    let (lease, owner, subscriptions) = match recipient {
+        Recipient::Wallet(address) => {
+            let items = subscription.get_by_address(address.to_owned()).await?;
+            (None, Some(address), items)
+        },
+        Recipient::Lease(lease) => {
+            let owner = app_state
+                .database
+                .ls_opening
+                .get_owner(lease.to_owned())
+                .await?;
+            let mut items = subscription.get_by_lease(lease.to_owned()).await?;
+
+            match &owner {
+                Some(owner) => {
+                    items.extend(
+                        subscription.get_by_address(owner.to_owned()).await?,
+                    );
+                },
+                None => {
+                    tracing::warn!("No owner found for lease {}", lease);
+                },
+            }
+
+            (Some(lease), owner, items)
+        },
+        Recipient::AlertRule(id) => {
+            match app_state.database.alert_rule.get(id).await? {
+                Some(rule) if rule.active => {
+                    let items = subscription
+                        .get_one(rule.address.to_owned(), rule.auth)
+                        .await?
+                        .filter(|s| s.active.unwrap_or(false))
+                        .into_iter()
+                        .collect();
+                    (rule.lease, Some(rule.address), items)
+                },
+                _ => (None, None, vec![]),
+            }
+        },
+        Recipient::Subscription(address, auth) => {
+            let items = subscription
+                .get_one(address.to_owned(), auth)
+                .await?
+                .filter(|s| s.active.unwrap_or(false))
+                .into_iter()
+                .collect();
+            (None, Some(address), items)
+        },
+    };
+
+    let (addresses, auths) = subscriptions
+        .iter()
+        .map(|s| (s.address.to_owned(), s.auth.to_owned()))
+        .unzip();
+    let channels = if subscriptions.is_empty() {
+        vec![]
+    } else {
+        app_state
+            .database
+            .subscription_channel
+            .get_active_by_subscriptions(addresses, auths)
+            .await?
+    };
+
+    let locales: HashMap<(String, String), String> = subscriptions
+        .iter()
+        .map(|s| {
+            (
+                (s.address.to_owned(), s.auth.to_owned()),
+                s.locale.to_owned(),
+            )
+        })
+        .collect();
+
+    let mut targets: Vec<Target> = subscriptions
+        .into_iter()
+        .filter(|s| s.web_push)
+        .map(|s| Target {
+            address: s.address.to_owned(),
+            auth: Some(s.auth.to_owned()),
+            locale: s.locale.to_owned(),
+            destination: Destination::WebPush(s),
+        })
+        .collect();
+
+    targets.extend(channels.iter().filter_map(|channel| {
+        let locale = locales
+            .get(&(channel.address.to_owned(), channel.auth.to_owned()))
+            .map_or(DEFAULT_LOCALE, |locale| locale.as_str());
+
+        Some(Target {
+            address: channel.address.to_owned(),
+            auth: Some(channel.auth.to_owned()),
+            locale: locale.to_owned(),
+            destination: Destination::from_channel(channel)?,
+        })
+    }));
+
+    if let (true, Some(webhook)) = (ops, &app_state.config.ops_webhook) {
+        targets.push(Target {
+            address: String::from(OPS_ADDRESS),
+            auth: None,
+            locale: String::from(DEFAULT_LOCALE),
+            destination: Destination::Webhook {
+                url: webhook.url.to_owned(),
+                secret: webhook.secret.to_owned(),
+                trusted: true,
+            },
+        });
+    }
+
+    let mut endpoints = HashSet::new();
+    targets.retain(|t| {
+        endpoints
+            .insert((t.destination.kind(), t.destination.endpoint().to_owned()))
+    });
+
+    Ok(Recipients {
+        lease,
+        owner,
+        targets,
+    })
+}
+
+/// Queue a notification in the transaction of the event that triggers it, so
+/// it is only delivered once that event is committed
+pub async fn enqueue(
+    app_state: &AppState<State>,
+    recipient: Recipient,
     push_data: PushData,
+    transaction: &mut Transaction<'_, DataBase>,
 ) -> Result<(), Error> {
-    let items = app_state
+    app_state
         .database
-        .subscription
-        .get_by_address(address.to_owned())
+        .push_outbox
+        .insert(
+            push_data.r#type,
+            push_data.body,
+            recipient.kind(),
+            recipient.target(),
+            transaction,
+        )
         .await?;
 
-    let push_headers = PushHeader {
-        ttl: 24 * 60 * 60,
-        urgency: Urgency::High,
+    Ok(())
+}
+
+/// Result of one delivery round of a queued notification
+#[derive(Debug)]
+pub enum Outcome {
+    /// Every subscription was handled, delivered or permanently rejected
+    Done,
+    /// Some subscriptions can be retried later
+    Retry(String),
+}
+
+/// Deliver a queued notification to the destinations it has not reached
+/// yet, within the preferences of their subscriptions. Every attempt is
+/// recorded in `push_delivery`.
+pub async fn deliver(
+    app_state: &AppState<State>,
+    item: &PushOutbox,
+) -> Result<Outcome, Error> {
+    let recipient = Recipient::parse(&item.recipient_type, &item.recipient)?;
+    let Recipients {
+        lease,
+        owner,
+        targets,
+    } = resolve(app_state, recipient).await?;
+
+    let notification_id = match item.notification_id {
+        Some(id) => id,
+        None => {
+            if targets.is_empty() {
+                return Ok(Outcome::Done);
+            }
+
+            let notification = app_state
+                .database
+                .push_notification
+                .insert(
+                    item.push_type.to_owned(),
+                    lease.to_owned(),
+                    owner,
+                    targets.len().try_into()?,
+                )
+                .await?;
+            app_state
+                .database
+                .push_outbox
+                .set_notification(item.id, notification.id)
+                .await?;
+            notification.id
+        },
     };
 
-    for subscription in items {
-        let app_state = app_state.clone();
-        send_push_task(
-            app_state,
-            subscription,
-            push_headers.clone(),
-            push_data.clone(),
-        );
+    let targets = apply_preferences(
+        app_state,
+        item,
+        notification_id,
+        lease.as_deref(),
+        targets,
+    )
+    .await?;
+
+    let delivered: HashSet<(String, String)> = app_state
+        .database
+        .push_delivery
+        .get_delivered(notification_id)
+        .await?
+        .into_iter()
+        .collect();
+
+    // Rendered once per locale
+    let mut notifications: HashMap<&str, Notification> = HashMap::new();
+    for target in &targets {
+        notifications
+            .entry(&target.locale)
+            .or_insert_with(|| Notification {
+                id: notification_id,
+                header: PushHeader {
+                    ttl: 24 * 60 * 60,
+                    urgency: Urgency::High,
+                },
+                data: PushData {
+                    r#type: item.push_type.to_owned(),
+                    body: item.body.to_owned(),
+                },
+                content: app_state.templates.render(
+                    &target.locale,
+                    &item.push_type,
+                    &item.body,
+                ),
+            });
     }
-    Ok(())
+
+    let attempts = targets
+        .iter()
+        .filter(|t| {
+            !delivered.contains(&(
+                t.destination.kind().to_string(),
+                t.destination.endpoint().to_owned(),
+            ))
+        })
+        .map(|t| attempt(app_state, &notifications[t.locale.as_str()], t));
+
+    let retryable = join_all(attempts)
+        .await
+        .into_iter()
+        .collect::<Result<Vec<bool>, Error>>()?
+        .into_iter()
+        .filter(|retry| *retry)
+        .count();
+
+    if retryable > 0 {
+        return Ok(Outcome::Retry(format!(
+            "{} destinations not reached",
+            retryable
+        )));
+    }
+
+    Ok(Outcome::Done)
 }
 
-pub fn send_push_task(
-    state: AppState<State>,
-    subscription: Subscription,
-    push_header: PushHeader,
-    push_data: PushData,
-) {
-    let permits = state.push_permits.clone();
-    tokio::spawn(async move {
-        // Acquire permit to limit concurrent push tasks
-        let _permit = match permits.acquire().await {
-            Ok(permit) => permit,
-            Err(_) => {
-                tracing::error!("Push notification semaphore closed");
-                return;
+/// Send to one destination and record the attempt. Returns whether the
+/// failure, if any, is worth retrying.
+async fn attempt(
+    app_state: &AppState<State>,
+    notification: &Notification,
+    target: &Target,
+) -> Result<bool, Error> {
+    let kind = target.destination.kind();
+    let endpoint = target.destination.endpoint().to_owned();
+
+    // A channel removed from the configuration can not be retried
+    let Some(channel) = app_state.channels.get(kind) else {
+        app_state
+            .database
+            .push_delivery
+            .record(DeliveryAttempt {
+                notification_id: notification.id,
+                address: target.address.to_owned(),
+                channel: kind.to_string(),
+                endpoint,
+                status_code: None,
+                latency_ms: 0,
+                error: Some(format!("{} channel not configured", kind)),
+            })
+            .await?;
+        delivered(kind, "unconfigured");
+        return Ok(false);
+    };
+
+    let _permit = app_state.push_permits.acquire().await?;
+    let started = Instant::now();
+    let result = channel.send(&target.destination, notification).await;
+    let latency_ms =
+        started.elapsed().as_millis().try_into().unwrap_or(i32::MAX);
+
+    let (status, error) = match result {
+        Ok(status) => (Some(status), None),
+        Err(error) => (None, Some(error.to_string())),
+    };
+    let removed = status
+        .is_some_and(|s| app_state.config.status_code_to_delete.contains(&s));
+    let retry = match status {
+        Some(status) => status == 429 || status >= 500,
+        None => true,
+    };
+    delivered(
+        kind,
+        match status {
+            _ if removed => "removed",
+            Some(200..=299) => "delivered",
+            _ if retry => "retry",
+            _ => "rejected",
+        },
+    );
+
+    app_state
+        .database
+        .push_delivery
+        .record(DeliveryAttempt {
+            notification_id: notification.id,
+            address: target.address.to_owned(),
+            channel: kind.to_string(),
+            endpoint: endpoint.to_owned(),
+            status_code: status.map(i32::from),
+            latency_ms,
+            error,
+        })
+        .await?;
+
+    if removed {
+        match kind {
+            ChannelKind::WebPush => {
+                app_state.database.subscription.deactivate(endpoint).await?;
             },
-        };
-        let res = send_push(state, subscription, push_header, push_data).await;
-        if let Err(e) = res {
-            tracing::error!("Push notification failed: {}", e);
-        };
-    });
+            _ if target.address == OPS_ADDRESS => {
+                tracing::warn!("Ops webhook answered with {:?}", status);
+            },
+            _ => {
+                app_state
+                    .database
+                    .subscription_channel
+                    .deactivate(kind.to_string(), endpoint)
+                    .await?;
+            },
+        }
+        return Ok(false);
+    }
+
+    Ok(retry)
 }
 
-pub async fn send_push(
-    state: AppState<State>,
-    subscription: Subscription,
-    push_header: PushHeader,
-    push_data: PushData,
-) -> Result<(), Error> {
-    let url = Url::parse(&subscription.endpoint)?;
-    let exp = Local::now().timestamp_millis() / 1000 + push_header.ttl;
+fn delivered(kind: ChannelKind, outcome: &str) {
+    metrics()
+        .push_deliveries
+        .with_label_values(&[&kind.to_string(), outcome])
+        .inc();
+}
+
+/// What happens to a notification for one subscription
+#[derive(Debug, PartialEq)]
+enum Gate {
+    Send,
+    /// Kept for the digest of the subscription
+    Hold,
+    Drop,
+}
+
+/// Preferences of a subscription as they apply at the moment
+#[derive(Debug)]
+struct Policy {
+    muted: Vec<String>,
+    interval: TimeDelta,
+    quiet: bool,
+    digest: bool,
+}
+
+impl Policy {
+    fn new(state: Option<&PreferenceState>, default_interval: i32) -> Self {
+        let Some(state) = state else {
+            return Self {
+                muted: vec![],
+                interval: TimeDelta::minutes(default_interval.into()),
+                quiet: false,
+                digest: true,
+            };
+        };
+        let preference = &state.preference;
+
+        Self {
+            muted: preference.muted_types.to_owned(),
+            interval: TimeDelta::minutes(
+                preference
+                    .min_interval_minutes
+                    .unwrap_or(default_interval)
+                    .into(),
+            ),
+            quiet: match (preference.quiet_start, preference.quiet_end) {
+                (Some(start), Some(end)) => {
+                    in_quiet_hours(start, end, state.local_time)
+                },
+                _ => false,
+            },
+            digest: preference.digest,
+        }
+    }
+
+    /// `last` is the last notification of the type for the lease let
+    /// through to the subscription. A retry of that notification is sent
+    /// regardless of the quiet hours, so a subscription never gets only part
+    /// of its destinations.
+    fn gate(
+        &self,
+        push_type: &str,
+        last: Option<&PushThrottle>,
+        notification_id: i64,
+        now: DateTime<Utc>,
+    ) -> Gate {
+        if self.muted.iter().any(|muted| muted == push_type) {
+            return Gate::Drop;
+        }
+
+        if last.is_some_and(|last| last.notification_id == notification_id) {
+            return Gate::Send;
+        }
+
+        let throttled =
+            last.is_some_and(|last| now - last.sent_at < self.interval);
+        match (self.quiet || throttled, self.digest) {
+            (false, _) => Gate::Send,
+            (true, true) => Gate::Hold,
+            (true, false) => Gate::Drop,
+        }
+    }
+}
 
-    let scheme = url.scheme();
-    let host = if let Some(h) = url.host() {
-        h.to_string()
+/// Quiet hours ending before they start run past midnight
+fn in_quiet_hours(start: NaiveTime, end: NaiveTime, time: NaiveTime) -> bool {
+    if start <= end {
+        start <= time && time < end
     } else {
-        return Err(Error::InvalidOption {
-            option: String::from("host"),
-        });
-    };
+        time >= start || time < end
+    }
+}
+
+/// Drop the destinations of subscriptions that muted the type, and hold back
+/// those in quiet hours or within the minimum interval since the last
+/// notification of the type for the lease. Digests are not held back again.
+async fn apply_preferences(
+    app_state: &AppState<State>,
+    item: &PushOutbox,
+    notification_id: i64,
+    lease: Option<&str>,
+    targets: Vec<Target>,
+) -> Result<Vec<Target>, Error> {
+    let subscriptions: HashSet<(String, String)> = targets
+        .iter()
+        .filter_map(|t| Some((t.address.to_owned(), t.auth.to_owned()?)))
+        .collect();
+
+    if item.push_type == PUSH_TYPES::Digest.to_string()
+        || subscriptions.is_empty()
+    {
+        return Ok(targets);
+    }
+
+    let (addresses, auths): (Vec<String>, Vec<String>) =
+        subscriptions.iter().cloned().unzip();
+    let throttle_lease = lease.unwrap_or_default().to_owned();
+    let (preferences, throttles) = tokio::try_join!(
+        app_state
+            .database
+            .subscription_preference
+            .get_by_subscriptions(addresses.to_owned(), auths.to_owned()),
+        app_state.database.push_throttle.get_by_subscriptions(
+            addresses,
+            auths,
+            throttle_lease.to_owned(),
+            item.push_type.to_owned(),
+        ),
+    )?;
+
+    let preferences: HashMap<(String, String), PreferenceState> = preferences
+        .into_iter()
+        .map(|p| {
+            (
+                (
+                    p.preference.address.to_owned(),
+                    p.preference.auth.to_owned(),
+                ),
+                p,
+            )
+        })
+        .collect();
+    let throttles: HashMap<(String, String), PushThrottle> = throttles
+        .into_iter()
+        .map(|t| ((t.address.to_owned(), t.auth.to_owned()), t))
+        .collect();
+
+    let now = Utc::now();
+    let mut sent = HashSet::new();
+    for key in subscriptions {
+        let policy = Policy::new(
+            preferences.get(&key),
+            app_state.config.push_min_interval_minutes,
+        );
+        let last = throttles.get(&key);
+
+        match policy.gate(&item.push_type, last, notification_id, now) {
+            Gate::Send => {
+                if last.map(|l| l.notification_id) != Some(notification_id) {
+                    app_state
+                        .database
+                        .push_throttle
+                        .record(
+                            key.0.to_owned(),
+                            key.1.to_owned(),
+                            throttle_lease.to_owned(),
+                            item.push_type.to_owned(),
+                            notification_id,
+                        )
+                        .await?;
+                }
+                sent.insert(key);
+            },
+            Gate::Hold => {
+                app_state
+                    .database
+                    .push_digest
+                    .insert(
+                        key.0,
+                        key.1,
+                        notification_id,
+                        item.push_type.to_owned(),
+                        lease.map(str::to_owned),
+                    )
+                    .await?;
+            },
+            Gate::Drop => {},
+        }
+    }
+
+    Ok(targets
+        .into_iter()
+        .filter(|t| match &t.auth {
+            Some(auth) => {
+                sent.contains(&(t.address.to_owned(), auth.to_owned()))
+            },
+            None => true,
+        })
+        .collect())
+}
 
-    let aud = format!("{}://{}", scheme, host);
-    let sub = format!("mailto:{}", &state.config.mail_to);
+/// Queue a digest for every subscription with held notifications once its
+/// quiet hours are over and the minimum interval passed since the oldest was
+/// held. Returns the number of digests queued.
+pub async fn flush_digests(
+    app_state: &AppState<State>,
+) -> Result<usize, Error> {
+    let pending = app_state.database.push_digest.get_pending().await?;
+    if pending.is_empty() {
+        return Ok(0);
+    }
 
-    let key = EncodingKey::from_ec_pem(&state.config.vapid_private_key)?;
-    let claims = Claims { aud, sub, exp };
-    let token = encode(&Header::new(Algorithm::ES256), &claims, &key)?;
+    let (addresses, auths) = pending
+        .iter()
+        .map(|(address, auth, _)| (address.to_owned(), auth.to_owned()))
+        .unzip();
+    let preferences: HashMap<(String, String), PreferenceState> = app_state
+        .database
+        .subscription_preference
+        .get_by_subscriptions(addresses, auths)
+        .await?
+        .into_iter()
+        .map(|p| {
+            (
+                (
+                    p.preference.address.to_owned(),
+                    p.preference.auth.to_owned(),
+                ),
+                p,
+            )
+        })
+        .collect();
 
-    let p256dh = BASE64_URL.decode(subscription.p256dh)?;
-    let auth = BASE64_URL.decode(subscription.auth)?;
+    let now = Utc::now();
+    let mut queued = 0;
+    for (address, auth, oldest) in pending {
+        let policy = Policy::new(
+            preferences.get(&(address.to_owned(), auth.to_owned())),
+            app_state.config.push_min_interval_minutes,
+        );
+        if policy.quiet || now - oldest < policy.interval {
+            continue;
+        }
 
-    let data = ece::encrypt(&p256dh, &auth, push_data.to_string().as_bytes())?;
-    let endpoint = subscription.endpoint.to_string();
+        let mut transaction = app_state.database.pool.begin().await?;
+        let items = app_state
+            .database
+            .push_digest
+            .take(address.to_owned(), auth.to_owned(), &mut transaction)
+            .await?;
 
-    let _status = state
-        .http
-        .post_push(subscription.endpoint, token, push_header, data)
+        // Taken by another worker
+        if items.is_empty() {
+            continue;
+        }
+
+        let push_data = PushData::new(PUSH_TYPES::Digest, &digest(&items))?;
+        enqueue(
+            app_state,
+            Recipient::Subscription(address, auth),
+            push_data,
+            &mut transaction,
+        )
         .await?;
+        transaction.commit().await?;
+        queued += 1;
+    }
+
+    Ok(queued)
+}
 
-    if state.config.status_code_to_delete.contains(&_status) {
-        state.database.subscription.deactivate(endpoint).await?;
+/// Held notifications counted per type and lease
+fn digest(items: &[PushDigest]) -> DigestPush {
+    let mut counts: BTreeMap<(&str, Option<&str>), usize> = BTreeMap::new();
+    for item in items {
+        *counts
+            .entry((item.push_type.as_str(), item.lease.as_deref()))
+            .or_default() += 1;
     }
 
-    Ok(())
+    let positions = items
+        .iter()
+        .filter_map(|item| item.lease.as_deref())
+        .collect::<HashSet<_>>()
+        .len();
+
+    DigestPush {
+        count: items.len(),
+        positions,
+        items: counts
+            .into_iter()
+            .map(|((push_type, lease), count)| DigestItem {
+                r#type: push_type.to_owned(),
+                position: lease.map(str::to_owned),
+                count,
+            })
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn time(hour: u32, minute: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    fn policy(quiet: bool, digest: bool) -> Policy {
+        Policy {
+            muted: vec![String::from("Funding")],
+            interval: TimeDelta::minutes(15),
+            quiet,
+            digest,
+        }
+    }
+
+    fn last(notification_id: i64, sent_at: DateTime<Utc>) -> PushThrottle {
+        PushThrottle {
+            address: String::from("nolus1wallet"),
+            auth: String::from("auth"),
+            lease: String::from("nolus1lease"),
+            push_type: String::from("FundNow"),
+            notification_id,
+            sent_at,
+        }
+    }
+
+    #[test]
+    fn quiet_hours_may_span_midnight() {
+        assert!(in_quiet_hours(time(9, 0), time(17, 0), time(12, 0)));
+        assert!(!in_quiet_hours(time(9, 0), time(17, 0), time(17, 0)));
+        assert!(in_quiet_hours(time(22, 0), time(7, 0), time(23, 30)));
+        assert!(in_quiet_hours(time(22, 0), time(7, 0), time(6, 59)));
+        assert!(!in_quiet_hours(time(22, 0), time(7, 0), time(12, 0)));
+        assert!(!in_quiet_hours(time(8, 0), time(8, 0), time(8, 0)));
+    }
+
+    #[test]
+    fn gate_follows_preferences() {
+        let now = Utc::now();
+        let recent = last(1, now - TimeDelta::minutes(5));
+        let old = last(1, now - TimeDelta::minutes(30));
+
+        assert_eq!(
+            policy(false, true).gate("Funding", None, 2, now),
+            Gate::Drop
+        );
+        assert_eq!(
+            policy(false, true).gate("FundNow", None, 2, now),
+            Gate::Send
+        );
+        assert_eq!(
+            policy(false, true).gate("FundNow", Some(&old), 2, now),
+            Gate::Send
+        );
+        assert_eq!(
+            policy(false, true).gate("FundNow", Some(&recent), 2, now),
+            Gate::Hold
+        );
+        assert_eq!(
+            policy(false, false).gate("FundNow", Some(&recent), 2, now),
+            Gate::Drop
+        );
+        assert_eq!(
+            policy(true, true).gate("FundNow", None, 2, now),
+            Gate::Hold
+        );
+
+        // Retry of the notification let through
+        assert_eq!(
+            policy(true, true).gate("FundNow", Some(&recent), 1, now),
+            Gate::Send
+        );
+    }
+
+    #[test]
+    fn digest_counts_per_type_and_lease() {
+        let item = |id: i64, push_type: &str, lease: Option<&str>| PushDigest {
+            address: String::from("nolus1wallet"),
+            auth: String::from("auth"),
+            notification_id: id,
+            push_type: push_type.to_owned(),
+            lease: lease.map(str::to_owned),
+            created_at: Utc::now(),
+        };
+        let digest = digest(&[
+            item(1, "Funding", Some("nolus1a")),
+            item(2, "Funding", Some("nolus1a")),
+            item(3, "FundNow", Some("nolus1a")),
+            item(4, "Funding", Some("nolus1b")),
+            item(5, "Alert", None),
+        ]);
+
+        assert_eq!(digest.count, 5);
+        assert_eq!(digest.positions, 2);
+        assert_eq!(digest.items.len(), 4);
+        assert_eq!(digest.items[2].r#type, "Funding");
+        assert_eq!(digest.items[2].position.as_deref(), Some("nolus1a"));
+        assert_eq!(digest.items[2].count, 2);
+    }
 }
@@ -0,0 +1,377 @@
+//! Layered settings
+//!
+//! Values come, lowest precedence first, from the defaults of
+//! [`Config::from_settings`], an optional TOML file and the environment.
+//! Keys are the lowercase names of their environment variables. Tables of
+//! the file are flattened with `_`, so `[db] max_connections` is the
+//! `db_max_connections` key and `DB_MAX_CONNECTIONS` overrides it. Arrays
+//! are joined with `,` and empty values count as unset.
+//!
+//! Getters record every missing or invalid value under its key and
+//! [`Settings::finish`] reports them together.
+//!
+//! [`Config::from_settings`]: crate::configuration::Config::from_settings
+
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    env,
+    fmt::Display,
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use url::Url;
+
+use crate::error::Error;
+
+/// Environment variable naming the configuration file when `--config` is
+/// not given
+pub const CONFIG_ENV: &str = "ETL_CONFIG";
+
+/// Environment variables still read for keys renamed since
+const ENV_ALIASES: [(&str, &str); 1] =
+    [("status_code_to_delete", "STATUS_COODE_TO_DELETE")];
+
+#[derive(Debug)]
+pub struct Settings {
+    file: HashMap<String, String>,
+    env: HashMap<String, String>,
+    /// Directory relative paths are resolved against
+    base_dir: PathBuf,
+    path: Option<PathBuf>,
+    errors: RefCell<Vec<String>>,
+}
+
+/// Path of the configuration file from `--config <path>`,
+/// `--config=<path>` or `ETL_CONFIG`
+pub fn config_path() -> Option<PathBuf> {
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--config" {
+            return args.next().map(PathBuf::from);
+        }
+        if let Some(path) = arg.strip_prefix("--config=") {
+            return Some(PathBuf::from(path));
+        }
+    }
+
+    env::var_os(CONFIG_ENV).map(PathBuf::from)
+}
+
+impl Settings {
+    /// Settings of the file at `path`, if any, under the process
+    /// environment. Relative paths are resolved against the directory of
+    /// the file, or the working directory without one.
+    pub fn load(path: Option<&Path>) -> Result<Settings, Error> {
+        let env = env::vars().collect();
+        let Some(path) = path else {
+            return Ok(Settings::new(
+                HashMap::new(),
+                env,
+                env::current_dir()?,
+                None,
+            ));
+        };
+
+        let file = fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|contents| parse_file(&contents))
+            .map_err(|e| {
+                Error::ConfigurationError(format!("{}: {}", path.display(), e))
+            })?;
+        let base_dir = match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+            _ => env::current_dir()?,
+        };
+
+        Ok(Settings::new(file, env, base_dir, Some(path.to_path_buf())))
+    }
+
+    pub fn new(
+        file: HashMap<String, String>,
+        env: HashMap<String, String>,
+        base_dir: PathBuf,
+        path: Option<PathBuf>,
+    ) -> Settings {
+        Settings {
+            file,
+            env,
+            base_dir,
+            path,
+            errors: RefCell::new(vec![]),
+        }
+    }
+
+    /// Configuration file the settings were read from
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    fn raw(&self, key: &str) -> Option<&str> {
+        let alias = ENV_ALIASES
+            .iter()
+            .find(|(name, _)| *name == key)
+            .and_then(|(_, var)| self.env.get(*var));
+
+        self.env
+            .get(&key.to_uppercase())
+            .or(alias)
+            .or_else(|| self.file.get(key))
+            .map(|value| value.trim())
+            .filter(|value| !value.is_empty())
+    }
+
+    /// Record an error of `key`
+    pub fn error(&self, key: &str, message: impl Display) {
+        self.errors
+            .borrow_mut()
+            .push(format!("{}: {}", key, message));
+    }
+
+    /// Record `message` under `key` unless `valid`
+    pub fn check(&self, key: &str, valid: bool, message: &str) {
+        if !valid {
+            self.error(key, message);
+        }
+    }
+
+    /// Value of `key`, `None` when unset or invalid
+    pub fn get<T>(&self, key: &str) -> Option<T>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        let raw = self.raw(key)?;
+        match raw.parse() {
+            Ok(value) => Some(value),
+            Err(e) => {
+                self.error(key, format_args!("invalid value `{}`: {}", raw, e));
+                None
+            },
+        }
+    }
+
+    pub fn get_or<T>(&self, key: &str, default: T) -> T
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        self.get(key).unwrap_or(default)
+    }
+
+    /// Value of `key`, recorded as missing when unset. The default of `T`
+    /// stands in for missing and invalid values until [`Settings::finish`].
+    pub fn required<T>(&self, key: &str) -> T
+    where
+        T: FromStr + Default,
+        T::Err: Display,
+    {
+        if self.raw(key).is_none() {
+            self.error(key, "required");
+        }
+
+        self.get(key).unwrap_or_default()
+    }
+
+    /// Comma-separated values of `key`, blanks skipped
+    pub fn list<T>(&self, key: &str) -> Option<Vec<T>>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        let raw = self.raw(key)?;
+        let mut values = vec![];
+        for item in raw.split(',').map(str::trim).filter(|i| !i.is_empty()) {
+            match item.parse() {
+                Ok(value) => values.push(value),
+                Err(e) => {
+                    self.error(
+                        key,
+                        format_args!("invalid item `{}`: {}", item, e),
+                    );
+                },
+            }
+        }
+
+        Some(values)
+    }
+
+    /// Directory of `key`, resolved against the directory of the
+    /// configuration file when relative
+    pub fn dir(&self, key: &str, default: &str) -> PathBuf {
+        let dir = PathBuf::from(self.raw(key).unwrap_or(default));
+        if dir.is_absolute() {
+            dir
+        } else {
+            self.base_dir.join(dir)
+        }
+    }
+
+    /// Record an error of `key` unless `value` is empty or a URL of one of
+    /// `schemes`
+    pub fn check_url(&self, key: &str, value: &str, schemes: &[&str]) {
+        if value.is_empty() {
+            return;
+        }
+
+        match Url::parse(value) {
+            Ok(url) if schemes.contains(&url.scheme()) => {},
+            _ => self.error(
+                key,
+                format_args!("expected a {} URL", schemes.join(" or ")),
+            ),
+        }
+    }
+
+    /// Fail with every error recorded, in the order found
+    pub fn finish(&self) -> Result<(), Error> {
+        let errors = self.errors.borrow();
+        if errors.is_empty() {
+            return Ok(());
+        }
+
+        Err(Error::ConfigurationError(format!(
+            "{} invalid setting(s): {}",
+            errors.len(),
+            errors.join("; ")
+        )))
+    }
+}
+
+fn parse_file(contents: &str) -> Result<HashMap<String, String>, String> {
+    let table: toml::Table = contents.parse().map_err(|e| format!("{}", e))?;
+    let mut values = HashMap::new();
+    flatten("", table, &mut values);
+
+    Ok(values)
+}
+
+fn flatten(
+    prefix: &str,
+    table: toml::Table,
+    values: &mut HashMap<String, String>,
+) {
+    for (key, value) in table {
+        let key = if prefix.is_empty() {
+            key.to_lowercase()
+        } else {
+            format!("{}_{}", prefix, key.to_lowercase())
+        };
+
+        match value {
+            toml::Value::Table(table) => flatten(&key, table, values),
+            toml::Value::Array(items) => {
+                let items: Vec<String> = items.iter().map(scalar).collect();
+                values.insert(key, items.join(","));
+            },
+            value => {
+                values.insert(key, scalar(&value));
+            },
+        }
+    }
+}
+
+fn scalar(value: &toml::Value) -> String {
+    match value {
+        toml::Value::String(value) => value.to_owned(),
+        value => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::configuration::Config;
+
+    fn settings(file: &str, env: &[(&str, &str)]) -> Settings {
+        Settings::new(
+            parse_file(file).unwrap(),
+            env.iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            PathBuf::from("/etc/etl"),
+            None,
+        )
+    }
+
+    #[test]
+    fn environment_overrides_flattened_file() {
+        let settings = settings(
+            r#"
+            port = 8080
+            allowed_origins = ["https://a.io", "https://b.io"]
+            status_code_to_delete = [410]
+
+            [db]
+            max_connections = 10
+            min_connections = 2
+            "#,
+            &[
+                ("DB_MAX_CONNECTIONS", "20"),
+                ("STATUS_COODE_TO_DELETE", "404"),
+            ],
+        );
+
+        assert_eq!(settings.get::<u16>("port"), Some(8080));
+        assert_eq!(settings.get::<u32>("db_max_connections"), Some(20));
+        assert_eq!(settings.get::<u32>("db_min_connections"), Some(2));
+        assert_eq!(
+            settings.list::<String>("allowed_origins").unwrap(),
+            ["https://a.io", "https://b.io"]
+        );
+        assert_eq!(
+            settings.list::<u16>("status_code_to_delete").unwrap(),
+            [404]
+        );
+        assert_eq!(
+            settings.dir("cert_dir", "cert"),
+            Path::new("/etc/etl/cert")
+        );
+        assert!(settings.finish().is_ok());
+    }
+
+    #[test]
+    fn example_file_parses() {
+        let settings =
+            settings(include_str!("../../../config.example.toml"), &[]);
+
+        assert_eq!(
+            settings.get::<String>("host").unwrap(),
+            "rpc.nolus.network"
+        );
+        assert_eq!(settings.get::<u32>("db_max_connections"), None);
+        assert!(settings.finish().is_ok());
+    }
+
+    #[test]
+    fn errors_name_every_field() {
+        let settings = settings(
+            r#"
+            port = "http"
+            database_url = "mysql://localhost/etl"
+
+            [db]
+            max_connections = 2
+            min_connections = 4
+            "#,
+            &[],
+        );
+
+        let Err(Error::ConfigurationError(message)) =
+            Config::from_settings(&settings)
+        else {
+            panic!("invalid settings accepted");
+        };
+        for field in [
+            "host: required",
+            "auth: required",
+            "port: invalid value `http`",
+            "database_url: expected a postgres or postgresql URL",
+            "db_min_connections: ",
+        ] {
+            assert!(message.contains(field), "{} not in {}", field, message);
+        }
+    }
+}
@@ -0,0 +1,340 @@
+//! Prometheus metrics
+//!
+//! One registry per process, rendered in the text format by `/metrics` of
+//! the API server and by [`serve`] in the ingest process. Instrumented code
+//! reaches the series through [`metrics`], which registers them on first
+//! use.
+
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::OnceLock,
+    task::{ready, Context, Poll},
+    time::{Duration, Instant},
+};
+
+use prometheus::{
+    Encoder as _, Histogram, HistogramOpts, HistogramVec, IntCounterVec,
+    IntGauge, IntGaugeVec, Opts, Registry, TextEncoder,
+};
+use tokio::{
+    io::{AsyncReadExt as _, AsyncWriteExt as _},
+    net::{TcpListener, TcpStream},
+};
+
+use crate::{dao::PoolType, error::Error};
+
+/// Content type of the text exposition format
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
+/// Buckets of database queries and gRPC requests, in seconds
+const LATENCY_BUCKETS: [f64; 12] = [
+    0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 10.0,
+];
+
+/// Buckets of scheduled tasks, in seconds
+const TASK_BUCKETS: [f64; 10] =
+    [0.1, 0.5, 1.0, 5.0, 10.0, 30.0, 60.0, 300.0, 900.0, 3600.0];
+
+/// Pause after a failed accept of the metrics listener
+const ACCEPT_RETRY: Duration = Duration::from_millis(100);
+
+static METRICS: OnceLock<Metrics> = OnceLock::new();
+
+/// Series of the process, see [`metrics`]
+#[derive(Debug)]
+pub struct Metrics {
+    registry: Registry,
+    /// Latest height reported by the node
+    pub chain_head_height: IntGauge,
+    /// Highest height indexed by this process
+    pub indexed_height: IntGauge,
+    /// Blocks indexed, by `source` (`live` or `backfill`)
+    pub blocks_indexed: IntCounterVec,
+    /// Contract events indexed, by `type`
+    pub events: IntCounterVec,
+    /// Contract events whose attributes could not be parsed, by `type`
+    pub event_parse_failures: IntCounterVec,
+    /// Transaction messages that could not be decoded, by `type`
+    pub message_parse_failures: IntCounterVec,
+    /// gRPC attempts, by `rpc` and `outcome` (`ok`, `retry` or `error`)
+    pub grpc_attempts: IntCounterVec,
+    /// Latency of gRPC attempts, by `rpc`
+    pub grpc_duration: HistogramVec,
+    /// Wait for a gRPC permit
+    pub grpc_permit_wait: Histogram,
+    /// Connections of the database pool, by `state` (`idle` or `in_use`)
+    pub db_connections: IntGaugeVec,
+    pub db_max_connections: IntGauge,
+    /// Database queries, by `dao` and `method`
+    pub db_query_duration: HistogramVec,
+    /// Reads of the API caches, by `cache` and `result` (`hit` or `miss`)
+    pub cache_requests: IntCounterVec,
+    /// Notification attempts, by `channel` and `outcome`
+    pub push_deliveries: IntCounterVec,
+    /// Aggregation and other scheduled tasks, by `task`
+    pub task_duration: HistogramVec,
+}
+
+/// Series of the process, registered on first use
+pub fn metrics() -> &'static Metrics {
+    METRICS.get_or_init(|| {
+        Metrics::new().expect("metric names and labels are valid")
+    })
+}
+
+impl Metrics {
+    fn new() -> Result<Metrics, prometheus::Error> {
+        let registry = Registry::new_custom(Some(String::from("etl")), None)?;
+
+        let gauge = |name: &str, help: &str| -> Result<IntGauge, _> {
+            let gauge = IntGauge::new(name, help)?;
+            registry.register(Box::new(gauge.clone()))?;
+            Ok::<_, prometheus::Error>(gauge)
+        };
+        let counter = |name: &str, help: &str, labels: &[&str]| {
+            let counter = IntCounterVec::new(Opts::new(name, help), labels)?;
+            registry.register(Box::new(counter.clone()))?;
+            Ok::<_, prometheus::Error>(counter)
+        };
+        let histogram =
+            |name: &str, help: &str, labels: &[&str], buckets: &[f64]| {
+                let histogram = HistogramVec::new(
+                    HistogramOpts::new(name, help).buckets(buckets.to_vec()),
+                    labels,
+                )?;
+                registry.register(Box::new(histogram.clone()))?;
+                Ok::<_, prometheus::Error>(histogram)
+            };
+
+        let grpc_permit_wait = Histogram::with_opts(
+            HistogramOpts::new(
+                "grpc_permit_wait_seconds",
+                "Wait for a gRPC permit",
+            )
+            .buckets(LATENCY_BUCKETS.to_vec()),
+        )?;
+        registry.register(Box::new(grpc_permit_wait.clone()))?;
+
+        let db_connections = IntGaugeVec::new(
+            Opts::new("db_connections", "Connections of the database pool"),
+            &["state"],
+        )?;
+        registry.register(Box::new(db_connections.clone()))?;
+
+        Ok(Metrics {
+            chain_head_height: gauge(
+                "chain_head_height",
+                "Latest height reported by the node",
+            )?,
+            indexed_height: gauge(
+                "indexed_height",
+                "Highest height indexed by this process",
+            )?,
+            blocks_indexed: counter(
+                "blocks_indexed_total",
+                "Blocks indexed",
+                &["source"],
+            )?,
+            events: counter(
+                "events_total",
+                "Contract events indexed",
+                &["type"],
+            )?,
+            event_parse_failures: counter(
+                "event_parse_failures_total",
+                "Contract events whose attributes could not be parsed",
+                &["type"],
+            )?,
+            message_parse_failures: counter(
+                "message_parse_failures_total",
+                "Transaction messages that could not be decoded",
+                &["type"],
+            )?,
+            grpc_attempts: counter(
+                "grpc_attempts_total",
+                "gRPC attempts",
+                &["rpc", "outcome"],
+            )?,
+            grpc_duration: histogram(
+                "grpc_attempt_duration_seconds",
+                "Latency of gRPC attempts",
+                &["rpc"],
+                &LATENCY_BUCKETS,
+            )?,
+            grpc_permit_wait,
+            db_connections,
+            db_max_connections: gauge(
+                "db_max_connections",
+                "Maximum connections of the database pool",
+            )?,
+            db_query_duration: histogram(
+                "db_query_duration_seconds",
+                "Database queries",
+                &["dao", "method"],
+                &LATENCY_BUCKETS,
+            )?,
+            cache_requests: counter(
+                "cache_requests_total",
+                "Reads of the API caches",
+                &["cache", "result"],
+            )?,
+            push_deliveries: counter(
+                "push_deliveries_total",
+                "Notification attempts",
+                &["channel", "outcome"],
+            )?,
+            task_duration: histogram(
+                "task_duration_seconds",
+                "Aggregation and other scheduled tasks",
+                &["task"],
+                &TASK_BUCKETS,
+            )?,
+            registry,
+        })
+    }
+
+    /// Record an indexed block
+    pub fn block_indexed(&self, source: &str, height: i64) {
+        self.blocks_indexed.with_label_values(&[source]).inc();
+        if height > self.indexed_height.get() {
+            self.indexed_height.set(height);
+        }
+    }
+
+    pub fn task_finished(&self, task: &str, elapsed: Duration) {
+        self.task_duration
+            .with_label_values(&[task])
+            .observe(elapsed.as_secs_f64());
+    }
+
+    /// Every series in the text format, with the pool usage of the moment
+    pub fn render(&self, pool: &PoolType) -> Result<String, Error> {
+        let idle: i64 = pool.num_idle().try_into()?;
+        let size: i64 = pool.size().into();
+        self.db_connections.with_label_values(&["idle"]).set(idle);
+        self.db_connections
+            .with_label_values(&["in_use"])
+            .set(size - idle);
+        self.db_max_connections
+            .set(pool.options().get_max_connections().into());
+
+        let mut buffer = vec![];
+        TextEncoder::new()
+            .encode(&self.registry.gather(), &mut buffer)
+            .map_err(|e| Error::ServerError(e.to_string()))?;
+
+        String::from_utf8(buffer).map_err(|e| Error::ServerError(e.to_string()))
+    }
+}
+
+/// Times a database query into `db_query_duration_seconds`
+pub trait Timed: Future + Sized {
+    fn timed(
+        self,
+        dao: &'static str,
+        method: &'static str,
+    ) -> TimedQuery<Self> {
+        TimedQuery {
+            future: Box::pin(self),
+            labels: [dao, method],
+            started: None,
+        }
+    }
+}
+
+impl<F: Future> Timed for F {}
+
+pub struct TimedQuery<F: Future> {
+    future: Pin<Box<F>>,
+    labels: [&'static str; 2],
+    started: Option<Instant>,
+}
+
+impl<F: Future> Future for TimedQuery<F> {
+    type Output = F::Output;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<F::Output> {
+        let started = *self.started.get_or_insert_with(Instant::now);
+        let output = ready!(self.future.as_mut().poll(cx));
+        metrics()
+            .db_query_duration
+            .with_label_values(&self.labels)
+            .observe(started.elapsed().as_secs_f64());
+
+        Poll::Ready(output)
+    }
+}
+
+/// Serve `GET /metrics` on `host:port` for processes without an HTTP
+/// server. Port 0 disables it.
+pub async fn serve(host: &str, port: u16, pool: PoolType) -> Result<(), Error> {
+    if port == 0 {
+        return Ok(());
+    }
+
+    let listener = TcpListener::bind((host, port)).await?;
+    tracing::info!("Serving metrics on {}:{}", host, port);
+
+    loop {
+        // Errors of a single connection, like EMFILE, must not stop the
+        // process
+        let stream = match listener.accept().await {
+            Ok((stream, _)) => stream,
+            Err(e) => {
+                tracing::warn!("Metrics accept error {}", e);
+                tokio::time::sleep(ACCEPT_RETRY).await;
+                continue;
+            },
+        };
+        let pool = pool.clone();
+        tokio::spawn(async move {
+            if let Err(e) = respond(stream, &pool).await {
+                tracing::warn!("Metrics request error {}", e);
+            }
+        });
+    }
+}
+
+async fn respond(mut stream: TcpStream, pool: &PoolType) -> Result<(), Error> {
+    let mut request = vec![0; 1024];
+    let read = stream.read(&mut request).await?;
+    let line = String::from_utf8_lossy(&request[..read]);
+
+    let (status, content_type, body) = if line.starts_with("GET /metrics ") {
+        ("200 OK", CONTENT_TYPE, metrics().render(pool)?)
+    } else {
+        ("404 Not Found", "text/plain", String::from("Not Found"))
+    };
+
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn timed_query_observes_latency() {
+        let rows = async { Ok::<_, Error>(3) }
+            .timed("test", "timed_query")
+            .await;
+        assert_eq!(rows.unwrap(), 3);
+
+        let count = metrics()
+            .db_query_duration
+            .with_label_values(&["test", "timed_query"])
+            .get_sample_count();
+        assert_eq!(count, 1);
+    }
+}
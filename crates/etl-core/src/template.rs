@@ -0,0 +1,198 @@
+//! Notification templates
+//!
+//! Every push type has a title, body, deep link and icon per locale, in
+//! `templates/push/{locale}.json`. Placeholders such as `{symbol}` are
+//! filled from the fields of the notification body, plus `{app_url}`. A body
+//! with a `variant` field uses the `{type}.{variant}` template when the
+//! locale has one, e.g. `Alert.price`.
+//!
+//! Templates are rendered at delivery, in the locale of each subscription.
+//! Unknown locales fall back to their language, then to English.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::{error::Error, types::PUSH_TYPES};
+
+pub const DEFAULT_LOCALE: &str = "en";
+
+/// Rendered for missing placeholders
+const MISSING: &str = "-";
+
+const LOCALES: [(&str, &str); 2] = [
+    ("en", include_str!("../templates/push/en.json")),
+    ("es", include_str!("../templates/push/es.json")),
+];
+
+const PUSH_TYPES_ALL: [PUSH_TYPES; 8] = [
+    PUSH_TYPES::Funding,
+    PUSH_TYPES::FundingRecommended,
+    PUSH_TYPES::FundNow,
+    PUSH_TYPES::PartiallyLiquidated,
+    PUSH_TYPES::FullyLiquidated,
+    PUSH_TYPES::Alert,
+    PUSH_TYPES::Digest,
+    PUSH_TYPES::Unsupported,
+];
+
+/// Text of a notification as shown to the user
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Content {
+    pub title: String,
+    pub body: String,
+    pub link: String,
+    pub icon: String,
+}
+
+pub struct Templates {
+    app_url: String,
+    locales: HashMap<String, HashMap<String, Content>>,
+}
+
+impl Templates {
+    /// Parse the bundled templates. Every push type needs an English
+    /// template.
+    pub fn new(app_url: &str) -> Result<Self, Error> {
+        let mut locales = HashMap::new();
+        for (locale, source) in LOCALES {
+            let templates: HashMap<String, Content> =
+                serde_json::from_str(source)?;
+            locales.insert(locale.to_owned(), templates);
+        }
+
+        for push_type in PUSH_TYPES_ALL {
+            if !locales[DEFAULT_LOCALE].contains_key(&push_type.to_string()) {
+                return Err(Error::ConfigurationError(format!(
+                    "Missing {} template for {}",
+                    DEFAULT_LOCALE, push_type
+                )));
+            }
+        }
+
+        Ok(Self {
+            app_url: app_url.trim_end_matches('/').to_owned(),
+            locales,
+        })
+    }
+
+    /// Locale the templates are rendered in for a requested one
+    pub fn resolve_locale(&self, locale: &str) -> &str {
+        let locale = locale.to_lowercase().replace('_', "-");
+        let language = locale.split('-').next().unwrap_or_default();
+
+        let resolved = [locale.as_str(), language]
+            .into_iter()
+            .find_map(|l| self.locales.get_key_value(l))
+            .map_or(DEFAULT_LOCALE, |(l, _)| l.as_str());
+
+        resolved
+    }
+
+    pub fn render(&self, locale: &str, push_type: &str, body: &str) -> Content {
+        let fields = match serde_json::from_str(body) {
+            Ok(Value::Object(fields)) => fields,
+            _ => Map::new(),
+        };
+
+        let mut keys = vec![];
+        if let Some(Value::String(variant)) = fields.get("variant") {
+            keys.push(format!("{}.{}", push_type, variant));
+        }
+        keys.push(push_type.to_owned());
+
+        let locale = self.resolve_locale(locale);
+        let template = [locale, DEFAULT_LOCALE]
+            .into_iter()
+            .filter_map(|l| self.locales.get(l))
+            .find_map(|templates| keys.iter().find_map(|k| templates.get(k)))
+            .unwrap_or_else(|| {
+                &self.locales[DEFAULT_LOCALE]
+                    [&PUSH_TYPES::Unsupported.to_string()]
+            });
+
+        Content {
+            title: self.fill(&template.title, &fields),
+            body: self.fill(&template.body, &fields),
+            link: self.fill(&template.link, &fields),
+            icon: self.fill(&template.icon, &fields),
+        }
+    }
+
+    fn fill(&self, template: &str, fields: &Map<String, Value>) -> String {
+        let mut output = String::with_capacity(template.len());
+        let mut rest = template;
+
+        while let Some(start) = rest.find('{') {
+            output.push_str(&rest[..start]);
+            let Some(end) = rest[start..].find('}') else {
+                rest = &rest[start..];
+                break;
+            };
+
+            let name = &rest[start + 1..start + end];
+            match (name, fields.get(name)) {
+                ("app_url", _) => output.push_str(&self.app_url),
+                (_, Some(Value::String(value))) => output.push_str(value),
+                (_, Some(Value::Number(value))) => {
+                    output.push_str(&value.to_string())
+                },
+                (_, Some(Value::Bool(value))) => {
+                    output.push_str(&value.to_string())
+                },
+                _ => output.push_str(MISSING),
+            }
+            rest = &rest[start + end + 1..];
+        }
+        output.push_str(rest);
+
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_locale_parses() {
+        let templates = Templates::new("https://app.nolus.io/").unwrap();
+        for (locale, _) in LOCALES {
+            assert!(templates.locales.contains_key(locale));
+        }
+    }
+
+    #[test]
+    fn renders_fields_in_locale() {
+        let templates = Templates::new("https://app.nolus.io/").unwrap();
+        let body = r#"{"position": "nolus1lease", "symbol": "ATOM", "protocol": "OSMOSIS-OSMOSIS-USDC_NOBLE", "amount": "10", "amount_stable": "52.40"}"#;
+
+        let content = templates.render("es-AR", "FullyLiquidated", body);
+        assert_eq!(content.title, "Posición liquidada");
+        assert_eq!(
+            content.body,
+            "Tu posición de ATOM en OSMOSIS-OSMOSIS-USDC_NOBLE fue liquidada: 10 ATOM (52.40 USD)."
+        );
+        assert_eq!(content.link, "https://app.nolus.io/positions/nolus1lease");
+
+        let content = templates.render("fr", "FullyLiquidated", body);
+        assert_eq!(content.title, "Position liquidated");
+    }
+
+    #[test]
+    fn variant_selects_template() {
+        let templates = Templates::new("https://app.nolus.io").unwrap();
+        let body = r#"{"variant": "price", "symbol": "ATOM", "comparison": "below", "threshold": "8", "value": "7.95"}"#;
+
+        let content = templates.render("en", "Alert", body);
+        assert_eq!(content.title, "ATOM price alert");
+        assert_eq!(content.body, "ATOM on - went below 8 USD: now 7.95 USD.");
+
+        let content = templates.render("en", "Alert", r#"{"rule": 3}"#);
+        assert_eq!(content.body, "Your alert 3 was triggered.");
+
+        let content = templates.render("en", "Unknown", "{}");
+        assert_eq!(content.title, "Nolus");
+    }
+}
@@ -227,6 +227,30 @@ pub struct MsgReceivePacket {
     pub sender: String,
 }
 
+/// Attributes of the `send_packet`, `recv_packet` and
+/// `write_acknowledgement` events of IBC core
+#[derive(Debug)]
+pub struct Ibc_Packet_Type {
+    pub sequence: String,
+    pub src_port: String,
+    pub src_channel: String,
+    pub dst_port: String,
+    pub dst_channel: String,
+    pub data: String,
+    pub timeout_timestamp: Option<String>,
+    pub ack: Option<String>,
+}
+
+/// Attributes of the `unbond` event of the staking module. The delegator
+/// is only emitted by recent SDK versions.
+#[derive(Debug)]
+pub struct Unbond_Type {
+    pub validator: String,
+    pub delegator: Option<String>,
+    pub amount: String,
+    pub completion_time: String,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct BodyError {
     pub code: String,
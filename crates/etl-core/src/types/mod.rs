@@ -11,5 +11,6 @@ pub use common::*;
 
 // Re-export from push
 pub use push::{
-    Claims, PushData, PushHeader, Subscription, Urgency, PUSH_TYPES,
+    Claims, DigestItem, DigestPush, LeasePush, PushData, PushHeader,
+    Subscription, Urgency, PUSH_TYPES,
 };
@@ -21,6 +21,61 @@ pub struct PushData {
     pub body: String,
 }
 
+impl PushData {
+    /// Notification of `push_type` with the fields of `body`, see
+    /// `crate::template` for how they are rendered
+    pub fn new<T: Serialize>(
+        push_type: PUSH_TYPES,
+        body: &T,
+    ) -> Result<Self, serde_json::Error> {
+        Ok(PushData {
+            r#type: push_type.to_string(),
+            body: serde_json::to_string(body)?,
+        })
+    }
+}
+
+/// Fields of a lease notification. `position` is the lease contract, used
+/// for deep links; amounts are in whole units of the asset and of the stable
+/// currency.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct LeasePush {
+    pub position: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub level: Option<i16>,
+    /// LTV in permille, as reported by the lease
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ltv: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ltv_percent: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub symbol: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub amount: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub amount_stable: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub protocol: Option<String>,
+}
+
+/// Fields of a digest of the notifications held back from a subscription
+#[derive(Debug, Clone, Serialize)]
+pub struct DigestPush {
+    pub count: usize,
+    /// Distinct leases of the held notifications
+    pub positions: usize,
+    pub items: Vec<DigestItem>,
+}
+
+/// Held notifications of one type for one lease, or for the wallet
+#[derive(Debug, Clone, Serialize)]
+pub struct DigestItem {
+    pub r#type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub position: Option<String>,
+    pub count: usize,
+}
+
 impl fmt::Display for PushData {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, r#"{{"type": "{}", "data": {}}}"#, self.r#type, self.body)
@@ -86,6 +141,8 @@ pub enum PUSH_TYPES {
     FundNow,
     PartiallyLiquidated,
     FullyLiquidated,
+    Alert,
+    Digest,
     Unsupported,
 }
 
@@ -97,6 +154,8 @@ impl fmt::Display for PUSH_TYPES {
             PUSH_TYPES::FundNow => write!(f, "FundNow"),
             PUSH_TYPES::PartiallyLiquidated => write!(f, "PartiallyLiquidated"),
             PUSH_TYPES::FullyLiquidated => write!(f, "FullyLiquidated"),
+            PUSH_TYPES::Alert => write!(f, "Alert"),
+            PUSH_TYPES::Digest => write!(f, "Digest"),
             PUSH_TYPES::Unsupported => write!(f, "Unsupported"),
         }
     }
@@ -114,6 +173,8 @@ impl From<PUSH_TYPES> for String {
                 String::from("PartiallyLiquidated")
             },
             PUSH_TYPES::FullyLiquidated => String::from("FullyLiquidated"),
+            PUSH_TYPES::Alert => String::from("Alert"),
+            PUSH_TYPES::Digest => String::from("Digest"),
             PUSH_TYPES::Unsupported => String::from("Unsupported"),
         }
     }
@@ -129,6 +190,8 @@ impl FromStr for PUSH_TYPES {
             "FundNow" => Ok(PUSH_TYPES::FundNow),
             "PartiallyLiquidated" => Ok(PUSH_TYPES::PartiallyLiquidated),
             "FullyLiquidated" => Ok(PUSH_TYPES::FullyLiquidated),
+            "Alert" => Ok(PUSH_TYPES::Alert),
+            "Digest" => Ok(PUSH_TYPES::Digest),
             "Unsupported" => Ok(PUSH_TYPES::Unsupported),
             _ => Err(io::Error::other("PUSH_TYPES not supported")),
         }
@@ -154,6 +217,15 @@ impl std::convert::From<i16> for PUSH_TYPES {
 pub struct Subscription {
     pub address: String,
     pub data: SubscriptionData,
+    /// Locale of the notifications, e.g. `en` or `es-AR`
+    #[serde(default)]
+    pub locale: Option<String>,
+    /// Subscribe to a single lease instead of the wallet
+    #[serde(default)]
+    pub lease: Option<String>,
+    /// VAPID key the browser subscribed with, the current key by default
+    #[serde(default)]
+    pub vapid_key_id: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
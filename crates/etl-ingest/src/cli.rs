@@ -0,0 +1,136 @@
+use std::env;
+
+use etl_core::{error::Error, helpers::IngestRole};
+
+/// Command line of the ingest process. `--config` is read by
+/// `etl_core::settings::config_path`.
+///
+/// `--roles live,backfill,aggregator,prices` overrides the `ingest_roles`
+/// setting. `--from <height> --to <height>` backfills that range, both
+/// inclusive, and exits without running the other tasks of the roles; it
+/// needs the backfill role, which it defaults to, and `enable_sync`.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Args {
+    pub roles: Option<Vec<IngestRole>>,
+    pub range: Option<(i64, i64)>,
+}
+
+impl Args {
+    pub fn parse() -> Result<Args, Error> {
+        Args::from_args(env::args().skip(1))
+    }
+
+    fn from_args(
+        args: impl IntoIterator<Item = String>,
+    ) -> Result<Args, Error> {
+        let mut parsed = Args::default();
+        let (mut from, mut to) = (None, None);
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            let (flag, inline) = match arg.split_once('=') {
+                Some((flag, value)) => {
+                    (flag.to_owned(), Some(value.to_owned()))
+                },
+                None => (arg, None),
+            };
+            let mut value = || {
+                inline.clone().or_else(|| args.next()).ok_or_else(|| {
+                    Error::ConfigurationError(format!(
+                        "{}: missing value",
+                        flag
+                    ))
+                })
+            };
+
+            match flag.as_str() {
+                "--config" => {
+                    value()?;
+                },
+                "--roles" => {
+                    let roles = value()?
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|role| !role.is_empty())
+                        .map(|role| {
+                            role.parse().map_err(|_| {
+                                Error::ConfigurationError(format!(
+                                    "--roles: unknown role {}",
+                                    role
+                                ))
+                            })
+                        })
+                        .collect::<Result<Vec<IngestRole>, Error>>()?;
+                    parsed.roles = Some(roles);
+                },
+                "--from" => from = Some(height(&flag, value()?)?),
+                "--to" => to = Some(height(&flag, value()?)?),
+                _ => {
+                    return Err(Error::ConfigurationError(format!(
+                        "unknown argument {}",
+                        flag
+                    )))
+                },
+            }
+        }
+
+        parsed.range = match (from, to) {
+            (None, None) => None,
+            (Some(from), Some(to)) if from <= to => Some((from, to)),
+            (Some(_), Some(_)) => {
+                return Err(Error::ConfigurationError(String::from(
+                    "--from: must not exceed --to",
+                )))
+            },
+            _ => {
+                return Err(Error::ConfigurationError(String::from(
+                    "--from and --to go together",
+                )))
+            },
+        };
+
+        if parsed.range.is_some() && parsed.roles.is_none() {
+            parsed.roles = Some(vec![IngestRole::Backfill]);
+        }
+
+        Ok(parsed)
+    }
+}
+
+fn height(flag: &str, value: String) -> Result<i64, Error> {
+    match value.parse() {
+        Ok(height) if height > 0 => Ok(height),
+        _ => Err(Error::ConfigurationError(format!(
+            "{}: invalid height {}",
+            flag, value
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Args, Error> {
+        Args::from_args(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn range_runs_backfill_alone() {
+        let args = parse(&["--config", "etl.toml", "--from", "10", "--to=20"])
+            .unwrap();
+        assert_eq!(args.roles, Some(vec![IngestRole::Backfill]));
+        assert_eq!(args.range, Some((10, 20)));
+
+        let args = parse(&["--roles=live,prices"]).unwrap();
+        assert_eq!(
+            args.roles,
+            Some(vec![IngestRole::Live, IngestRole::Prices])
+        );
+        assert_eq!(args.range, None);
+
+        assert!(parse(&["--from", "20", "--to", "10"]).is_err());
+        assert!(parse(&["--from", "20"]).is_err());
+        assert!(parse(&["--roles", "indexer"]).is_err());
+    }
+}
@@ -1,4 +1,5 @@
 use anyhow::Context as _;
+use chrono::DateTime;
 use cosmrs::{
     proto::{
         cosmos::base::abci::v1beta1::TxResponse, tendermint::abci::Event,
@@ -14,17 +15,18 @@ use etl_core::{
     dao::DataBase,
     error::Error,
     helpers::EventsType,
-    model::{Block, RawMsgParams, RawTxParams, Raw_Message},
+    model::{classify_failure, Block, RawMsgParams, RawTxParams, Raw_Message},
+    telemetry::metrics,
 };
 
 use crate::{
     event_parsing::*,
     handler::{
-        wasm_lp_deposit, wasm_lp_withdraw, wasm_ls_auto_close_position,
-        wasm_ls_close, wasm_ls_close_position, wasm_ls_liquidation,
-        wasm_ls_liquidation_warning, wasm_ls_open, wasm_ls_repay,
-        wasm_ls_slippage_anomaly, wasm_reserve_cover_loss, wasm_tr_profit,
-        wasm_tr_rewards,
+        ibc_transfer, staking, wasm_lp_deposit, wasm_lp_withdraw,
+        wasm_ls_auto_close_position, wasm_ls_close, wasm_ls_close_position,
+        wasm_ls_liquidation, wasm_ls_liquidation_warning, wasm_ls_open,
+        wasm_ls_repay, wasm_ls_slippage_anomaly, wasm_reserve_cover_loss,
+        wasm_tr_profit, wasm_tr_rewards,
     },
 };
 
@@ -38,9 +40,11 @@ pub async fn parse_event(
     tx: &mut Transaction<'_, DataBase>,
 ) -> Result<(), Error> {
     if let Ok(t) = EventsType::from_str(&event.r#type) {
+        metrics().events.with_label_values(&[&event.r#type]).inc();
         match t {
             EventsType::LS_Opening => {
-                let wasm_ls_opening = parse_wasm_ls_open(&event.attributes)?;
+                let wasm_ls_opening =
+                    parsed(event, parse_wasm_ls_open(&event.attributes))?;
                 wasm_ls_open::parse_and_insert(
                     &app_state,
                     wasm_ls_opening,
@@ -51,7 +55,8 @@ pub async fn parse_event(
                 .await?;
             },
             EventsType::LS_Closing => {
-                let wasm_ls_closing = parse_wasm_ls_close(&event.attributes)?;
+                let wasm_ls_closing =
+                    parsed(event, parse_wasm_ls_close(&event.attributes))?;
                 wasm_ls_close::parse_and_insert(
                     &app_state,
                     wasm_ls_closing,
@@ -61,8 +66,10 @@ pub async fn parse_event(
                 .await?;
             },
             EventsType::LS_Close_Position => {
-                let wasm_ls_close_position =
-                    parse_wasm_ls_close_position(&event.attributes)?;
+                let wasm_ls_close_position = parsed(
+                    event,
+                    parse_wasm_ls_close_position(&event.attributes),
+                )?;
                 if let Some(item) = wasm_ls_close_position {
                     wasm_ls_close_position::parse_and_insert(
                         &app_state, item, tx_hash, height, tx,
@@ -71,7 +78,8 @@ pub async fn parse_event(
                 }
             },
             EventsType::LS_Repay => {
-                let wasm_ls_repay = parse_wasm_ls_repayment(&event.attributes)?;
+                let wasm_ls_repay =
+                    parsed(event, parse_wasm_ls_repayment(&event.attributes))?;
                 wasm_ls_repay::parse_and_insert(
                     &app_state,
                     wasm_ls_repay,
@@ -82,8 +90,10 @@ pub async fn parse_event(
                 .await?;
             },
             EventsType::LS_Liquidation => {
-                let wasm_ls_liquidation =
-                    parse_wasm_ls_liquidation(&event.attributes)?;
+                let wasm_ls_liquidation = parsed(
+                    event,
+                    parse_wasm_ls_liquidation(&event.attributes),
+                )?;
                 wasm_ls_liquidation::parse_and_insert(
                     &app_state,
                     wasm_ls_liquidation,
@@ -94,8 +104,10 @@ pub async fn parse_event(
                 .await?;
             },
             EventsType::LS_Liquidation_Warning => {
-                let ls_liquidation_warning =
-                    parse_wasm_ls_liquidation_warning(&event.attributes)?;
+                let ls_liquidation_warning = parsed(
+                    event,
+                    parse_wasm_ls_liquidation_warning(&event.attributes),
+                )?;
                 wasm_ls_liquidation_warning::parse_and_insert(
                     &app_state,
                     ls_liquidation_warning,
@@ -106,8 +118,10 @@ pub async fn parse_event(
                 .await?;
             },
             EventsType::LS_Slippage_Anomaly => {
-                let ls_slippage_anomaly =
-                    parse_wasm_ls_slippage_anomaly(&event.attributes)?;
+                let ls_slippage_anomaly = parsed(
+                    event,
+                    parse_wasm_ls_slippage_anomaly(&event.attributes),
+                )?;
                 wasm_ls_slippage_anomaly::parse_and_insert(
                     &app_state,
                     ls_slippage_anomaly,
@@ -118,8 +132,10 @@ pub async fn parse_event(
                 .await?;
             },
             EventsType::LS_Auto_Close_Position => {
-                let ls_auto_close_position =
-                    parse_wasm_ls_auto_close_position(&event.attributes)?;
+                let ls_auto_close_position = parsed(
+                    event,
+                    parse_wasm_ls_auto_close_position(&event.attributes),
+                )?;
                 wasm_ls_auto_close_position::parse_and_insert(
                     &app_state,
                     ls_auto_close_position,
@@ -130,8 +146,10 @@ pub async fn parse_event(
                 .await?;
             },
             EventsType::Reserve_Cover_Loss => {
-                let reserve_cover_loss =
-                    parse_wasm_reserve_cover_loss(&event.attributes)?;
+                let reserve_cover_loss = parsed(
+                    event,
+                    parse_wasm_reserve_cover_loss(&event.attributes),
+                )?;
                 wasm_reserve_cover_loss::parse_and_insert(
                     &app_state,
                     reserve_cover_loss,
@@ -143,7 +161,8 @@ pub async fn parse_event(
                 .await?;
             },
             EventsType::LP_deposit => {
-                let wasm_lp_deposit = parse_wasm_lp_deposit(&event.attributes)?;
+                let wasm_lp_deposit =
+                    parsed(event, parse_wasm_lp_deposit(&event.attributes))?;
                 wasm_lp_deposit::parse_and_insert(
                     &app_state,
                     wasm_lp_deposit,
@@ -154,7 +173,7 @@ pub async fn parse_event(
             },
             EventsType::LP_Withdraw => {
                 let wasm_lp_withdraw =
-                    parse_wasm_lp_withdraw(&event.attributes)?;
+                    parsed(event, parse_wasm_lp_withdraw(&event.attributes))?;
                 wasm_lp_withdraw::parse_and_insert(
                     &app_state,
                     wasm_lp_withdraw,
@@ -164,7 +183,8 @@ pub async fn parse_event(
                 .await?;
             },
             EventsType::TR_Profit => {
-                let wasm_tr_profit = parse_wasm_tr_profit(&event.attributes)?;
+                let wasm_tr_profit =
+                    parsed(event, parse_wasm_tr_profit(&event.attributes))?;
                 wasm_tr_profit::parse_and_insert(
                     &app_state,
                     wasm_tr_profit,
@@ -174,8 +194,10 @@ pub async fn parse_event(
                 .await?;
             },
             EventsType::TR_Rewards_Distribution => {
-                let wasm_tr_rewards_distribution =
-                    parse_wasm_tr_rewards_distribution(&event.attributes)?;
+                let wasm_tr_rewards_distribution = parsed(
+                    event,
+                    parse_wasm_tr_rewards_distribution(&event.attributes),
+                )?;
                 wasm_tr_rewards::parse_and_insert(
                     &app_state,
                     wasm_tr_rewards_distribution,
@@ -190,6 +212,18 @@ pub async fn parse_event(
     Ok(())
 }
 
+/// Count the attributes of `event` that could not be parsed
+fn parsed<T>(event: &Event, result: Result<T, Error>) -> Result<T, Error> {
+    if result.is_err() {
+        metrics()
+            .event_parse_failures
+            .with_label_values(&[&event.r#type])
+            .inc();
+    }
+
+    result
+}
+
 pub async fn insert_txs(
     app_state: AppState<State>,
     txs: Vec<Option<TxResponse>>,
@@ -211,6 +245,10 @@ pub async fn insert_txs(
                     tx_data,
                     height,
                     code: tx_results.code,
+                    codespace: tx_results.codespace,
+                    raw_log: tx_results.raw_log,
+                    gas_wanted: tx_results.gas_wanted,
+                    gas_used: tx_results.gas_used,
                     time_stamp,
                     tx_events: &tx_results.events,
                 },
@@ -229,6 +267,11 @@ pub async fn insert_txs(
                 )
                 .await?;
             }
+            app_state
+                .database
+                .address_activity
+                .insert_by_tx(&hash, &mut tx)
+                .await?;
         }
 
         app_state
@@ -237,6 +280,13 @@ pub async fn insert_txs(
             .insert(Block { id: height }, &mut tx)
             .await?;
 
+        let at = DateTime::from_timestamp(
+            time_stamp.seconds,
+            time_stamp.nanos.try_into()?,
+        )
+        .context("Could not parse time stamp")?;
+        app_state.database.fee_daily.mark_day(at, &mut tx).await?;
+
         tx.commit().await?;
     }
 
@@ -249,10 +299,11 @@ pub async fn parse_raw_tx(
     tx: &mut Transaction<'_, DataBase>,
 ) -> Result<(), Error> {
     let c = Tx::from_bytes(&params.tx_data.value)?;
+    let registry = app_state.registry();
     for (index, msg) in c.body.messages.iter().enumerate() {
         let fee = c.auth_info.fee.clone();
         let memo = c.body.memo.to_owned();
-        let msg: Result<Raw_Message, anyhow::Error> =
+        let msgs: Result<Vec<Raw_Message>, anyhow::Error> =
             Raw_Message::from_any(RawMsgParams {
                 index: index.try_into()?,
                 value: msg.clone(),
@@ -264,9 +315,44 @@ pub async fn parse_raw_tx(
                 events: app_state.config.events_subscribe.clone(),
                 tx_events: params.tx_events,
                 code: params.code,
+                denom_tickers: &registry.hash_map_denom_ticker,
             });
 
-        if let Ok(msg) = msg {
+        let msgs = msgs.unwrap_or_else(|_| {
+            metrics()
+                .message_parse_failures
+                .with_label_values(&[&msg.type_url])
+                .inc();
+            vec![]
+        });
+
+        for mut msg in msgs {
+            msg.gas_wanted = Some(params.gas_wanted);
+            msg.gas_used = Some(params.gas_used);
+
+            if params.code != 0 {
+                msg.failure = Some(
+                    classify_failure(
+                        params.code,
+                        &params.codespace,
+                        &params.raw_log,
+                    )
+                    .into(),
+                );
+                msg.codespace = Some(params.codespace.to_owned());
+                msg.raw_log = Some(params.raw_log.to_owned());
+            }
+
+            if params.code == 0 {
+                staking::parse_and_insert(
+                    &app_state,
+                    &msg,
+                    params.tx_events,
+                    tx,
+                )
+                .await?;
+            }
+
             app_state
                 .database
                 .raw_message
@@ -275,5 +361,23 @@ pub async fn parse_raw_tx(
         }
     }
 
+    if params.code == 0 {
+        let at = DateTime::from_timestamp(
+            params.time_stamp.seconds,
+            params.time_stamp.nanos.try_into()?,
+        )
+        .context("Could not parse time stamp")?;
+
+        ibc_transfer::parse_and_insert(
+            &app_state,
+            &c.body.messages,
+            params.tx_events,
+            &params.tx_hash,
+            at,
+            tx,
+        )
+        .await?;
+    }
+
     Ok(())
 }
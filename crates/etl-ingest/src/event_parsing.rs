@@ -5,11 +5,11 @@ use cosmrs::proto::tendermint::abci::EventAttribute;
 use etl_core::{
     error::Error,
     types::{
-        Interest_values, LP_Deposit_Type, LP_Withdraw_Type,
+        Ibc_Packet_Type, Interest_values, LP_Deposit_Type, LP_Withdraw_Type,
         LS_Auto_Close_Position_Type, LS_Close_Position_Type, LS_Closing_Type,
         LS_Liquidation_Type, LS_Liquidation_Warning_Type, LS_Opening_Type,
         LS_Repayment_Type, LS_Slippage_Anomaly_Type, Reserve_Cover_Loss_Type,
-        TR_Profit_Type, TR_Rewards_Distribution_Type,
+        TR_Profit_Type, TR_Rewards_Distribution_Type, Unbond_Type,
     },
 };
 
@@ -310,6 +310,38 @@ pub fn parse_wasm_tr_rewards_distribution(
     Ok(c)
 }
 
+pub fn parse_ibc_packet(
+    attributes: &Vec<EventAttribute>,
+) -> Result<Ibc_Packet_Type, Error> {
+    let packet = parse_data(attributes)?;
+    let c = Ibc_Packet_Type {
+        sequence: extract_field(&packet, "packet_sequence")?,
+        src_port: extract_field(&packet, "packet_src_port")?,
+        src_channel: extract_field(&packet, "packet_src_channel")?,
+        dst_port: extract_field(&packet, "packet_dst_port")?,
+        dst_channel: extract_field(&packet, "packet_dst_channel")?,
+        data: extract_field(&packet, "packet_data")?,
+        timeout_timestamp: packet.get("packet_timeout_timestamp").cloned(),
+        ack: packet.get("packet_ack").cloned(),
+    };
+
+    Ok(c)
+}
+
+pub fn parse_unbond(
+    attributes: &Vec<EventAttribute>,
+) -> Result<Unbond_Type, Error> {
+    let unbond = parse_data(attributes)?;
+    let c = Unbond_Type {
+        validator: extract_field(&unbond, "validator")?,
+        delegator: unbond.get("delegator").cloned(),
+        amount: extract_field(&unbond, "amount")?,
+        completion_time: extract_field(&unbond, "completion_time")?,
+    };
+
+    Ok(c)
+}
+
 fn parse_data(
     attributes: &Vec<EventAttribute>,
 ) -> Result<HashMap<String, String>, Error> {
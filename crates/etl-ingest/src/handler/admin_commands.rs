@@ -0,0 +1,151 @@
+use std::time::Duration;
+
+use tokio::time;
+use tracing::{error, info, warn};
+
+use etl_core::{
+    configuration::{AppState, State},
+    error::Error,
+    helpers::{AdminCommandStatus, AdminCommandType},
+    model::AdminCommand,
+};
+
+use crate::provider::synchronization;
+
+use super::{aggregation_task, message_backfill};
+
+/// Interval between two polls of the admin command queue
+const POLL_INTERVAL_SECS: u64 = 10;
+
+/// Lease of a running command, extended every third of it while it runs.
+/// A command whose consumer stopped is claimed again once it expired.
+const CLAIM_TIMEOUT_SECS: i64 = 5 * 60;
+
+/// Execute the commands queued through the admin API, one at a time
+pub async fn admin_commands_task(
+    app_state: AppState<State>,
+) -> Result<(), Error> {
+    if !app_state.config.enable_sync {
+        return Ok(());
+    }
+
+    let mut interval = time::interval(Duration::from_secs(POLL_INTERVAL_SECS));
+    tokio::spawn(async move {
+        loop {
+            interval.tick().await;
+
+            let command = match app_state
+                .database
+                .admin_command
+                .claim_next(CLAIM_TIMEOUT_SECS)
+                .await
+            {
+                Ok(Some(command)) => command,
+                Ok(None) => continue,
+                Err(error) => {
+                    error!("Admin command poll error {}", error);
+                    continue;
+                },
+            };
+
+            info!("Running admin command {} ({})", command.id, command.command);
+
+            let (status, message) =
+                match run_claimed(app_state.clone(), &command).await {
+                    Ok(()) => (AdminCommandStatus::Done, None),
+                    Err(error) => {
+                        error!("Admin command {} failed {}", command.id, error);
+                        (AdminCommandStatus::Failed, Some(error.to_string()))
+                    },
+                };
+
+            match app_state
+                .database
+                .admin_command
+                .finish(command.id, command.claim, status, message)
+                .await
+            {
+                Ok(result) if result.rows_affected() == 0 => {
+                    warn!(
+                        "Admin command {} lost its claim, outcome dropped",
+                        command.id
+                    );
+                },
+                Ok(_) => {},
+                Err(error) => {
+                    error!(
+                        "Admin command {} not updated {}",
+                        command.id, error
+                    );
+                },
+            }
+        }
+    })
+    .await?
+}
+
+/// Run a command, extending its lease until it returns
+async fn run_claimed(
+    app_state: AppState<State>,
+    command: &AdminCommand,
+) -> Result<(), Error> {
+    let period = Duration::from_secs(CLAIM_TIMEOUT_SECS as u64 / 3);
+    let mut heartbeat =
+        time::interval_at(time::Instant::now() + period, period);
+    let run = run(app_state.clone(), command);
+    tokio::pin!(run);
+
+    loop {
+        tokio::select! {
+            result = &mut run => return result,
+            _ = heartbeat.tick() => {
+                match app_state
+                    .database
+                    .admin_command
+                    .extend_claim(command.id, command.claim, CLAIM_TIMEOUT_SECS)
+                    .await
+                {
+                    Ok(result) if result.rows_affected() == 0 => {
+                        warn!("Admin command {} lost its claim", command.id);
+                    },
+                    Ok(_) => {},
+                    Err(error) => {
+                        error!(
+                            "Admin command {} lease not extended {}",
+                            command.id, error
+                        );
+                    },
+                }
+            },
+        }
+    }
+}
+
+async fn run(
+    app_state: AppState<State>,
+    command: &AdminCommand,
+) -> Result<(), Error> {
+    match command.command.parse()? {
+        AdminCommandType::Aggregation => aggregation_task(app_state).await?,
+        AdminCommandType::Resync => {
+            let (Some(from), Some(to)) =
+                (command.from_height, command.to_height)
+            else {
+                return Err(Error::MissingParams(String::from(
+                    "from_height, to_height",
+                )));
+            };
+
+            synchronization::sync_range(app_state, from, to).await
+        },
+        AdminCommandType::DecodeMessages => {
+            message_backfill::decode_messages(app_state).await
+        },
+        AdminCommandType::StakingBackfill => {
+            message_backfill::staking_backfill(app_state).await
+        },
+        AdminCommandType::ActivityBackfill => {
+            message_backfill::activity_backfill(app_state).await
+        },
+    }
+}
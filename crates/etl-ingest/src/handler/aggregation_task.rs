@@ -1,3 +1,5 @@
+use std::time::Instant;
+
 use chrono::{DateTime, Utc};
 use tokio::task::JoinHandle;
 
@@ -5,14 +7,18 @@ use etl_core::{
     configuration::{AppState, State},
     error::Error,
     model::{Action_History, Actions, Table},
+    telemetry::metrics,
 };
 
-use super::{lp_lender_state, lp_pool_state, ls_state, pl_state, tr_state};
+use super::{
+    fee_daily, lp_lender_state, lp_pool_state, ls_state, pl_state, tr_state,
+};
 
 pub fn aggregation_task(
     app_state: AppState<State>,
 ) -> JoinHandle<Result<(), Error>> {
     tokio::spawn(async move {
+        let started = Instant::now();
         let timestsamp = Utc::now();
         let action = app_state
             .database
@@ -48,31 +54,60 @@ pub fn aggregation_task(
         insert_action(&app_state.database.action_history, timestsamp).await?;
 
         let joins = vec![
-            ls_state::start_task(app_state.clone(), timestsamp),
-            lp_lender_state::start_task(app_state.clone(), timestsamp),
-            lp_pool_state::start_task(app_state.clone(), timestsamp),
-            tr_state::start_task(app_state.clone(), timestsamp),
+            timed(
+                "ls_state",
+                ls_state::start_task(app_state.clone(), timestsamp),
+            ),
+            timed(
+                "lp_lender_state",
+                lp_lender_state::start_task(app_state.clone(), timestsamp),
+            ),
+            timed(
+                "lp_pool_state",
+                lp_pool_state::start_task(app_state.clone(), timestsamp),
+            ),
+            timed(
+                "tr_state",
+                tr_state::start_task(app_state.clone(), timestsamp),
+            ),
+            timed("fee_daily", fee_daily::start_task(app_state.clone())),
         ];
 
         for j in joins {
             j.await??
         }
 
-        if let Err(error) = pl_state::start_task(
+        let pl_started = Instant::now();
+        let pl_result = pl_state::start_task(
             app_state.clone(),
             prev_action_timestamp,
             last_action_timestamp,
             timestsamp,
         )
-        .await?
-        {
+        .await?;
+        metrics().task_finished("pl_state", pl_started.elapsed());
+        if let Err(error) = pl_result {
             return Err(Error::ServerError(error.to_string()));
         };
 
+        metrics().task_finished("aggregation", started.elapsed());
         Ok(())
     })
 }
 
+/// Record the duration of an aggregation sub-task under `task`
+fn timed(
+    task: &'static str,
+    handle: JoinHandle<Result<(), Error>>,
+) -> JoinHandle<Result<(), Error>> {
+    let started = Instant::now();
+    tokio::spawn(async move {
+        let result = handle.await?;
+        metrics().task_finished(task, started.elapsed());
+        result
+    })
+}
+
 async fn insert_action(
     action_model: &Table<Action_History>,
     timestamp: DateTime<Utc>,
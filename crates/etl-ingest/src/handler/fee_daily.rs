@@ -0,0 +1,29 @@
+use tokio::task::JoinHandle;
+
+use etl_core::{
+    configuration::{AppState, State},
+    error::Error,
+};
+
+/// Roll up the fees and gas of the days since the latest rollup, the last
+/// of which may have been partial, or of all days on the first run, then of
+/// the earlier days blocks were backfilled for
+pub async fn refresh(app_state: AppState<State>) -> Result<(), Error> {
+    let fee_daily = &app_state.database.fee_daily;
+    let since = fee_daily.get_last_day().await?;
+    let pending = fee_daily.get_pending_days().await?;
+    fee_daily.refresh(since, None).await?;
+
+    for (day, version) in pending {
+        if since.is_some_and(|since| day < since) {
+            fee_daily.refresh(Some(day), Some(day)).await?;
+        }
+        fee_daily.clear_pending_day(day, version).await?;
+    }
+
+    Ok(())
+}
+
+pub fn start_task(app_state: AppState<State>) -> JoinHandle<Result<(), Error>> {
+    tokio::spawn(async move { refresh(app_state).await })
+}
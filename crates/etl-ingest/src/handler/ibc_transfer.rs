@@ -0,0 +1,303 @@
+use std::{collections::HashMap, str::FromStr as _};
+
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, Utc};
+use cosmrs::{proto::tendermint::abci::Event, Any};
+use ibc_proto::ibc::core::channel::v1::{
+    MsgAcknowledgement, MsgTimeout, Packet,
+};
+use serde_json::Value;
+use sqlx::Transaction;
+
+use etl_core::{
+    configuration::{AppState, State},
+    dao::DataBase,
+    error::Error,
+    helpers::{IbcTransferDirection, IbcTransferStatus},
+    model::{local_denom, received_denom, CosmosTypes, IbcTransfer},
+    types::{Ibc_Packet_Type, MsgReceivePacket},
+};
+
+use crate::event_parsing::parse_ibc_packet;
+
+const SEND_PACKET: &str = "send_packet";
+const RECV_PACKET: &str = "recv_packet";
+const WRITE_ACKNOWLEDGEMENT: &str = "write_acknowledgement";
+
+/// Channel ends and sequence of a packet
+struct Route {
+    sequence: u64,
+    source_port: String,
+    source_channel: String,
+    destination_port: String,
+    destination_channel: String,
+    timeout_timestamp: u64,
+}
+
+/// Track the ICS-20 transfers of a successful transaction. Transfers sent
+/// by `MsgTransfer` or a contract come from the `send_packet` events,
+/// received ones from the `recv_packet` events with the acknowledgement this
+/// chain wrote, and the outcome of outgoing ones from the relayed
+/// `MsgAcknowledgement` and `MsgTimeout`. Packets of other applications are
+/// skipped.
+pub async fn parse_and_insert(
+    app_state: &AppState<State>,
+    messages: &[Any],
+    tx_events: &[Event],
+    tx_hash: &str,
+    at: DateTime<Utc>,
+    transaction: &mut Transaction<'_, DataBase>,
+) -> Result<(), Error> {
+    let table = &app_state.database.ibc_transfer;
+
+    let mut written_acks = HashMap::new();
+    for event in tx_events {
+        if event.r#type == WRITE_ACKNOWLEDGEMENT {
+            let packet = parse_ibc_packet(&event.attributes)?;
+            written_acks.insert(
+                (packet.dst_channel, packet.sequence),
+                packet.ack.unwrap_or_default(),
+            );
+        }
+    }
+
+    for event in tx_events {
+        let direction = match event.r#type.as_str() {
+            SEND_PACKET => IbcTransferDirection::Outgoing,
+            RECV_PACKET => IbcTransferDirection::Incoming,
+            _ => continue,
+        };
+        let packet = parse_ibc_packet(&event.attributes)?;
+        let Ok(data) = serde_json::from_str::<MsgReceivePacket>(&packet.data)
+        else {
+            continue;
+        };
+        let route = Route::try_from(&packet)?;
+
+        match direction {
+            IbcTransferDirection::Outgoing => {
+                let mut transfer = transfer(
+                    route,
+                    data,
+                    direction,
+                    IbcTransferStatus::Pending,
+                )?;
+                transfer.tx_hash = Some(tx_hash.to_owned());
+                transfer.sent_at = Some(at);
+
+                table.insert_sent(transfer, transaction).await?;
+            },
+            IbcTransferDirection::Incoming => {
+                let ack = written_acks
+                    .get(&(packet.dst_channel, packet.sequence))
+                    .map(String::as_bytes)
+                    .unwrap_or_default();
+                let (status, error) =
+                    acknowledgement(ack, IbcTransferStatus::Received);
+
+                let mut transfer = transfer(route, data, direction, status)?;
+                transfer.error = error;
+                transfer.completion_tx_hash = Some(tx_hash.to_owned());
+                transfer.completed_at = Some(at);
+
+                table.complete(transfer, transaction).await?;
+            },
+        }
+    }
+
+    for message in messages {
+        let (packet, status, error) =
+            match CosmosTypes::from_str(&message.type_url) {
+                Ok(CosmosTypes::MsgAcknowledgement) => {
+                    let m = message.to_msg::<MsgAcknowledgement>()?;
+                    let (status, error) = acknowledgement(
+                        &m.acknowledgement,
+                        IbcTransferStatus::Acknowledged,
+                    );
+                    (m.packet, status, error)
+                },
+                Ok(CosmosTypes::MsgTimeout) => {
+                    let m = message.to_msg::<MsgTimeout>()?;
+                    (m.packet, IbcTransferStatus::TimedOut, None)
+                },
+                _ => continue,
+            };
+
+        let Some(packet) = packet else {
+            continue;
+        };
+        let Ok(data) = serde_json::from_slice::<MsgReceivePacket>(&packet.data)
+        else {
+            continue;
+        };
+
+        let mut transfer = transfer(
+            Route::from(packet),
+            data,
+            IbcTransferDirection::Outgoing,
+            status,
+        )?;
+        transfer.error = error;
+        transfer.completion_tx_hash = Some(tx_hash.to_owned());
+        transfer.completed_at = Some(at);
+
+        table.complete(transfer, transaction).await?;
+    }
+
+    Ok(())
+}
+
+fn transfer(
+    route: Route,
+    data: MsgReceivePacket,
+    direction: IbcTransferDirection,
+    status: IbcTransferStatus,
+) -> Result<IbcTransfer, Error> {
+    let denom = match direction {
+        IbcTransferDirection::Outgoing => local_denom(&data.denom),
+        IbcTransferDirection::Incoming => received_denom(
+            &route.source_port,
+            &route.source_channel,
+            &route.destination_port,
+            &route.destination_channel,
+            &data.denom,
+        ),
+    };
+    let timeout_at = i64::try_from(route.timeout_timestamp)
+        .ok()
+        .filter(|nanos| *nanos > 0)
+        .map(DateTime::from_timestamp_nanos);
+
+    Ok(IbcTransfer {
+        source_channel: route.source_channel,
+        sequence: route.sequence.try_into()?,
+        direction: direction.to_string(),
+        source_port: route.source_port,
+        destination_port: route.destination_port,
+        destination_channel: route.destination_channel,
+        sender: data.sender,
+        receiver: data.receiver,
+        denom,
+        packet_denom: data.denom,
+        amount: BigDecimal::from_str(&data.amount)?,
+        status: status.to_string(),
+        error: None,
+        timeout_at,
+        tx_hash: None,
+        sent_at: None,
+        completion_tx_hash: None,
+        completed_at: None,
+    })
+}
+
+/// State of a transfer from its ICS-20 acknowledgement, `success` unless
+/// the acknowledgement is an error
+fn acknowledgement(
+    ack: &[u8],
+    success: IbcTransferStatus,
+) -> (IbcTransferStatus, Option<String>) {
+    let ack: Value = serde_json::from_slice(ack).unwrap_or_default();
+    match ack.get("error") {
+        Some(error) => (
+            IbcTransferStatus::Failed,
+            Some(error.as_str().unwrap_or_default().to_owned()),
+        ),
+        None => (success, None),
+    }
+}
+
+impl TryFrom<&Ibc_Packet_Type> for Route {
+    type Error = Error;
+
+    fn try_from(packet: &Ibc_Packet_Type) -> Result<Self, Self::Error> {
+        Ok(Route {
+            sequence: packet.sequence.parse()?,
+            source_port: packet.src_port.to_owned(),
+            source_channel: packet.src_channel.to_owned(),
+            destination_port: packet.dst_port.to_owned(),
+            destination_channel: packet.dst_channel.to_owned(),
+            timeout_timestamp: packet
+                .timeout_timestamp
+                .as_deref()
+                .map(str::parse)
+                .transpose()?
+                .unwrap_or_default(),
+        })
+    }
+}
+
+impl From<Packet> for Route {
+    fn from(packet: Packet) -> Self {
+        Route {
+            sequence: packet.sequence,
+            source_port: packet.source_port,
+            source_channel: packet.source_channel,
+            destination_port: packet.destination_port,
+            destination_channel: packet.destination_channel,
+            timeout_timestamp: packet.timeout_timestamp,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn error_acknowledgement_fails_transfer() {
+        assert_eq!(
+            acknowledgement(
+                br#"{"result":"AQ=="}"#,
+                IbcTransferStatus::Acknowledged
+            ),
+            (IbcTransferStatus::Acknowledged, None)
+        );
+        assert_eq!(
+            acknowledgement(
+                br#"{"error":"ABCI code: 1: error handling packet"}"#,
+                IbcTransferStatus::Received
+            ),
+            (
+                IbcTransferStatus::Failed,
+                Some(String::from("ABCI code: 1: error handling packet"))
+            )
+        );
+    }
+
+    #[test]
+    fn incoming_packets_of_two_chains_are_kept_apart() {
+        let route = |destination_channel: &str| Route {
+            sequence: 7,
+            source_port: String::from("transfer"),
+            source_channel: String::from("channel-0"),
+            destination_port: String::from("transfer"),
+            destination_channel: destination_channel.to_owned(),
+            timeout_timestamp: 0,
+        };
+        let data = || MsgReceivePacket {
+            amount: String::from("100"),
+            denom: String::from("uosmo"),
+            receiver: String::from("nolus1receiver"),
+            sender: String::from("osmo1sender"),
+        };
+
+        let first = transfer(
+            route("channel-0"),
+            data(),
+            IbcTransferDirection::Incoming,
+            IbcTransferStatus::Received,
+        )
+        .unwrap();
+        let second = transfer(
+            route("channel-1"),
+            data(),
+            IbcTransferDirection::Incoming,
+            IbcTransferStatus::Received,
+        )
+        .unwrap();
+
+        assert_eq!(first.source_channel, second.source_channel);
+        assert_eq!(first.sequence, second.sequence);
+        assert_ne!(first.key(), second.key());
+    }
+}
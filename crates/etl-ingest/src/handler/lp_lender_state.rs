@@ -23,13 +23,14 @@ pub async fn parse_and_insert(
     let mut tasks = vec![];
     let max_tasks = app_state.config.max_tasks;
 
+    let registry = app_state.registry();
     for item in items {
         // Check if the pool is from an active protocol
         if let Some(protocol_name) =
-            app_state.hash_map_pool_protocol.get(&item.1)
+            registry.hash_map_pool_protocol.get(&item.1)
         {
             // Only proceed if the protocol is active
-            if app_state.protocols.contains_key(protocol_name) {
+            if registry.protocols.contains_key(protocol_name) {
                 tasks.push(proceed(app_state.clone(), item, timestsamp));
             }
         }
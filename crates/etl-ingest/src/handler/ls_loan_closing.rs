@@ -129,15 +129,14 @@ async fn proceed_repayment(
         );
     }
 
+    let registry = state.registry();
     for b in balances.balances {
         // Look up currency ticker by bank_symbol (IBC denom)
-        let ticker = state
-            .config
-            .hash_map_denom_ticker
-            .get(&b.denom.to_uppercase());
+        let ticker =
+            registry.hash_map_denom_ticker.get(&b.denom.to_uppercase());
 
         if let Some(ticker) = ticker {
-            if let Some(c) = state.config.hash_map_currencies.get(ticker) {
+            if let Some(c) = registry.hash_map_currencies.get(ticker) {
                 let ticker = c.0.to_owned();
                 data.insert(
                     ticker.clone(),
@@ -248,15 +247,14 @@ async fn proceed_market_close(
             );
         }
 
+        let registry = state.registry();
         for b in balances.balances {
             // Look up currency ticker by bank_symbol (IBC denom)
-            let ticker = state
-                .config
-                .hash_map_denom_ticker
-                .get(&b.denom.to_uppercase());
+            let ticker =
+                registry.hash_map_denom_ticker.get(&b.denom.to_uppercase());
 
             if let Some(ticker) = ticker {
-                if let Some(c) = state.config.hash_map_currencies.get(ticker) {
+                if let Some(c) = registry.hash_map_currencies.get(ticker) {
                     let ticker = c.0.to_owned();
                     data.insert(
                         ticker.clone(),
@@ -472,18 +470,20 @@ pub async fn get_pnl_long(
         .context("Loan not opened")?;
 
     let lease_currency = app_state
-        .config
+        .registry()
         .hash_map_currencies
         .get(&lease_status.amount.ticker)
+        .cloned()
         .context(format!(
             "LS_asset_symbol not found {}",
             &lease_status.amount.ticker
         ))?;
 
     let downpayment_currency = app_state
-        .config
+        .registry()
         .hash_map_currencies
         .get(&lease.LS_cltr_symbol)
+        .cloned()
         .context(format!(
             "lease.LS_cltr_symbol not found {}",
             &lease.LS_cltr_symbol
@@ -537,9 +537,10 @@ pub async fn get_pnl_long(
     for repayment in repayments {
         if !repayment.LS_loan_close {
             let currency = app_state
-                .config
+                .registry()
                 .hash_map_currencies
                 .get(&repayment.LS_payment_symbol)
+                .cloned()
                 .context(format!(
                     "currency not found  {}",
                     &repayment.LS_payment_symbol
@@ -581,18 +582,20 @@ pub async fn get_pnl_short(
         .context("Loan not opened")?;
 
     let lease_currency = app_state
-        .config
+        .registry()
         .hash_map_currencies
         .get(&lease_status.amount.ticker)
+        .cloned()
         .context(format!(
             "LS_asset_symbol not found {}",
             &lease_status.amount.ticker
         ))?;
 
     let downpayment_currency = app_state
-        .config
+        .registry()
         .hash_map_currencies
         .get(&lease.LS_cltr_symbol)
+        .cloned()
         .context(format!(
             "lease.LS_cltr_symbol not found {}",
             &lease.LS_cltr_symbol
@@ -652,9 +655,10 @@ pub async fn get_pnl_short(
     for repayment in repayments {
         if !repayment.LS_loan_close {
             let currency = app_state
-                .config
+                .registry()
                 .hash_map_currencies
                 .get(&repayment.LS_payment_symbol)
+                .cloned()
                 .context(format!(
                     "currency not found  {}",
                     &repayment.LS_payment_symbol
@@ -690,18 +694,20 @@ pub async fn get_fees(
 ) -> Result<BigDecimal, Error> {
     let symbol = &lease.LS_asset_symbol.to_owned();
     let ctrl_currency = app_state
-        .config
+        .registry()
         .hash_map_currencies
         .get(&lease.LS_cltr_symbol)
+        .cloned()
         .context(format!(
             "ctrl_currencyt not found {}",
             &lease.LS_cltr_symbol
         ))?;
 
     let loan_currency = app_state
-        .config
+        .registry()
         .hash_map_currencies
         .get(&symbol.to_owned())
+        .cloned()
         .context(format!("LS_asset_symbol not found {}", &symbol))?;
 
     let ctrl_amount_stable = &lease.LS_cltr_amnt_stable
@@ -747,18 +753,20 @@ pub async fn get_pnl_liquidated(
     at: DateTime<Utc>,
 ) -> Result<LS_Loan, Error> {
     let lease_currency = app_state
-        .config
+        .registry()
         .hash_map_currencies
         .get(&lease.LS_asset_symbol)
+        .cloned()
         .context(format!(
             "LS_asset_symbol not found {}",
             &lease.LS_asset_symbol
         ))?;
 
     let downpayment_currency = app_state
-        .config
+        .registry()
         .hash_map_currencies
         .get(&lease.LS_cltr_symbol)
+        .cloned()
         .context(format!(
             "lease.LS_cltr_symbol not found {}",
             &lease.LS_cltr_symbol
@@ -777,9 +785,10 @@ pub async fn get_pnl_liquidated(
 
     for repayment in repayments {
         let currency = app_state
-            .config
+            .registry()
             .hash_map_currencies
             .get(&repayment.LS_payment_symbol)
+            .cloned()
             .context(format!(
                 "currency not found  {}",
                 &repayment.LS_payment_symbol
@@ -0,0 +1,149 @@
+use std::collections::HashMap;
+
+use base64::engine::{general_purpose::STANDARD as BASE64_STANDARD, Engine};
+use cosmrs::Any;
+use tracing::{info, warn};
+
+use etl_core::{
+    configuration::{AppState, State},
+    error::Error,
+    model::{decode_message, Raw_Message},
+};
+
+use super::staking;
+
+/// Messages processed per batch
+const BATCH_SIZE: i64 = 500;
+
+/// Blocks processed per batch of the activity backfill
+const BLOCK_BATCH_SIZE: i64 = 10_000;
+
+/// Decode the messages ingested before `raw_message.data` was filled at
+/// ingest. Messages that do not decode are logged and left empty.
+pub async fn decode_messages(app_state: AppState<State>) -> Result<(), Error> {
+    let registry = app_state.registry();
+    let denom_tickers = &registry.hash_map_denom_ticker;
+    let mut after = (0, String::new(), -1, -1);
+    let mut decoded = 0;
+
+    loop {
+        let messages = app_state
+            .database
+            .raw_message
+            .get_undecoded(after.to_owned(), BATCH_SIZE)
+            .await?;
+        let Some(last) = messages.last() else {
+            break;
+        };
+        after = (
+            last.block,
+            last.tx_hash.to_owned(),
+            last.index,
+            last.inner_index,
+        );
+
+        let mut data = Vec::with_capacity(messages.len());
+        for message in messages {
+            match decode(&message, denom_tickers) {
+                Ok(value) => data.push((
+                    message.index,
+                    message.inner_index,
+                    message.tx_hash,
+                    value,
+                )),
+                Err(error) => warn!(
+                    "Message {}/{}/{} not decoded {}",
+                    message.tx_hash, message.index, message.inner_index, error
+                ),
+            }
+        }
+
+        decoded += data.len();
+        app_state.database.raw_message.set_data(data).await?;
+    }
+
+    info!("Decoded {} messages", decoded);
+    Ok(())
+}
+
+/// Derive the staking and governance tables from the messages indexed
+/// before them. Undelegations get their completion time from the
+/// configured unbonding period.
+pub async fn staking_backfill(app_state: AppState<State>) -> Result<(), Error> {
+    let types = staking::TYPES.map(|t| t.to_string()).to_vec();
+    let mut after = (0, String::new(), -1, -1);
+    let mut processed = 0;
+
+    loop {
+        let messages = app_state
+            .database
+            .raw_message
+            .get_by_types(types.to_owned(), after.to_owned(), BATCH_SIZE)
+            .await?;
+        let Some(last) = messages.last() else {
+            break;
+        };
+        after = (
+            last.block,
+            last.tx_hash.to_owned(),
+            last.index,
+            last.inner_index,
+        );
+
+        let mut tx = app_state.database.pool.begin().await?;
+        for message in &messages {
+            if let Err(error) =
+                staking::parse_and_insert(&app_state, message, &[], &mut tx)
+                    .await
+            {
+                warn!(
+                    "Message {}/{}/{} not derived {}",
+                    message.tx_hash, message.index, message.inner_index, error
+                );
+            }
+        }
+        tx.commit().await?;
+
+        processed += messages.len();
+    }
+
+    info!("Derived staking data from {} messages", processed);
+    Ok(())
+}
+
+/// Derive the address activity index from the messages indexed before it,
+/// a range of blocks at a time. Rows already present are kept.
+pub async fn activity_backfill(
+    app_state: AppState<State>,
+) -> Result<(), Error> {
+    let (first,) = app_state.database.block.get_first_block().await?;
+    let (last,) = app_state.database.block.get_last_block().await?;
+    let mut inserted = 0;
+
+    for from in (first..=last).step_by(BLOCK_BATCH_SIZE as usize) {
+        let to = (from + BLOCK_BATCH_SIZE - 1).min(last);
+        let result = app_state
+            .database
+            .address_activity
+            .insert_by_blocks(from, to)
+            .await?;
+        inserted += result.rows_affected();
+    }
+
+    info!("Derived {} address activity rows", inserted);
+    Ok(())
+}
+
+fn decode(
+    message: &Raw_Message,
+    denom_tickers: &HashMap<String, String>,
+) -> Result<serde_json::Value, Error> {
+    let value = Any {
+        type_url: message.r#type.to_owned(),
+        value: BASE64_STANDARD.decode(&message.value)?,
+    };
+    let data =
+        decode_message(&value, message.rewards.as_deref(), denom_tickers)?;
+
+    Ok(serde_json::to_value(data)?)
+}
@@ -14,14 +14,21 @@ pub fn parse_event_timestamp(nanos_str: &str) -> Result<DateTime<Utc>, Error> {
     })
 }
 
+pub mod admin_commands;
 mod aggregation_task;
+pub mod fee_daily;
+pub mod ibc_transfer;
 pub mod lp_lender_state;
 pub mod lp_pool_state;
 pub mod ls_loan_closing;
 pub mod ls_state;
+pub mod message_backfill;
 pub mod mp_assets;
 pub mod pl_state;
-pub mod send_push;
+pub mod push_outbox;
+pub mod registry_reconcile;
+pub mod staking;
+pub mod subscription_sweep;
 pub mod tr_state;
 pub mod wasm_lp_deposit;
 pub mod wasm_lp_withdraw;
@@ -8,6 +8,7 @@ use tokio::{time, time::Duration};
 use tracing::error;
 
 use etl_core::{
+    alerts,
     configuration::{AppState, State},
     error::Error,
     model::{Action_History, Actions, MP_Asset},
@@ -23,7 +24,7 @@ pub async fn fetch_insert(
     let timestamp = Utc::now();
     let mut lpns = HashMap::new();
 
-    for protocol in app_state.protocols.values() {
+    for protocol in app_state.registry().protocols.values() {
         protocl_data_joins.push(get_lpn_data(
             app_state.clone(),
             protocol.protocol.to_owned(),
@@ -65,9 +66,10 @@ pub async fn fetch_insert(
 
                 for price in assets.prices {
                     if let Some(asset) = app_state
-                        .config
+                        .registry()
                         .hash_map_currencies
                         .get(&price.amount.ticker)
+                        .cloned()
                     {
                         let decimals = asset.1 - lpn_decimals;
                         let mut value =
@@ -127,6 +129,10 @@ pub async fn fetch_insert(
         .insert(action_history)
         .await?;
 
+    if let Err(error) = alerts::evaluate_prices(&app_state).await {
+        error!("Alert rules evaluation error {}", error);
+    }
+
     Ok(())
 }
 
@@ -155,7 +161,8 @@ pub async fn get_lpn_data(
     app_state: AppState<State>,
     protocol: String,
 ) -> Result<(String, String, BigDecimal, i16), Error> {
-    let prtcs = app_state
+    let registry = app_state.registry();
+    let prtcs = registry
         .protocols
         .get(&protocol)
         .context(format!("protocol not found {}", &protocol))?;
@@ -173,15 +180,13 @@ pub async fn get_lpn_data(
         )
         .await?;
 
-    let lpn_decimals = app_state
-        .config
+    let lpn_decimals = registry
         .hash_map_currencies
         .get(&base_currency)
         .context(format!("currency not found {}", &base_currency))?
         .1;
 
-    let asset = app_state
-        .config
+    let asset = registry
         .hash_map_currencies
         .get(&lpn_price.amount_quote.ticker)
         .context(format!(
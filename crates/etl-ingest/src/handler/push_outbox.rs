@@ -0,0 +1,140 @@
+use std::time::Duration;
+
+use chrono::Utc;
+use tokio::time;
+use tracing::{error, info, warn};
+
+use etl_core::{
+    configuration::{AppState, State},
+    error::Error,
+    helpers::PushOutboxStatus,
+    model::PushOutbox,
+    push::{self, Outcome},
+};
+
+/// Interval between two polls of the push outbox
+const POLL_INTERVAL_SECS: u64 = 2;
+
+/// Notifications claimed per poll
+const BATCH_SIZE: i64 = 50;
+
+/// Time a claimed notification stays hidden from other workers, after which a
+/// notification of a crashed worker is picked up again
+const CLAIM_TIMEOUT_SECS: i64 = 5 * 60;
+
+/// Delay before the first retry, doubled on every further attempt
+const RETRY_BASE_SECS: i64 = 30;
+const RETRY_MAX_SECS: i64 = 60 * 60;
+
+/// Attempts after which a notification is given up
+const MAX_ATTEMPTS: i32 = 8;
+
+/// Interval between two checks for digests of held notifications
+const DIGEST_INTERVAL_SECS: u64 = 60;
+
+/// Deliver the notifications queued in `push_outbox` once the transaction of
+/// their event is committed
+pub async fn push_outbox_task(app_state: AppState<State>) -> Result<(), Error> {
+    let mut interval = time::interval(Duration::from_secs(POLL_INTERVAL_SECS));
+    tokio::spawn(async move {
+        loop {
+            interval.tick().await;
+
+            let items = match app_state
+                .database
+                .push_outbox
+                .claim_due(BATCH_SIZE, CLAIM_TIMEOUT_SECS)
+                .await
+            {
+                Ok(items) => items,
+                Err(error) => {
+                    error!("Push outbox poll error {}", error);
+                    continue;
+                },
+            };
+
+            for item in items {
+                if let Err(error) = process(&app_state, &item).await {
+                    error!(
+                        "Push outbox item {} not updated {}",
+                        item.id, error
+                    );
+                }
+            }
+        }
+    })
+    .await?
+}
+
+/// Queue digests of the notifications held back by quiet hours and minimum
+/// intervals, see `push::flush_digests`
+pub async fn push_digest_task(app_state: AppState<State>) -> Result<(), Error> {
+    let mut interval =
+        time::interval(Duration::from_secs(DIGEST_INTERVAL_SECS));
+    tokio::spawn(async move {
+        loop {
+            interval.tick().await;
+
+            match push::flush_digests(&app_state).await {
+                Ok(0) => {},
+                Ok(queued) => info!("Queued {} push digests", queued),
+                Err(error) => error!("Push digest error {}", error),
+            }
+        }
+    })
+    .await?
+}
+
+async fn process(
+    app_state: &AppState<State>,
+    item: &PushOutbox,
+) -> Result<(), Error> {
+    let outbox = &app_state.database.push_outbox;
+    let error = match push::deliver(app_state, item).await {
+        Ok(Outcome::Done) => {
+            outbox.finish(item.id, PushOutboxStatus::Done, None).await?;
+            return Ok(());
+        },
+        Ok(Outcome::Retry(error)) => error,
+        Err(error) => error.to_string(),
+    };
+
+    if item.attempts >= MAX_ATTEMPTS {
+        warn!(
+            "Push outbox item {} failed after {} attempts: {}",
+            item.id, item.attempts, error
+        );
+        outbox
+            .finish(item.id, PushOutboxStatus::Failed, Some(error))
+            .await?;
+        return Ok(());
+    }
+
+    let next_attempt_at =
+        Utc::now() + chrono::Duration::seconds(retry_delay(item.attempts));
+    outbox.retry(item.id, next_attempt_at, error).await?;
+
+    Ok(())
+}
+
+/// Seconds to wait after the given number of attempts
+fn retry_delay(attempts: i32) -> i64 {
+    let exponent = attempts.saturating_sub(1).clamp(0, 20) as u32;
+    RETRY_BASE_SECS
+        .saturating_mul(2_i64.pow(exponent))
+        .min(RETRY_MAX_SECS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retry_delay_doubles_up_to_max() {
+        assert_eq!(retry_delay(1), RETRY_BASE_SECS);
+        assert_eq!(retry_delay(2), RETRY_BASE_SECS * 2);
+        assert_eq!(retry_delay(4), RETRY_BASE_SECS * 8);
+        assert_eq!(retry_delay(MAX_ATTEMPTS), RETRY_MAX_SECS);
+        assert_eq!(retry_delay(i32::MAX), RETRY_MAX_SECS);
+    }
+}
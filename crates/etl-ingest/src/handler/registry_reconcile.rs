@@ -0,0 +1,33 @@
+use std::time::Duration;
+
+use tokio::time::{self, Instant};
+use tracing::{error, info};
+
+use etl_core::{
+    configuration::{AppState, State},
+    error::Error,
+};
+
+/// Sync the registry tables with the admin contract and oracles and swap
+/// in the reloaded registry, so protocols and currencies added by
+/// governance are indexed without a restart
+pub async fn registry_reconcile_task(
+    app_state: AppState<State>,
+) -> Result<(), Error> {
+    let period = Duration::from_secs(app_state.config.registry_reload_interval);
+    let mut interval = time::interval_at(Instant::now() + period, period);
+    tokio::spawn(async move {
+        loop {
+            interval.tick().await;
+
+            match app_state.reconcile_registry().await {
+                Ok(changes) if !changes.is_empty() => {
+                    info!("Registry reloaded with {} changes", changes.len());
+                },
+                Ok(_) => {},
+                Err(error) => error!("Registry reconcile error {}", error),
+            }
+        }
+    })
+    .await?
+}
@@ -0,0 +1,399 @@
+use std::str::FromStr as _;
+
+use base64::engine::{general_purpose::STANDARD as BASE64_STANDARD, Engine};
+use bigdecimal::{BigDecimal, Zero as _};
+use chrono::{DateTime, TimeDelta, Utc};
+use cosmrs::{proto::tendermint::abci::Event, Any};
+use sqlx::Transaction;
+
+use etl_core::{
+    configuration::{AppState, State},
+    dao::DataBase,
+    error::Error,
+    helpers::StakingAction,
+    model::{
+        decode_message, CosmosTypes, GovernanceVote, MessageCoin, MessageData,
+        Raw_Message, StakingEvent,
+    },
+};
+
+use crate::event_parsing::parse_unbond;
+
+const UNBOND: &str = "unbond";
+
+/// Message types the staking and governance tables are derived from
+pub const TYPES: [CosmosTypes; 7] = [
+    CosmosTypes::MsgDelegate,
+    CosmosTypes::MsgUndelegate,
+    CosmosTypes::MsgBeginRedelegate,
+    CosmosTypes::MsgCancelUnbondingDelegation,
+    CosmosTypes::MsgWithdrawDelegatorReward,
+    CosmosTypes::MsgVote,
+    CosmosTypes::MsgVoteLegacy,
+];
+
+/// Record the delegation changes and votes of a message of a successful
+/// transaction. Undelegations take their completion time from the `unbond`
+/// event, or from the configured unbonding period when indexed without the
+/// transaction events. Other messages are skipped.
+pub async fn parse_and_insert(
+    app_state: &AppState<State>,
+    message: &Raw_Message,
+    tx_events: &[Event],
+    transaction: &mut Transaction<'_, DataBase>,
+) -> Result<(), Error> {
+    let Ok(kind) = CosmosTypes::from_str(&message.r#type) else {
+        return Ok(());
+    };
+    if !TYPES.contains(&kind) {
+        return Ok(());
+    }
+
+    let value = Any {
+        type_url: message.r#type.to_owned(),
+        value: BASE64_STANDARD.decode(&message.value)?,
+    };
+    let data = decode_message(
+        &value,
+        message.rewards.as_deref(),
+        &app_state.registry().hash_map_denom_ticker,
+    )?;
+
+    if let MessageData::Vote {
+        voter,
+        proposal_id,
+        option,
+    } = data
+    {
+        app_state
+            .database
+            .governance_vote
+            .upsert(
+                GovernanceVote {
+                    proposal_id: proposal_id.try_into()?,
+                    voter,
+                    option,
+                    tx_hash: message.tx_hash.to_owned(),
+                    block: message.block,
+                    timestamp: message.timestamp,
+                },
+                transaction,
+            )
+            .await?;
+
+        return Ok(());
+    }
+
+    let native_currency = &app_state.config.native_currency;
+    let mut events = staking_events(
+        kind,
+        data,
+        message,
+        tx_events,
+        native_currency,
+        TimeDelta::days(app_state.config.staking_unbonding_days),
+    )?;
+
+    for event in events.iter_mut().filter(|e| !e.rewards.is_zero()) {
+        event.rewards_stable = app_state
+            .in_stable_by_date(
+                native_currency,
+                &event.rewards.to_string(),
+                None,
+                &event.timestamp,
+            )
+            .await
+            .ok();
+    }
+
+    app_state
+        .database
+        .staking_event
+        .insert_many(events, transaction)
+        .await?;
+
+    Ok(())
+}
+
+fn staking_events(
+    kind: CosmosTypes,
+    data: MessageData,
+    message: &Raw_Message,
+    tx_events: &[Event],
+    native_currency: &str,
+    unbonding_period: TimeDelta,
+) -> Result<Vec<StakingEvent>, Error> {
+    let event = |validator: String,
+                 delegator: String,
+                 action: StakingAction,
+                 amount: BigDecimal| StakingEvent {
+        tx_hash: message.tx_hash.to_owned(),
+        index: message.index,
+        inner_index: message.inner_index,
+        validator,
+        delegator,
+        action: action.to_string(),
+        amount,
+        rewards: BigDecimal::zero(),
+        rewards_stable: None,
+        completion_time: None,
+        creation_height: None,
+        block: message.block,
+        timestamp: message.timestamp,
+    };
+
+    let events = match data {
+        MessageData::Delegate {
+            delegator_address,
+            validator_address,
+            amount,
+        } => {
+            let amount = coin_amount(amount)?;
+            if kind == CosmosTypes::MsgUndelegate {
+                let completion_time = completion_time(
+                    &validator_address,
+                    &delegator_address,
+                    tx_events,
+                )?
+                .unwrap_or(message.timestamp + unbonding_period);
+
+                let mut undelegation = event(
+                    validator_address,
+                    delegator_address,
+                    StakingAction::Undelegate,
+                    -amount,
+                );
+                undelegation.completion_time = Some(completion_time);
+                vec![undelegation]
+            } else {
+                vec![event(
+                    validator_address,
+                    delegator_address,
+                    StakingAction::Delegate,
+                    amount,
+                )]
+            }
+        },
+        MessageData::Redelegate {
+            delegator_address,
+            validator_src_address,
+            validator_dst_address,
+            amount,
+        } => {
+            let amount = coin_amount(amount)?;
+            vec![
+                event(
+                    validator_src_address,
+                    delegator_address.to_owned(),
+                    StakingAction::Redelegate,
+                    -amount.clone(),
+                ),
+                event(
+                    validator_dst_address,
+                    delegator_address,
+                    StakingAction::Redelegate,
+                    amount,
+                ),
+            ]
+        },
+        MessageData::CancelUnbondingDelegation {
+            delegator_address,
+            validator_address,
+            amount,
+            creation_height,
+        } => {
+            let mut cancel = event(
+                validator_address,
+                delegator_address,
+                StakingAction::CancelUnbonding,
+                coin_amount(amount)?,
+            );
+            cancel.creation_height = Some(creation_height);
+            vec![cancel]
+        },
+        MessageData::WithdrawDelegatorReward {
+            delegator_address,
+            validator_address,
+            rewards,
+        } => {
+            let mut claim = event(
+                validator_address,
+                delegator_address,
+                StakingAction::WithdrawRewards,
+                BigDecimal::zero(),
+            );
+            for coin in rewards {
+                if coin.ticker.as_deref() == Some(native_currency) {
+                    claim.rewards += BigDecimal::from_str(&coin.amount)?;
+                }
+            }
+            vec![claim]
+        },
+        _ => vec![],
+    };
+
+    Ok(events)
+}
+
+fn coin_amount(coin: Option<MessageCoin>) -> Result<BigDecimal, Error> {
+    let amount = match coin {
+        Some(coin) => BigDecimal::from_str(&coin.amount)?,
+        None => BigDecimal::zero(),
+    };
+
+    Ok(amount)
+}
+
+/// Completion time of an undelegation from the `unbond` event of the
+/// validator and delegator
+fn completion_time(
+    validator: &str,
+    delegator: &str,
+    tx_events: &[Event],
+) -> Result<Option<DateTime<Utc>>, Error> {
+    for event in tx_events.iter().filter(|e| e.r#type == UNBOND) {
+        let unbond = parse_unbond(&event.attributes)?;
+        if unbond.validator != validator
+            || unbond.delegator.as_deref().is_some_and(|d| d != delegator)
+        {
+            continue;
+        }
+
+        let completion_time =
+            DateTime::parse_from_rfc3339(&unbond.completion_time)
+                .map_err(|_| {
+                    Error::DecodeDateTimeError(format!(
+                        "completion_time: {}",
+                        unbond.completion_time
+                    ))
+                })?
+                .with_timezone(&Utc);
+
+        return Ok(Some(completion_time));
+    }
+
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmrs::proto::tendermint::abci::EventAttribute;
+
+    use super::*;
+
+    fn message() -> Raw_Message {
+        Raw_Message {
+            index: 0,
+            inner_index: 0,
+            from: String::from("nolus1delegator"),
+            to: String::from("nolusvaloper1dst"),
+            r#type: CosmosTypes::MsgBeginRedelegate.to_string(),
+            value: String::new(),
+            tx_hash: String::from("ABC"),
+            block: 100,
+            fee_amount: BigDecimal::zero(),
+            fee_denom: None,
+            memo: String::new(),
+            timestamp: DateTime::from_timestamp(1_700_000_000, 0).unwrap(),
+            rewards: None,
+            code: Some(0),
+            grantee: None,
+            data: None,
+            codespace: None,
+            raw_log: None,
+            failure: None,
+            gas_wanted: None,
+            gas_used: None,
+        }
+    }
+
+    fn coin(amount: &str) -> Option<MessageCoin> {
+        Some(MessageCoin {
+            amount: amount.to_owned(),
+            denom: String::from("unls"),
+            ticker: Some(String::from("NLS")),
+        })
+    }
+
+    #[test]
+    fn redelegation_moves_stake() {
+        let events = staking_events(
+            CosmosTypes::MsgBeginRedelegate,
+            MessageData::Redelegate {
+                delegator_address: String::from("nolus1delegator"),
+                validator_src_address: String::from("nolusvaloper1src"),
+                validator_dst_address: String::from("nolusvaloper1dst"),
+                amount: coin("250"),
+            },
+            &message(),
+            &[],
+            "NLS",
+            TimeDelta::days(21),
+        )
+        .unwrap();
+
+        let changes: Vec<_> = events
+            .iter()
+            .map(|e| (e.validator.to_owned(), e.amount.to_string()))
+            .collect();
+        assert_eq!(
+            changes,
+            [
+                (String::from("nolusvaloper1src"), String::from("-250")),
+                (String::from("nolusvaloper1dst"), String::from("250"))
+            ]
+        );
+    }
+
+    #[test]
+    fn undelegation_completes_at_unbond_event() {
+        let data = || MessageData::Delegate {
+            delegator_address: String::from("nolus1delegator"),
+            validator_address: String::from("nolusvaloper1src"),
+            amount: coin("40"),
+        };
+        let attribute = |key: &str, value: &str| EventAttribute {
+            key: key.to_owned(),
+            value: value.to_owned(),
+            index: true,
+        };
+        let unbond = Event {
+            r#type: String::from(UNBOND),
+            attributes: vec![
+                attribute("validator", "nolusvaloper1src"),
+                attribute("delegator", "nolus1delegator"),
+                attribute("amount", "40unls"),
+                attribute("completion_time", "2023-12-06T22:13:20Z"),
+            ],
+        };
+
+        let events = staking_events(
+            CosmosTypes::MsgUndelegate,
+            data(),
+            &message(),
+            &[unbond],
+            "NLS",
+            TimeDelta::days(21),
+        )
+        .unwrap();
+        assert_eq!(events[0].amount.to_string(), "-40");
+        assert_eq!(
+            events[0].completion_time,
+            DateTime::from_timestamp(1_701_900_800, 0)
+        );
+
+        let events = staking_events(
+            CosmosTypes::MsgUndelegate,
+            data(),
+            &message(),
+            &[],
+            "NLS",
+            TimeDelta::days(21),
+        )
+        .unwrap();
+        assert_eq!(
+            events[0].completion_time,
+            DateTime::from_timestamp(1_700_000_000 + 21 * 86_400, 0)
+        );
+    }
+}
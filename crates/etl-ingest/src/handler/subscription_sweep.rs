@@ -0,0 +1,60 @@
+use std::time::Duration;
+
+use tokio::time;
+use tracing::{error, info};
+
+use etl_core::{
+    configuration::{AppState, State},
+    error::Error,
+};
+
+/// Interval between two sweeps of the push subscriptions
+const SWEEP_INTERVAL_SECS: u64 = 60 * 60;
+
+/// A subscription with at least `FAILING_MIN_DELIVERIES` Web Push deliveries
+/// in the last `FAILING_DAYS` days, none of them accepted, is deactivated
+const FAILING_DAYS: i32 = 7;
+const FAILING_MIN_DELIVERIES: i64 = 5;
+
+/// Deactivate push subscriptions that expired, keep failing or were signed
+/// with a VAPID key listed in `vapid_retired_key_ids`
+pub async fn subscription_sweep_task(
+    app_state: AppState<State>,
+) -> Result<(), Error> {
+    let mut interval = time::interval(Duration::from_secs(SWEEP_INTERVAL_SECS));
+    tokio::spawn(async move {
+        loop {
+            interval.tick().await;
+
+            if let Err(error) = sweep(&app_state).await {
+                error!("Subscription sweep error {}", error);
+            }
+        }
+    })
+    .await?
+}
+
+async fn sweep(app_state: &AppState<State>) -> Result<(), Error> {
+    let subscription = &app_state.database.subscription;
+    let retired_key_ids = app_state.config.vapid_retired_key_ids.to_owned();
+
+    let (expired, failing, retired) = tokio::try_join!(
+        subscription.deactivate_expired(),
+        subscription.deactivate_failing(FAILING_DAYS, FAILING_MIN_DELIVERIES),
+        subscription.deactivate_retired_keys(retired_key_ids),
+    )?;
+
+    let (expired, failing, retired) = (
+        expired.rows_affected(),
+        failing.rows_affected(),
+        retired.rows_affected(),
+    );
+    if expired + failing + retired > 0 {
+        info!(
+            "Deactivated subscriptions: {} expired, {} failing, {} with retired VAPID keys",
+            expired, failing, retired
+        );
+    }
+
+    Ok(())
+}
@@ -17,7 +17,7 @@ pub async fn parse_and_insert(
     let mut data = Vec::new();
     let all_balances = app_state
         .grpc
-        .get_balances(app_state.config.treasury_contract.to_owned())
+        .get_balances(app_state.registry().treasury_contract.to_owned())
         .await?;
 
     if let Some(page) = all_balances.pagination {
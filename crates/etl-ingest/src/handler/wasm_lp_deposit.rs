@@ -20,12 +20,13 @@ pub async fn parse_and_insert(
     transaction: &mut Transaction<'_, DataBase>,
 ) -> Result<(), Error> {
     let at = parse_event_timestamp(&item.at)?;
-    let protocol = app_state.get_protocol_by_pool_id(&item.to);
+    let height = item.height.parse()?;
+    let protocol = app_state.get_protocol_by_pool_id_at(&item.to, height);
 
     let lp_deposit = LP_Deposit {
         Tx_Hash: tx_hash,
         LP_deposit_idx: None,
-        LP_deposit_height: item.height.parse()?,
+        LP_deposit_height: height,
         LP_address_id: item.from.to_owned(),
         LP_timestamp: at,
         LP_Pool_id: item.to.to_owned(),
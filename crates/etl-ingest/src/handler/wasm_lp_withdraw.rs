@@ -20,10 +20,11 @@ pub async fn parse_and_insert(
     transaction: &mut Transaction<'_, DataBase>,
 ) -> Result<(), Error> {
     let at = parse_event_timestamp(&item.at)?;
-    let protocol = app_state.get_protocol_by_pool_id(&item.from);
+    let height = item.height.parse()?;
+    let protocol = app_state.get_protocol_by_pool_id_at(&item.from, height);
     let lp_withdraw = LP_Withdraw {
         Tx_Hash: tx_hash,
-        LP_withdraw_height: item.height.parse()?,
+        LP_withdraw_height: height,
         LP_withdraw_idx: None,
         LP_address_id: item.to.to_owned(),
         LP_timestamp: at,
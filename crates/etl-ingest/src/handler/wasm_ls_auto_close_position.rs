@@ -4,6 +4,7 @@ use cosmrs::proto::Timestamp;
 use sqlx::Transaction;
 
 use etl_core::{
+    alerts,
     configuration::{AppState, State},
     dao::DataBase,
     error::Error,
@@ -36,17 +37,28 @@ pub async fn parse_and_insert(
 
     let ls_auto_close_position = LS_Auto_Close_Position {
         Tx_Hash: tx_hash,
-        LS_contract_id: item.to,
+        LS_contract_id: item.to.to_owned(),
         LS_timestamp: time_stamp,
         LS_Close_Strategy: strategy.to_string(),
         LS_Close_Strategy_Ltv: amout,
     };
 
-    app_state
+    let result = app_state
         .database
         .ls_auto_close_position
         .insert_if_not_exists(ls_auto_close_position, transaction)
         .await?;
 
+    // Alerts only fire the first time the event is ingested
+    if result.rows_affected() > 0 {
+        alerts::on_position_closed(
+            app_state,
+            item.to,
+            Some(strategy),
+            transaction,
+        )
+        .await?;
+    }
+
     Ok(())
 }
@@ -3,6 +3,7 @@ use sqlx::Transaction;
 use super::parse_event_timestamp;
 
 use etl_core::{
+    alerts,
     configuration::{AppState, State},
     dao::DataBase,
     error::Error,
@@ -20,15 +21,21 @@ pub async fn parse_and_insert(
 
     let ls_closing = LS_Closing {
         Tx_Hash: tx_hash,
-        LS_contract_id: item.id,
+        LS_contract_id: item.id.to_owned(),
         LS_timestamp: at,
     };
 
-    app_state
+    let result = app_state
         .database
         .ls_closing
         .insert_if_not_exists(ls_closing, transaction)
         .await?;
 
+    // Alerts only fire the first time the event is ingested
+    if result.rows_affected() > 0 {
+        alerts::on_position_closed(app_state, item.id, None, transaction)
+            .await?;
+    }
+
     Ok(())
 }
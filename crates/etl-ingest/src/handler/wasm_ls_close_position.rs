@@ -33,7 +33,7 @@ pub async fn parse_and_insert(
 
     let protocol = match lease {
         Some(lease) => {
-            app_state.get_protocol_by_pool_id(&lease.LS_loan_pool_id)
+            app_state.get_protocol_by_pool_id_at(&lease.LS_loan_pool_id, block)
         },
         None => None,
     };
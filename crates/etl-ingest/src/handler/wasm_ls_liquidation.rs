@@ -9,12 +9,12 @@ use etl_core::{
     error::Error,
     helpers::Loan_Closing_Status,
     model::{LS_Liquidation, LS_Liquidation_Type as LS_Liquidation_Data},
-    types::{LS_Liquidation_Type, PushData, PUSH_TYPES},
+    push::{self, Recipient},
+    types::{LS_Liquidation_Type, LeasePush, PushData, PUSH_TYPES},
 };
 
 use super::{
     ls_loan_closing as ls_loan_closing_handler, parse_event_timestamp,
-    send_push::send,
 };
 
 pub async fn parse_and_insert(
@@ -34,7 +34,7 @@ pub async fn parse_and_insert(
 
     let protocol = match lease {
         Some(lease) => {
-            app_state.get_protocol_by_pool_id(&lease.LS_loan_pool_id)
+            app_state.get_protocol_by_pool_id_at(&lease.LS_loan_pool_id, block)
         },
         None => None,
     };
@@ -118,22 +118,44 @@ pub async fn parse_and_insert(
         .await?;
     }
 
-    let push_data = match LS_Liquidation_Data::from(status.as_str()) {
-        LS_Liquidation_Data::OverdueInterest => PushData {
-            r#type: PUSH_TYPES::PartiallyLiquidated.to_string(),
-            body: format!(r#"{{"position": "{}"}}"#, contract),
-        },
-        LS_Liquidation_Data::HighLiability => PushData {
-            r#type: PUSH_TYPES::FullyLiquidated.to_string(),
-            body: format!(r#"{{"position": "{}"}}"#, contract),
-        },
-        LS_Liquidation_Data::Unsupported => PushData {
-            r#type: PUSH_TYPES::Unsupported.to_string(),
-            body: r#"{}"#.to_string(),
+    let push_type = match LS_Liquidation_Data::from(status.as_str()) {
+        LS_Liquidation_Data::OverdueInterest => PUSH_TYPES::PartiallyLiquidated,
+        LS_Liquidation_Data::HighLiability => PUSH_TYPES::FullyLiquidated,
+        LS_Liquidation_Data::Unsupported => PUSH_TYPES::Unsupported,
+    };
+    let push_data = match push_type {
+        PUSH_TYPES::Unsupported => {
+            PushData::new(push_type, &serde_json::json!({}))?
         },
+        _ => PushData::new(
+            push_type,
+            &LeasePush {
+                position: contract.to_owned(),
+                symbol: Some(item.amount_symbol.to_owned()),
+                amount: app_state
+                    .in_whole_units(&item.amount_symbol, &amount)
+                    .ok()
+                    .map(|a| a.to_string()),
+                amount_stable: app_state
+                    .in_whole_units(
+                        &item.amount_symbol,
+                        &ls_liquidation.LS_amnt_stable,
+                    )
+                    .ok()
+                    .map(|a| a.round(2).to_string()),
+                protocol,
+                ..LeasePush::default()
+            },
+        )?,
     };
 
-    send(app_state.clone(), contract, push_data).await?;
+    push::enqueue(
+        app_state,
+        Recipient::Lease(contract),
+        push_data,
+        transaction,
+    )
+    .await?;
 
     Ok(())
 }
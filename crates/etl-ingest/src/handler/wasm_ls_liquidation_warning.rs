@@ -1,4 +1,5 @@
 use anyhow::Context as _;
+use bigdecimal::BigDecimal;
 use chrono::DateTime;
 use cosmrs::proto::Timestamp;
 use sqlx::Transaction;
@@ -8,11 +9,10 @@ use etl_core::{
     dao::DataBase,
     error::Error,
     model::LS_Liquidation_Warning,
-    types::{LS_Liquidation_Warning_Type, PushData, PUSH_TYPES},
+    push::{self, Recipient},
+    types::{LS_Liquidation_Warning_Type, LeasePush, PushData, PUSH_TYPES},
 };
 
-use super::send_push::send;
-
 pub async fn parse_and_insert(
     app_state: &AppState<State>,
     item: LS_Liquidation_Warning_Type,
@@ -27,6 +27,7 @@ pub async fn parse_and_insert(
     let level = item.level.parse()?;
     let ltv = item.ltv.parse()?;
     let contract = item.lease.to_owned();
+    let symbol = item.lease_asset.to_owned();
 
     let ls_liquidation_warning = LS_Liquidation_Warning {
         Tx_Hash: Some(tx_hash),
@@ -44,35 +45,54 @@ pub async fn parse_and_insert(
         .insert_if_not_exists(ls_liquidation_warning, transaction)
         .await?;
 
-    let push_data = match PUSH_TYPES::from(level) {
-        PUSH_TYPES::Funding => PushData {
-            r#type: PUSH_TYPES::Funding.to_string(),
-            body: format!(
-                r#"{{"level": {}, "ltv": {}, "position": "{}"}}"#,
-                item.level, item.ltv, item.lease
-            ),
-        },
-        PUSH_TYPES::FundingRecommended => PushData {
-            r#type: PUSH_TYPES::FundingRecommended.to_string(),
-            body: format!(
-                r#"{{"level": {}, "ltv": {}, "position": "{}"}}"#,
-                item.level, item.ltv, item.lease
-            ),
-        },
-        PUSH_TYPES::FundNow => PushData {
-            r#type: PUSH_TYPES::FundNow.to_string(),
-            body: format!(
-                r#"{{"level": {}, "ltv": {}, "position": "{}"}}"#,
-                item.level, item.ltv, item.lease
-            ),
-        },
-        _ => PushData {
-            r#type: PUSH_TYPES::Unsupported.to_string(),
-            body: r#"{}"#.to_string(),
+    let push_type = PUSH_TYPES::from(level);
+    let push_data = match push_type {
+        PUSH_TYPES::Funding
+        | PUSH_TYPES::FundingRecommended
+        | PUSH_TYPES::FundNow => {
+            let (lease, amount_stable) = tokio::try_join!(
+                app_state.database.ls_opening.get(contract.to_owned()),
+                app_state
+                    .database
+                    .ls_state
+                    .get_latest_amount_stable(contract.to_owned()),
+            )?;
+            let protocol = lease.and_then(|lease| {
+                app_state.get_protocol_by_pool_id(&lease.LS_loan_pool_id)
+            });
+            let amount_stable = amount_stable.and_then(|amount| {
+                app_state.in_whole_units(&symbol, &amount).ok()
+            });
+
+            PushData::new(
+                push_type,
+                &LeasePush {
+                    position: contract.to_owned(),
+                    level: Some(level),
+                    ltv: Some(ltv.into()),
+                    ltv_percent: Some(
+                        (BigDecimal::from(ltv) / BigDecimal::from(10))
+                            .round(1)
+                            .to_string(),
+                    ),
+                    symbol: Some(symbol),
+                    amount_stable: amount_stable
+                        .map(|a| a.round(2).to_string()),
+                    protocol,
+                    ..LeasePush::default()
+                },
+            )?
         },
+        _ => PushData::new(PUSH_TYPES::Unsupported, &serde_json::json!({}))?,
     };
 
-    send(app_state.clone(), contract, push_data).await?;
+    push::enqueue(
+        app_state,
+        Recipient::Lease(contract),
+        push_data,
+        transaction,
+    )
+    .await?;
 
     Ok(())
 }
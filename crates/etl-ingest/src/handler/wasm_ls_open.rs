@@ -57,7 +57,8 @@ pub async fn parse_and_insert(
 ) -> Result<(), Error> {
     let at = parse_event_timestamp(&item.at)?;
 
-    let protocol = app_state.get_protocol_by_pool_id(&item.loan_pool_id);
+    let protocol =
+        app_state.get_protocol_by_pool_id_at(&item.loan_pool_id, height);
     let lpn_currency = app_state.get_currency_by_pool_id(&item.loan_pool_id)?;
 
     let f1 = app_state
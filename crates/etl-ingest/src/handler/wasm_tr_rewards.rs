@@ -21,11 +21,12 @@ pub async fn parse_and_insert(
     transaction: &mut Transaction<'_, DataBase>,
 ) -> Result<(), Error> {
     let at = parse_event_timestamp(&item.at)?;
-    let protocol = app_state.get_protocol_by_pool_id(&item.to);
+    let height = item.height.parse()?;
+    let protocol = app_state.get_protocol_by_pool_id_at(&item.to, height);
 
     let tr_rewards_distribution = TR_Rewards_Distribution {
         Tx_Hash: tx_hash,
-        TR_Rewards_height: item.height.parse()?,
+        TR_Rewards_height: height,
         TR_Rewards_idx: None,
         TR_Rewards_Pool_id: item.to.to_owned(),
         TR_Rewards_timestamp: at,
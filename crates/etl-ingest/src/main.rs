@@ -2,24 +2,33 @@ use std::time::Duration;
 
 use chrono::Utc;
 use tokio::time;
-use tracing::{error, Level};
+use tracing::{error, info, Level};
 
 use etl_core::{
     configuration::{
-        get_configuration, set_configuration, AppState, Config, State,
+        get_configuration, registry_reload_task, set_configuration, AppState,
+        Config, State,
     },
     error::Error,
+    helpers::IngestRole,
     model::Actions,
-    provider::{DatabasePool, Grpc, HTTP},
+    provider::{DatabasePool, Grpc, LeaderLock, HTTP},
+    settings::config_path,
+    telemetry,
 };
 
+mod cli;
 mod event_dispatch;
 mod event_parsing;
 mod handler;
 mod provider;
 
-use handler::{aggregation_task, mp_assets};
-use provider::Event;
+use cli::Args;
+use handler::{
+    admin_commands, aggregation_task, ls_loan_closing, mp_assets, push_outbox,
+    registry_reconcile, subscription_sweep,
+};
+use provider::{synchronization, Event};
 
 #[tokio::main]
 async fn main() -> Result<(), Error> {
@@ -56,12 +65,45 @@ async fn app_main() -> Result<(), Error> {
     run_server().await
 }
 
-/// Run the ETL ingest server (default mode)
+/// Advisory lock key electing the aggregator
+const AGGREGATOR_LOCK: i64 = 0x0065_746c_5f61_6767;
+
+/// Interval between two attempts of a standby aggregator to take the lock
+const AGGREGATOR_RETRY_SECS: u64 = 30;
+
+/// Interval between two checks of the connection holding the lock
+const AGGREGATOR_CHECK_SECS: u64 = 10;
+
+/// Run the ETL ingest server with the roles of the configuration or the
+/// command line
 async fn run_server() -> Result<(), Error> {
-    let (config, database) = match init().await {
+    let args = Args::parse()?;
+    let (mut config, database) = match init().await {
         Ok((config, database)) => (config, database),
         Err(e) => return Err(Error::ConfigurationError(e.to_string())),
     };
+    if let Some(roles) = args.roles {
+        config.ingest_roles = roles;
+    }
+    if args.range.is_some()
+        && !config.ingest_roles.contains(&IngestRole::Backfill)
+    {
+        return Err(Error::ConfigurationError(String::from(
+            "--from and --to need the backfill role",
+        )));
+    }
+    if args.range.is_some() && !config.enable_sync {
+        return Err(Error::ConfigurationError(String::from(
+            "--from and --to need enable_sync",
+        )));
+    }
+
+    let roles: Vec<String> = config
+        .ingest_roles
+        .iter()
+        .map(|role| role.to_string())
+        .collect();
+    info!("Running ingest roles {}", roles.join(", "));
 
     let db_pool = database;
     let grpc = Grpc::new(config.clone()).await?;
@@ -70,21 +112,111 @@ async fn run_server() -> Result<(), Error> {
     let state = State::new(config.clone(), db_pool, grpc, http).await?;
     let app_state = AppState::new(state);
 
-    mp_assets::fetch_insert(app_state.clone(), None).await?;
-    let event_manager = Event::new(app_state.clone());
+    if let Some((from, to)) = args.range {
+        return backfill_range(app_state, from, to).await;
+    }
+
+    let metrics = telemetry::serve(
+        &config.metrics_host,
+        config.metrics_port,
+        app_state.database.pool.clone(),
+    );
+    let (_, _, _, _, _, _) = tokio::try_join!(
+        run_live(app_state.clone()),
+        run_backfill(app_state.clone()),
+        run_aggregator(app_state.clone()),
+        run_prices(app_state.clone()),
+        registry_reload_task(app_state.clone()),
+        metrics,
+    )?;
+
+    Ok(())
+}
+
+fn has_role(app_state: &AppState<State>, role: IngestRole) -> bool {
+    app_state.config.ingest_roles.contains(&role)
+}
+
+/// Tail new blocks. A process with the backfill role too indexes the
+/// missing blocks at every connection, as a single deployment always did.
+async fn run_live(app_state: AppState<State>) -> Result<(), Error> {
+    if !has_role(&app_state, IngestRole::Live) {
+        return Ok(());
+    }
+
+    let sync = has_role(&app_state, IngestRole::Backfill);
+    Event::new(app_state).run(sync).await
+}
+
+/// Index the missing blocks periodically when no live tailing does it on
+/// connection
+async fn run_backfill(app_state: AppState<State>) -> Result<(), Error> {
+    if !has_role(&app_state, IngestRole::Backfill)
+        || !app_state.config.enable_sync
+        || has_role(&app_state, IngestRole::Live)
+    {
+        return Ok(());
+    }
+
+    synchronization::gap_sync_task(app_state).await
+}
 
-    let (_, _, _) = tokio::try_join!(
-        event_manager.run(),
-        mp_assets::mp_assets_task(app_state.clone()),
+/// Index the blocks of `from..=to` and stop, without the other tasks of
+/// the roles
+async fn backfill_range(
+    app_state: AppState<State>,
+    from: i64,
+    to: i64,
+) -> Result<(), Error> {
+    info!("Backfilling blocks {} to {}", from, to);
+    synchronization::sync_range(app_state.clone(), from, to).await?;
+    ls_loan_closing::proceed_leases(app_state).await?;
+    info!("Backfill of blocks {} to {} completed", from, to);
+
+    Ok(())
+}
+
+/// Wait for the aggregator lock, then run the jobs that must not run twice.
+/// Losing the lock stops the process, so another one takes over.
+async fn run_aggregator(app_state: AppState<State>) -> Result<(), Error> {
+    if !has_role(&app_state, IngestRole::Aggregator) {
+        return Ok(());
+    }
+
+    info!("Waiting for the aggregator lock");
+    let lock = LeaderLock::acquire(
+        &app_state.config.database_url,
+        AGGREGATOR_LOCK,
+        Duration::from_secs(AGGREGATOR_RETRY_SECS),
+    )
+    .await?;
+    info!("Aggregator lock acquired");
+
+    let (_, _, _, _, _, _, _) = tokio::try_join!(
+        lock.hold(Duration::from_secs(AGGREGATOR_CHECK_SECS)),
         start_aggregation_tasks(app_state.clone()),
+        admin_commands::admin_commands_task(app_state.clone()),
+        push_outbox::push_outbox_task(app_state.clone()),
+        push_outbox::push_digest_task(app_state.clone()),
+        subscription_sweep::subscription_sweep_task(app_state.clone()),
+        registry_reconcile::registry_reconcile_task(app_state.clone()),
     )?;
 
     Ok(())
 }
 
+async fn run_prices(app_state: AppState<State>) -> Result<(), Error> {
+    if !has_role(&app_state, IngestRole::Prices) {
+        return Ok(());
+    }
+
+    mp_assets::fetch_insert(app_state.clone(), None).await?;
+    mp_assets::mp_assets_task(app_state).await
+}
+
 async fn init() -> Result<(Config, DatabasePool), Error> {
     set_configuration()?;
-    let config = get_configuration()?;
+    let config = get_configuration(config_path().as_deref())?;
 
     // Run migrations before creating the connection pool
     etl_core::migration::run_migrations(&config.database_url).await?;
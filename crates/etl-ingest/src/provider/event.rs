@@ -11,6 +11,7 @@ use tracing::{error, info};
 use etl_core::{
     configuration::{AppState, State},
     error::Error,
+    telemetry::metrics,
 };
 
 use crate::{
@@ -37,19 +38,23 @@ impl Event {
         Self { app_state }
     }
 
-    pub async fn run(&self) -> Result<(), Error> {
+    /// Tail new blocks, reconnecting on failure. With `sync`, the missing
+    /// blocks are indexed at every connection too.
+    pub async fn run(&self, sync: bool) -> Result<(), Error> {
         if !self.app_state.config.enable_sync {
             return Ok(());
         }
 
         loop {
             // Spawn sync independently — errors logged, don't affect WS
-            let sync_state = self.app_state.clone();
-            tokio::spawn(async move {
-                if let Err(e) = start_sync(sync_state).await {
-                    error!("Sync error: {}", e);
-                }
-            });
+            if sync {
+                let sync_state = self.app_state.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = start_sync(sync_state).await {
+                        error!("Sync error: {}", e);
+                    }
+                });
+            }
 
             // Run WebSocket session with guaranteed cleanup
             if let Err(e) = self.run_session().await {
@@ -136,6 +141,7 @@ impl Event {
                 error!("Block event missing block data");
                 continue;
             };
+            metrics().chain_head_height.set(height.try_into()?);
 
             // If consumer dropped (broke out of loop), exit cleanly
             if height_tx
@@ -211,5 +217,6 @@ async fn process_block(
     let height: i64 = height.try_into()?;
     let (txs, time_stamp) = app_state.grpc.get_block(height).await?;
     insert_txs(app_state.clone(), txs, height, time_stamp).await?;
+    metrics().block_indexed("live", height);
     Ok(())
 }
@@ -1,16 +1,25 @@
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::{
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
 
 use futures::future::try_join_all;
+use tokio::time;
 use tracing::{error, info};
 
 use etl_core::{
     configuration::{AppState, State},
     error::Error,
     provider::Grpc,
+    telemetry::metrics,
 };
 
 use crate::{event_dispatch::insert_txs, handler::ls_loan_closing};
 
+/// Interval between two gap scans of a backfill deployment without live
+/// tailing
+const GAP_SCAN_INTERVAL_SECS: u64 = 300;
+
 static RUNNING: AtomicBool = AtomicBool::new(false);
 /// Tracks whether the initial full gap scan has been performed.
 /// On startup, we do a full scan to catch historical gaps.
@@ -162,6 +171,7 @@ impl Handler {
     async fn insert_tx(&mut self, height: i64) -> Result<(), Error> {
         let (txs, time_stamp) = self.grpc.get_block(height).await?;
         insert_txs(self.app_state.clone(), txs, height, time_stamp).await?;
+        metrics().block_indexed("backfill", height);
         Ok(())
     }
 }
@@ -187,6 +197,42 @@ pub async fn start_sync(app_state: AppState<State>) -> Result<(), Error> {
     .await?
 }
 
+/// Index the blocks missing from the block table, again at every interval,
+/// when no live tailing triggers it on connection
+pub async fn gap_sync_task(app_state: AppState<State>) -> Result<(), Error> {
+    let mut interval =
+        time::interval(Duration::from_secs(GAP_SCAN_INTERVAL_SECS));
+    loop {
+        interval.tick().await;
+        if let Err(e) = start_sync(app_state.clone()).await {
+            error!("Sync error: {}", e);
+        }
+    }
+}
+
+/// Index the blocks of `[from, to]` missing from the block table.
+/// Heights already indexed are skipped by `insert_txs`.
+pub async fn sync_range(
+    app_state: AppState<State>,
+    from: i64,
+    to: i64,
+) -> Result<(), Error> {
+    let sync_manager = Synchronization {};
+    if sync_manager.is_running() {
+        return Err(Error::TaskError(String::from(
+            "synchronization is already running",
+        )));
+    }
+
+    let threads_count = app_state.config.sync_threads;
+    let result = sync_manager
+        .start_tasks(threads_count, vec![(from, to + 1)], app_state)
+        .await;
+    sync_manager.set_running(false);
+
+    result
+}
+
 pub fn is_sync_running() -> bool {
     let running = &RUNNING;
     running.load(Ordering::SeqCst)
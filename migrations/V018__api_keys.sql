@@ -0,0 +1,27 @@
+-- Migration: API keys and daily usage counters
+-- Keys are stored as SHA-256 hashes; the plaintext is only shown once at creation.
+-- The scope grants access hierarchically: public < partner < admin.
+
+CREATE TABLE IF NOT EXISTS "api_key" (
+    "key_hash" VARCHAR(64) PRIMARY KEY,
+    "key_prefix" VARCHAR(16) NOT NULL,
+    "name" VARCHAR(100) NOT NULL,
+    "scope" VARCHAR(16) NOT NULL DEFAULT 'public',
+    "rate_limit_per_minute" INTEGER NOT NULL,
+    "burst" INTEGER NOT NULL,
+    "active" BOOLEAN NOT NULL DEFAULT true,
+    "created_at" TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_api_key_prefix ON "api_key" ("key_prefix");
+
+-- Requests accepted and rejected by the rate limiter, per key and UTC day
+CREATE TABLE IF NOT EXISTS "api_key_usage" (
+    "key_hash" VARCHAR(64) NOT NULL REFERENCES "api_key"("key_hash"),
+    "day" DATE NOT NULL,
+    "requests" BIGINT NOT NULL DEFAULT 0,
+    "throttled" BIGINT NOT NULL DEFAULT 0,
+    PRIMARY KEY ("key_hash", "day")
+);
+
+CREATE INDEX IF NOT EXISTS idx_api_key_usage_day ON "api_key_usage" ("day");
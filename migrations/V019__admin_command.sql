@@ -0,0 +1,18 @@
+-- Migration: admin command queue
+-- Commands issued through the admin API and executed by the ingest process,
+-- which owns the aggregation task and the block synchronization.
+
+CREATE TABLE IF NOT EXISTS "admin_command" (
+    "id" BIGSERIAL PRIMARY KEY,
+    "command" VARCHAR(32) NOT NULL,
+    "from_height" BIGINT,
+    "to_height" BIGINT,
+    "status" VARCHAR(16) NOT NULL DEFAULT 'pending',
+    "error" TEXT,
+    "requested_by" VARCHAR(100),
+    "created_at" TIMESTAMPTZ NOT NULL DEFAULT now(),
+    "started_at" TIMESTAMPTZ,
+    "finished_at" TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_admin_command_pending ON "admin_command" ("id") WHERE "status" = 'pending';
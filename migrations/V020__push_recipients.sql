@@ -0,0 +1,35 @@
+-- Migration: push notification recipients
+-- Lease notifications reach the lease owner resolved through "LS_Opening" and
+-- browsers subscribed to the lease itself. Every notification records the
+-- subscriptions it was delivered to.
+
+CREATE TABLE IF NOT EXISTS "subscription_lease" (
+    "address" VARCHAR(44) NOT NULL,
+    "auth" VARCHAR(22) NOT NULL,
+    "lease" VARCHAR(128) NOT NULL,
+    "active" BOOLEAN NOT NULL DEFAULT true,
+    "created_at" TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY ("auth", "lease")
+);
+
+CREATE INDEX IF NOT EXISTS idx_subscription_lease_lease ON "subscription_lease" ("lease") WHERE "active" = true;
+
+CREATE TABLE IF NOT EXISTS "push_notification" (
+    "id" BIGSERIAL PRIMARY KEY,
+    "push_type" VARCHAR(32) NOT NULL,
+    "lease" VARCHAR(128),
+    "owner" VARCHAR(44),
+    "recipients" INT NOT NULL,
+    "created_at" TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS idx_push_notification_lease ON "push_notification" ("lease", "created_at" DESC);
+
+CREATE TABLE IF NOT EXISTS "push_delivery" (
+    "notification_id" BIGINT NOT NULL REFERENCES "push_notification" ("id") ON DELETE CASCADE,
+    "address" VARCHAR(44) NOT NULL,
+    "endpoint" TEXT NOT NULL,
+    "status_code" INT NOT NULL,
+    "delivered_at" TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY ("notification_id", "endpoint")
+);
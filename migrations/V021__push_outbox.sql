@@ -0,0 +1,32 @@
+-- Migration: push notification outbox
+-- Notifications are queued in the transaction of the event that triggers them
+-- and delivered by a worker once committed, with exponential backoff.
+
+CREATE TABLE IF NOT EXISTS "push_outbox" (
+    "id" BIGSERIAL PRIMARY KEY,
+    "push_type" VARCHAR(32) NOT NULL,
+    "body" TEXT NOT NULL,
+    "recipient_type" VARCHAR(16) NOT NULL,
+    "recipient" VARCHAR(128) NOT NULL,
+    "notification_id" BIGINT REFERENCES "push_notification" ("id") ON DELETE SET NULL,
+    "status" VARCHAR(16) NOT NULL DEFAULT 'pending',
+    "attempts" INT NOT NULL DEFAULT 0,
+    "next_attempt_at" TIMESTAMPTZ NOT NULL DEFAULT now(),
+    "last_error" TEXT,
+    "created_at" TIMESTAMPTZ NOT NULL DEFAULT now(),
+    "processed_at" TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_push_outbox_due ON "push_outbox" ("next_attempt_at") WHERE "status" = 'pending';
+
+-- Every attempt updates the delivery row of a subscription, failed ones included
+ALTER TABLE "push_delivery"
+    ALTER COLUMN "status_code" DROP NOT NULL,
+    ALTER COLUMN "delivered_at" DROP NOT NULL,
+    ALTER COLUMN "delivered_at" DROP DEFAULT,
+    ADD COLUMN IF NOT EXISTS "attempts" INT NOT NULL DEFAULT 1,
+    ADD COLUMN IF NOT EXISTS "latency_ms" INT,
+    ADD COLUMN IF NOT EXISTS "error" TEXT,
+    ADD COLUMN IF NOT EXISTS "last_attempt_at" TIMESTAMPTZ NOT NULL DEFAULT now();
+
+CREATE INDEX IF NOT EXISTS idx_push_delivery_last_attempt ON "push_delivery" ("last_attempt_at");
@@ -0,0 +1,26 @@
+-- Migration: user-configurable alert rules
+-- Rules belong to a browser subscription and are evaluated by the ingest
+-- process on every price tick and on lease close events. Threshold rules fire
+-- once when their condition starts to hold and re-arm when it stops holding.
+
+CREATE TABLE IF NOT EXISTS "alert_rule" (
+    "id" BIGSERIAL PRIMARY KEY,
+    "address" VARCHAR(44) NOT NULL,
+    "auth" VARCHAR(22) NOT NULL,
+    "kind" VARCHAR(32) NOT NULL,
+    "lease" VARCHAR(128),
+    "symbol" VARCHAR(20),
+    "protocol" VARCHAR(64),
+    "comparison" VARCHAR(8),
+    "threshold" NUMERIC(39, 18),
+    "strategy" VARCHAR(16),
+    "active" BOOLEAN NOT NULL DEFAULT true,
+    "triggered" BOOLEAN NOT NULL DEFAULT false,
+    "last_value" NUMERIC(39, 18),
+    "last_triggered_at" TIMESTAMPTZ,
+    "created_at" TIMESTAMPTZ NOT NULL DEFAULT now(),
+    "updated_at" TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS idx_alert_rule_subscription ON "alert_rule" ("address", "auth");
+CREATE INDEX IF NOT EXISTS idx_alert_rule_kind ON "alert_rule" ("kind") WHERE "active" = true;
@@ -0,0 +1,22 @@
+-- Migration: notification channels
+-- Besides Web Push, a subscription can be delivered to signed webhooks, email
+-- and a bot API. Deliveries are recorded per channel.
+
+ALTER TABLE "subscription" ADD COLUMN IF NOT EXISTS "web_push" BOOLEAN NOT NULL DEFAULT true;
+
+CREATE TABLE IF NOT EXISTS "subscription_channel" (
+    "address" VARCHAR(44) NOT NULL,
+    "auth" VARCHAR(22) NOT NULL,
+    "channel" VARCHAR(16) NOT NULL,
+    "target" TEXT NOT NULL,
+    "secret" VARCHAR(64),
+    "active" BOOLEAN NOT NULL DEFAULT true,
+    "created_at" TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY ("auth", "channel", "target")
+);
+
+CREATE INDEX IF NOT EXISTS idx_subscription_channel_subscription ON "subscription_channel" ("address", "auth") WHERE "active" = true;
+
+ALTER TABLE "push_delivery" ADD COLUMN IF NOT EXISTS "channel" VARCHAR(16) NOT NULL DEFAULT 'web_push';
+ALTER TABLE "push_delivery" DROP CONSTRAINT IF EXISTS "push_delivery_pkey";
+ALTER TABLE "push_delivery" ADD PRIMARY KEY ("notification_id", "channel", "endpoint");
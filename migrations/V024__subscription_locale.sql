@@ -0,0 +1,4 @@
+-- Migration: subscription locale
+-- Notifications are rendered from templates in the locale of each subscription.
+
+ALTER TABLE "subscription" ADD COLUMN IF NOT EXISTS "locale" VARCHAR(16) NOT NULL DEFAULT 'en';
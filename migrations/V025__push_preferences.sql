@@ -0,0 +1,37 @@
+-- Migration: push preferences
+-- Per subscription opt-outs, minimum interval per lease and type, quiet hours
+-- and digests. Notifications held back by quiet hours or the interval are
+-- kept in push_digest and sent as one digest notification later.
+
+CREATE TABLE IF NOT EXISTS "subscription_preference" (
+    "address" VARCHAR(44) NOT NULL,
+    "auth" VARCHAR(22) NOT NULL,
+    "muted_types" TEXT[] NOT NULL DEFAULT '{}',
+    "min_interval_minutes" INT,
+    "quiet_start" TIME,
+    "quiet_end" TIME,
+    "timezone" VARCHAR(64) NOT NULL DEFAULT 'UTC',
+    "digest" BOOLEAN NOT NULL DEFAULT true,
+    "updated_at" TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY ("address", "auth")
+);
+
+CREATE TABLE IF NOT EXISTS "push_throttle" (
+    "address" VARCHAR(44) NOT NULL,
+    "auth" VARCHAR(22) NOT NULL,
+    "lease" VARCHAR(128) NOT NULL,
+    "push_type" VARCHAR(32) NOT NULL,
+    "notification_id" BIGINT NOT NULL,
+    "sent_at" TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY ("address", "auth", "lease", "push_type")
+);
+
+CREATE TABLE IF NOT EXISTS "push_digest" (
+    "address" VARCHAR(44) NOT NULL,
+    "auth" VARCHAR(22) NOT NULL,
+    "notification_id" BIGINT NOT NULL,
+    "push_type" VARCHAR(32) NOT NULL,
+    "lease" VARCHAR(128),
+    "created_at" TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY ("address", "auth", "notification_id")
+);
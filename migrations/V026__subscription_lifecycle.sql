@@ -0,0 +1,10 @@
+-- Migration: subscription lifecycle
+-- Subscriptions record the VAPID key they were created with, so keys can be
+-- rotated, and their creation time. Existing subscriptions use the key that
+-- was configured before key versions.
+
+ALTER TABLE "subscription" ADD COLUMN IF NOT EXISTS "vapid_key_id" VARCHAR(32) NOT NULL DEFAULT 'default';
+ALTER TABLE "subscription" ADD COLUMN IF NOT EXISTS "created_at" TIMESTAMPTZ NOT NULL DEFAULT now();
+
+CREATE INDEX IF NOT EXISTS idx_subscription_expiration ON "subscription" ("expiration") WHERE "active" = true;
+CREATE INDEX IF NOT EXISTS idx_push_delivery_endpoint ON "push_delivery" ("endpoint", "last_attempt_at") WHERE "channel" = 'web_push';
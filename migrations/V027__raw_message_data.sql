@@ -0,0 +1,8 @@
+-- Migration: decoded raw messages
+-- Messages are decoded into structured JSON at ingest. Rows ingested before
+-- keep the base64 body only until the `decode_messages` admin command fills
+-- them in; the partial index serves that backfill and shrinks as it runs.
+
+ALTER TABLE "raw_message" ADD COLUMN IF NOT EXISTS "data" JSONB;
+
+CREATE INDEX IF NOT EXISTS idx_raw_message_undecoded ON "raw_message" ("block", "tx_hash", "index") WHERE "data" IS NULL;
@@ -0,0 +1,14 @@
+-- Migration: authz-wrapped messages
+-- The messages of an authz MsgExec are stored as their own rows under the
+-- index of the MsgExec, numbered from 1 in "inner_index", with the grantee
+-- that executed them. Top-level messages keep inner_index 0.
+
+ALTER TABLE "raw_message" ADD COLUMN IF NOT EXISTS "inner_index" INT NOT NULL DEFAULT 0;
+ALTER TABLE "raw_message" ADD COLUMN IF NOT EXISTS "grantee" VARCHAR(128);
+
+ALTER TABLE "raw_message" DROP CONSTRAINT IF EXISTS "raw_message_pkey";
+ALTER TABLE "raw_message" ADD PRIMARY KEY ("index", "inner_index", "tx_hash");
+
+DROP INDEX IF EXISTS idx_raw_message_undecoded;
+CREATE INDEX IF NOT EXISTS idx_raw_message_undecoded ON "raw_message" ("block", "tx_hash", "index", "inner_index") WHERE "data" IS NULL;
+CREATE INDEX IF NOT EXISTS idx_raw_message_grantee ON "raw_message" ("grantee") WHERE "grantee" IS NOT NULL;
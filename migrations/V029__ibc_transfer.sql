@@ -0,0 +1,30 @@
+-- Migration: IBC transfer lifecycle
+-- One row per ICS-20 packet, keyed by its source channel and sequence in
+-- each direction. Outgoing transfers start "pending" and end "acknowledged",
+-- "failed" (error acknowledgement, refunded) or "timed_out" (refunded).
+-- Incoming transfers are "received" or "failed".
+
+CREATE TABLE IF NOT EXISTS "ibc_transfer" (
+  "source_channel" VARCHAR(64) NOT NULL,
+  "sequence" BIGINT NOT NULL,
+  "direction" VARCHAR(16) NOT NULL,
+  "source_port" VARCHAR(128) NOT NULL,
+  "destination_port" VARCHAR(128) NOT NULL,
+  "destination_channel" VARCHAR(64) NOT NULL,
+  "sender" VARCHAR(256) NOT NULL,
+  "receiver" VARCHAR(256) NOT NULL,
+  "denom" VARCHAR(256) NOT NULL,
+  "packet_denom" VARCHAR(256) NOT NULL,
+  "amount" DECIMAL(39, 0) NOT NULL,
+  "status" VARCHAR(16) NOT NULL,
+  "error" TEXT,
+  "timeout_at" TIMESTAMPTZ,
+  "tx_hash" VARCHAR(64),
+  "sent_at" TIMESTAMPTZ,
+  "completion_tx_hash" VARCHAR(64),
+  "completed_at" TIMESTAMPTZ,
+  PRIMARY KEY ("source_channel", "sequence", "direction")
+);
+
+CREATE INDEX IF NOT EXISTS idx_ibc_transfer_sender ON "ibc_transfer" ("sender", "status");
+CREATE INDEX IF NOT EXISTS idx_ibc_transfer_receiver ON "ibc_transfer" ("receiver", "status");
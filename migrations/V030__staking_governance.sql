@@ -0,0 +1,40 @@
+-- Migration: staking and governance analytics
+-- "staking_event" holds one row per change of a delegation, derived from
+-- the indexed staking and distribution messages of successful transactions.
+-- "amount" is the signed change of the staked balance in the native denom:
+-- negative for undelegations and the source of redelegations, zero for
+-- reward withdrawals. Claimed rewards are in the native denom, with their
+-- stable value at the time of the claim.
+
+CREATE TABLE IF NOT EXISTS "staking_event" (
+  "tx_hash" VARCHAR(64) NOT NULL,
+  "index" INT NOT NULL,
+  "inner_index" INT NOT NULL,
+  "validator" VARCHAR(128) NOT NULL,
+  "delegator" VARCHAR(128) NOT NULL,
+  "action" VARCHAR(32) NOT NULL,
+  "amount" DECIMAL(39, 0) NOT NULL,
+  "rewards" DECIMAL(39, 0) NOT NULL DEFAULT 0,
+  "rewards_stable" DECIMAL(39, 0),
+  "completion_time" TIMESTAMPTZ,
+  "creation_height" BIGINT,
+  "block" BIGINT NOT NULL,
+  "timestamp" TIMESTAMPTZ NOT NULL,
+  PRIMARY KEY ("tx_hash", "index", "inner_index", "validator")
+);
+
+CREATE INDEX IF NOT EXISTS idx_staking_event_delegator ON "staking_event" ("delegator", "timestamp");
+CREATE INDEX IF NOT EXISTS idx_staking_event_validator ON "staking_event" ("validator", "timestamp");
+
+-- Latest vote of each voter on a proposal, from "MsgVote" of both gov
+-- versions
+
+CREATE TABLE IF NOT EXISTS "governance_vote" (
+  "proposal_id" BIGINT NOT NULL,
+  "voter" VARCHAR(128) NOT NULL,
+  "option" VARCHAR(32) NOT NULL,
+  "tx_hash" VARCHAR(64) NOT NULL,
+  "block" BIGINT NOT NULL,
+  "timestamp" TIMESTAMPTZ NOT NULL,
+  PRIMARY KEY ("proposal_id", "voter")
+);
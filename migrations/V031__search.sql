@@ -0,0 +1,10 @@
+-- Migration: search
+-- "/api/search" looks up transaction hashes, addresses and memo fragments.
+-- The primary key of "raw_message" leads with "index", so hashes get their
+-- own index; memos are matched with ILIKE, served by a trigram index.
+-- Lease contracts, blocks and protocols are found by their primary keys.
+
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+CREATE INDEX IF NOT EXISTS idx_raw_message_tx_hash ON "raw_message" ("tx_hash");
+CREATE INDEX IF NOT EXISTS idx_raw_message_memo_trgm ON "raw_message" USING GIN ("memo" gin_trgm_ops) WHERE "memo" <> '';
@@ -0,0 +1,11 @@
+-- Migration: failed transactions
+-- Messages of failed transactions keep the codespace and error log of the
+-- transaction and the reason it failed ("out_of_gas", "insufficient_funds",
+-- "insufficient_fee", "slippage", "contract_error" or "other"). Failures
+-- ingested before stay unclassified.
+
+ALTER TABLE "raw_message" ADD COLUMN IF NOT EXISTS "codespace" VARCHAR(64);
+ALTER TABLE "raw_message" ADD COLUMN IF NOT EXISTS "raw_log" TEXT;
+ALTER TABLE "raw_message" ADD COLUMN IF NOT EXISTS "failure" VARCHAR(32);
+
+CREATE INDEX IF NOT EXISTS idx_raw_message_type_timestamp ON "raw_message" ("type", "timestamp");
@@ -0,0 +1,29 @@
+-- Migration: gas and fee analytics
+-- Messages keep the gas wanted and used by their transaction. "fee_daily"
+-- rolls up the fees and gas of the transactions of each day by the type of
+-- their first message, its contract action and the fee payer. A
+-- transaction counts once, with its first message. "action" is empty for
+-- messages other than contract executions; the lease and pool operations
+-- are named "lease_open", "repay", "close", "deposit" and "withdraw", any
+-- other execution by its message name.
+
+ALTER TABLE "raw_message" ADD COLUMN IF NOT EXISTS "gas_wanted" BIGINT;
+ALTER TABLE "raw_message" ADD COLUMN IF NOT EXISTS "gas_used" BIGINT;
+
+CREATE INDEX IF NOT EXISTS idx_raw_message_timestamp ON "raw_message" ("timestamp");
+
+CREATE TABLE IF NOT EXISTS "fee_daily" (
+  "day" TIMESTAMPTZ NOT NULL,
+  "type" VARCHAR(64) NOT NULL,
+  "action" VARCHAR(64) NOT NULL,
+  "address" VARCHAR(128) NOT NULL,
+  "fee_denom" VARCHAR(68) NOT NULL,
+  "txs" BIGINT NOT NULL,
+  "failed" BIGINT NOT NULL,
+  "fee_amount" DECIMAL(39, 0) NOT NULL,
+  "gas_wanted" DECIMAL(39, 0) NOT NULL,
+  "gas_used" DECIMAL(39, 0) NOT NULL,
+  PRIMARY KEY ("day", "type", "action", "address", "fee_denom")
+);
+
+CREATE INDEX IF NOT EXISTS idx_fee_daily_address ON "fee_daily" ("address", "day");
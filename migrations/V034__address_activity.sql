@@ -0,0 +1,30 @@
+-- Migration: per-address activity index
+-- One row per address taking part in a message: its "sender", "recipient"
+-- or "grantee". Rows are written at ingest with the message, carrying its
+-- type, what it did ("kind": "transfer", "staking", "governance", the
+-- contract action of executions, or "other") and the lease or pool it
+-- touched, so the transactions of an address are listed and paged by
+-- (block, tx_hash, index, inner_index) without scanning "raw_message".
+-- Messages indexed before this table are derived by the
+-- "activity_backfill" admin command.
+
+CREATE TABLE IF NOT EXISTS "address_activity" (
+  "address" VARCHAR(128) NOT NULL,
+  "tx_hash" VARCHAR(64) NOT NULL,
+  "index" INT NOT NULL,
+  "inner_index" INT NOT NULL,
+  "role" VARCHAR(16) NOT NULL,
+  "type" VARCHAR(64) NOT NULL,
+  "kind" VARCHAR(64) NOT NULL,
+  "lease" VARCHAR(64),
+  "pool" VARCHAR(64),
+  "block" BIGINT NOT NULL,
+  "timestamp" TIMESTAMPTZ NOT NULL,
+  "code" INT,
+  PRIMARY KEY ("address", "tx_hash", "index", "inner_index", "role")
+);
+
+CREATE INDEX IF NOT EXISTS idx_address_activity_keyset
+  ON "address_activity" ("address", "block" DESC, "tx_hash" DESC, "index" DESC, "inner_index" DESC);
+
+CREATE INDEX IF NOT EXISTS idx_ls_opening_tx_hash ON "LS_Opening" ("Tx_Hash");
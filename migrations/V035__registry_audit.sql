@@ -0,0 +1,14 @@
+-- Migration: registry audit log
+-- Every protocol, currency or pool found added to or deprecated from the
+-- admin contract by a registry sync, at startup, by the periodic
+-- reconciler or on request.
+
+CREATE TABLE IF NOT EXISTS "registry_audit" (
+  "id" BIGSERIAL PRIMARY KEY,
+  "entity" VARCHAR(16) NOT NULL,
+  "name" VARCHAR(128) NOT NULL,
+  "change" VARCHAR(16) NOT NULL,
+  "created_at" TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_registry_audit_created_at ON "registry_audit" ("created_at" DESC);
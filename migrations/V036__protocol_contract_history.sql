@@ -0,0 +1,40 @@
+-- Migration: protocol contract history
+-- The contract of each role of a protocol ("leaser", "lpp", "oracle",
+-- "profit", "reserve") and the heights it was valid over, both inclusive.
+-- The registry sync closes the row of a role whose contract was migrated or
+-- replaced at the last indexed height and opens a row for the new contract
+-- from the next one; "valid_to" is NULL while a contract is current. The
+-- contracts known before this table are valid from height 0, until the
+-- last indexed block for deprecated protocols.
+
+CREATE TABLE IF NOT EXISTS "protocol_contract_history" (
+  "protocol" VARCHAR(100) NOT NULL,
+  "role" VARCHAR(16) NOT NULL,
+  "contract" VARCHAR(64) NOT NULL,
+  "valid_from" BIGINT NOT NULL,
+  "valid_to" BIGINT,
+  "recorded_at" TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  PRIMARY KEY ("protocol", "role", "contract", "valid_from")
+);
+
+CREATE INDEX IF NOT EXISTS idx_protocol_contract_history_contract
+  ON "protocol_contract_history" ("contract", "valid_from");
+
+INSERT INTO "protocol_contract_history" ("protocol", "role", "contract", "valid_from", "valid_to")
+SELECT
+  p."protocol_name",
+  c."role",
+  c."contract",
+  0,
+  CASE WHEN p."is_active" THEN NULL ELSE (SELECT COALESCE(MAX("id"), 0) FROM "block") END
+FROM "protocol_registry" p
+CROSS JOIN LATERAL (
+  VALUES
+    ('leaser', p."leaser_contract"),
+    ('lpp', p."lpp_contract"),
+    ('oracle', p."oracle_contract"),
+    ('profit', p."profit_contract"),
+    ('reserve', p."reserve_contract")
+) AS c ("role", "contract")
+WHERE c."contract" IS NOT NULL
+ON CONFLICT DO NOTHING;
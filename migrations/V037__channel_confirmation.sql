@@ -0,0 +1,11 @@
+-- Migration: confirmation of email channels
+-- An email channel is delivered to once its owner confirmed it with the code
+-- mailed to it. Other channels are confirmed when added. Email channels added
+-- before are unconfirmed and must be added again to receive a code.
+
+ALTER TABLE "subscription_channel" ADD COLUMN IF NOT EXISTS "confirmed_at" TIMESTAMPTZ;
+ALTER TABLE "subscription_channel" ADD COLUMN IF NOT EXISTS "confirmation_code" VARCHAR(64);
+ALTER TABLE "subscription_channel" ADD COLUMN IF NOT EXISTS "confirmation_sent_at" TIMESTAMPTZ;
+
+UPDATE "subscription_channel" SET "confirmed_at" = "created_at"
+WHERE "channel" <> 'email' AND "confirmed_at" IS NULL;
@@ -0,0 +1,9 @@
+-- Migration: lease of running admin commands
+-- A running command is claimed until "claimed_until", which its consumer
+-- extends while it runs. Commands of a consumer that stopped are claimed again
+-- once the lease expired. Commands left running before are claimed again at once.
+
+ALTER TABLE "admin_command" ADD COLUMN IF NOT EXISTS "claimed_until" TIMESTAMPTZ;
+
+UPDATE "admin_command" SET "claimed_until" = now()
+WHERE "status" = 'running' AND "claimed_until" IS NULL;
@@ -0,0 +1,9 @@
+-- Migration: days of the fee rollup to recompute
+-- A block indexed for a day before the latest rollup, by a backfill or a
+-- resync, marks its day. The next rollup recomputes the marked days and
+-- removes the marks whose "version" did not change meanwhile.
+
+CREATE TABLE IF NOT EXISTS "fee_daily_pending" (
+  "day" TIMESTAMPTZ PRIMARY KEY,
+  "version" BIGINT NOT NULL DEFAULT 1
+);
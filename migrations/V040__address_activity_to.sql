@@ -0,0 +1,14 @@
+-- Migration: recipient of the indexed messages
+-- The "to" filter of the transactions of an address matches the recipient
+-- of its executions, as before the activity index, as well as the lease or
+-- pool they touched.
+
+ALTER TABLE "address_activity" ADD COLUMN IF NOT EXISTS "to" VARCHAR(128);
+
+UPDATE "address_activity" a
+SET "to" = m."to"
+FROM "raw_message" m
+WHERE m."tx_hash" = a."tx_hash"
+  AND m."index" = a."index"
+  AND m."inner_index" = a."inner_index"
+  AND a."to" IS NULL;
@@ -0,0 +1,9 @@
+-- Migration: key IBC transfers by both channel ends
+-- Incoming packets carry the source channel of the counterparty, so two
+-- chains sending on their own "channel-0" with the same sequence collided.
+-- The destination channel tells them apart.
+
+ALTER TABLE "ibc_transfer" DROP CONSTRAINT IF EXISTS "ibc_transfer_pkey";
+
+ALTER TABLE "ibc_transfer"
+  ADD PRIMARY KEY ("source_channel", "destination_channel", "sequence", "direction");
@@ -0,0 +1,6 @@
+-- Migration: claim of running admin commands
+-- Every claim of a command increments "claim". A consumer extends the lease
+-- and records the outcome only while its claim is the current one, so a
+-- consumer whose lease expired cannot overwrite the run that took over.
+
+ALTER TABLE "admin_command" ADD COLUMN IF NOT EXISTS "claim" BIGINT NOT NULL DEFAULT 0;
@@ -0,0 +1,10 @@
+-- Migration: recipient of contract instantiations
+-- Instantiations are sent to the contract they create, taken from the
+-- "instantiate" event. Those indexed before stored the code id, which the
+-- stored messages cannot resolve to an address, so it is cleared.
+
+UPDATE "raw_message" SET "to" = ''
+WHERE "type" = '/cosmwasm.wasm.v1.MsgInstantiateContract' AND "to" ~ '^[0-9]+$';
+
+UPDATE "address_activity" SET "to" = ''
+WHERE "type" = '/cosmwasm.wasm.v1.MsgInstantiateContract' AND "to" ~ '^[0-9]+$';